@@ -0,0 +1,135 @@
+//! Parser throughput for plain, JSON and XML events.
+//!
+//! Events are written to an in-memory stream and read back through
+//! [`EslClient::from_stream`], so each iteration measures the reader task's
+//! framing and event parsing as applications see it.
+//!
+//! Run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use freeswitch_esl_tokio::{EslClient, EslEvent, EslEventStream};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio::runtime::Runtime;
+
+/// Events written per iteration.
+const BATCH: usize = 100;
+
+/// A CHANNEL_CREATE-sized event with encoded values and a variable array.
+fn sample_event() -> EslEvent {
+    let mut event = EslEvent::new();
+    event.set_header("Event-Name", "CHANNEL_CREATE");
+    event.set_header("Core-UUID", "6d6c6f2a-3b1e-4c7a-9f0e-2a7d1c5b8e90");
+    event.set_header("FreeSWITCH-Hostname", "fs01.example.com");
+    event.set_header("Event-Date-Local", "2024-01-15 10:30:00");
+    event.set_header(
+        "Event-Calling-Function",
+        "switch_channel_perform_set_running_state",
+    );
+    event.set_header("Channel-State", "CS_INIT");
+    event.set_header("Channel-Call-State", "DOWN");
+    event.set_header("Unique-ID", "a1b2c3d4-0000-1111-2222-333344445555");
+    event.set_header("Call-Direction", "inbound");
+    event.set_header("Channel-Name", "sofia/internal/1000@example.com");
+    event.set_header("Caller-Caller-ID-Name", "Alice Example");
+    event.set_header("Caller-Caller-ID-Number", "1000");
+    event.set_header("Caller-Destination-Number", "5551234");
+    for i in 0..60 {
+        event.set_header(
+            format!("variable_sip_h_X-Custom-{}", i),
+            format!("value {} with spaces; and=params", i),
+        );
+    }
+    event.set_header(
+        "variable_sip_via",
+        "ARRAY::SIP/2.0/UDP 10.0.0.1|:SIP/2.0/UDP 10.0.0.2",
+    );
+    event
+}
+
+fn frame(content_type: &str, body: &str) -> Vec<u8> {
+    format!(
+        "Content-Length: {}\nContent-Type: {}\n\n{}",
+        body.len(),
+        content_type,
+        body
+    )
+    .into_bytes()
+}
+
+/// Act as FreeSWITCH for the auth handshake and return the connected client.
+async fn connect() -> (EslClient, EslEventStream, DuplexStream) {
+    let (client_side, mut server) = tokio::io::duplex(1024 * 1024);
+    let handshake = async {
+        server
+            .write_all(b"Content-Type: auth/request\n\n")
+            .await
+            .unwrap();
+        let mut buf = [0u8; 64];
+        let mut read = 0;
+        while !buf[..read].ends_with(b"\n\n") {
+            read += server
+                .read(&mut buf[read..])
+                .await
+                .unwrap();
+        }
+        server
+            .write_all(b"Content-Type: command/reply\nReply-Text: +OK accepted\n\n")
+            .await
+            .unwrap();
+    };
+    let (_, connected) = tokio::join!(handshake, EslClient::from_stream(client_side, "ClueCon"));
+    let (client, events) = connected.unwrap();
+    (client, events, server)
+}
+
+fn bench_format(c: &mut Criterion, rt: &Runtime, name: &str, frame: Vec<u8>) {
+    let (_client, mut events, mut server) = rt.block_on(connect());
+    let batch = frame.repeat(BATCH);
+
+    let mut group = c.benchmark_group("parse_event");
+    group.throughput(Throughput::Bytes(batch.len() as u64));
+    group.bench_function(name, |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let write = async {
+                    server
+                        .write_all(&batch)
+                        .await
+                        .unwrap();
+                };
+                let read = async {
+                    for _ in 0..BATCH {
+                        black_box(
+                            events
+                                .recv()
+                                .await
+                                .unwrap()
+                                .unwrap(),
+                        );
+                    }
+                };
+                tokio::join!(write, read);
+            })
+        })
+    });
+    group.finish();
+}
+
+fn parser_benches(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let event = sample_event();
+    bench_format(
+        c,
+        &rt,
+        "plain",
+        frame("text/event-plain", &event.to_plain_format()),
+    );
+    bench_format(c, &rt, "json", frame("text/event-json", &event.to_json()));
+    bench_format(c, &rt, "xml", frame("text/event-xml", &event.to_xml()));
+}
+
+criterion_group!(benches, parser_benches);
+criterion_main!(benches);
@@ -12,10 +12,16 @@
 //!   # Multiple events
 //!   cargo run --example event_filter -- -e CHANNEL_CREATE -e CHANNEL_ANSWER -f Call-Direction -v inbound
 //!
+//!   # Filter expression, evaluated client-side with server-side pre-filtering
+//!   cargo run --example event_filter -- -e ALL -w 'Event-Name in (CHANNEL_ANSWER, CHANNEL_HANGUP) and variable_sip_from_user =~ /^1[0-9]{3}$/'
+//!
 //!   # With userauth (user@domain format required)
 //!   cargo run --example event_filter -- -u admin@default -p secret -e ALL
 
-use freeswitch_esl_tokio::{EslClient, EslError, EslEventType, EventFormat};
+use freeswitch_esl_tokio::{
+    EslClient, EslError, EslEvent, EslEventStream, EslEventType, EventFilter, EventFormat,
+    FilteredEventStream,
+};
 
 fn print_usage() {
     eprintln!(
@@ -34,6 +40,8 @@ Filter Options:
                            Examples: CHANNEL_CREATE, CHANNEL_ANSWER, ALL
   -f, --filter <HEADER>    Header name to filter on
   -v, --value <VALUE>      Value to match (use /regex/ for regex matching)
+  -w, --where <EXPR>       Filter expression (==, !=, in, =~, !~, exists,
+                           and, or, not); cannot be combined with -f/-v
 
 Output Options:
   -j, --json               Output events as JSON
@@ -50,6 +58,9 @@ Examples:
   # Multiple events with JSON output
   event_filter -e CHANNEL_CREATE -e CHANNEL_ANSWER -f Caller-Context -v public -j
 
+  # Answered or hung up calls from 4-digit extensions starting with 1
+  event_filter -e ALL -w 'Event-Name in (CHANNEL_ANSWER, CHANNEL_HANGUP) and variable_sip_from_user =~ /^1[0-9]{{3}}$/'
+
   # With userauth (user@domain format)
   event_filter -u admin@default -p secret -e ALL
 
@@ -75,6 +86,7 @@ struct Args {
     events: Vec<String>,
     filter_header: Option<String>,
     filter_value: Option<String>,
+    filter_expr: Option<EventFilter>,
     json_output: bool,
     raw_output: bool,
     quiet: bool,
@@ -90,6 +102,7 @@ impl Default for Args {
             events: vec!["CHANNEL_CREATE".to_string()],
             filter_header: None,
             filter_value: None,
+            filter_expr: None,
             json_output: false,
             raw_output: false,
             quiet: false,
@@ -173,6 +186,16 @@ fn parse_args() -> Result<Args, String> {
                         .clone(),
                 );
             }
+            "-w" | "--where" => {
+                i += 1;
+                let expr = args
+                    .get(i)
+                    .ok_or("Missing filter expression")?;
+                result.filter_expr = Some(
+                    expr.parse()
+                        .map_err(|e| format!("{}", e))?,
+                );
+            }
             "-j" | "--json" => {
                 result.json_output = true;
             }
@@ -208,6 +231,16 @@ fn parse_args() -> Result<Args, String> {
         return Err("Both --filter and --value must be specified together".to_string());
     }
 
+    if result
+        .filter_expr
+        .is_some()
+        && result
+            .filter_header
+            .is_some()
+    {
+        return Err("--where cannot be combined with --filter/--value".to_string());
+    }
+
     Ok(result)
 }
 
@@ -255,7 +288,29 @@ fn format_event_full(event: &freeswitch_esl_tokio::EslEvent) -> String {
 }
 
 fn format_event_json(event: &freeswitch_esl_tokio::EslEvent) -> String {
-    serde_json::to_string(event.headers()).unwrap_or_else(|_| "{}".to_string())
+    event.to_json()
+}
+
+enum EventSource {
+    Main(EslEventStream),
+    Filtered(FilteredEventStream, EslEventStream),
+}
+
+impl EventSource {
+    async fn recv(&mut self) -> Option<Result<EslEvent, EslError>> {
+        match self {
+            Self::Main(events) => {
+                events
+                    .recv()
+                    .await
+            }
+            Self::Filtered(filtered, _main) => {
+                filtered
+                    .recv()
+                    .await
+            }
+        }
+    }
 }
 
 #[tokio::main]
@@ -285,7 +340,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         EslClient::connect(&args.host, args.port, &args.password).await
     };
 
-    let (client, mut events) = match connect_result {
+    let (client, events) = match connect_result {
         Ok(pair) => pair,
         Err(EslError::AuthenticationFailed { ref reason }) => {
             eprintln!("Authentication failed: {}", reason);
@@ -327,16 +382,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     eprintln!("Subscribing to events: {:?}", args.events);
-    client
-        .subscribe_events(format, &event_types)
-        .await?;
-
-    if let (Some(header), Some(value)) = (&args.filter_header, &args.filter_value) {
-        eprintln!("Applying filter: {} = {}", header, value);
-        client
-            .filter_events(header, value)
-            .await?;
-    }
+    // Both streams end when the connection closes; keep the main one alive
+    // while reading from the filtered one.
+    let mut events = match args.filter_expr {
+        Some(filter) => {
+            for (header, value) in filter.server_filters() {
+                eprintln!("Applying server filter: {} = {}", header, value);
+            }
+            let filtered = client
+                .subscribe_events_filtered(format, &event_types, filter)
+                .await?;
+            EventSource::Filtered(filtered, events)
+        }
+        None => {
+            client
+                .subscribe_events(format, &event_types)
+                .await?;
+            if let (Some(header), Some(value)) = (&args.filter_header, &args.filter_value) {
+                eprintln!("Applying filter: {} = {}", header, value);
+                client
+                    .filter_events(header, value)
+                    .await?;
+            }
+            EventSource::Main(events)
+        }
+    };
 
     eprintln!("Listening for events... (Ctrl+C to exit)\n");
 
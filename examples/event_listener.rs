@@ -124,7 +124,8 @@ fn process_event(
                 if let Some(call_info) = active_calls.get(uuid) {
                     let cause = event
                         .hangup_cause()
-                        .unwrap_or("UNKNOWN");
+                        .map(|c| c.to_string())
+                        .unwrap_or_else(|| "UNKNOWN".to_string());
                     let talk_time = call_info
                         .answered_time
                         .map(|t| t.elapsed());
@@ -8,8 +8,10 @@
 //! To test this, configure FreeSWITCH with:
 //! <action application="socket" data="localhost:8040 async full"/>
 
-use freeswitch_esl_tokio::{AppCommand, EslClient, EslEventType, EventFormat};
-use tokio::net::TcpListener;
+use freeswitch_esl_tokio::{
+    AppCommand, EslClient, EslEventType, EventFormat, HangupCause, OutboundCall, OutboundOptions,
+    OutboundServer,
+};
 use tracing::{debug, error, info};
 
 #[tokio::main]
@@ -19,39 +21,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let bind_addr = "0.0.0.0:8040";
     info!("Starting outbound ESL server on {}", bind_addr);
 
-    let listener = TcpListener::bind(bind_addr).await?;
+    // Sends connect, myevents and linger for each call before the handler runs
+    let server = OutboundServer::bind(bind_addr, OutboundOptions::default()).await?;
     info!("Listening for outbound connections from FreeSWITCH...");
 
-    loop {
-        match EslClient::accept_outbound(&listener).await {
-            Ok((client, mut events)) => {
-                info!("Accepted new outbound connection");
-                tokio::spawn(async move {
-                    if let Err(e) = handle_call(&client, &mut events).await {
-                        error!("Error handling call: {}", e);
-                    }
-                });
-            }
-            Err(e) => {
-                error!("Failed to accept connection: {}", e);
-                break;
-            }
-        }
-    }
+    server
+        .serve(handle_call)
+        .await?;
 
     Ok(())
 }
 
-async fn handle_call(
-    client: &EslClient,
-    events: &mut freeswitch_esl_tokio::EslEventStream,
-) -> Result<(), freeswitch_esl_tokio::EslError> {
-    info!("Handling new call...");
-
-    let connect_resp = client
-        .connect_session()
-        .await?;
-    let channel = connect_resp
+async fn handle_call(call: OutboundCall) -> Result<(), freeswitch_esl_tokio::EslError> {
+    let OutboundCall {
+        client,
+        mut events,
+        channel_data,
+        ..
+    } = call;
+    let channel = channel_data
         .header("Channel-Name")
         .unwrap_or("(unknown)");
     info!("Session established: {}", channel);
@@ -139,7 +127,7 @@ async fn handle_call(
 
                     if digit == "#" {
                         info!("User entered: {}", dtmf_buffer);
-                        handle_dtmf_input(client, &dtmf_buffer).await?;
+                        handle_dtmf_input(&client, &dtmf_buffer).await?;
                         dtmf_buffer.clear();
                     } else {
                         dtmf_buffer.push_str(digit);
@@ -184,7 +172,7 @@ async fn handle_dtmf_input(
                 .send_command(AppCommand::playback("voicemail/vm-goodbye.wav"))
                 .await?;
             client
-                .send_command(AppCommand::hangup(Some("NORMAL_CLEARING")))
+                .send_command(AppCommand::hangup(Some(HangupCause::NormalClearing)))
                 .await?;
         }
         "" => {
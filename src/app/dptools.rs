@@ -1,8 +1,526 @@
 //! FreeSWITCH dptools application commands (`answer`, `hangup`, `playback`, etc.).
+//!
+//! Applications that split their argument string with FreeSWITCH's
+//! `switch_separate_string()` get their arguments quoted here: any argument
+//! that is empty or contains whitespace, the separator, `'` or `\` is wrapped
+//! in single quotes with `'` and `\` backslash-escaped. Applications that
+//! take their argument verbatim (`playback`, `export`, `redirect`, ...) are
+//! passed through unchanged.
 
+use std::fmt;
+use std::time::Duration;
+
+use crate::channel::HangupCause;
 use crate::command::EslCommand;
 
-/// Constructors for common dptools application commands.
+/// Quote `arg` for an application that splits its data on `delim`.
+fn quote_arg(arg: &str, delim: char) -> String {
+    let needs_quotes = arg.is_empty()
+        || arg
+            .chars()
+            .any(|c| c.is_whitespace() || c == delim || c == '\'' || c == '\\');
+    if !needs_quotes {
+        return arg.to_string();
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('\'');
+    for c in arg.chars() {
+        if c == '\'' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Write `args` quoted and separated by `delim`.
+fn write_args(f: &mut fmt::Formatter<'_>, args: &[&str], delim: char) -> fmt::Result {
+    for (i, arg) in args
+        .iter()
+        .enumerate()
+    {
+        if i > 0 {
+            write!(f, "{}", delim)?;
+        }
+        f.write_str(&quote_arg(arg, delim))?;
+    }
+    Ok(())
+}
+
+/// Drop trailing `None`s; earlier gaps become empty (quoted) arguments.
+fn positional(args: Vec<Option<String>>) -> Vec<String> {
+    let len = args
+        .iter()
+        .rposition(Option::is_some)
+        .map_or(0, |i| i + 1);
+    args.into_iter()
+        .take(len)
+        .map(Option::unwrap_or_default)
+        .collect()
+}
+
+fn execute(app: &str, args: Option<String>) -> EslCommand {
+    EslCommand::Execute {
+        app: app.to_string(),
+        args,
+        uuid: None,
+    }
+}
+
+/// Arguments for `play_and_get_digits`.
+///
+/// `<min> <max> <tries> <timeout> <terminators> <file> [<invalid_file>
+/// [<var_name> [<regex> [<digit_timeout> [<transfer_on_failure>]]]]]`.
+/// Optional arguments skipped before a later one are sent empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayAndGetDigits {
+    /// Minimum number of digits to collect.
+    pub min_digits: u32,
+    /// Maximum number of digits to collect.
+    pub max_digits: u32,
+    /// Number of attempts before giving up.
+    pub tries: u32,
+    /// Time to wait for the first digit after the prompt.
+    pub timeout: Duration,
+    /// Digits that end input early (e.g. `"#"`).
+    pub terminators: String,
+    /// Prompt to play.
+    pub file: String,
+    /// Played when the input doesn't match `regex`.
+    pub invalid_file: Option<String>,
+    /// Channel variable receiving the digits.
+    pub var_name: Option<String>,
+    /// Regular expression the input must match.
+    pub regex: Option<String>,
+    /// Inter-digit timeout; FreeSWITCH uses `timeout` when unset.
+    pub digit_timeout: Option<Duration>,
+    /// `<extension> [<dialplan> [<context>]]` to transfer to after the last failed try.
+    pub transfer_on_failure: Option<String>,
+}
+
+impl PlayAndGetDigits {
+    /// Collect `min_digits..=max_digits` digits after playing `file`.
+    ///
+    /// Defaults to 3 tries, a 5 second timeout and `#` as terminator.
+    pub fn new(min_digits: u32, max_digits: u32, file: impl Into<String>) -> Self {
+        Self {
+            min_digits,
+            max_digits,
+            tries: 3,
+            timeout: Duration::from_secs(5),
+            terminators: "#".to_string(),
+            file: file.into(),
+            invalid_file: None,
+            var_name: None,
+            regex: None,
+            digit_timeout: None,
+            transfer_on_failure: None,
+        }
+    }
+}
+
+impl fmt::Display for PlayAndGetDigits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut args = vec![
+            self.min_digits
+                .to_string(),
+            self.max_digits
+                .to_string(),
+            self.tries
+                .to_string(),
+            self.timeout
+                .as_millis()
+                .to_string(),
+            self.terminators
+                .clone(),
+            self.file
+                .clone(),
+        ];
+        args.extend(positional(vec![
+            self.invalid_file
+                .clone(),
+            self.var_name
+                .clone(),
+            self.regex
+                .clone(),
+            self.digit_timeout
+                .map(|t| {
+                    t.as_millis()
+                        .to_string()
+                }),
+            self.transfer_on_failure
+                .clone(),
+        ]));
+        let args: Vec<&str> = args
+            .iter()
+            .map(String::as_str)
+            .collect();
+        write_args(f, &args, ' ')
+    }
+}
+
+/// Arguments for `read`: `<min> <max> <file> <var_name> <timeout> <terminators>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadDigits {
+    /// Minimum number of digits to collect.
+    pub min_digits: u32,
+    /// Maximum number of digits to collect.
+    pub max_digits: u32,
+    /// Prompt to play.
+    pub file: String,
+    /// Channel variable receiving the digits.
+    pub var_name: String,
+    /// Total time to wait for input.
+    pub timeout: Duration,
+    /// Digits that end input early (e.g. `"#"`).
+    pub terminators: String,
+}
+
+impl ReadDigits {
+    /// Read digits into `var_name` after playing `file`.
+    ///
+    /// Defaults to a 5 second timeout and `#` as terminator.
+    pub fn new(
+        min_digits: u32,
+        max_digits: u32,
+        file: impl Into<String>,
+        var_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            min_digits,
+            max_digits,
+            file: file.into(),
+            var_name: var_name.into(),
+            timeout: Duration::from_secs(5),
+            terminators: "#".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ReadDigits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let min = self
+            .min_digits
+            .to_string();
+        let max = self
+            .max_digits
+            .to_string();
+        let timeout = self
+            .timeout
+            .as_millis()
+            .to_string();
+        write_args(
+            f,
+            &[
+                &min,
+                &max,
+                &self.file,
+                &self.var_name,
+                &timeout,
+                &self.terminators,
+            ],
+            ' ',
+        )
+    }
+}
+
+/// Arguments for `record`: `<path> [<time_limit> [<silence_thresh> [<silence_hits>]]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// Destination file; the extension selects the format.
+    pub path: String,
+    /// Maximum recording length, in whole seconds. `None` records until hangup or `#`.
+    pub time_limit: Option<Duration>,
+    /// Energy level below which audio counts as silence.
+    pub silence_threshold: Option<u32>,
+    /// Seconds of silence that stop the recording. Ignored without `silence_threshold`.
+    pub silence_hits: Option<u32>,
+}
+
+impl Record {
+    /// Record to `path` with no time limit or silence detection.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            time_limit: None,
+            silence_threshold: None,
+            silence_hits: None,
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut args = vec![self
+            .path
+            .clone()];
+        if self
+            .time_limit
+            .is_some()
+            || self
+                .silence_threshold
+                .is_some()
+        {
+            args.push(
+                self.time_limit
+                    .map_or(0, |t| t.as_secs())
+                    .to_string(),
+            );
+        }
+        if let Some(threshold) = self.silence_threshold {
+            args.push(threshold.to_string());
+            if let Some(hits) = self.silence_hits {
+                args.push(hits.to_string());
+            }
+        }
+        let args: Vec<&str> = args
+            .iter()
+            .map(String::as_str)
+            .collect();
+        write_args(f, &args, ' ')
+    }
+}
+
+/// Arguments for `say`: `<module>[:<lang>] <type> <method> [<gender>] <text>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Say {
+    /// Say module, optionally with a language (e.g. `"en"`, `"es:es"`).
+    pub module: String,
+    /// What `text` is (e.g. `"NUMBER"`, `"CURRENCY"`, `"SHORT_DATE_TIME"`).
+    pub say_type: String,
+    /// How to say it (e.g. `"pronounced"`, `"iterated"`, `"counted"`).
+    pub say_method: String,
+    /// Grammatical gender (`"FEMININE"`, `"MASCULINE"`, `"NEUTER"`).
+    pub gender: Option<String>,
+    /// The value to say.
+    pub text: String,
+}
+
+impl fmt::Display for Say {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut args = vec![
+            self.module
+                .as_str(),
+            self.say_type
+                .as_str(),
+            self.say_method
+                .as_str(),
+        ];
+        if let Some(ref gender) = self.gender {
+            args.push(gender);
+        }
+        args.push(&self.text);
+        write_args(f, &args, ' ')
+    }
+}
+
+/// Arguments for `conference`: `<name>[@<profile>][+<pin>][+flags{<flag>|...}]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConferenceJoin {
+    /// Conference room name.
+    pub name: String,
+    /// Conference profile; FreeSWITCH uses `default` when `None`.
+    pub profile: Option<String>,
+    /// PIN the member must enter.
+    pub pin: Option<String>,
+    /// Member flags (e.g. `"mute"`, `"moderator"`, `"endconf"`).
+    pub flags: Vec<String>,
+}
+
+impl ConferenceJoin {
+    /// Join conference `name` on the default profile.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            profile: None,
+            pin: None,
+            flags: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for ConferenceJoin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(ref profile) = self.profile {
+            write!(f, "@{}", profile)?;
+        }
+        if let Some(ref pin) = self.pin {
+            write!(f, "+{}", pin)?;
+        }
+        if !self
+            .flags
+            .is_empty()
+        {
+            write!(
+                f,
+                "+flags{{{}}}",
+                self.flags
+                    .join("|")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Arguments for `bind_meta_app`: `<key> <listen_to> <respond_on> <app>[::<args>]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindMetaApp {
+    /// DTMF digit pressed after the meta key (`*`).
+    pub key: char,
+    /// Leg(s) to listen on: `"a"`, `"b"` or `"ab"`.
+    pub listen_to: String,
+    /// Leg to run the application on: `"s"` (same), `"o"` (opposite) or `"b"` (both),
+    /// optionally followed by `i` (inline) or `1` (one-shot) flags.
+    pub respond_on: String,
+    /// Application to run.
+    pub app: String,
+    /// Application arguments.
+    pub args: Option<String>,
+}
+
+impl fmt::Display for BindMetaApp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = self
+            .key
+            .to_string();
+        let app = match self.args {
+            Some(ref args) => format!("{}::{}", self.app, args),
+            None => self
+                .app
+                .clone(),
+        };
+        write_args(f, &[&key, &self.listen_to, &self.respond_on, &app], ' ')
+    }
+}
+
+/// Arguments for `bind_digit_action`:
+/// `<realm>,<digits>,<action>[,<value>[,<dtmf_target>[,<event_target>]]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindDigitAction {
+    /// Binding realm, selected with `digit_action_set_realm`.
+    pub realm: String,
+    /// Digit string, or a regex prefixed with `~`.
+    pub digits: String,
+    /// Action to take (e.g. `"exec:execute_extension"`, `"api:uuid_kill"`).
+    pub action: String,
+    /// Argument for `action`.
+    pub value: Option<String>,
+    /// Leg whose DTMF is matched: `"self"`, `"peer"` or `"both"`.
+    pub dtmf_target: Option<String>,
+    /// Leg the action runs on: `"self"` or `"peer"`.
+    pub event_target: Option<String>,
+}
+
+impl fmt::Display for BindDigitAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut args = vec![
+            self.realm
+                .clone(),
+            self.digits
+                .clone(),
+            self.action
+                .clone(),
+        ];
+        args.extend(positional(vec![
+            self.value
+                .clone(),
+            self.dtmf_target
+                .clone(),
+            self.event_target
+                .clone(),
+        ]));
+        let args: Vec<&str> = args
+            .iter()
+            .map(String::as_str)
+            .collect();
+        write_args(f, &args, ',')
+    }
+}
+
+/// Arguments for `tone_detect`:
+/// `<key> <freqs> <flags> [+<timeout> [<app> [<args> [<hits>]]]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToneDetect {
+    /// Name for this detector; also the `Detected-Tone` header value.
+    pub key: String,
+    /// Tone frequencies in Hz.
+    pub frequencies: Vec<u32>,
+    /// Media direction to watch: `"r"` (read) or `"w"` (write).
+    pub flags: String,
+    /// Stop detecting after this long.
+    pub timeout: Option<Duration>,
+    /// Application to run when the tone is detected.
+    pub app: Option<String>,
+    /// Arguments for `app`.
+    pub app_args: Option<String>,
+    /// Number of detections required before firing.
+    pub hits: Option<u32>,
+}
+
+impl ToneDetect {
+    /// Detect `frequencies` on the read direction.
+    pub fn new(key: impl Into<String>, frequencies: Vec<u32>) -> Self {
+        Self {
+            key: key.into(),
+            frequencies,
+            flags: "r".to_string(),
+            timeout: None,
+            app: None,
+            app_args: None,
+            hits: None,
+        }
+    }
+}
+
+impl fmt::Display for ToneDetect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequencies = self
+            .frequencies
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut args = vec![
+            self.key
+                .clone(),
+            frequencies,
+            self.flags
+                .clone(),
+        ];
+        let optional = positional(vec![
+            self.timeout
+                .map(|t| format!("+{}", t.as_millis())),
+            self.app
+                .clone(),
+            self.app_args
+                .clone(),
+            self.hits
+                .map(|h| h.to_string()),
+        ]);
+        // A zero timeout means none; an empty one would not parse
+        args.extend(
+            optional
+                .into_iter()
+                .enumerate()
+                .map(|(i, arg)| {
+                    if i == 0 && arg.is_empty() {
+                        "0".into()
+                    } else {
+                        arg
+                    }
+                }),
+        );
+        let args: Vec<&str> = args
+            .iter()
+            .map(String::as_str)
+            .collect();
+        write_args(f, &args, ' ')
+    }
+}
+
+/// Constructors for dptools application commands.
 ///
 /// Each method returns a `sendmsg`/`execute` command ready for
 /// [`EslClient::send_command()`](crate::EslClient::send_command).
@@ -19,8 +537,8 @@ impl AppCommand {
         }
     }
 
-    /// `cause`: hangup cause string (e.g. `NORMAL_CLEARING`). `None` uses default.
-    pub fn hangup(cause: Option<&str>) -> EslCommand {
+    /// Hang up the channel with `cause`. `None` uses default (`NORMAL_CLEARING`).
+    pub fn hangup(cause: Option<HangupCause>) -> EslCommand {
         EslCommand::Execute {
             app: "hangup".to_string(),
             args: cause.map(|c| c.to_string()),
@@ -82,4 +600,584 @@ impl AppCommand {
             uuid: None,
         }
     }
+
+    /// Answer with early media: audio flows before the call is answered (SIP 183).
+    pub fn pre_answer() -> EslCommand {
+        execute("pre_answer", None)
+    }
+
+    /// Signal ringing to the caller (SIP 180) without answering.
+    pub fn ring_ready() -> EslCommand {
+        execute("ring_ready", None)
+    }
+
+    /// Reply to an unanswered inbound SIP call with `code` and an optional reason phrase.
+    pub fn respond(code: u16, reason: Option<&str>) -> EslCommand {
+        let args = match reason {
+            Some(reason) => format!("{} {}", code, reason),
+            None => code.to_string(),
+        };
+        execute("respond", Some(args))
+    }
+
+    /// Redirect an unanswered SIP call to `uri` with a 302.
+    pub fn redirect(uri: &str) -> EslCommand {
+        execute("redirect", Some(uri.to_string()))
+    }
+
+    /// Send an answered SIP call to `uri` with a REFER.
+    pub fn deflect(uri: &str) -> EslCommand {
+        execute("deflect", Some(uri.to_string()))
+    }
+
+    /// Collect digits with prompts, retries and validation.
+    pub fn play_and_get_digits(args: &PlayAndGetDigits) -> EslCommand {
+        execute("play_and_get_digits", Some(args.to_string()))
+    }
+
+    /// Play a prompt and read digits into a channel variable.
+    pub fn read(args: &ReadDigits) -> EslCommand {
+        execute("read", Some(args.to_string()))
+    }
+
+    /// Record the caller's audio to a file (blocks until done).
+    pub fn record(args: &Record) -> EslCommand {
+        execute("record", Some(args.to_string()))
+    }
+
+    /// Record the whole session in the background. `time_limit` is in whole seconds.
+    pub fn record_session(path: &str, time_limit: Option<Duration>) -> EslCommand {
+        let args = match time_limit {
+            Some(limit) => format!("{} +{}", path, limit.as_secs()),
+            None => path.to_string(),
+        };
+        execute("record_session", Some(args))
+    }
+
+    /// Say a number, date, amount, etc. with the pre-recorded sound files.
+    pub fn say(args: &Say) -> EslCommand {
+        execute("say", Some(args.to_string()))
+    }
+
+    /// Speak `text` with text-to-speech `engine` and `voice`.
+    pub fn speak(engine: &str, voice: &str, text: &str) -> EslCommand {
+        let args = [engine, voice, text]
+            .iter()
+            .map(|a| quote_arg(a, '|'))
+            .collect::<Vec<_>>()
+            .join("|");
+        execute("speak", Some(args))
+    }
+
+    /// Pause for `duration` (millisecond resolution).
+    pub fn sleep(duration: Duration) -> EslCommand {
+        execute(
+            "sleep",
+            Some(
+                duration
+                    .as_millis()
+                    .to_string(),
+            ),
+        )
+    }
+
+    /// Set a channel variable and copy it to any bridged B-leg.
+    pub fn export(name: &str, value: &str) -> EslCommand {
+        execute("export", Some(format!("{}={}", name, value)))
+    }
+
+    /// Remove a channel variable.
+    pub fn unset(name: &str) -> EslCommand {
+        execute("unset", Some(name.to_string()))
+    }
+
+    /// Set several channel variables at once.
+    pub fn multiset(vars: &[(&str, &str)]) -> EslCommand {
+        let args = vars
+            .iter()
+            .map(|(name, value)| quote_arg(&format!("{}={}", name, value), ' '))
+            .collect::<Vec<_>>()
+            .join(" ");
+        execute("multiset", Some(args))
+    }
+
+    /// Join a conference room.
+    pub fn conference(args: &ConferenceJoin) -> EslCommand {
+        execute("conference", Some(args.to_string()))
+    }
+
+    /// Park the channel in valet `lot` at `extension`.
+    pub fn valet_park(lot: &str, extension: &str) -> EslCommand {
+        execute(
+            "valet_park",
+            Some(format!(
+                "{} {}",
+                quote_arg(lot, ' '),
+                quote_arg(extension, ' ')
+            )),
+        )
+    }
+
+    /// Bind `*<key>` to an application.
+    pub fn bind_meta_app(args: &BindMetaApp) -> EslCommand {
+        execute("bind_meta_app", Some(args.to_string()))
+    }
+
+    /// Bind a digit string to an action within a realm.
+    pub fn bind_digit_action(args: &BindDigitAction) -> EslCommand {
+        execute("bind_digit_action", Some(args.to_string()))
+    }
+
+    /// Send the caller's audio back to them (blocks until hangup).
+    pub fn echo() -> EslCommand {
+        execute("echo", None)
+    }
+
+    /// Watch for tones and fire `DETECTED_TONE` (or run an application).
+    pub fn tone_detect(args: &ToneDetect) -> EslCommand {
+        execute("tone_detect", Some(args.to_string()))
+    }
+
+    /// Start in-band DTMF detection on the channel.
+    pub fn start_dtmf() -> EslCommand {
+        execute("start_dtmf", None)
+    }
+
+    /// Listen in on channel `uuid`, or on every channel with `"all"`.
+    pub fn eavesdrop(uuid: &str) -> EslCommand {
+        execute("eavesdrop", Some(uuid.to_string()))
+    }
+
+    /// Pick up ringing channel `uuid`. With `bleg`, intercept its bridged partner instead.
+    pub fn intercept(uuid: &str, bleg: bool) -> EslCommand {
+        let args = if bleg {
+            format!("-bleg {}", uuid)
+        } else {
+            uuid.to_string()
+        };
+        execute("intercept", Some(args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Split `data` the way FreeSWITCH's `switch_separate_string()` does.
+    ///
+    /// Only what the builders emit is modelled: quotes always come in pairs.
+    fn separate(data: &str, delim: char) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let mut token = String::new();
+        let mut started = false;
+        let mut in_quotes = false;
+        let mut chars = data
+            .chars()
+            .peekable();
+        while let Some(c) = chars.next() {
+            let is_delim = c == delim;
+            if c == '\\' {
+                let unescaped = match chars.peek() {
+                    Some(&n) if n == '\'' || n == '"' || n == '\\' || n == delim => Some(n),
+                    Some('n') => Some('\n'),
+                    Some('t') => Some('\t'),
+                    Some('r') => Some('\r'),
+                    Some('s') => Some(' '),
+                    _ => None,
+                };
+                if let Some(u) = unescaped {
+                    chars.next();
+                    token.push(u);
+                } else {
+                    token.push(c);
+                }
+                started = true;
+            } else if c == '\'' {
+                in_quotes = !in_quotes;
+                started = true;
+            } else if is_delim && !in_quotes {
+                if started || delim != ' ' {
+                    tokens.push(std::mem::take(&mut token));
+                }
+                started = false;
+            } else {
+                token.push(c);
+                started = true;
+            }
+        }
+        if started || (delim != ' ' && !tokens.is_empty()) {
+            tokens.push(token);
+        }
+        tokens
+    }
+
+    fn app_args(cmd: EslCommand) -> (String, Option<String>) {
+        match cmd {
+            EslCommand::Execute { app, args, uuid } => {
+                assert!(uuid.is_none());
+                (app, args)
+            }
+            other => panic!("expected Execute, got {:?}", other),
+        }
+    }
+
+    /// Check the exact argument string, then split it as FreeSWITCH would.
+    fn split(cmd: EslCommand, expected_app: &str, delim: char, expected_args: &str) -> Vec<String> {
+        let (app, args) = app_args(cmd);
+        assert_eq!(app, expected_app);
+        let args = args.expect("missing args");
+        assert_eq!(args, expected_args);
+        separate(&args, delim)
+    }
+
+    #[test]
+    fn quote_arg_only_when_needed() {
+        assert_eq!(quote_arg("ivr/ivr-welcome.wav", ' '), "ivr/ivr-welcome.wav");
+        assert_eq!(quote_arg("", ' '), "''");
+        assert_eq!(quote_arg("two words", ' '), "'two words'");
+        assert_eq!(quote_arg("it's", ' '), "'it\\'s'");
+        assert_eq!(quote_arg("^\\d+$", ' '), "'^\\\\d+$'");
+        assert_eq!(quote_arg("a,b", ','), "'a,b'");
+        assert_eq!(quote_arg("a,b", ' '), "a,b");
+    }
+
+    #[test]
+    fn play_and_get_digits_round_trip() {
+        let pagd = PlayAndGetDigits {
+            var_name: Some("account".into()),
+            regex: Some("^\\d{4}$".into()),
+            digit_timeout: Some(Duration::from_millis(2500)),
+            transfer_on_failure: Some("operator XML default".into()),
+            ..PlayAndGetDigits::new(4, 4, "/sounds/enter account.wav")
+        };
+        assert_eq!(
+            split(
+                AppCommand::play_and_get_digits(&pagd),
+                "play_and_get_digits",
+                ' ',
+                r"4 4 3 5000 # '/sounds/enter account.wav' '' account '^\\d{4}$' 2500 'operator XML default'"
+            ),
+            [
+                "4",
+                "4",
+                "3",
+                "5000",
+                "#",
+                "/sounds/enter account.wav",
+                "",
+                "account",
+                "^\\d{4}$",
+                "2500",
+                "operator XML default",
+            ]
+        );
+    }
+
+    #[test]
+    fn play_and_get_digits_omits_trailing_options() {
+        let pagd = PlayAndGetDigits::new(1, 1, "ivr/ivr-menu.wav");
+        assert_eq!(pagd.to_string(), "1 1 3 5000 # ivr/ivr-menu.wav");
+    }
+
+    #[test]
+    fn read_round_trip() {
+        let read = ReadDigits {
+            terminators: "*#".into(),
+            ..ReadDigits::new(1, 10, "say:please enter your pin", "pin")
+        };
+        assert_eq!(
+            split(
+                AppCommand::read(&read),
+                "read",
+                ' ',
+                "1 10 'say:please enter your pin' pin 5000 *#"
+            ),
+            ["1", "10", "say:please enter your pin", "pin", "5000", "*#"]
+        );
+    }
+
+    #[test]
+    fn record_round_trip() {
+        let record = Record::new("/tmp/voice mail.wav");
+        assert_eq!(
+            split(
+                AppCommand::record(&record),
+                "record",
+                ' ',
+                "'/tmp/voice mail.wav'"
+            ),
+            ["/tmp/voice mail.wav"]
+        );
+
+        let record = Record {
+            silence_threshold: Some(200),
+            silence_hits: Some(3),
+            ..Record::new("/tmp/vm.wav")
+        };
+        assert_eq!(record.to_string(), "/tmp/vm.wav 0 200 3");
+
+        let record = Record {
+            time_limit: Some(Duration::from_secs(120)),
+            silence_hits: Some(3),
+            ..Record::new("/tmp/vm.wav")
+        };
+        assert_eq!(record.to_string(), "/tmp/vm.wav 120");
+    }
+
+    #[test]
+    fn record_session_appends_limit() {
+        let (app, args) = app_args(AppCommand::record_session(
+            "/recordings/${uuid}.wav",
+            Some(Duration::from_secs(3600)),
+        ));
+        assert_eq!(app, "record_session");
+        assert_eq!(args.as_deref(), Some("/recordings/${uuid}.wav +3600"));
+
+        let (_, args) = app_args(AppCommand::record_session("/tmp/a.wav", None));
+        assert_eq!(args.as_deref(), Some("/tmp/a.wav"));
+    }
+
+    #[test]
+    fn say_round_trip() {
+        let say = Say {
+            module: "en".into(),
+            say_type: "CURRENCY".into(),
+            say_method: "pronounced".into(),
+            gender: None,
+            text: "12.50".into(),
+        };
+        assert_eq!(say.to_string(), "en CURRENCY pronounced 12.50");
+
+        let say = Say {
+            module: "fr:fr".into(),
+            say_type: "NAME_SPELLED".into(),
+            say_method: "iterated".into(),
+            gender: Some("FEMININE".into()),
+            text: "d'Arc Jeanne".into(),
+        };
+        assert_eq!(
+            split(
+                AppCommand::say(&say),
+                "say",
+                ' ',
+                r"fr:fr NAME_SPELLED iterated FEMININE 'd\'Arc Jeanne'"
+            ),
+            [
+                "fr:fr",
+                "NAME_SPELLED",
+                "iterated",
+                "FEMININE",
+                "d'Arc Jeanne"
+            ]
+        );
+    }
+
+    #[test]
+    fn speak_round_trip() {
+        let cmd = AppCommand::speak("flite", "kal", "Press 1 | or 2, it's free");
+        assert_eq!(
+            split(cmd, "speak", '|', r"flite|kal|'Press 1 | or 2, it\'s free'"),
+            ["flite", "kal", "Press 1 | or 2, it's free"]
+        );
+    }
+
+    #[test]
+    fn multiset_round_trip() {
+        let cmd = AppCommand::multiset(&[
+            ("hangup_after_bridge", "true"),
+            ("effective_caller_id_name", "Front Desk"),
+        ]);
+        assert_eq!(
+            split(
+                cmd,
+                "multiset",
+                ' ',
+                "hangup_after_bridge=true 'effective_caller_id_name=Front Desk'"
+            ),
+            [
+                "hangup_after_bridge=true",
+                "effective_caller_id_name=Front Desk"
+            ]
+        );
+    }
+
+    #[test]
+    fn conference_join() {
+        let join = ConferenceJoin::new("room-1");
+        assert_eq!(
+            app_args(AppCommand::conference(&join)),
+            ("conference".to_string(), Some("room-1".to_string()))
+        );
+
+        let join = ConferenceJoin {
+            profile: Some("wideband".into()),
+            pin: Some("1234".into()),
+            flags: vec!["mute".into(), "endconf".into()],
+            ..ConferenceJoin::new("sales")
+        };
+        assert_eq!(join.to_string(), "sales@wideband+1234+flags{mute|endconf}");
+    }
+
+    #[test]
+    fn valet_park_round_trip() {
+        assert_eq!(
+            split(
+                AppCommand::valet_park("main lot", "6001"),
+                "valet_park",
+                ' ',
+                "'main lot' 6001"
+            ),
+            ["main lot", "6001"]
+        );
+    }
+
+    #[test]
+    fn bind_meta_app_round_trip() {
+        let bind = BindMetaApp {
+            key: '2',
+            listen_to: "a".into(),
+            respond_on: "s".into(),
+            app: "record_session".into(),
+            args: Some("/tmp/it's mine.wav".into()),
+        };
+        assert_eq!(
+            split(
+                AppCommand::bind_meta_app(&bind),
+                "bind_meta_app",
+                ' ',
+                r"2 a s 'record_session::/tmp/it\'s mine.wav'"
+            ),
+            ["2", "a", "s", "record_session::/tmp/it's mine.wav"]
+        );
+    }
+
+    #[test]
+    fn bind_digit_action_round_trip() {
+        let bind = BindDigitAction {
+            realm: "features".into(),
+            digits: "~^\\*\\d{2}$".into(),
+            action: "exec:execute_extension".into(),
+            value: Some("dx,XML features".into()),
+            dtmf_target: None,
+            event_target: Some("peer".into()),
+        };
+        assert_eq!(
+            split(
+                AppCommand::bind_digit_action(&bind),
+                "bind_digit_action",
+                ',',
+                r"features,'~^\\*\\d{2}$',exec:execute_extension,'dx,XML features','',peer"
+            ),
+            [
+                "features",
+                "~^\\*\\d{2}$",
+                "exec:execute_extension",
+                "dx,XML features",
+                "",
+                "peer"
+            ]
+        );
+    }
+
+    #[test]
+    fn tone_detect_round_trip() {
+        let detect = ToneDetect::new("fax", vec![1100, 2100]);
+        assert_eq!(detect.to_string(), "fax 1100,2100 r");
+
+        let detect = ToneDetect {
+            app: Some("transfer".into()),
+            app_args: Some("fax XML default".into()),
+            hits: Some(2),
+            ..ToneDetect::new("fax", vec![1100])
+        };
+        assert_eq!(
+            split(
+                AppCommand::tone_detect(&detect),
+                "tone_detect",
+                ' ',
+                "fax 1100 r 0 transfer 'fax XML default' 2"
+            ),
+            ["fax", "1100", "r", "0", "transfer", "fax XML default", "2"]
+        );
+
+        let detect = ToneDetect {
+            timeout: Some(Duration::from_secs(30)),
+            ..ToneDetect::new("busy", vec![480, 620])
+        };
+        assert_eq!(detect.to_string(), "busy 480,620 r +30000");
+    }
+
+    #[test]
+    fn verbatim_arguments() {
+        let cases = [
+            (
+                AppCommand::sleep(Duration::from_millis(1500)),
+                "sleep",
+                "1500",
+            ),
+            (
+                AppCommand::export("sip_h_X-Ref", "a b"),
+                "export",
+                "sip_h_X-Ref=a b",
+            ),
+            (AppCommand::unset("call_timeout"), "unset", "call_timeout"),
+            (
+                AppCommand::respond(486, Some("Busy Here")),
+                "respond",
+                "486 Busy Here",
+            ),
+            (AppCommand::respond(180, None), "respond", "180"),
+            (
+                AppCommand::redirect("sip:1000@pbx"),
+                "redirect",
+                "sip:1000@pbx",
+            ),
+            (
+                AppCommand::deflect("sip:1000@pbx"),
+                "deflect",
+                "sip:1000@pbx",
+            ),
+            (AppCommand::eavesdrop("all"), "eavesdrop", "all"),
+            (
+                AppCommand::intercept("abc-123", false),
+                "intercept",
+                "abc-123",
+            ),
+            (
+                AppCommand::intercept("abc-123", true),
+                "intercept",
+                "-bleg abc-123",
+            ),
+        ];
+        for (cmd, app, args) in cases {
+            assert_eq!(app_args(cmd), (app.to_string(), Some(args.to_string())));
+        }
+    }
+
+    #[test]
+    fn no_argument_apps() {
+        for (cmd, app) in [
+            (AppCommand::pre_answer(), "pre_answer"),
+            (AppCommand::ring_ready(), "ring_ready"),
+            (AppCommand::echo(), "echo"),
+            (AppCommand::start_dtmf(), "start_dtmf"),
+        ] {
+            assert_eq!(app_args(cmd), (app.to_string(), None));
+        }
+    }
+
+    #[test]
+    fn quoted_args_survive_wire_format() {
+        let wire = AppCommand::say(&Say {
+            module: "en".into(),
+            say_type: "NUMBER".into(),
+            say_method: "pronounced".into(),
+            gender: None,
+            text: "1 000".into(),
+        })
+        .to_wire_format()
+        .unwrap();
+        assert!(wire.contains("execute-app-name: say\n"));
+        assert!(wire.contains("execute-app-arg: en NUMBER pronounced '1 000'\n"));
+    }
 }
@@ -4,12 +4,18 @@ use crate::{
     constants::*,
     error::{EslError, EslResult},
 };
-use bytes::{BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 /// Buffer wrapper for efficient ESL protocol parsing
+///
+/// Extracted frames are split off the front as [`Bytes`] without copying;
+/// consumed space is reclaimed by `BytesMut` when the buffer next grows.
 pub struct EslBuffer {
     buffer: BytesMut,
-    position: usize,
+    /// Bytes already searched by `find_pattern` without a match, so a
+    /// partial frame is not rescanned from the start on every read.
+    scanned: usize,
 }
 
 impl EslBuffer {
@@ -17,7 +23,7 @@ impl EslBuffer {
     pub fn new() -> Self {
         Self {
             buffer: BytesMut::with_capacity(BUF_CHUNK),
-            position: 0,
+            scanned: 0,
         }
     }
 
@@ -25,41 +31,59 @@ impl EslBuffer {
     pub fn len(&self) -> usize {
         self.buffer
             .len()
-            - self.position
     }
 
-    /// Extend buffer with more data
-    pub fn extend_from_slice(&mut self, data: &[u8]) {
+    /// Make room for at least `additional` more bytes, logging growth.
+    fn reserve(&mut self, additional: usize) {
         if self
             .buffer
             .remaining_mut()
-            < data.len()
+            < additional
         {
             let old_cap = self
                 .buffer
                 .capacity();
-            let new_space = data
-                .len()
-                .max(BUF_CHUNK);
             self.buffer
-                .reserve(new_space);
-            tracing::debug!(
-                "Buffer grew from {} to {} bytes (added {} bytes)",
-                old_cap,
-                self.buffer
-                    .capacity(),
-                self.buffer
-                    .capacity()
-                    - old_cap
-            );
+                .reserve(additional.max(BUF_CHUNK));
+            if self
+                .buffer
+                .capacity()
+                > old_cap
+            {
+                tracing::debug!(
+                    "Buffer grew from {} to {} bytes",
+                    old_cap,
+                    self.buffer
+                        .capacity()
+                );
+            }
         }
+    }
+
+    /// Extend buffer with more data
+    #[cfg(test)]
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        self.reserve(data.len());
         self.buffer
             .extend_from_slice(data);
     }
 
+    /// Read from `reader` straight into the buffer's spare capacity.
+    ///
+    /// Returns the number of bytes read; `0` means EOF. Cancel-safe: if the
+    /// future is dropped before completing, no data has been consumed.
+    pub async fn read_from<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> EslResult<usize> {
+        self.reserve(SOCKET_BUF_SIZE);
+        reader
+            .read_buf(&mut self.buffer)
+            .await
+            .map_err(EslError::Io)
+    }
+
     /// Get reference to current data
+    #[cfg(test)]
     pub fn data(&self) -> &[u8] {
-        &self.buffer[self.position..]
+        &self.buffer
     }
 
     /// Consume bytes from the front of buffer.
@@ -73,66 +97,59 @@ impl EslBuffer {
                 count, available
             )));
         }
-        self.position += count;
+        self.buffer
+            .advance(count);
+        self.scanned = 0;
         Ok(())
     }
 
-    /// Find position of pattern in buffer, starting from current position
-    pub fn find_pattern(&self, pattern: &[u8]) -> Option<usize> {
-        let data = self.data();
+    /// Find position of pattern in buffer
+    ///
+    /// Resumes after the bytes searched by a previous unsuccessful call.
+    pub fn find_pattern(&mut self, pattern: &[u8]) -> Option<usize> {
+        let data = &self.buffer[..];
         if pattern.is_empty() || data.len() < pattern.len() {
             return None;
         }
 
-        (0..=(data.len() - pattern.len())).find(|&i| data[i..i + pattern.len()] == *pattern)
-    }
-
-    /// Extract data up to (but not including) the pattern
-    pub fn extract_until_pattern(&mut self, pattern: &[u8]) -> Option<Vec<u8>> {
-        if let Some(pos) = self.find_pattern(pattern) {
-            let result = self.data()[..pos].to_vec();
-            // pos + pattern.len() <= self.len() is guaranteed by find_pattern
-            let _ = self.advance(pos + pattern.len());
-            Some(result)
-        } else {
-            None
+        // A match may straddle the previously scanned region
+        let start = self
+            .scanned
+            .saturating_sub(pattern.len() - 1);
+        let found = data[start..]
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+            .map(|i| start + i);
+        if found.is_none() {
+            self.scanned = data.len();
         }
+        found
     }
 
-    /// Extract exact number of bytes
-    pub fn extract_bytes(&mut self, count: usize) -> Option<Vec<u8>> {
-        if self.len() >= count {
-            let result = self.data()[..count].to_vec();
-            let _ = self.advance(count);
-            Some(result)
-        } else {
-            None
-        }
+    /// Split off the data up to (but not including) the pattern, and
+    /// consume the pattern.
+    pub fn extract_until_pattern(&mut self, pattern: &[u8]) -> Option<Bytes> {
+        let pos = self.find_pattern(pattern)?;
+        let result = self
+            .buffer
+            .split_to(pos)
+            .freeze();
+        // pos + pattern.len() <= self.len() is guaranteed by find_pattern
+        let _ = self.advance(pattern.len());
+        Some(result)
     }
 
-    /// Compact buffer by removing consumed data
-    pub fn compact(&mut self) {
-        if self.position > 0 {
-            let remaining_len = self.len();
-            if remaining_len > 0 {
-                // Move remaining data to front
-                self.buffer
-                    .copy_within(self.position.., 0);
-            }
-            self.buffer
-                .truncate(remaining_len);
-            self.position = 0;
-
-            // Reserve more space if needed
-            if self
-                .buffer
-                .capacity()
-                < BUF_CHUNK
-            {
-                self.buffer
-                    .reserve(BUF_CHUNK);
-            }
+    /// Split off exactly `count` bytes.
+    pub fn extract_bytes(&mut self, count: usize) -> Option<Bytes> {
+        if self.len() < count {
+            return None;
         }
+        self.scanned = 0;
+        Some(
+            self.buffer
+                .split_to(count)
+                .freeze(),
+        )
     }
 
     /// Check if buffer size exceeds reasonable limits
@@ -209,6 +226,17 @@ mod tests {
         assert_eq!(pos, Some(32));
     }
 
+    #[test]
+    fn test_find_pattern_across_reads() {
+        let mut buffer = EslBuffer::new();
+        buffer.extend_from_slice(b"Header1: Value1\n");
+        assert_eq!(buffer.find_pattern(b"\n\n"), None);
+
+        // The terminator straddles the data already scanned
+        buffer.extend_from_slice(b"\nBody");
+        assert_eq!(buffer.find_pattern(b"\n\n"), Some(15));
+    }
+
     #[test]
     fn test_extract_until_pattern() {
         let mut buffer = EslBuffer::new();
@@ -217,7 +245,7 @@ mod tests {
         let headers = buffer
             .extract_until_pattern(b"\r\n\r\n")
             .unwrap();
-        assert_eq!(headers, b"Header1: Value1\r\nHeader2: Value2");
+        assert_eq!(headers, &b"Header1: Value1\r\nHeader2: Value2"[..]);
         assert_eq!(buffer.data(), b"Body");
     }
 
@@ -229,20 +257,34 @@ mod tests {
         let data = buffer
             .extract_bytes(5)
             .unwrap();
-        assert_eq!(data, b"Hello");
+        assert_eq!(data, &b"Hello"[..]);
         assert_eq!(buffer.data(), b" World");
     }
 
     #[test]
-    fn test_compact() {
+    fn test_extracted_bytes_survive_growth() {
         let mut buffer = EslBuffer::new();
         buffer.extend_from_slice(b"Hello World");
-        buffer
-            .advance(6)
+        let hello = buffer
+            .extract_bytes(5)
             .unwrap();
 
-        assert_eq!(buffer.data(), b"World");
-        buffer.compact();
-        assert_eq!(buffer.data(), b"World");
+        buffer.extend_from_slice(&vec![b'x'; BUF_CHUNK * 2]);
+        assert_eq!(hello, &b"Hello"[..]);
+        assert_eq!(&buffer.data()[..6], b" World");
+    }
+
+    #[tokio::test]
+    async fn test_read_from() {
+        let mut buffer = EslBuffer::new();
+        buffer.extend_from_slice(b"Hello ");
+        let mut reader: &[u8] = b"World";
+
+        let n = buffer
+            .read_from(&mut reader)
+            .await
+            .unwrap();
+        assert_eq!(n, 5);
+        assert_eq!(buffer.data(), b"Hello World");
     }
 }
@@ -0,0 +1,1015 @@
+//! Call-leg correlation: which channels form a call, and what is bridged to what.
+//!
+//! [`CallGraph`] groups channels into [`Call`]s from the headers FreeSWITCH
+//! puts on channel events:
+//!
+//! - `variable_originator` on a B-leg names the leg that originated it;
+//! - `Channel-Call-UUID` names the A-leg when `originator` is not set yet;
+//! - `variable_other_loopback_leg_uuid` pairs the two halves of a
+//!   `loopback/` channel;
+//! - `CHANNEL_BRIDGE` / `CHANNEL_UNBRIDGE` (`Bridge-A-Unique-ID`,
+//!   `Bridge-B-Unique-ID`, `Other-Leg-*`) track the current bridge of each leg.
+//!
+//! Bridging two legs of different calls (`uuid_bridge`, the final step of
+//! `att_xfer`) merges them into one call, and re-bridging a leg releases its
+//! previous partner. Calls are never split; a call is dropped once all its
+//! legs got `CHANNEL_DESTROY`.
+//!
+//! [`ChannelTracker`](crate::ChannelTracker) keeps a graph up to date, see
+//! [`ChannelTracker::call_for`](crate::ChannelTracker::call_for). A graph can
+//! also be fed directly:
+//!
+//! ```rust
+//! use freeswitch_esl_tokio::{CallGraph, EslEvent, EslEventType};
+//!
+//! let mut create_a = EslEvent::with_type(EslEventType::ChannelCreate);
+//! create_a.set_header("Unique-ID", "a");
+//! let mut create_b = EslEvent::with_type(EslEventType::ChannelCreate);
+//! create_b.set_header("Unique-ID", "b");
+//! create_b.set_header("variable_originator", "a");
+//! let mut bridge = EslEvent::with_type(EslEventType::ChannelBridge);
+//! bridge.set_header("Unique-ID", "a");
+//! bridge.set_header("Other-Leg-Unique-ID", "b");
+//!
+//! let mut graph = CallGraph::new();
+//! graph.apply(&create_a);
+//! graph.apply(&create_b);
+//! graph.apply(&bridge);
+//!
+//! let call = graph.call_for_leg("b").unwrap();
+//! assert_eq!(call.id(), "a");
+//! assert_eq!(call.leg("a").unwrap().bridged_to(), Some("b"));
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    channel::ChannelTimetable,
+    event::{EslEvent, EslEventType},
+};
+
+/// Position of a leg in its call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LegRole {
+    /// A leg that was not originated from another leg of the call (usually
+    /// the inbound channel). A call merged by `uuid_bridge` has several.
+    ALeg,
+    /// A leg originated from, or bridged to, another leg of the call.
+    BLeg,
+}
+
+/// One channel of a [`Call`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallLeg {
+    uuid: String,
+    role: LegRole,
+    channel_name: Option<String>,
+    originator: Option<String>,
+    bridged_to: Option<String>,
+    loopback_peer: Option<String>,
+    timetable: Option<ChannelTimetable>,
+    ended: bool,
+}
+
+impl CallLeg {
+    fn new(uuid: &str, role: LegRole) -> Self {
+        Self {
+            uuid: uuid.to_string(),
+            role,
+            channel_name: None,
+            originator: None,
+            bridged_to: None,
+            loopback_peer: None,
+            timetable: None,
+            ended: false,
+        }
+    }
+
+    /// Channel UUID.
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    /// A-leg or B-leg.
+    pub fn role(&self) -> LegRole {
+        self.role
+    }
+
+    /// `Channel-Name`, if seen.
+    pub fn channel_name(&self) -> Option<&str> {
+        self.channel_name
+            .as_deref()
+    }
+
+    /// Leg this one was created from.
+    pub fn originator(&self) -> Option<&str> {
+        self.originator
+            .as_deref()
+    }
+
+    /// Leg currently bridged to this one.
+    pub fn bridged_to(&self) -> Option<&str> {
+        self.bridged_to
+            .as_deref()
+    }
+
+    /// Other half of a `loopback/` channel pair.
+    pub fn loopback_peer(&self) -> Option<&str> {
+        self.loopback_peer
+            .as_deref()
+    }
+
+    /// `true` for a `loopback/` channel.
+    pub fn is_loopback(&self) -> bool {
+        self.loopback_peer
+            .is_some()
+            || self
+                .channel_name()
+                .is_some_and(|name| name.starts_with("loopback/"))
+    }
+
+    /// Latest timestamps of the leg, from its own `Caller-*-Time` headers or
+    /// from the `Other-Leg-*-Time` headers of its bridge partner's events.
+    pub fn timetable(&self) -> Option<&ChannelTimetable> {
+        self.timetable
+            .as_ref()
+    }
+
+    /// `true` once the leg got `CHANNEL_DESTROY`.
+    pub fn is_ended(&self) -> bool {
+        self.ended
+    }
+}
+
+/// What happened in a [`CallHistoryEntry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CallHistoryKind {
+    /// A leg became part of the call.
+    LegJoined {
+        /// Leg UUID.
+        uuid: String,
+        /// Role it joined with.
+        role: LegRole,
+    },
+    /// Two legs were bridged.
+    Bridged {
+        /// `Bridge-A-Unique-ID`.
+        a: String,
+        /// `Bridge-B-Unique-ID`.
+        b: String,
+    },
+    /// Two legs were unbridged.
+    Unbridged {
+        /// `Bridge-A-Unique-ID`.
+        a: String,
+        /// `Bridge-B-Unique-ID`.
+        b: String,
+    },
+    /// A leg got `CHANNEL_DESTROY`.
+    LegEnded {
+        /// Leg UUID.
+        uuid: String,
+    },
+    /// Another call was merged into this one by a bridge; its history
+    /// was interleaved with this call's by timestamp, before this entry.
+    Merged {
+        /// Id of the merged call.
+        call_id: String,
+    },
+}
+
+/// Entry of [`Call::history`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CallHistoryEntry {
+    /// `Event-Date-Timestamp` of the event (microseconds since epoch), if present.
+    pub timestamp: Option<i64>,
+    /// What happened.
+    pub kind: CallHistoryKind,
+}
+
+/// Channels that belong together, with their bridges over time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    id: String,
+    legs: Vec<CallLeg>,
+    history: Vec<CallHistoryEntry>,
+}
+
+impl Call {
+    fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            legs: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, timestamp: Option<i64>, kind: CallHistoryKind) {
+        self.history
+            .push(CallHistoryEntry { timestamp, kind });
+    }
+
+    fn leg_mut(&mut self, uuid: &str) -> Option<&mut CallLeg> {
+        self.legs
+            .iter_mut()
+            .find(|leg| leg.uuid == uuid)
+    }
+
+    /// Call id: the UUID of its first A-leg, which is also the
+    /// `Channel-Call-UUID` of legs originated from it.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// All legs, ended ones included, in the order they joined.
+    pub fn legs(&self) -> &[CallLeg] {
+        &self.legs
+    }
+
+    /// Leg with the given UUID.
+    pub fn leg(&self, uuid: &str) -> Option<&CallLeg> {
+        self.legs
+            .iter()
+            .find(|leg| leg.uuid == uuid)
+    }
+
+    /// First A-leg.
+    pub fn a_leg(&self) -> Option<&CallLeg> {
+        self.legs
+            .iter()
+            .find(|leg| leg.role == LegRole::ALeg)
+    }
+
+    /// B-legs, ended ones included.
+    pub fn b_legs(&self) -> impl Iterator<Item = &CallLeg> {
+        self.legs
+            .iter()
+            .filter(|leg| leg.role == LegRole::BLeg)
+    }
+
+    /// Legs that have not ended.
+    pub fn active_legs(&self) -> impl Iterator<Item = &CallLeg> {
+        self.legs
+            .iter()
+            .filter(|leg| !leg.ended)
+    }
+
+    /// Currently bridged pairs, each listed once.
+    pub fn bridges(&self) -> Vec<(&str, &str)> {
+        let mut pairs = Vec::new();
+        for (index, leg) in self
+            .legs
+            .iter()
+            .enumerate()
+        {
+            let Some(other) = leg.bridged_to() else {
+                continue;
+            };
+            // List a pair at its first leg only
+            let seen = self.legs[..index]
+                .iter()
+                .any(|l| l.uuid == other);
+            if !seen {
+                pairs.push((leg.uuid(), other));
+            }
+        }
+        pairs
+    }
+
+    /// Joins, bridges, unbridges, merges and ends, in the order applied.
+    ///
+    /// When calls are merged, the two histories are interleaved by
+    /// `Event-Date-Timestamp`; entries without one keep their place
+    /// relative to the call they came from.
+    pub fn history(&self) -> &[CallHistoryEntry] {
+        &self.history
+    }
+}
+
+/// Channels grouped into [`Call`]s, updated from channel events.
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    calls: HashMap<String, Call>,
+    /// Leg UUID → id of the call holding it
+    leg_calls: HashMap<String, String>,
+}
+
+impl CallGraph {
+    /// Empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a channel event.
+    ///
+    /// Any event with a `Unique-ID` adds the channel and follows its
+    /// `originator`, `Channel-Call-UUID` and loopback links; only
+    /// `CHANNEL_BRIDGE`, `CHANNEL_UNBRIDGE` and `CHANNEL_DESTROY` change
+    /// bridges and liveness. Other events are ignored.
+    pub fn apply(&mut self, event: &EslEvent) {
+        let Some(uuid) = event.unique_id() else {
+            return;
+        };
+        let timestamp = event
+            .header("Event-Date-Timestamp")
+            .and_then(|v| {
+                v.parse()
+                    .ok()
+            });
+        if event.is_event_type(EslEventType::ChannelDestroy) {
+            self.end_leg_at(uuid, timestamp);
+            return;
+        }
+
+        self.ensure_leg(uuid, timestamp);
+        let loopback_peer = event
+            .variable("other_loopback_leg_uuid")
+            .filter(|peer| !peer.is_empty() && *peer != uuid);
+        if let Some(leg) = self.leg_mut(uuid) {
+            if let Some(name) = event.channel_name() {
+                leg.channel_name = Some(name.to_string());
+            }
+            if let Some(timetable) = event.caller_timetable() {
+                leg.timetable = Some(timetable);
+            }
+            if let Some(peer) = loopback_peer {
+                leg.loopback_peer = Some(peer.to_string());
+            }
+        }
+        let parent = [
+            event.variable("originator"),
+            event.header("Channel-Call-UUID"),
+            loopback_peer,
+        ]
+        .into_iter()
+        .flatten()
+        .find(|parent| !parent.is_empty() && *parent != uuid);
+        if let Some(parent) = parent {
+            self.attach(uuid, parent, timestamp);
+        }
+
+        match event.event_type() {
+            Some(EslEventType::ChannelBridge) => {
+                if let Some((a, b)) = bridge_pair(event, uuid) {
+                    self.ensure_peer(a, uuid, timestamp);
+                    self.ensure_peer(b, a, timestamp);
+                    if event.header("Other-Leg-Unique-ID") == Some(b) {
+                        if let Some(leg) = self.leg_mut(b) {
+                            if let Some(name) = event.header("Other-Leg-Channel-Name") {
+                                leg.channel_name = Some(name.to_string());
+                            }
+                            if let Some(timetable) = event.other_leg_timetable() {
+                                leg.timetable = Some(timetable);
+                            }
+                        }
+                    }
+                    self.bridge(a, b, timestamp);
+                }
+            }
+            Some(EslEventType::ChannelUnbridge) => {
+                if let Some((a, b)) = bridge_pair(event, uuid) {
+                    self.unbridge(a, b, timestamp);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Mark a leg as ended, as `CHANNEL_DESTROY` does. Used when a channel is
+    /// known to be gone without its event, e.g. after a reconnect.
+    pub fn end_leg(&mut self, uuid: &str) {
+        self.end_leg_at(uuid, None);
+    }
+
+    /// Call with the given id.
+    pub fn call(&self, id: &str) -> Option<&Call> {
+        self.calls
+            .get(id)
+    }
+
+    /// Call holding the given leg.
+    pub fn call_for_leg(&self, uuid: &str) -> Option<&Call> {
+        self.calls
+            .get(
+                self.leg_calls
+                    .get(uuid)?,
+            )
+    }
+
+    /// All calls, in no particular order.
+    pub fn calls(&self) -> impl Iterator<Item = &Call> {
+        self.calls
+            .values()
+    }
+
+    /// Number of calls.
+    pub fn len(&self) -> usize {
+        self.calls
+            .len()
+    }
+
+    /// `true` when no call is tracked.
+    pub fn is_empty(&self) -> bool {
+        self.calls
+            .is_empty()
+    }
+
+    fn leg_mut(&mut self, uuid: &str) -> Option<&mut CallLeg> {
+        let call_id = self
+            .leg_calls
+            .get(uuid)?;
+        self.calls
+            .get_mut(call_id)?
+            .leg_mut(uuid)
+    }
+
+    fn add_leg(&mut self, uuid: &str, call_id: &str, role: LegRole, timestamp: Option<i64>) {
+        let call = self
+            .calls
+            .entry(call_id.to_string())
+            .or_insert_with(|| Call::new(call_id));
+        let leg = CallLeg::new(uuid, role);
+        // The A-leg a call is named after goes first, even if its B-legs came earlier
+        if uuid == call_id {
+            call.legs
+                .insert(0, leg);
+        } else {
+            call.legs
+                .push(leg);
+        }
+        call.record(
+            timestamp,
+            CallHistoryKind::LegJoined {
+                uuid: uuid.to_string(),
+                role,
+            },
+        );
+        self.leg_calls
+            .insert(uuid.to_string(), call_id.to_string());
+    }
+
+    /// Add an unknown leg as the A-leg of its own call, or of the call its
+    /// B-legs already named after it.
+    fn ensure_leg(&mut self, uuid: &str, timestamp: Option<i64>) {
+        if !self
+            .leg_calls
+            .contains_key(uuid)
+        {
+            self.add_leg(uuid, uuid, LegRole::ALeg, timestamp);
+        }
+    }
+
+    /// Add an unknown bridge partner to `anchor`'s call as a B-leg.
+    fn ensure_peer(&mut self, uuid: &str, anchor: &str, timestamp: Option<i64>) {
+        if self
+            .leg_calls
+            .contains_key(uuid)
+        {
+            return;
+        }
+        if self
+            .calls
+            .contains_key(uuid)
+        {
+            self.ensure_leg(uuid, timestamp);
+            return;
+        }
+        if let Some(call_id) = self
+            .leg_calls
+            .get(anchor)
+            .cloned()
+        {
+            self.add_leg(uuid, &call_id, LegRole::BLeg, timestamp);
+        }
+    }
+
+    /// Make `child`, still the root of its own call, a B-leg of `parent`'s call.
+    fn attach(&mut self, child: &str, parent: &str, timestamp: Option<i64>) {
+        let Some(call_id) = self
+            .leg_calls
+            .get(child)
+            .cloned()
+        else {
+            return;
+        };
+        // The parent link is only followed once
+        if call_id != child {
+            return;
+        }
+        if let Some(leg) = self.leg_mut(child) {
+            leg.role = LegRole::BLeg;
+            leg.originator = Some(parent.to_string());
+        }
+        let target = self
+            .leg_calls
+            .get(parent)
+            .cloned()
+            .or_else(|| {
+                self.calls
+                    .contains_key(parent)
+                    .then(|| parent.to_string())
+            });
+        match target {
+            Some(target) => self.merge(&target, &call_id, timestamp),
+            None => {
+                // Parent not seen yet: name the call after it so it joins when it shows up
+                let Some(mut call) = self
+                    .calls
+                    .remove(&call_id)
+                else {
+                    return;
+                };
+                call.id = parent.to_string();
+                for leg in &call.legs {
+                    self.leg_calls
+                        .insert(
+                            leg.uuid
+                                .clone(),
+                            parent.to_string(),
+                        );
+                }
+                self.calls
+                    .insert(parent.to_string(), call);
+            }
+        }
+    }
+
+    /// Move every leg and the history of call `source` into call `target`.
+    fn merge(&mut self, target: &str, source: &str, timestamp: Option<i64>) {
+        if target == source {
+            return;
+        }
+        let Some(source_call) = self
+            .calls
+            .remove(source)
+        else {
+            return;
+        };
+        let Some(target_call) = self
+            .calls
+            .get_mut(target)
+        else {
+            self.calls
+                .insert(source.to_string(), source_call);
+            return;
+        };
+        for leg in &source_call.legs {
+            self.leg_calls
+                .insert(
+                    leg.uuid
+                        .clone(),
+                    target.to_string(),
+                );
+        }
+        target_call
+            .legs
+            .extend(source_call.legs);
+        let history = std::mem::take(&mut target_call.history);
+        target_call.history = merge_history(history, source_call.history);
+        target_call.record(
+            timestamp,
+            CallHistoryKind::Merged {
+                call_id: source.to_string(),
+            },
+        );
+    }
+
+    /// Bridge `a` and `b`, releasing their previous partners.
+    fn bridge(&mut self, a: &str, b: &str, timestamp: Option<i64>) {
+        let (Some(call_a), Some(call_b)) = (
+            self.leg_calls
+                .get(a)
+                .cloned(),
+            self.leg_calls
+                .get(b)
+                .cloned(),
+        ) else {
+            return;
+        };
+        self.merge(&call_a, &call_b, timestamp);
+
+        for (leg, other) in [(a, b), (b, a)] {
+            let previous = self
+                .leg_mut(leg)
+                .and_then(|l| {
+                    l.bridged_to
+                        .replace(other.to_string())
+                });
+            if let Some(previous) = previous.filter(|p| p != other) {
+                self.release(&previous, leg);
+            }
+        }
+        if let Some(call) = self
+            .calls
+            .get_mut(&call_a)
+        {
+            call.record(
+                timestamp,
+                CallHistoryKind::Bridged {
+                    a: a.to_string(),
+                    b: b.to_string(),
+                },
+            );
+        }
+    }
+
+    fn unbridge(&mut self, a: &str, b: &str, timestamp: Option<i64>) {
+        self.release(a, b);
+        self.release(b, a);
+        if let Some(call) = self
+            .leg_calls
+            .get(a)
+            .and_then(|call_id| {
+                self.calls
+                    .get_mut(call_id)
+            })
+        {
+            call.record(
+                timestamp,
+                CallHistoryKind::Unbridged {
+                    a: a.to_string(),
+                    b: b.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Clear `leg`'s bridge if it still points at `partner`.
+    fn release(&mut self, leg: &str, partner: &str) {
+        if let Some(leg) = self.leg_mut(leg) {
+            if leg.bridged_to() == Some(partner) {
+                leg.bridged_to = None;
+            }
+        }
+    }
+
+    fn end_leg_at(&mut self, uuid: &str, timestamp: Option<i64>) {
+        let Some(call_id) = self
+            .leg_calls
+            .get(uuid)
+            .cloned()
+        else {
+            return;
+        };
+        let partner = self
+            .leg_mut(uuid)
+            .and_then(|leg| {
+                leg.ended = true;
+                leg.bridged_to
+                    .take()
+            });
+        if let Some(partner) = partner {
+            self.release(&partner, uuid);
+        }
+        let Some(call) = self
+            .calls
+            .get_mut(&call_id)
+        else {
+            return;
+        };
+        call.record(
+            timestamp,
+            CallHistoryKind::LegEnded {
+                uuid: uuid.to_string(),
+            },
+        );
+        if call
+            .legs
+            .iter()
+            .all(|leg| leg.ended)
+        {
+            if let Some(call) = self
+                .calls
+                .remove(&call_id)
+            {
+                for leg in &call.legs {
+                    self.leg_calls
+                        .remove(&leg.uuid);
+                }
+            }
+        }
+    }
+}
+
+/// Interleave two histories by timestamp, keeping each one's own order.
+///
+/// A `source` entry goes before a `target` entry only when both have a
+/// timestamp and the source one is earlier.
+fn merge_history(
+    target: Vec<CallHistoryEntry>,
+    source: Vec<CallHistoryEntry>,
+) -> Vec<CallHistoryEntry> {
+    let mut merged = Vec::with_capacity(target.len() + source.len());
+    let mut source = source
+        .into_iter()
+        .peekable();
+    for entry in target {
+        while let Some(earlier) = source
+            .next_if(|s| matches!((s.timestamp, entry.timestamp), (Some(s), Some(t)) if s < t))
+        {
+            merged.push(earlier);
+        }
+        merged.push(entry);
+    }
+    merged.extend(source);
+    merged
+}
+
+/// `(Bridge-A-Unique-ID, Bridge-B-Unique-ID)` of a bridge event, falling back
+/// to the event's channel and `Other-Leg-Unique-ID`.
+fn bridge_pair<'a>(event: &'a EslEvent, uuid: &'a str) -> Option<(&'a str, &'a str)> {
+    let a = event
+        .header("Bridge-A-Unique-ID")
+        .unwrap_or(uuid);
+    let b = event
+        .header("Bridge-B-Unique-ID")
+        .or_else(|| event.header("Other-Leg-Unique-ID"))?;
+    (a != b && !b.is_empty()).then_some((a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bridge(a: &str, b: &str) -> EslEvent {
+        EslEvent::test_event(
+            EslEventType::ChannelBridge,
+            &[
+                ("Unique-ID", a),
+                ("Bridge-A-Unique-ID", a),
+                ("Bridge-B-Unique-ID", b),
+            ],
+        )
+    }
+
+    fn unbridge(a: &str, b: &str) -> EslEvent {
+        EslEvent::test_event(
+            EslEventType::ChannelUnbridge,
+            &[
+                ("Unique-ID", a),
+                ("Bridge-A-Unique-ID", a),
+                ("Bridge-B-Unique-ID", b),
+            ],
+        )
+    }
+
+    fn destroy(uuid: &str) -> EslEvent {
+        EslEvent::test_event(EslEventType::ChannelDestroy, &[("Unique-ID", uuid)])
+    }
+
+    #[test]
+    fn test_originated_legs_join_the_a_leg() {
+        let mut graph = CallGraph::new();
+        // B-leg seen before its A-leg: the call is named after the A-leg
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "b"), ("Channel-Call-UUID", "a")],
+        ));
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "a"), ("Channel-Call-UUID", "a")],
+        ));
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "c"), ("variable_originator", "a")],
+        ));
+
+        assert_eq!(graph.len(), 1);
+        let call = graph
+            .call("a")
+            .unwrap();
+        assert_eq!(
+            call.a_leg()
+                .unwrap()
+                .uuid(),
+            "a"
+        );
+        let b_legs: Vec<&str> = call
+            .b_legs()
+            .map(|leg| leg.uuid())
+            .collect();
+        assert_eq!(b_legs, ["b", "c"]);
+        assert_eq!(
+            call.leg("c")
+                .unwrap()
+                .originator(),
+            Some("a")
+        );
+    }
+
+    #[test]
+    fn test_loopback_pair_joins_one_call() {
+        let mut graph = CallGraph::new();
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "a")],
+        ));
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[
+                ("Unique-ID", "lb-a"),
+                ("Channel-Name", "loopback/1000-a"),
+                ("variable_originator", "a"),
+            ],
+        ));
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[
+                ("Unique-ID", "lb-b"),
+                ("Channel-Name", "loopback/1000-b"),
+                ("variable_other_loopback_leg_uuid", "lb-a"),
+            ],
+        ));
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "b"), ("variable_originator", "lb-b")],
+        ));
+
+        let call = graph
+            .call_for_leg("b")
+            .unwrap();
+        assert_eq!(call.id(), "a");
+        assert_eq!(
+            call.legs()
+                .len(),
+            4
+        );
+        let lb_b = call
+            .leg("lb-b")
+            .unwrap();
+        assert!(lb_b.is_loopback());
+        assert_eq!(lb_b.loopback_peer(), Some("lb-a"));
+        assert!(call
+            .leg("lb-a")
+            .unwrap()
+            .is_loopback());
+    }
+
+    #[test]
+    fn test_uuid_bridge_merges_calls_and_releases_partners() {
+        let mut graph = CallGraph::new();
+        for (uuid, originator) in [("a", None), ("b", Some("a")), ("x", None), ("y", Some("x"))] {
+            let mut headers = vec![("Unique-ID", uuid)];
+            headers.extend(originator.map(|o| ("variable_originator", o)));
+            graph.apply(&EslEvent::test_event(EslEventType::ChannelCreate, &headers));
+        }
+        graph.apply(&bridge("a", "b"));
+        graph.apply(&bridge("x", "y"));
+        assert_eq!(graph.len(), 2);
+
+        // uuid_bridge a x: both previous partners are released
+        graph.apply(&bridge("a", "x"));
+        assert_eq!(graph.len(), 1);
+        let call = graph
+            .call("a")
+            .unwrap();
+        assert_eq!(call.bridges(), [("a", "x")]);
+        assert_eq!(
+            call.leg("b")
+                .unwrap()
+                .bridged_to(),
+            None
+        );
+        assert_eq!(
+            call.leg("y")
+                .unwrap()
+                .bridged_to(),
+            None
+        );
+        assert!(call
+            .history()
+            .iter()
+            .any(|e| e.kind
+                == CallHistoryKind::Merged {
+                    call_id: "x".into()
+                }));
+    }
+
+    #[test]
+    fn test_merge_interleaves_history_by_timestamp() {
+        let mut graph = CallGraph::new();
+        for (uuid, ts) in [("a", "1000"), ("x", "2000")] {
+            graph.apply(&EslEvent::test_event(
+                EslEventType::ChannelCreate,
+                &[("Unique-ID", uuid), ("Event-Date-Timestamp", ts)],
+            ));
+        }
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[
+                ("Unique-ID", "b"),
+                ("variable_originator", "a"),
+                ("Event-Date-Timestamp", "3000"),
+            ],
+        ));
+        let mut uuid_bridge = bridge("a", "x");
+        uuid_bridge.set_header("Event-Date-Timestamp", "4000");
+        graph.apply(&uuid_bridge);
+
+        let call = graph
+            .call("a")
+            .unwrap();
+        let timestamps: Vec<_> = call
+            .history()
+            .iter()
+            .map(|e| e.timestamp)
+            .collect();
+        let mut sorted = timestamps.clone();
+        sorted.sort();
+        assert_eq!(timestamps, sorted);
+        assert!(matches!(
+            &call.history()[1].kind,
+            CallHistoryKind::LegJoined { uuid, .. } if uuid == "x"
+        ));
+    }
+
+    #[test]
+    fn test_attended_transfer_rebridges_a_to_c() {
+        let mut graph = CallGraph::new();
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "a")],
+        ));
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "b"), ("variable_originator", "a")],
+        ));
+        graph.apply(&bridge("a", "b"));
+
+        // b runs att_xfer to c, then hangs up
+        graph.apply(&unbridge("a", "b"));
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "c"), ("variable_originator", "b")],
+        ));
+        graph.apply(&bridge("b", "c"));
+        graph.apply(&unbridge("b", "c"));
+        graph.apply(&bridge("a", "c"));
+        graph.apply(&destroy("b"));
+
+        let call = graph
+            .call("a")
+            .unwrap();
+        assert_eq!(call.bridges(), [("a", "c")]);
+        let active: Vec<&str> = call
+            .active_legs()
+            .map(|leg| leg.uuid())
+            .collect();
+        assert_eq!(active, ["a", "c"]);
+        let bridges: Vec<(&str, &str)> = call
+            .history()
+            .iter()
+            .filter_map(|e| match &e.kind {
+                CallHistoryKind::Bridged { a, b } => Some((a.as_str(), b.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(bridges, [("a", "b"), ("b", "c"), ("a", "c")]);
+
+        graph.apply(&destroy("a"));
+        graph.apply(&destroy("c"));
+        assert!(graph.is_empty());
+        assert!(graph
+            .call_for_leg("a")
+            .is_none());
+    }
+
+    #[test]
+    fn test_bridge_learns_other_leg_from_headers() {
+        let mut graph = CallGraph::new();
+        graph.apply(&EslEvent::test_event(
+            EslEventType::ChannelBridge,
+            &[
+                ("Unique-ID", "a"),
+                ("Other-Leg-Unique-ID", "b"),
+                ("Other-Leg-Channel-Name", "sofia/internal/2000@pbx"),
+                ("Other-Leg-Channel-Created-Time", "1700000001000000"),
+                ("Event-Date-Timestamp", "1700000006000000"),
+            ],
+        ));
+        let call = graph
+            .call("a")
+            .unwrap();
+        let b = call
+            .leg("b")
+            .unwrap();
+        assert_eq!(b.role(), LegRole::BLeg);
+        assert_eq!(b.channel_name(), Some("sofia/internal/2000@pbx"));
+        assert_eq!(
+            b.timetable()
+                .unwrap()
+                .created,
+            Some(1700000001000000)
+        );
+        assert_eq!(
+            call.history()
+                .last()
+                .unwrap()
+                .timestamp,
+            Some(1700000006000000)
+        );
+    }
+}
@@ -0,0 +1,663 @@
+//! Call detail records built from channel events.
+//!
+//! [`CallDetailRecord`] collects what a CDR needs from a
+//! `CHANNEL_HANGUP_COMPLETE` event, or from a
+//! [`TrackedChannel`] once it is removed: identities,
+//! hangup cause, codecs, and the setup/ring/talk/hold durations computed from
+//! the [`ChannelTimetable`].
+//!
+//! Field names follow the FreeSWITCH channel variables (`caller_id_number`,
+//! `billsec`, `read_codec`, ...), so [`CdrTemplate`] accepts the same
+//! `${name}` templates as mod_cdr_csv; names that are not record fields are
+//! looked up among the channel variables.
+//!
+//! ```rust
+//! use freeswitch_esl_tokio::{CallDetailRecord, CdrTemplate, EslEvent, EslEventType};
+//!
+//! let mut event = EslEvent::with_type(EslEventType::ChannelHangupComplete);
+//! event.set_header("Unique-ID", "abc");
+//! event.set_header("Caller-Caller-ID-Number", "1000");
+//! event.set_header("Hangup-Cause", "NORMAL_CLEARING");
+//! event.set_header("variable_billsec", "42");
+//!
+//! let cdr = CallDetailRecord::try_from(&event).unwrap();
+//! let template: CdrTemplate = r#""${uuid}","${caller_id_number}",${billsec},${hangup_cause}"#
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(cdr.to_csv(&template), r#""abc","1000",42,NORMAL_CLEARING"#);
+//! assert!(cdr.to_json().contains(r#""billsec":42"#));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use indexmap::IndexMap;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::{
+    channel::{CallDirection, ChannelTimetable, HangupCause},
+    event::EslEvent,
+    tracker::TrackedChannel,
+    typed::TypedEventError,
+};
+
+/// Typed CDR of one channel.
+///
+/// Serializes (serde, [`to_json`](Self::to_json)) as a flat object of the
+/// [`FIELDS`](Self::FIELDS) that are set, plus a `variables` object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CallDetailRecord {
+    /// `Unique-ID`.
+    pub uuid: String,
+    /// `Channel-Call-UUID`.
+    pub call_uuid: Option<String>,
+    /// Last bridged leg: `variable_last_bridge_to`, else `Other-Leg-Unique-ID`.
+    pub bleg_uuid: Option<String>,
+    /// `Call-Direction`.
+    pub direction: Option<CallDirection>,
+    /// `Channel-Name`.
+    pub channel_name: Option<String>,
+    /// `Caller-Caller-ID-Name`.
+    pub caller_id_name: Option<String>,
+    /// `Caller-Caller-ID-Number`.
+    pub caller_id_number: Option<String>,
+    /// `Caller-Destination-Number`.
+    pub destination_number: Option<String>,
+    /// `Caller-Context`.
+    pub context: Option<String>,
+    /// `variable_accountcode`.
+    pub accountcode: Option<String>,
+    /// `Caller-*-Time` timestamps.
+    pub timetable: Option<ChannelTimetable>,
+    /// Seconds from creation to hangup (`variable_duration`, else computed).
+    pub duration: Option<u64>,
+    /// Seconds from answer to hangup (`variable_billsec`, else computed).
+    pub billsec: Option<u64>,
+    /// Creation to first ringing, early media or answer (post-dial delay).
+    pub setup: Option<Duration>,
+    /// First ringing or early media to answer, or to hangup if unanswered.
+    pub ring: Option<Duration>,
+    /// Answer to hangup, minus time on hold.
+    pub talk: Option<Duration>,
+    /// Accumulated hold time.
+    pub hold: Option<Duration>,
+    /// `Hangup-Cause`.
+    pub hangup_cause: Option<HangupCause>,
+    /// `variable_sip_hangup_disposition`, e.g. `recv_bye`.
+    pub hangup_disposition: Option<String>,
+    /// `variable_read_codec`, else `Channel-Read-Codec-Name`.
+    pub read_codec: Option<String>,
+    /// `variable_write_codec`, else `Channel-Write-Codec-Name`.
+    pub write_codec: Option<String>,
+    /// `variable_sip_call_id`.
+    pub sip_call_id: Option<String>,
+    /// `variable_sip_from_user`.
+    pub sip_from_user: Option<String>,
+    /// `variable_sip_from_host`.
+    pub sip_from_host: Option<String>,
+    /// `variable_sip_to_user`.
+    pub sip_to_user: Option<String>,
+    /// `variable_sip_to_host`.
+    pub sip_to_host: Option<String>,
+    /// `variable_sip_user_agent`.
+    pub sip_user_agent: Option<String>,
+    /// Every channel variable, without the `variable_` prefix.
+    pub variables: IndexMap<String, String>,
+}
+
+/// A field value, typed for JSON output.
+enum FieldValue<'a> {
+    Text(&'a str),
+    Owned(String),
+    Number(u64),
+}
+
+impl fmt::Display for FieldValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Text(s) => f.write_str(s),
+            FieldValue::Owned(s) => f.write_str(s),
+            FieldValue::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl Serialize for FieldValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            FieldValue::Text(s) => serializer.serialize_str(s),
+            FieldValue::Owned(s) => serializer.serialize_str(s),
+            FieldValue::Number(n) => serializer.serialize_u64(*n),
+        }
+    }
+}
+
+fn text_value(value: &Option<String>) -> Option<FieldValue<'_>> {
+    value
+        .as_deref()
+        .map(FieldValue::Text)
+}
+
+/// Timestamp in microseconds, treating FreeSWITCH's `0` as unset.
+fn stamp(value: Option<i64>) -> Option<i64> {
+    value.filter(|v| *v > 0)
+}
+
+/// Time between two microsecond timestamps.
+fn between(start: Option<i64>, end: Option<i64>) -> Option<Duration> {
+    let micros = end? - start?;
+    u64::try_from(micros)
+        .ok()
+        .map(Duration::from_micros)
+}
+
+impl CallDetailRecord {
+    /// Record fields by name, in serialization order. Durations are in
+    /// milliseconds (`setup_msec`, ...), timestamps in microseconds since the
+    /// epoch (`start_uepoch`, ...).
+    pub const FIELDS: &'static [&'static str] = &[
+        "uuid",
+        "call_uuid",
+        "bleg_uuid",
+        "direction",
+        "channel_name",
+        "caller_id_name",
+        "caller_id_number",
+        "destination_number",
+        "context",
+        "accountcode",
+        "start_uepoch",
+        "progress_uepoch",
+        "answer_uepoch",
+        "bridge_uepoch",
+        "end_uepoch",
+        "duration",
+        "billsec",
+        "setup_msec",
+        "ring_msec",
+        "talk_msec",
+        "hold_msec",
+        "hangup_cause",
+        "hangup_disposition",
+        "read_codec",
+        "write_codec",
+        "sip_call_id",
+        "sip_from_user",
+        "sip_from_host",
+        "sip_to_user",
+        "sip_to_host",
+        "sip_user_agent",
+    ];
+
+    fn from_headers(event: &EslEvent, uuid: &str) -> Self {
+        let text = |name: &str| {
+            event
+                .header(name)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let var = |name: &str| {
+            event
+                .variable(name)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let number = |name: &str| {
+            event
+                .variable(name)
+                .and_then(|v| {
+                    v.parse()
+                        .ok()
+                })
+        };
+
+        let timetable = event.caller_timetable();
+        let tt = timetable.unwrap_or_default();
+        let created = stamp(tt.created);
+        let answered = stamp(tt.answered);
+        let hungup = stamp(tt.hungup);
+        let ringing = [stamp(tt.progress), stamp(tt.progress_media)]
+            .into_iter()
+            .flatten()
+            .min();
+        let hold = stamp(tt.hold_accum).map(|us| Duration::from_micros(us as u64));
+
+        let setup = between(created, ringing.or(answered));
+        let ring = ringing.and_then(|start| between(Some(start), answered.or(hungup)));
+        let talk =
+            between(answered, hungup).map(|talk| talk.saturating_sub(hold.unwrap_or_default()));
+        let duration = number("duration").or_else(|| between(created, hungup).map(|d| d.as_secs()));
+        let billsec = number("billsec").or_else(|| match (answered, hungup) {
+            (Some(_), _) => between(answered, hungup).map(|d| d.as_secs()),
+            // Hung up without answer
+            (None, Some(_)) => Some(0),
+            (None, None) => None,
+        });
+
+        let variables = event
+            .headers()
+            .filter_map(|(key, value)| {
+                key.strip_prefix("variable_")
+                    .map(|name| (name.to_string(), value.to_string()))
+            })
+            .collect();
+
+        Self {
+            uuid: uuid.to_string(),
+            call_uuid: text("Channel-Call-UUID"),
+            bleg_uuid: var("last_bridge_to").or_else(|| text("Other-Leg-Unique-ID")),
+            direction: event.call_direction(),
+            channel_name: text("Channel-Name"),
+            caller_id_name: text("Caller-Caller-ID-Name"),
+            caller_id_number: text("Caller-Caller-ID-Number"),
+            destination_number: text("Caller-Destination-Number"),
+            context: text("Caller-Context"),
+            accountcode: var("accountcode"),
+            timetable,
+            duration,
+            billsec,
+            setup,
+            ring,
+            talk,
+            hold,
+            hangup_cause: event.hangup_cause(),
+            hangup_disposition: var("sip_hangup_disposition"),
+            read_codec: var("read_codec").or_else(|| text("Channel-Read-Codec-Name")),
+            write_codec: var("write_codec").or_else(|| text("Channel-Write-Codec-Name")),
+            sip_call_id: var("sip_call_id"),
+            sip_from_user: var("sip_from_user"),
+            sip_from_host: var("sip_from_host"),
+            sip_to_user: var("sip_to_user"),
+            sip_to_host: var("sip_to_host"),
+            sip_user_agent: var("sip_user_agent"),
+            variables,
+        }
+    }
+
+    fn field_value(&self, name: &str) -> Option<FieldValue<'_>> {
+        let msec = |v: Option<Duration>| v.map(|d| FieldValue::Number(d.as_millis() as u64));
+        let uepoch = |v: Option<i64>| stamp(v).map(|us| FieldValue::Number(us as u64));
+        let tt = self
+            .timetable
+            .unwrap_or_default();
+        match name {
+            "uuid" => Some(FieldValue::Text(&self.uuid)),
+            "call_uuid" => text_value(&self.call_uuid),
+            "bleg_uuid" => text_value(&self.bleg_uuid),
+            "direction" => self
+                .direction
+                .map(|d| FieldValue::Owned(d.to_string())),
+            "channel_name" => text_value(&self.channel_name),
+            "caller_id_name" => text_value(&self.caller_id_name),
+            "caller_id_number" => text_value(&self.caller_id_number),
+            "destination_number" => text_value(&self.destination_number),
+            "context" => text_value(&self.context),
+            "accountcode" => text_value(&self.accountcode),
+            "start_uepoch" => uepoch(tt.created),
+            "progress_uepoch" => uepoch(
+                [stamp(tt.progress), stamp(tt.progress_media)]
+                    .into_iter()
+                    .flatten()
+                    .min(),
+            ),
+            "answer_uepoch" => uepoch(tt.answered),
+            "bridge_uepoch" => uepoch(tt.bridged),
+            "end_uepoch" => uepoch(tt.hungup),
+            "duration" => self
+                .duration
+                .map(FieldValue::Number),
+            "billsec" => self
+                .billsec
+                .map(FieldValue::Number),
+            "setup_msec" => msec(self.setup),
+            "ring_msec" => msec(self.ring),
+            "talk_msec" => msec(self.talk),
+            "hold_msec" => msec(self.hold),
+            "hangup_cause" => self
+                .hangup_cause
+                .as_ref()
+                .map(|c| FieldValue::Text(c.as_str())),
+            "hangup_disposition" => text_value(&self.hangup_disposition),
+            "read_codec" => text_value(&self.read_codec),
+            "write_codec" => text_value(&self.write_codec),
+            "sip_call_id" => text_value(&self.sip_call_id),
+            "sip_from_user" => text_value(&self.sip_from_user),
+            "sip_from_host" => text_value(&self.sip_from_host),
+            "sip_to_user" => text_value(&self.sip_to_user),
+            "sip_to_host" => text_value(&self.sip_to_host),
+            "sip_user_agent" => text_value(&self.sip_user_agent),
+            _ => None,
+        }
+    }
+
+    /// Value of a [`FIELDS`](Self::FIELDS) entry, else of the channel
+    /// variable `name`.
+    pub fn field(&self, name: &str) -> Option<String> {
+        match self.field_value(name) {
+            Some(value) => Some(value.to_string()),
+            None => self
+                .variables
+                .get(name)
+                .cloned(),
+        }
+    }
+
+    /// Serialize to a JSON object.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Render one CSV line with `template`.
+    pub fn to_csv(&self, template: &CdrTemplate) -> String {
+        template.render(self)
+    }
+}
+
+impl TryFrom<&EslEvent> for CallDetailRecord {
+    type Error = TypedEventError;
+
+    /// Works with any channel event; `CHANNEL_HANGUP_COMPLETE` carries the
+    /// final variables and timestamps.
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        let uuid = event
+            .unique_id()
+            .ok_or(TypedEventError::MissingHeader("Unique-ID"))?;
+        Ok(Self::from_headers(event, uuid))
+    }
+}
+
+impl From<&TrackedChannel> for CallDetailRecord {
+    /// CDR from everything the tracker merged for the channel, typically the
+    /// channel delivered with [`ChannelChange::Removed`](crate::ChannelChange::Removed).
+    fn from(channel: &TrackedChannel) -> Self {
+        Self::from_headers(&channel.data, channel.uuid())
+    }
+}
+
+impl Serialize for CallDetailRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for name in Self::FIELDS {
+            if let Some(value) = self.field_value(name) {
+                map.serialize_entry(name, &value)?;
+            }
+        }
+        if !self
+            .variables
+            .is_empty()
+        {
+            map.serialize_entry("variables", &self.variables)?;
+        }
+        map.end()
+    }
+}
+
+/// Error returned when a [`CdrTemplate`] has an unterminated `${`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCdrTemplateError {
+    /// Byte offset of the unterminated `${`.
+    pub offset: usize,
+}
+
+impl fmt::Display for ParseCdrTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unterminated ${{ in CDR template at offset {}",
+            self.offset
+        )
+    }
+}
+
+impl std::error::Error for ParseCdrTemplateError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Field(String),
+}
+
+/// CSV line template in the mod_cdr_csv format: `${name}` is replaced by
+/// [`CallDetailRecord::field`], everything else is copied as is.
+///
+/// Substituted values have `"` doubled, so templates should quote the
+/// fields that can contain commas or quotes. Unknown names render empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CdrTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl CdrTemplate {
+    /// The `example` template shipped in FreeSWITCH's `cdr_csv.conf.xml`.
+    pub const DEFAULT: &'static str = concat!(
+        r#""${caller_id_name}","${caller_id_number}","${destination_number}","${context}","#,
+        r#""${start_stamp}","${answer_stamp}","${end_stamp}","${duration}","${billsec}","#,
+        r#""${hangup_cause}","${uuid}","${bleg_uuid}","${accountcode}","${read_codec}","#,
+        r#""${write_codec}""#
+    );
+
+    /// Parse a template.
+    pub fn new(template: &str) -> Result<Self, ParseCdrTemplateError> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        let mut offset = 0;
+        while let Some(start) = rest.find("${") {
+            let end = rest[start..]
+                .find('}')
+                .ok_or(ParseCdrTemplateError {
+                    offset: offset + start,
+                })?;
+            if start > 0 {
+                parts.push(TemplatePart::Literal(rest[..start].to_string()));
+            }
+            parts.push(TemplatePart::Field(
+                rest[start + 2..start + end].to_string(),
+            ));
+            offset += start + end + 1;
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Literal(rest.to_string()));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Render `cdr` as one line (without line terminator).
+    pub fn render(&self, cdr: &CallDetailRecord) -> String {
+        let mut line = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Literal(text) => line.push_str(text),
+                TemplatePart::Field(name) => {
+                    if let Some(value) = cdr.field(name) {
+                        line.push_str(&value.replace('"', "\"\""));
+                    }
+                }
+            }
+        }
+        line
+    }
+
+    /// Header line: the template with every `${name}` replaced by `name`.
+    pub fn header(&self) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Literal(text) | TemplatePart::Field(text) => text.as_str(),
+            })
+            .collect()
+    }
+}
+
+impl Default for CdrTemplate {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT).expect("default CDR template is valid")
+    }
+}
+
+impl FromStr for CdrTemplate {
+    type Err = ParseCdrTemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EslEventType;
+
+    fn hangup_complete() -> EslEvent {
+        let mut event = EslEvent::with_type(EslEventType::ChannelHangupComplete);
+        for (name, value) in [
+            ("Unique-ID", "a-leg"),
+            ("Channel-Call-UUID", "a-leg"),
+            ("Call-Direction", "inbound"),
+            ("Caller-Caller-ID-Name", "Alice \"A\""),
+            ("Caller-Caller-ID-Number", "1000"),
+            ("Caller-Destination-Number", "2000"),
+            ("Caller-Context", "default"),
+            ("Caller-Channel-Created-Time", "1700000000000000"),
+            ("Caller-Channel-Progress-Time", "1700000000500000"),
+            ("Caller-Channel-Progress-Media-Time", "0"),
+            ("Caller-Channel-Answered-Time", "1700000004500000"),
+            ("Caller-Channel-Hangup-Time", "1700000064500000"),
+            ("Caller-Channel-Hold-Accum", "10000000"),
+            ("Hangup-Cause", "NORMAL_CLEARING"),
+            ("variable_last_bridge_to", "b-leg"),
+            ("variable_read_codec", "PCMU"),
+            ("Channel-Write-Codec-Name", "PCMA"),
+            ("variable_sip_call_id", "abc@pbx"),
+            ("variable_sip_from_user", "1000"),
+            ("variable_sip_hangup_disposition", "recv_bye"),
+            ("variable_start_stamp", "2023-11-14 22:13:20"),
+        ] {
+            event.set_header(name, value);
+        }
+        event
+    }
+
+    #[test]
+    fn test_cdr_from_hangup_complete() {
+        let cdr = CallDetailRecord::try_from(&hangup_complete()).unwrap();
+        assert_eq!(cdr.uuid, "a-leg");
+        assert_eq!(
+            cdr.bleg_uuid
+                .as_deref(),
+            Some("b-leg")
+        );
+        assert_eq!(cdr.direction, Some(CallDirection::Inbound));
+        assert_eq!(cdr.hangup_cause, Some(HangupCause::NormalClearing));
+        assert_eq!(cdr.setup, Some(Duration::from_millis(500)));
+        assert_eq!(cdr.ring, Some(Duration::from_secs(4)));
+        assert_eq!(cdr.hold, Some(Duration::from_secs(10)));
+        assert_eq!(cdr.talk, Some(Duration::from_secs(50)));
+        assert_eq!(cdr.duration, Some(64));
+        assert_eq!(cdr.billsec, Some(60));
+        assert_eq!(
+            cdr.read_codec
+                .as_deref(),
+            Some("PCMU")
+        );
+        assert_eq!(
+            cdr.write_codec
+                .as_deref(),
+            Some("PCMA")
+        );
+        assert_eq!(
+            cdr.hangup_disposition
+                .as_deref(),
+            Some("recv_bye")
+        );
+        assert_eq!(
+            cdr.field("start_stamp")
+                .as_deref(),
+            Some("2023-11-14 22:13:20")
+        );
+
+        let mut event = hangup_complete();
+        event.set_header("variable_billsec", "59");
+        event.del_header("Caller-Channel-Answered-Time");
+        let cdr = CallDetailRecord::try_from(&event).unwrap();
+        assert_eq!(cdr.billsec, Some(59));
+        assert_eq!(cdr.talk, None);
+        assert_eq!(cdr.ring, Some(Duration::from_secs(64)));
+
+        assert_eq!(
+            CallDetailRecord::try_from(&EslEvent::new()),
+            Err(TypedEventError::MissingHeader("Unique-ID"))
+        );
+    }
+
+    #[test]
+    fn test_cdr_keeps_unknown_hangup_cause() {
+        let mut event = hangup_complete();
+        event.set_header("Hangup-Cause", "VENDOR_SPECIFIC");
+        let cdr = CallDetailRecord::try_from(&event).unwrap();
+        assert_eq!(
+            cdr.hangup_cause,
+            Some(HangupCause::Unknown("VENDOR_SPECIFIC".to_string()))
+        );
+        assert_eq!(
+            cdr.field("hangup_cause"),
+            Some("VENDOR_SPECIFIC".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cdr_unanswered_billsec_is_zero() {
+        let mut event = EslEvent::with_type(EslEventType::ChannelHangupComplete);
+        event.set_header("Unique-ID", "x");
+        event.set_header("Caller-Channel-Created-Time", "1700000000000000");
+        event.set_header("Caller-Channel-Answered-Time", "0");
+        event.set_header("Caller-Channel-Hangup-Time", "1700000030000000");
+        let cdr = CallDetailRecord::try_from(&event).unwrap();
+        assert_eq!(cdr.billsec, Some(0));
+        assert_eq!(cdr.duration, Some(30));
+        assert_eq!(cdr.setup, None);
+        assert_eq!(cdr.field("answer_uepoch"), None);
+    }
+
+    #[test]
+    fn test_cdr_json() {
+        let cdr = CallDetailRecord::try_from(&hangup_complete()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&cdr.to_json()).unwrap();
+        assert_eq!(json["uuid"], "a-leg");
+        assert_eq!(json["direction"], "inbound");
+        assert_eq!(json["billsec"], 60);
+        assert_eq!(json["talk_msec"], 50000);
+        assert_eq!(json["start_uepoch"], 1700000000000000u64);
+        assert_eq!(json["hangup_cause"], "NORMAL_CLEARING");
+        assert_eq!(json["variables"]["sip_call_id"], "abc@pbx");
+        assert!(json
+            .get("accountcode")
+            .is_none());
+    }
+
+    #[test]
+    fn test_cdr_csv_template() {
+        let cdr = CallDetailRecord::try_from(&hangup_complete()).unwrap();
+        let template = CdrTemplate::default();
+        assert_eq!(
+            template.render(&cdr),
+            concat!(
+                r#""Alice ""A""","1000","2000","default","2023-11-14 22:13:20","","","64","60","#,
+                r#""NORMAL_CLEARING","a-leg","b-leg","","PCMU","PCMA""#
+            )
+        );
+        assert!(template
+            .header()
+            .starts_with(r#""caller_id_name","caller_id_number","#));
+
+        let custom: CdrTemplate = "${uuid};${ring_msec};${sip_from_user};${missing}"
+            .parse()
+            .unwrap();
+        assert_eq!(custom.render(&cdr), "a-leg;4000;1000;");
+
+        let err = "ok,${uuid".parse::<CdrTemplate>();
+        assert_eq!(err, Err(ParseCdrTemplateError { offset: 3 }));
+    }
+}
@@ -282,6 +282,273 @@ impl FromStr for CallDirection {
     }
 }
 
+macro_rules! hangup_causes {
+    ($($variant:ident = $code:literal => $name:literal,)+) => {
+        /// Hangup cause from `switch_call_cause_t` — carried in the `Hangup-Cause`
+        /// header and accepted by `hangup`/`uuid_kill`.
+        ///
+        /// Codes up to 127 are ITU-T Q.850 cause values; higher codes are
+        /// FreeSWITCH-specific. Parsing is case-insensitive and also accepts
+        /// the numeric code, like `switch_channel_str2cause()`. Causes this
+        /// crate does not know are kept as [`Unknown`](Self::Unknown) by the
+        /// event accessors. Serializes as the wire name (`NORMAL_CLEARING`).
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        #[allow(missing_docs)]
+        pub enum HangupCause {
+            $($variant,)+
+            /// A cause name not in the table above, as received.
+            Unknown(String),
+        }
+
+        impl HangupCause {
+            /// Parse from the numeric cause code.
+            pub fn from_number(n: u16) -> Option<Self> {
+                match n {
+                    $($code => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+
+            /// Numeric cause code (Q.850 value for codes up to 127), `None`
+            /// for [`Unknown`](Self::Unknown).
+            pub fn as_number(&self) -> Option<u16> {
+                match self {
+                    $(Self::$variant => Some($code),)+
+                    Self::Unknown(_) => None,
+                }
+            }
+
+            /// Wire name, e.g. `NORMAL_CLEARING`.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $name,)+
+                    Self::Unknown(name) => name,
+                }
+            }
+
+            fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+hangup_causes! {
+    None = 0 => "NONE",
+    UnallocatedNumber = 1 => "UNALLOCATED_NUMBER",
+    NoRouteTransitNet = 2 => "NO_ROUTE_TRANSIT_NET",
+    NoRouteDestination = 3 => "NO_ROUTE_DESTINATION",
+    ChannelUnacceptable = 6 => "CHANNEL_UNACCEPTABLE",
+    CallAwardedDelivered = 7 => "CALL_AWARDED_DELIVERED",
+    NormalClearing = 16 => "NORMAL_CLEARING",
+    UserBusy = 17 => "USER_BUSY",
+    NoUserResponse = 18 => "NO_USER_RESPONSE",
+    NoAnswer = 19 => "NO_ANSWER",
+    SubscriberAbsent = 20 => "SUBSCRIBER_ABSENT",
+    CallRejected = 21 => "CALL_REJECTED",
+    NumberChanged = 22 => "NUMBER_CHANGED",
+    RedirectionToNewDestination = 23 => "REDIRECTION_TO_NEW_DESTINATION",
+    ExchangeRoutingError = 25 => "EXCHANGE_ROUTING_ERROR",
+    DestinationOutOfOrder = 27 => "DESTINATION_OUT_OF_ORDER",
+    InvalidNumberFormat = 28 => "INVALID_NUMBER_FORMAT",
+    FacilityRejected = 29 => "FACILITY_REJECTED",
+    ResponseToStatusEnquiry = 30 => "RESPONSE_TO_STATUS_ENQUIRY",
+    NormalUnspecified = 31 => "NORMAL_UNSPECIFIED",
+    NormalCircuitCongestion = 34 => "NORMAL_CIRCUIT_CONGESTION",
+    NetworkOutOfOrder = 38 => "NETWORK_OUT_OF_ORDER",
+    NormalTemporaryFailure = 41 => "NORMAL_TEMPORARY_FAILURE",
+    SwitchCongestion = 42 => "SWITCH_CONGESTION",
+    AccessInfoDiscarded = 43 => "ACCESS_INFO_DISCARDED",
+    RequestedChanUnavail = 44 => "REQUESTED_CHAN_UNAVAIL",
+    PreEmpted = 45 => "PRE_EMPTED",
+    FacilityNotSubscribed = 50 => "FACILITY_NOT_SUBSCRIBED",
+    OutgoingCallBarred = 52 => "OUTGOING_CALL_BARRED",
+    IncomingCallBarred = 54 => "INCOMING_CALL_BARRED",
+    BearercapabilityNotauth = 57 => "BEARERCAPABILITY_NOTAUTH",
+    BearercapabilityNotavail = 58 => "BEARERCAPABILITY_NOTAVAIL",
+    ServiceUnavailable = 63 => "SERVICE_UNAVAILABLE",
+    BearercapabilityNotimpl = 65 => "BEARERCAPABILITY_NOTIMPL",
+    ChanNotImplemented = 66 => "CHAN_NOT_IMPLEMENTED",
+    FacilityNotImplemented = 69 => "FACILITY_NOT_IMPLEMENTED",
+    ServiceNotImplemented = 79 => "SERVICE_NOT_IMPLEMENTED",
+    InvalidCallReference = 81 => "INVALID_CALL_REFERENCE",
+    IncompatibleDestination = 88 => "INCOMPATIBLE_DESTINATION",
+    InvalidMsgUnspecified = 95 => "INVALID_MSG_UNSPECIFIED",
+    MandatoryIeMissing = 96 => "MANDATORY_IE_MISSING",
+    MessageTypeNonexist = 97 => "MESSAGE_TYPE_NONEXIST",
+    WrongMessage = 98 => "WRONG_MESSAGE",
+    IeNonexist = 99 => "IE_NONEXIST",
+    InvalidIeContents = 100 => "INVALID_IE_CONTENTS",
+    WrongCallState = 101 => "WRONG_CALL_STATE",
+    RecoveryOnTimerExpire = 102 => "RECOVERY_ON_TIMER_EXPIRE",
+    MandatoryIeLengthError = 103 => "MANDATORY_IE_LENGTH_ERROR",
+    ProtocolError = 111 => "PROTOCOL_ERROR",
+    Interworking = 127 => "INTERWORKING",
+    Success = 142 => "SUCCESS",
+    OriginatorCancel = 487 => "ORIGINATOR_CANCEL",
+    LoseRace = 502 => "LOSE_RACE",
+    ManagerRequest = 503 => "MANAGER_REQUEST",
+    BlindTransfer = 600 => "BLIND_TRANSFER",
+    AttendedTransfer = 601 => "ATTENDED_TRANSFER",
+    AllottedTimeout = 602 => "ALLOTTED_TIMEOUT",
+    UserChallenge = 603 => "USER_CHALLENGE",
+    MediaTimeout = 604 => "MEDIA_TIMEOUT",
+    PickedOff = 605 => "PICKED_OFF",
+    UserNotRegistered = 606 => "USER_NOT_REGISTERED",
+    ProgressTimeout = 607 => "PROGRESS_TIMEOUT",
+    InvalidGateway = 608 => "INVALID_GATEWAY",
+    GatewayDown = 609 => "GATEWAY_DOWN",
+    InvalidUrl = 610 => "INVALID_URL",
+    InvalidProfile = 611 => "INVALID_PROFILE",
+    NoPickup = 612 => "NO_PICKUP",
+    SrtpReadError = 613 => "SRTP_READ_ERROR",
+    Bowout = 614 => "BOWOUT",
+    BusyEverywhere = 615 => "BUSY_EVERYWHERE",
+    Decline = 616 => "DECLINE",
+    DoesNotExistAnywhere = 617 => "DOES_NOT_EXIST_ANYWHERE",
+    NotAcceptable = 618 => "NOT_ACCEPTABLE",
+    Unwanted = 619 => "UNWANTED",
+    NoIdentity = 620 => "NO_IDENTITY",
+    BadIdentityInfo = 621 => "BAD_IDENTITY_INFO",
+    UnsupportedCertificate = 622 => "UNSUPPORTED_CERTIFICATE",
+    InvalidIdentity = 623 => "INVALID_IDENTITY",
+    StaleDate = 624 => "STALE_DATE",
+    RejectAll = 625 => "REJECT_ALL",
+    Crash = 700 => "CRASH",
+    SystemShutdown = 701 => "SYSTEM_SHUTDOWN",
+}
+
+impl HangupCause {
+    /// Parse a `Hangup-Cause` header value, keeping unknown names as
+    /// [`Unknown`](Self::Unknown).
+    pub fn from_header(value: &str) -> Self {
+        value
+            .parse()
+            .unwrap_or_else(|_| Self::Unknown(value.to_string()))
+    }
+
+    /// SIP response status sent for this cause, following mod_sofia's
+    /// `hangup_cause_to_sip()`. Causes without a specific mapping give `480`.
+    pub fn to_sip_status(&self) -> u16 {
+        match self {
+            Self::UnallocatedNumber | Self::NoRouteTransitNet | Self::NoRouteDestination => 404,
+            Self::UserBusy => 486,
+            Self::NoUserResponse => 408,
+            Self::NoAnswer | Self::SubscriberAbsent | Self::NormalUnspecified => 480,
+            Self::CallRejected | Self::Decline | Self::RejectAll => 603,
+            Self::NumberChanged | Self::RedirectionToNewDestination => 410,
+            Self::NetworkOutOfOrder | Self::DestinationOutOfOrder | Self::InvalidProfile => 502,
+            Self::InvalidNumberFormat | Self::InvalidUrl | Self::InvalidGateway => 484,
+            Self::FacilityRejected | Self::FacilityNotImplemented | Self::ServiceNotImplemented => {
+                501
+            }
+            Self::RequestedChanUnavail
+            | Self::NormalCircuitCongestion
+            | Self::NormalTemporaryFailure
+            | Self::SwitchCongestion
+            | Self::GatewayDown
+            | Self::BearercapabilityNotavail => 503,
+            Self::OutgoingCallBarred
+            | Self::IncomingCallBarred
+            | Self::BearercapabilityNotauth
+            | Self::StaleDate => 403,
+            Self::BearercapabilityNotimpl | Self::IncompatibleDestination => 488,
+            Self::RecoveryOnTimerExpire => 504,
+            Self::OriginatorCancel => 487,
+            Self::ExchangeRoutingError => 483,
+            Self::BusyEverywhere => 600,
+            Self::DoesNotExistAnywhere => 604,
+            Self::NotAcceptable => 606,
+            Self::Unwanted => 607,
+            Self::NoIdentity => 428,
+            Self::BadIdentityInfo => 429,
+            Self::UnsupportedCertificate => 437,
+            Self::InvalidIdentity => 438,
+            _ => 480,
+        }
+    }
+
+    /// Hangup cause for a received SIP response status, following mod_sofia's
+    /// `sofia_glue_sip_cause_to_freeswitch()`. Unmapped statuses give
+    /// [`NormalUnspecified`](Self::NormalUnspecified).
+    pub fn from_sip_status(status: u16) -> Self {
+        match status {
+            200 => Self::NormalClearing,
+            401 | 402 | 403 | 407 | 603 | 608 => Self::CallRejected,
+            404 => Self::UnallocatedNumber,
+            485 | 604 => Self::NoRouteDestination,
+            408 | 504 => Self::RecoveryOnTimerExpire,
+            410 => Self::NumberChanged,
+            413 | 414 | 416 | 420 | 421 | 423 | 505 | 513 => Self::Interworking,
+            480 => Self::NoUserResponse,
+            400 | 481 | 500 | 503 => Self::NormalTemporaryFailure,
+            486 | 600 => Self::UserBusy,
+            484 => Self::InvalidNumberFormat,
+            488 | 606 => Self::IncompatibleDestination,
+            502 => Self::NetworkOutOfOrder,
+            405 => Self::ServiceUnavailable,
+            406 | 415 | 501 => Self::ServiceNotImplemented,
+            482 | 483 => Self::ExchangeRoutingError,
+            487 => Self::OriginatorCancel,
+            428 => Self::NoIdentity,
+            429 => Self::BadIdentityInfo,
+            437 => Self::UnsupportedCertificate,
+            438 => Self::InvalidIdentity,
+            607 => Self::Unwanted,
+            _ => Self::NormalUnspecified,
+        }
+    }
+}
+
+impl fmt::Display for HangupCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned when parsing an invalid hangup cause string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseHangupCauseError(pub String);
+
+impl fmt::Display for ParseHangupCauseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown hangup cause: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseHangupCauseError {}
+
+impl Serialize for HangupCause {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for HangupCause {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(Self::from_header(&value))
+    }
+}
+
+/// Parses known names and codes only; see [`HangupCause::from_header`] for
+/// a parse that keeps unknown names.
+impl FromStr for HangupCause {
+    type Err = ParseHangupCauseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cause = match s.parse::<u16>() {
+            Ok(n) => Self::from_number(n),
+            Err(_) => Self::from_name(&s.to_uppercase()),
+        };
+        cause.ok_or_else(|| ParseHangupCauseError(s.to_string()))
+    }
+}
+
 /// Channel timing data from FreeSWITCH's `switch_channel_timetable_t`.
 ///
 /// Timestamps are epoch microseconds (`i64`). A value of `0` means the
@@ -367,6 +634,110 @@ impl ChannelTimetable {
 mod tests {
     use super::*;
 
+    // --- HangupCause tests ---
+
+    #[test]
+    fn test_hangup_cause_display_and_codes() {
+        assert_eq!(HangupCause::NormalClearing.to_string(), "NORMAL_CLEARING");
+        assert_eq!(HangupCause::NormalClearing.as_number(), Some(16));
+        assert_eq!(HangupCause::UserBusy.as_number(), Some(17));
+        assert_eq!(HangupCause::Interworking.as_number(), Some(127));
+        assert_eq!(HangupCause::OriginatorCancel.as_number(), Some(487));
+        assert_eq!(HangupCause::from_number(19), Some(HangupCause::NoAnswer));
+        assert_eq!(HangupCause::from_number(4), None);
+    }
+
+    #[test]
+    fn test_hangup_cause_from_str() {
+        assert_eq!(
+            "NORMAL_CLEARING".parse::<HangupCause>(),
+            Ok(HangupCause::NormalClearing)
+        );
+        assert_eq!(
+            "user_busy".parse::<HangupCause>(),
+            Ok(HangupCause::UserBusy)
+        );
+        assert_eq!("17".parse::<HangupCause>(), Ok(HangupCause::UserBusy));
+        assert!("NOT_A_CAUSE"
+            .parse::<HangupCause>()
+            .is_err());
+        assert!("999"
+            .parse::<HangupCause>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_hangup_cause_from_header_keeps_unknown() {
+        assert_eq!(HangupCause::from_header("user_busy"), HangupCause::UserBusy);
+        let unknown = HangupCause::from_header("VENDOR_SPECIFIC");
+        assert_eq!(unknown, HangupCause::Unknown("VENDOR_SPECIFIC".to_string()));
+        assert_eq!(unknown.to_string(), "VENDOR_SPECIFIC");
+        assert_eq!(unknown.as_number(), None);
+        assert_eq!(unknown.to_sip_status(), 480);
+    }
+
+    #[test]
+    fn test_hangup_cause_serde_uses_wire_names() {
+        assert_eq!(
+            serde_json::to_string(&HangupCause::NormalClearing).unwrap(),
+            r#""NORMAL_CLEARING""#
+        );
+        assert_eq!(
+            serde_json::from_str::<HangupCause>(r#""USER_BUSY""#).unwrap(),
+            HangupCause::UserBusy
+        );
+        assert_eq!(
+            serde_json::from_str::<HangupCause>(r#""VENDOR_SPECIFIC""#).unwrap(),
+            HangupCause::Unknown("VENDOR_SPECIFIC".to_string())
+        );
+    }
+
+    #[test]
+    fn test_hangup_cause_round_trip_all_codes() {
+        for n in 0..=1000u16 {
+            if let Some(cause) = HangupCause::from_number(n) {
+                assert_eq!(cause.as_number(), Some(n));
+                assert_eq!(
+                    cause
+                        .to_string()
+                        .parse::<HangupCause>(),
+                    Ok(cause)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_hangup_cause_sip_mapping() {
+        assert_eq!(HangupCause::UserBusy.to_sip_status(), 486);
+        assert_eq!(HangupCause::NoRouteDestination.to_sip_status(), 404);
+        assert_eq!(HangupCause::OriginatorCancel.to_sip_status(), 487);
+        assert_eq!(HangupCause::NormalTemporaryFailure.to_sip_status(), 503);
+        assert_eq!(HangupCause::Crash.to_sip_status(), 480);
+
+        assert_eq!(HangupCause::from_sip_status(486), HangupCause::UserBusy);
+        assert_eq!(
+            HangupCause::from_sip_status(404),
+            HangupCause::UnallocatedNumber
+        );
+        assert_eq!(
+            HangupCause::from_sip_status(487),
+            HangupCause::OriginatorCancel
+        );
+        assert_eq!(
+            HangupCause::from_sip_status(699),
+            HangupCause::NormalUnspecified
+        );
+        // Busy and cancel survive a round trip through SIP
+        for cause in [
+            HangupCause::UserBusy,
+            HangupCause::OriginatorCancel,
+            HangupCause::IncompatibleDestination,
+        ] {
+            assert_eq!(HangupCause::from_sip_status(cause.to_sip_status()), cause);
+        }
+    }
+
     // --- ChannelState tests ---
 
     #[test]
@@ -5,7 +5,8 @@ use crate::{
     error::{EslError, EslResult},
     event::EslEvent,
 };
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Validate that a user-provided string contains no newline characters.
@@ -39,16 +40,43 @@ pub enum ReplyStatus {
 }
 
 /// Response from ESL command execution
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Serializes as `{"headers": {...}, "body": "..."}`; the reply status is
+/// derived again from `Reply-Text` when deserializing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "ResponseFields", from = "ResponseFields")]
 pub struct EslResponse {
-    headers: HashMap<String, String>,
+    headers: IndexMap<String, String>,
     body: Option<String>,
     status: ReplyStatus,
 }
 
+/// Serialized form of [`EslResponse`].
+#[derive(Serialize, Deserialize)]
+struct ResponseFields {
+    headers: IndexMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+impl From<ResponseFields> for EslResponse {
+    fn from(fields: ResponseFields) -> Self {
+        Self::new(fields.headers, fields.body)
+    }
+}
+
+impl From<EslResponse> for ResponseFields {
+    fn from(response: EslResponse) -> Self {
+        Self {
+            headers: response.headers,
+            body: response.body,
+        }
+    }
+}
+
 impl EslResponse {
     /// `ReplyStatus` is derived from the `Reply-Text` header.
-    pub fn new(headers: HashMap<String, String>, body: Option<String>) -> Self {
+    pub fn new(headers: IndexMap<String, String>, body: Option<String>) -> Self {
         let status = match headers
             .get(HEADER_REPLY_TEXT)
             .map(|s| s.as_str())
@@ -98,7 +126,7 @@ impl EslResponse {
     }
 
     /// All response headers.
-    pub fn headers(&self) -> &HashMap<String, String> {
+    pub fn headers(&self) -> &IndexMap<String, String> {
         &self.headers
     }
 
@@ -123,8 +151,8 @@ impl EslResponse {
     ///
     /// ```
     /// # use freeswitch_esl_tokio::EslResponse;
-    /// # use std::collections::HashMap;
-    /// let headers: HashMap<String, String> = [("Reply-Text".into(), "+OK".into())].into();
+    /// # use indexmap::IndexMap;
+    /// let headers: IndexMap<String, String> = [("Reply-Text".into(), "+OK".into())].into();
     /// let resp = EslResponse::new(headers, None);
     /// assert!(resp.into_result().is_ok());
     /// ```
@@ -149,6 +177,22 @@ impl EslResponse {
     }
 }
 
+/// Turn an `api/response` body starting with `-ERR` into an error.
+///
+/// FreeSWITCH answers every `api` command with a successful reply and reports
+/// failures in the body, so [`EslResponse::into_result`] cannot catch them.
+pub(crate) fn api_result(response: EslResponse) -> EslResult<String> {
+    let body = response.body_string();
+    if body.starts_with("-ERR") {
+        return Err(EslError::CommandFailed {
+            reply_text: body
+                .trim_end()
+                .to_string(),
+        });
+    }
+    Ok(body)
+}
+
 /// Builder for custom ESL commands not covered by [`EslClient`](crate::EslClient) methods.
 ///
 /// Produces the wire-format string including headers and optional body.
@@ -167,7 +211,7 @@ impl EslResponse {
 #[derive(Debug)]
 pub struct CommandBuilder {
     command: String,
-    headers: HashMap<String, String>,
+    headers: IndexMap<String, String>,
     body: Option<String>,
 }
 
@@ -176,7 +220,7 @@ impl CommandBuilder {
     pub fn new(command: &str) -> Self {
         Self {
             command: command.to_string(),
-            headers: HashMap::new(),
+            headers: IndexMap::new(),
             body: None,
         }
     }
@@ -232,7 +276,11 @@ pub enum EslCommand {
     /// Execute API command
     Api { command: String },
     /// Execute background API command
-    BgApi { command: String },
+    BgApi {
+        command: String,
+        /// Client-chosen `Job-UUID`; FreeSWITCH generates one when `None`
+        job_uuid: Option<String>,
+    },
     /// Subscribe to events
     Events { format: String, events: String },
     /// Set event filters
@@ -302,9 +350,10 @@ impl fmt::Debug for EslCommand {
                 .debug_struct("Api")
                 .field("command", command)
                 .finish(),
-            EslCommand::BgApi { command } => f
+            EslCommand::BgApi { command, job_uuid } => f
                 .debug_struct("BgApi")
                 .field("command", command)
+                .field("job_uuid", job_uuid)
                 .finish(),
             EslCommand::Events { format, events } => f
                 .debug_struct("Events")
@@ -403,9 +452,14 @@ impl EslCommand {
                 validate_no_newlines(command, "api command")?;
                 Ok(Self::format_simple_command("api", &[command]))
             }
-            EslCommand::BgApi { command } => {
+            EslCommand::BgApi { command, job_uuid } => {
                 validate_no_newlines(command, "bgapi command")?;
-                Ok(Self::format_simple_command("bgapi", &[command]))
+                match job_uuid {
+                    Some(uuid) => Ok(CommandBuilder::new(&format!("bgapi {}", command))
+                        .header(HEADER_JOB_UUID, uuid)?
+                        .build()),
+                    None => Ok(Self::format_simple_command("bgapi", &[command])),
+                }
             }
             EslCommand::Events { format, events } => {
                 validate_no_newlines(format, "event format")?;
@@ -543,8 +597,29 @@ impl EslCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::channel::HangupCause;
     use crate::event::EslEventType;
 
+    fn api_response(body: &str) -> EslResponse {
+        EslResponse::new(IndexMap::new(), Some(body.to_string()))
+    }
+
+    #[test]
+    fn test_api_result_passes_values_through() {
+        assert_eq!(api_result(api_response("+OK\n")).unwrap(), "+OK\n");
+        assert_eq!(api_result(api_response("1000")).unwrap(), "1000");
+    }
+
+    #[test]
+    fn test_api_result_maps_err_body() {
+        match api_result(api_response("-ERR No such channel!\n")) {
+            Err(EslError::CommandFailed { reply_text }) => {
+                assert_eq!(reply_text, "-ERR No such channel!")
+            }
+            other => panic!("expected CommandFailed, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_command_builder() {
         let cmd = CommandBuilder::new("api status")
@@ -591,6 +666,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bgapi_wire_format() {
+        let cmd = EslCommand::BgApi {
+            command: "status".to_string(),
+            job_uuid: None,
+        };
+        assert_eq!(
+            cmd.to_wire_format()
+                .unwrap(),
+            "bgapi status\n\n"
+        );
+
+        let cmd = EslCommand::BgApi {
+            command: "originate user/1000 &park".to_string(),
+            job_uuid: Some("d8c4a9e2-0000-4000-8000-000000000001".to_string()),
+        };
+        assert_eq!(
+            cmd.to_wire_format()
+                .unwrap(),
+            "bgapi originate user/1000 &park\nJob-UUID: d8c4a9e2-0000-4000-8000-000000000001\n\n"
+        );
+
+        let cmd = EslCommand::BgApi {
+            command: "status".to_string(),
+            job_uuid: Some("bad\nuuid".to_string()),
+        };
+        assert!(cmd
+            .to_wire_format()
+            .is_err());
+    }
+
     #[test]
     fn test_app_commands() {
         use crate::app::dptools::AppCommand;
@@ -600,7 +706,7 @@ mod tests {
             .unwrap();
         assert!(answer.contains("execute-app-name: answer"));
 
-        let hangup = AppCommand::hangup(Some("NORMAL_CLEARING"))
+        let hangup = AppCommand::hangup(Some(HangupCause::NormalClearing))
             .to_wire_format()
             .unwrap();
         assert!(hangup.contains("execute-app-name: hangup"));
@@ -873,7 +979,7 @@ mod tests {
 
     #[test]
     fn test_reply_status_ok() {
-        let headers: HashMap<String, String> =
+        let headers: IndexMap<String, String> =
             [("Reply-Text".into(), "+OK accepted".into())].into();
         let resp = EslResponse::new(headers, None);
         assert_eq!(resp.reply_status(), ReplyStatus::Ok);
@@ -885,7 +991,7 @@ mod tests {
 
     #[test]
     fn test_reply_status_ok_prefix_only() {
-        let headers: HashMap<String, String> = [("Reply-Text".into(), "+OK".into())].into();
+        let headers: IndexMap<String, String> = [("Reply-Text".into(), "+OK".into())].into();
         let resp = EslResponse::new(headers, None);
         assert_eq!(resp.reply_status(), ReplyStatus::Ok);
         assert!(resp.is_success());
@@ -893,7 +999,7 @@ mod tests {
 
     #[test]
     fn test_reply_status_empty() {
-        let headers: HashMap<String, String> = [("Reply-Text".into(), String::new())].into();
+        let headers: IndexMap<String, String> = [("Reply-Text".into(), String::new())].into();
         let resp = EslResponse::new(headers, None);
         assert_eq!(resp.reply_status(), ReplyStatus::Ok);
         assert!(resp.is_success());
@@ -901,14 +1007,14 @@ mod tests {
 
     #[test]
     fn test_reply_status_missing_header() {
-        let resp = EslResponse::new(HashMap::new(), None);
+        let resp = EslResponse::new(IndexMap::new(), None);
         assert_eq!(resp.reply_status(), ReplyStatus::Ok);
         assert!(resp.is_success());
     }
 
     #[test]
     fn test_reply_status_err() {
-        let headers: HashMap<String, String> =
+        let headers: IndexMap<String, String> =
             [("Reply-Text".into(), "-ERR invalid command".into())].into();
         let resp = EslResponse::new(headers, None);
         assert_eq!(resp.reply_status(), ReplyStatus::Err);
@@ -923,7 +1029,7 @@ mod tests {
 
     #[test]
     fn test_reply_status_err_bare() {
-        let headers: HashMap<String, String> = [("Reply-Text".into(), "-ERR".into())].into();
+        let headers: IndexMap<String, String> = [("Reply-Text".into(), "-ERR".into())].into();
         let resp = EslResponse::new(headers, None);
         assert_eq!(resp.reply_status(), ReplyStatus::Err);
         assert!(!resp.is_success());
@@ -931,7 +1037,7 @@ mod tests {
 
     #[test]
     fn test_reply_status_other_getvar() {
-        let headers: HashMap<String, String> =
+        let headers: IndexMap<String, String> =
             [("Reply-Text".into(), "sip_from_user".into())].into();
         let resp = EslResponse::new(headers, None);
         assert_eq!(resp.reply_status(), ReplyStatus::Other);
@@ -946,7 +1052,7 @@ mod tests {
 
     #[test]
     fn test_reply_status_other_random() {
-        let headers: HashMap<String, String> =
+        let headers: IndexMap<String, String> =
             [("Reply-Text".into(), "something unexpected".into())].into();
         let resp = EslResponse::new(headers, None);
         assert_eq!(resp.reply_status(), ReplyStatus::Other);
@@ -961,4 +1067,27 @@ mod tests {
         let result = CommandBuilder::new("test").header("X-Key", "bad\nvalue");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_response_serde_round_trip() {
+        let mut headers = IndexMap::new();
+        headers.insert("Content-Type".to_string(), "command/reply".to_string());
+        headers.insert("Reply-Text".to_string(), "-ERR invalid".to_string());
+        let response = EslResponse::new(headers, None);
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["headers"]["Reply-Text"], "-ERR invalid");
+        assert!(json
+            .get("body")
+            .is_none());
+
+        let back: EslResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(back, response);
+        assert_eq!(back.reply_status(), ReplyStatus::Err);
+
+        let api = EslResponse::new(IndexMap::new(), Some("+OK\n".to_string()));
+        let back: EslResponse =
+            serde_json::from_str(&serde_json::to_string(&api).unwrap()).unwrap();
+        assert_eq!(back.body(), Some("+OK\n"));
+    }
 }
@@ -2,6 +2,8 @@
 
 use std::fmt;
 
+use crate::channel::HangupCause;
+
 /// Answer a channel: `uuid_answer <uuid>`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UuidAnswer {
@@ -30,6 +32,25 @@ impl fmt::Display for UuidBridge {
     }
 }
 
+/// Interrupt the application running on a channel: `uuid_break <uuid> [all]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UuidBreak {
+    /// Channel UUID.
+    pub uuid: String,
+    /// `true` = also flush queued applications; `false` = stop the current one only.
+    pub all: bool,
+}
+
+impl fmt::Display for UuidBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.all {
+            write!(f, "uuid_break {} all", self.uuid)
+        } else {
+            write!(f, "uuid_break {}", self.uuid)
+        }
+    }
+}
+
 /// Deflect (redirect) a channel to a new SIP URI: `uuid_deflect <uuid> <uri>`.
 ///
 /// Sends a SIP REFER to the endpoint.
@@ -71,8 +92,8 @@ impl fmt::Display for UuidHold {
 pub struct UuidKill {
     /// Channel UUID.
     pub uuid: String,
-    /// Hangup cause string (e.g. `NORMAL_CLEARING`). If `None`, uses FreeSWITCH default.
-    pub cause: Option<String>,
+    /// Hangup cause. If `None`, uses FreeSWITCH default (`NORMAL_CLEARING`).
+    pub cause: Option<HangupCause>,
 }
 
 impl fmt::Display for UuidKill {
@@ -179,6 +200,20 @@ mod tests {
         assert_eq!(cmd.to_string(), format!("uuid_bridge {} {}", UUID, OTHER));
     }
 
+    #[test]
+    fn uuid_break() {
+        let cmd = UuidBreak {
+            uuid: UUID.into(),
+            all: false,
+        };
+        assert_eq!(cmd.to_string(), format!("uuid_break {}", UUID));
+        let cmd = UuidBreak {
+            uuid: UUID.into(),
+            all: true,
+        };
+        assert_eq!(cmd.to_string(), format!("uuid_break {} all", UUID));
+    }
+
     #[test]
     fn uuid_deflect() {
         let cmd = UuidDeflect {
@@ -222,7 +257,7 @@ mod tests {
     fn uuid_kill_with_cause() {
         let cmd = UuidKill {
             uuid: UUID.into(),
-            cause: Some("NORMAL_CLEARING".into()),
+            cause: Some(HangupCause::NormalClearing),
         };
         assert_eq!(
             cmd.to_string(),
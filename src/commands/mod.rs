@@ -11,8 +11,8 @@ pub mod conference;
 pub mod originate;
 
 pub use channel::{
-    UuidAnswer, UuidBridge, UuidDeflect, UuidGetVar, UuidHold, UuidKill, UuidSendDtmf, UuidSetVar,
-    UuidTransfer,
+    UuidAnswer, UuidBreak, UuidBridge, UuidDeflect, UuidGetVar, UuidHold, UuidKill, UuidSendDtmf,
+    UuidSetVar, UuidTransfer,
 };
 pub use conference::{ConferenceDtmf, ConferenceHold, ConferenceMute, HoldAction, MuteAction};
 pub use originate::{
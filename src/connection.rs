@@ -1,31 +1,67 @@
 //! Connection management for ESL
 
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{mpsc, oneshot, watch, Mutex};
 use tokio::time::{timeout, Instant};
 use tracing::{debug, info, trace, warn};
 
 use crate::{
-    command::{EslCommand, EslResponse},
+    command::{api_result, EslCommand, EslResponse},
+    commands::UuidBreak,
     constants::*,
     error::{EslError, EslResult},
-    event::{EslEvent, EslEventType, EventFormat},
+    event::{EslEvent, EslEventType, EventFormat, EventKind},
+    filter::{EventFilter, FilteredEventStream},
+    log::{EslLogStream, LogLine},
     protocol::{EslMessage, EslParser, MessageType},
+    queue::{self, PushError, QueueMonitor, QueueReceiver, QueueSender},
+    session::ChannelSession,
+    subscription::{SubscriptionChange, SubscriptionState},
 };
 
-fn event_types_to_string(events: &[EslEventType]) -> String {
-    events
+/// Item delivered on [`EslEventStream`].
+type EventItem = Result<EslEvent, EslError>;
+
+/// Join events for an `event`/`nixevent` command.
+///
+/// `ALL` absorbs the other event types. `CUSTOM` and its subclasses always
+/// come last: FreeSWITCH reads every name after `CUSTOM` as a subclass.
+pub(crate) fn event_types_to_string<E: Copy + Into<EventKind>>(events: &[E]) -> String {
+    let mut types = Vec::new();
+    let mut subclasses = Vec::new();
+    let mut custom = false;
+    for event in events {
+        match (*event).into() {
+            EventKind::Type(EslEventType::Custom) => custom = true,
+            EventKind::Type(event_type) => types.push(event_type.to_string()),
+            EventKind::Subclass(subclass) => subclasses.push(subclass.as_str()),
+        }
+    }
+    if types
         .iter()
-        .map(|e| e.to_string())
-        .collect::<Vec<_>>()
-        .join(" ")
+        .any(|t| t == "ALL")
+    {
+        // ALL covers every subclass; they are listed only to be recorded
+        types = vec!["ALL".to_string()];
+        custom = false;
+    }
+    if custom || !subclasses.is_empty() {
+        types.push("CUSTOM".to_string());
+        types.extend(
+            subclasses
+                .iter()
+                .map(|s| s.to_string()),
+        );
+    }
+    types.join(" ")
 }
 
 /// Connection status for ESL client
@@ -52,6 +88,8 @@ pub enum DisconnectReason {
     ConnectionClosed,
     /// Client called disconnect()
     ClientRequested,
+    /// A reply did not match the oldest pending command
+    Desync(String),
 }
 
 impl std::fmt::Display for DisconnectReason {
@@ -62,6 +100,7 @@ impl std::fmt::Display for DisconnectReason {
             DisconnectReason::IoError(msg) => write!(f, "I/O error: {}", msg),
             DisconnectReason::ConnectionClosed => write!(f, "connection closed"),
             DisconnectReason::ClientRequested => write!(f, "client requested disconnect"),
+            DisconnectReason::Desync(msg) => write!(f, "reply desync: {}", msg),
         }
     }
 }
@@ -107,17 +146,500 @@ pub enum ConnectionMode {
 /// Default command timeout in milliseconds (5 seconds)
 const DEFAULT_COMMAND_TIMEOUT_MS: u64 = 5000;
 
+/// Default `bgapi` job timeout in milliseconds (2 minutes)
+const DEFAULT_JOB_TIMEOUT_MS: u64 = 120_000;
+
+/// Event subscription facts the reader task needs for `BACKGROUND_JOB` routing.
+#[derive(Debug)]
+struct JobSubscription {
+    /// Format of the last `event`/`myevents` command; FreeSWITCH applies it
+    /// to the whole connection.
+    format: EventFormat,
+    /// The application subscribed to `BACKGROUND_JOB` (directly or via `ALL`).
+    user_requested: bool,
+    /// The library subscribed to `BACKGROUND_JOB` on its own for `bgapi_job`.
+    auto_subscribed: bool,
+}
+
+/// Whether an event list (as sent with `event`/`nixevent`) covers `BACKGROUND_JOB`.
+///
+/// Tokens after `CUSTOM` are subclass names and never match.
+fn event_list_has_background_job(events: &str) -> bool {
+    events
+        .split_whitespace()
+        .take_while(|e| !e.eq_ignore_ascii_case("CUSTOM"))
+        .any(|e| e.eq_ignore_ascii_case("ALL") || e.eq_ignore_ascii_case("BACKGROUND_JOB"))
+}
+
+/// A written command waiting for its `command/reply` or `api/response`.
+struct PendingReply {
+    id: u64,
+    /// `api` is answered with `api/response`, everything else with `command/reply`
+    expects_api_response: bool,
+    /// Closed once the caller times out or is dropped; the reply is then an
+    /// orphan and is discarded.
+    tx: oneshot::Sender<EslResult<EslMessage>>,
+}
+
+/// An `execute_and_wait` application waiting for its `CHANNEL_EXECUTE_COMPLETE`.
+struct PendingExecution {
+    /// Channel the application runs on
+    uuid: String,
+    /// Completions still expected (`loops`)
+    remaining: u32,
+    /// Most recent completion, handed over if the channel goes away early
+    last: Option<EslEvent>,
+    tx: oneshot::Sender<EslResult<EslEvent>>,
+}
+
 /// Shared state between EslClient and the reader task
 struct SharedState {
-    pending_reply: Mutex<Option<oneshot::Sender<EslMessage>>>,
+    /// Replies owed by the server, in wire order; `None` once the reader exits
+    pending_replies: std::sync::Mutex<Option<VecDeque<PendingReply>>>,
+    next_command_id: AtomicU64,
     /// Liveness timeout in milliseconds (0 = disabled)
     liveness_timeout_ms: AtomicU64,
     /// Command response timeout in milliseconds
     command_timeout_ms: AtomicU64,
+    /// What the reader does when the event queue is full
+    backpressure: BackpressurePolicy,
+    event_queue: QueueMonitor<EventItem>,
+    /// Times the reader waited for room in the event queue
+    backpressure_waits: AtomicU64,
     /// Set when events have been dropped due to a full queue
     event_overflow: AtomicBool,
     /// Total count of dropped events
     dropped_event_count: AtomicU64,
+    /// `bgapi_job` waiters keyed by Job-UUID
+    pending_jobs: std::sync::Mutex<HashMap<String, oneshot::Sender<EslEvent>>>,
+    /// `bgapi_job` result timeout in milliseconds
+    job_timeout_ms: AtomicU64,
+    job_subscription: std::sync::Mutex<JobSubscription>,
+    /// `execute_and_wait` waiters keyed by Application-UUID
+    pending_executions: std::sync::Mutex<HashMap<String, PendingExecution>>,
+    /// Recorded `event`/`filter` commands, see [`EslClient::subscriptions`]
+    subscriptions: std::sync::Mutex<SubscriptionState>,
+    /// [`ChannelSession`] routes keyed by Unique-ID; `None` once the reader exits
+    sessions: std::sync::Mutex<Option<HashMap<String, Vec<SessionRoute>>>>,
+    next_session_id: AtomicU64,
+    /// [`FilteredEventStream`] routes; `None` once the reader exits
+    subscribers: std::sync::Mutex<Option<Vec<SubscriberRoute>>>,
+    /// Capacity of each session's and the log stream's channel
+    route_queue_size: usize,
+    /// Set when `connect` reported `Control: single-channel`
+    single_channel: AtomicBool,
+    log_sink: std::sync::Mutex<LogSink>,
+}
+
+/// Destination of `log/data` lines.
+enum LogSink {
+    /// No [`EslLogStream`] requested; lines are dropped.
+    Idle,
+    Active(mpsc::Sender<LogLine>),
+    /// The reader task has exited.
+    Closed,
+}
+
+/// Sender side of one [`ChannelSession`].
+struct SessionRoute {
+    id: u64,
+    tx: mpsc::Sender<EslEvent>,
+}
+
+/// A [`FilteredEventStream`] receiving copies of matching events.
+struct SubscriberRoute {
+    filter: EventFilter,
+    tx: mpsc::Sender<EventItem>,
+    /// Events skipped since the last `Lagged` notice was queued
+    missed: u64,
+    /// Total skipped, shared with the receiver
+    skipped: Arc<AtomicU64>,
+}
+
+impl SharedState {
+    fn job_subscription(&self) -> std::sync::MutexGuard<'_, JobSubscription> {
+        self.job_subscription
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn subscriptions(&self) -> std::sync::MutexGuard<'_, SubscriptionState> {
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn pending_replies(&self) -> std::sync::MutexGuard<'_, Option<VecDeque<PendingReply>>> {
+        self.pending_replies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Hand a reply to the oldest written command. Replies arrive in command
+    /// order, so the front of the queue always owns the next one.
+    ///
+    /// Returns a description of the mismatch if the reply cannot belong to
+    /// that command.
+    fn route_reply(&self, message: EslMessage) -> Result<(), String> {
+        let pending = self
+            .pending_replies()
+            .as_mut()
+            .and_then(VecDeque::pop_front);
+        let Some(pending) = pending else {
+            return Err(format!(
+                "received {:?} with no pending command",
+                message.message_type
+            ));
+        };
+        let is_api_response = message.message_type == MessageType::ApiResponse;
+        if is_api_response != pending.expects_api_response {
+            let error = format!(
+                "command {} expected {} but received {:?}",
+                pending.id,
+                if pending.expects_api_response {
+                    "api/response"
+                } else {
+                    "command/reply"
+                },
+                message.message_type
+            );
+            let _ = pending
+                .tx
+                .send(Err(EslError::Desync {
+                    message: error.clone(),
+                }));
+            return Err(error);
+        }
+        if pending
+            .tx
+            .send(Ok(message))
+            .is_err()
+        {
+            debug!("Discarding orphaned reply for command {}", pending.id);
+        }
+        Ok(())
+    }
+
+    /// Fail every in-flight command after a desync.
+    fn fail_pending_replies(&self, message: &str) {
+        if let Some(queue) = self
+            .pending_replies()
+            .take()
+        {
+            for pending in queue {
+                let _ = pending
+                    .tx
+                    .send(Err(EslError::Desync {
+                        message: message.to_string(),
+                    }));
+            }
+        }
+    }
+
+    /// Withdraw a queued command whose write failed.
+    fn cancel_reply(&self, id: u64) {
+        if let Some(queue) = self
+            .pending_replies()
+            .as_mut()
+        {
+            queue.retain(|p| p.id != id);
+        }
+    }
+
+    fn pending_jobs(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<String, oneshot::Sender<EslEvent>>> {
+        self.pending_jobs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn pending_executions(&self) -> std::sync::MutexGuard<'_, HashMap<String, PendingExecution>> {
+        self.pending_executions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, Option<HashMap<String, Vec<SessionRoute>>>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn subscribers(&self) -> std::sync::MutexGuard<'_, Option<Vec<SubscriberRoute>>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Copy an event to every [`FilteredEventStream`] whose filter matches.
+    ///
+    /// Each receiver's channel has one slot more than its capacity, kept free
+    /// for a `Lagged` notice: an event is only queued while two slots are
+    /// free, so after a gap the notice and the next event always go in
+    /// together. Routes whose receiver was dropped are removed.
+    fn route_to_subscribers(&self, event: &EslEvent) {
+        let mut guard = self.subscribers();
+        let Some(routes) = guard.as_mut() else {
+            return;
+        };
+        routes.retain(|route| {
+            !route
+                .tx
+                .is_closed()
+        });
+        for route in routes
+            .iter_mut()
+            .filter(|route| {
+                route
+                    .filter
+                    .matches(event)
+            })
+        {
+            if route
+                .tx
+                .capacity()
+                < 2
+            {
+                route.missed += 1;
+                route
+                    .skipped
+                    .fetch_add(1, Ordering::Relaxed);
+                debug!("Subscriber queue full, skipping event");
+                continue;
+            }
+            if route.missed > 0 {
+                let _ = route
+                    .tx
+                    .try_send(Err(EslError::Lagged {
+                        count: route.missed,
+                    }));
+                route.missed = 0;
+            }
+            let _ = route
+                .tx
+                .try_send(Ok(event.clone()));
+        }
+    }
+
+    fn log_sink(&self) -> std::sync::MutexGuard<'_, LogSink> {
+        self.log_sink
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Hand a log line to the [`EslLogStream`], if one is open.
+    fn route_log(&self, line: LogLine) {
+        let mut sink = self.log_sink();
+        let LogSink::Active(tx) = &*sink else {
+            trace!("No log stream, dropping log line");
+            return;
+        };
+        match tx.try_send(line) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                debug!("Log queue full, dropping log line");
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => *sink = LogSink::Idle,
+        }
+    }
+
+    /// Copy a channel event to every [`ChannelSession`] open on its Unique-ID.
+    ///
+    /// `CHANNEL_DESTROY` is the last event routed; the routes are removed
+    /// afterwards, which ends the sessions' streams.
+    fn route_to_sessions(&self, event: &EslEvent) {
+        let Some(uuid) = event.unique_id() else {
+            return;
+        };
+        let mut guard = self.sessions();
+        let Some(sessions) = guard.as_mut() else {
+            return;
+        };
+        let Some(routes) = sessions.get_mut(uuid) else {
+            return;
+        };
+        routes.retain(|route| {
+            match route
+                .tx
+                .try_send(event.clone())
+            {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    warn!("Session queue full for {}, dropping event", uuid);
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            }
+        });
+        if routes.is_empty() || event.is_event_type(EslEventType::ChannelDestroy) {
+            debug!("Closing session routes for {}", uuid);
+            sessions.remove(uuid);
+        }
+    }
+
+    /// Hand a `CHANNEL_EXECUTE_COMPLETE` to its `execute_and_wait` waiter.
+    ///
+    /// With `loops`, the waiter resolves on the last completion. A
+    /// `CHANNEL_DESTROY` resolves the channel's remaining waiters with their
+    /// latest completion, or fails them if none arrived. The event itself
+    /// still reaches the event stream.
+    fn route_execution(&self, event: &EslEvent) {
+        let mut pending = self.pending_executions();
+        if pending.is_empty() {
+            return;
+        }
+        if event.is_event_type(EslEventType::ChannelExecuteComplete) {
+            let Some(app_uuid) = event.header(HEADER_APPLICATION_UUID) else {
+                return;
+            };
+            let Some(waiter) = pending.get_mut(app_uuid) else {
+                return;
+            };
+            waiter.remaining = waiter
+                .remaining
+                .saturating_sub(1);
+            if waiter.remaining > 0 {
+                waiter.last = Some(event.clone());
+                return;
+            }
+            if let Some(waiter) = pending.remove(app_uuid) {
+                let _ = waiter
+                    .tx
+                    .send(Ok(event.clone()));
+            }
+        } else if event.is_event_type(EslEventType::ChannelDestroy) {
+            let Some(uuid) = event.unique_id() else {
+                return;
+            };
+            let ended: Vec<String> = pending
+                .iter()
+                .filter(|(_, waiter)| waiter.uuid == uuid)
+                .map(|(app_uuid, _)| app_uuid.clone())
+                .collect();
+            for app_uuid in ended {
+                let Some(waiter) = pending.remove(&app_uuid) else {
+                    continue;
+                };
+                let result = waiter
+                    .last
+                    .ok_or_else(|| EslError::Generic {
+                        message: format!("channel {} destroyed before application completed", uuid),
+                    });
+                let _ = waiter
+                    .tx
+                    .send(result);
+            }
+        }
+    }
+
+    /// Hand a `BACKGROUND_JOB` event to its `bgapi_job` waiter.
+    ///
+    /// Returns the event back when it should also reach the event stream:
+    /// when it is not a `BACKGROUND_JOB`, or the application subscribed to it.
+    fn route_background_job(&self, event: EslEvent) -> Option<EslEvent> {
+        if !event.is_event_type(EslEventType::BackgroundJob) {
+            return Some(event);
+        }
+        let user_requested = self
+            .job_subscription()
+            .user_requested;
+        let waiter = event
+            .job_uuid()
+            .and_then(|uuid| {
+                self.pending_jobs()
+                    .remove(uuid)
+            });
+        match waiter {
+            Some(tx) if user_requested => {
+                let _ = tx.send(event.clone());
+                Some(event)
+            }
+            Some(tx) => {
+                let _ = tx.send(event);
+                None
+            }
+            None if user_requested => Some(event),
+            None => {
+                debug!("Dropping unrequested BACKGROUND_JOB event");
+                None
+            }
+        }
+    }
+}
+
+/// What the reader task does with an event when the event queue is full.
+///
+/// Every policy except [`Block`](Self::Block) counts dropped events in
+/// [`EslClient::dropped_event_count`] and reports them with an
+/// `Err(EslError::QueueFull)` on the stream. Queue depth is available from
+/// [`EslClient::event_queue_stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum BackpressurePolicy {
+    /// Drop the incoming event (default).
+    #[default]
+    DropNewest,
+    /// Drop the oldest queued event to make room.
+    DropOldest,
+    /// Stop reading the socket until there is room, pushing back on
+    /// FreeSWITCH through TCP. Nothing is dropped, but command replies are
+    /// not read either while the reader waits: commands time out if the
+    /// application stops draining events.
+    Block,
+    /// Never drop the listed event types: a critical event evicts the oldest
+    /// non-critical queued event, and waits like [`Block`](Self::Block) only
+    /// when the queue holds nothing but critical events. Other events are
+    /// dropped like [`DropNewest`](Self::DropNewest).
+    Priority {
+        /// Event types that must not be dropped, e.g. `CHANNEL_HANGUP_COMPLETE`.
+        critical: Vec<EslEventType>,
+    },
+}
+
+impl BackpressurePolicy {
+    fn is_critical(&self, item: &EventItem) -> bool {
+        match (self, item) {
+            (BackpressurePolicy::Priority { critical }, Ok(event)) => event
+                .event_type()
+                .is_some_and(|t| critical.contains(&t)),
+            _ => false,
+        }
+    }
+}
+
+/// Snapshot of the event queue for monitoring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EventQueueStats {
+    /// Items currently waiting to be received.
+    pub depth: usize,
+    /// Highest depth seen since the connection started.
+    pub max_depth: usize,
+    /// Queue capacity ([`EslConnectOptions::event_queue_size`]).
+    pub capacity: usize,
+    /// Times the reader waited for room ([`BackpressurePolicy::Block`] and
+    /// [`BackpressurePolicy::Priority`]).
+    pub reader_waits: u64,
+}
+
+/// Options for [`EslClient::execute_and_wait`].
+#[derive(Debug, Clone)]
+pub struct ExecuteOptions {
+    /// Send `event-lock: true` so the channel runs the application before
+    /// processing further messages. Default: `false`.
+    pub event_lock: bool,
+    /// Run the application this many times (`loops` header). Default: 1.
+    pub loops: u32,
+}
+
+impl Default for ExecuteOptions {
+    fn default() -> Self {
+        Self {
+            event_lock: false,
+            loops: 1,
+        }
+    }
 }
 
 /// Options for ESL connection configuration.
@@ -126,14 +648,27 @@ struct SharedState {
 /// queue capacity. Use [`Default::default()`] for standard settings.
 #[derive(Debug, Clone)]
 pub struct EslConnectOptions {
-    /// Capacity of the mpsc channel delivering events. Default: 1000.
+    /// Capacity of the queue delivering events. Default: 1000.
     pub event_queue_size: usize,
+    /// Behaviour when the event queue is full. Default: [`BackpressurePolicy::DropNewest`].
+    pub backpressure: BackpressurePolicy,
+    /// Connect over TLS (inbound mode). Default: `None`.
+    #[cfg(feature = "tls")]
+    pub tls: Option<crate::tls::TlsConnectOptions>,
+    /// Expect TLS on accepted outbound connections. Default: `None`.
+    #[cfg(feature = "tls")]
+    pub tls_accept: Option<crate::tls::TlsAcceptOptions>,
 }
 
 impl Default for EslConnectOptions {
     fn default() -> Self {
         Self {
             event_queue_size: MAX_EVENT_QUEUE_SIZE,
+            backpressure: BackpressurePolicy::default(),
+            #[cfg(feature = "tls")]
+            tls: None,
+            #[cfg(feature = "tls")]
+            tls_accept: None,
         }
     }
 }
@@ -146,11 +681,11 @@ enum AuthMethod<'a> {
 
 /// ESL client handle (Clone + Send)
 ///
-/// Commands are serialized through the writer mutex. The reader task
-/// routes replies to the pending oneshot channel.
+/// Commands are written through the writer mutex and queued for their reply;
+/// the reader task hands replies to the queued oneshot channels in order.
 #[derive(Clone)]
 pub struct EslClient {
-    writer: Arc<Mutex<OwnedWriteHalf>>,
+    writer: Arc<Mutex<EslWriter>>,
     shared: Arc<SharedState>,
     status_rx: watch::Receiver<ConnectionStatus>,
 }
@@ -165,13 +700,13 @@ impl std::fmt::Debug for EslClient {
 
 /// Event stream receiver (!Clone)
 ///
-/// Receives events from the background reader task via an mpsc channel.
+/// Receives events from the background reader task via a bounded queue.
 ///
 /// Events are delivered as `Result<EslEvent, EslError>`. An `Err(EslError::QueueFull)`
-/// indicates that one or more events were dropped because the application fell behind.
-/// Use [`EslClient::dropped_event_count`] for the exact count.
+/// indicates that one or more events were dropped because the application fell behind
+/// (see [`BackpressurePolicy`]). Use [`EslClient::dropped_event_count`] for the exact count.
 pub struct EslEventStream {
-    rx: mpsc::Receiver<Result<EslEvent, EslError>>,
+    rx: QueueReceiver<EventItem>,
     status_rx: watch::Receiver<ConnectionStatus>,
 }
 
@@ -183,16 +718,19 @@ impl std::fmt::Debug for EslEventStream {
     }
 }
 
+/// Write half of the transport, type-erased so [`EslClient`] is the same
+/// type over TCP and TLS.
+type EslWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
 /// Read a single ESL message from the socket into the parser.
 ///
-/// Used during auth handshake (on unsplit TcpStream) and would be the
+/// Used during auth handshake (on the unsplit stream) and would be the
 /// basis for the reader loop, but the reader loop inlines this logic
 /// to handle liveness tracking.
-async fn recv_message(
-    stream: &mut TcpStream,
-    parser: &mut EslParser,
-    read_buffer: &mut [u8],
-) -> EslResult<EslMessage> {
+async fn recv_message<S>(stream: &mut S, parser: &mut EslParser) -> EslResult<EslMessage>
+where
+    S: AsyncRead + Unpin,
+{
     loop {
         if let Some(message) = parser.parse_message()? {
             trace!(
@@ -205,13 +743,12 @@ async fn recv_message(
         trace!("[RECV] Buffer needs more data, reading from socket");
         let read_result = timeout(
             Duration::from_millis(DEFAULT_TIMEOUT_MS),
-            stream.read(read_buffer),
+            parser.read_from(stream),
         )
         .await;
 
         let bytes_read = match read_result {
-            Ok(Ok(n)) => n,
-            Ok(Err(e)) => return Err(EslError::Io(e)),
+            Ok(result) => result?,
             Err(_) => {
                 return Err(EslError::Timeout {
                     timeout_ms: DEFAULT_TIMEOUT_MS,
@@ -223,20 +760,20 @@ async fn recv_message(
         if bytes_read == 0 {
             return Err(EslError::ConnectionClosed);
         }
-
-        parser.add_data(&read_buffer[..bytes_read])?;
     }
 }
 
 /// Perform authentication on the stream.
-async fn authenticate(
-    stream: &mut TcpStream,
+async fn authenticate<S>(
+    stream: &mut S,
     parser: &mut EslParser,
-    read_buffer: &mut [u8],
     method: AuthMethod<'_>,
-) -> EslResult<()> {
+) -> EslResult<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
     debug!("[AUTH] Waiting for auth request from FreeSWITCH");
-    let message = recv_message(stream, parser, read_buffer).await?;
+    let message = recv_message(stream, parser).await?;
 
     if message.message_type != MessageType::AuthRequest {
         return Err(EslError::protocol_error("Expected auth request"));
@@ -259,7 +796,7 @@ async fn authenticate(
         .await
         .map_err(EslError::Io)?;
 
-    let response_msg = recv_message(stream, parser, read_buffer).await?;
+    let response_msg = recv_message(stream, parser).await?;
     let response = response_msg.into_response();
 
     if !response.is_success() {
@@ -275,59 +812,95 @@ async fn authenticate(
     Ok(())
 }
 
-/// Try to send an event (or error) to the application via try_send.
+/// Queue an event (or error) for the application according to the
+/// connection's [`BackpressurePolicy`].
 ///
-/// If the channel is full, drop the item, set the overflow flag, and
-/// increment the dropped counter. Before each dispatch, check the overflow
-/// flag and attempt to deliver a QueueFull error notification first.
-fn dispatch_event(
-    event_tx: &mpsc::Sender<Result<EslEvent, EslError>>,
+/// When an item is dropped, set the overflow flag and increment the dropped
+/// counter. Before each dispatch, check the overflow flag and attempt to
+/// deliver a QueueFull error notification first. Returns `false` once the
+/// event stream is gone.
+async fn dispatch_event(
+    event_tx: &QueueSender<EventItem>,
     shared: &SharedState,
-    item: Result<EslEvent, EslError>,
+    item: EventItem,
 ) -> bool {
     if shared
         .event_overflow
         .load(Ordering::Relaxed)
     {
-        match event_tx.try_send(Err(EslError::QueueFull)) {
+        match event_tx.try_push(Err(EslError::QueueFull)) {
             Ok(()) => {
                 shared
                     .event_overflow
                     .store(false, Ordering::Relaxed);
             }
-            Err(mpsc::error::TrySendError::Closed(_)) => return false,
-            Err(mpsc::error::TrySendError::Full(_)) => {}
+            Err(PushError::Closed(_)) => return false,
+            Err(PushError::Full(_)) => {}
         }
     }
 
-    match event_tx.try_send(item) {
-        Ok(()) => true,
-        Err(mpsc::error::TrySendError::Closed(_)) => false,
-        Err(mpsc::error::TrySendError::Full(_)) => {
-            shared
-                .event_overflow
-                .store(true, Ordering::Relaxed);
-            shared
-                .dropped_event_count
-                .fetch_add(1, Ordering::Relaxed);
-            warn!("Event queue full, dropping event");
+    let critical = shared
+        .backpressure
+        .is_critical(&item);
+    let pushed = match &shared.backpressure {
+        // Only events are evicted; a queued QueueFull notice or parse error stays
+        BackpressurePolicy::DropOldest => event_tx.push_evicting(item, Result::is_ok),
+        BackpressurePolicy::Priority { .. } if critical => event_tx.push_evicting(item, |queued| {
+            queued.is_ok()
+                && !shared
+                    .backpressure
+                    .is_critical(queued)
+        }),
+        _ => event_tx
+            .try_push(item)
+            .map(|()| None),
+    };
+    match pushed {
+        Ok(None) => true,
+        Ok(Some(_evicted)) => {
+            record_dropped_event(shared);
+            true
+        }
+        Err(PushError::Closed(_)) => false,
+        Err(PushError::Full(item)) => {
+            if critical || shared.backpressure == BackpressurePolicy::Block {
+                shared
+                    .backpressure_waits
+                    .fetch_add(1, Ordering::Relaxed);
+                debug!("Event queue full, waiting for the application");
+                return event_tx
+                    .push(item)
+                    .await
+                    .is_ok();
+            }
+            record_dropped_event(shared);
             true
         }
     }
 }
 
+fn record_dropped_event(shared: &SharedState) {
+    shared
+        .event_overflow
+        .store(true, Ordering::Relaxed);
+    shared
+        .dropped_event_count
+        .fetch_add(1, Ordering::Relaxed);
+    warn!("Event queue full, dropping event");
+}
+
 /// Background reader loop
-async fn reader_loop(
-    reader: OwnedReadHalf,
+async fn reader_loop<R: AsyncRead + Unpin>(
+    reader: R,
     parser: EslParser,
     shared: Arc<SharedState>,
     status_tx: watch::Sender<ConnectionStatus>,
-    event_tx: mpsc::Sender<Result<EslEvent, EslError>>,
+    event_tx: QueueSender<EventItem>,
 ) {
     let result = std::panic::AssertUnwindSafe(reader_loop_inner(
         reader,
         parser,
-        shared,
+        shared.clone(),
         status_tx.clone(),
         event_tx,
     ));
@@ -340,16 +913,28 @@ async fn reader_loop(
             "reader task panicked".to_string(),
         )));
     }
+    // Dropping the senders fails every in-flight command, outstanding BgJob and
+    // AppExecution with ConnectionClosed and ends every ChannelSession,
+    // FilteredEventStream and the log stream.
+    *shared.pending_replies() = None;
+    shared
+        .pending_jobs()
+        .clear();
+    shared
+        .pending_executions()
+        .clear();
+    *shared.sessions() = None;
+    *shared.subscribers() = None;
+    *shared.log_sink() = LogSink::Closed;
 }
 
-async fn reader_loop_inner(
-    mut reader: OwnedReadHalf,
+async fn reader_loop_inner<R: AsyncRead + Unpin>(
+    mut reader: R,
     mut parser: EslParser,
     shared: Arc<SharedState>,
     status_tx: watch::Sender<ConnectionStatus>,
-    event_tx: mpsc::Sender<Result<EslEvent, EslError>>,
+    event_tx: QueueSender<EventItem>,
 ) {
-    let mut read_buffer = [0u8; SOCKET_BUF_SIZE];
     let mut last_recv = Instant::now();
 
     loop {
@@ -364,24 +949,44 @@ async fn reader_loop_inner(
                             .map(|ct| EventFormat::from_content_type(ct))
                             .unwrap_or(EventFormat::Plain);
 
-                        let event_result = parser.parse_event(message, format);
-                        if !dispatch_event(&event_tx, &shared, event_result) {
+                        let event_result = match parser.parse_event(message, format) {
+                            Ok(event) => {
+                                shared.route_execution(&event);
+                                match shared.route_background_job(event) {
+                                    Some(event) => {
+                                        shared.route_to_sessions(&event);
+                                        shared.route_to_subscribers(&event);
+                                        Ok(event)
+                                    }
+                                    None => continue,
+                                }
+                            }
+                            Err(e) => Err(e),
+                        };
+                        if !dispatch_event(&event_tx, &shared, event_result).await {
                             debug!("Event channel closed, reader exiting");
                             return;
                         }
+                        // Time spent waiting for queue room is not silence
+                        last_recv = Instant::now();
+                    }
+                    MessageType::Log => {
+                        match message
+                            .body_str()
+                            .and_then(|body| LogLine::from_parts(&message.headers, body))
+                        {
+                            Ok(line) => shared.route_log(line),
+                            Err(e) => warn!("Invalid log/data message: {}", e),
+                        }
                     }
                     MessageType::CommandReply | MessageType::ApiResponse => {
-                        let mut pending = shared
-                            .pending_reply
-                            .lock()
-                            .await;
-                        if let Some(tx) = pending.take() {
-                            let _ = tx.send(message);
-                        } else {
-                            warn!(
-                                "Received {:?} but no pending command",
-                                "CommandReply/ApiResponse"
-                            );
+                        if let Err(reason) = shared.route_reply(message) {
+                            warn!("Reply desync, closing connection: {}", reason);
+                            shared.fail_pending_replies(&reason);
+                            let _ = status_tx.send(ConnectionStatus::Disconnected(
+                                DisconnectReason::Desync(reason),
+                            ));
+                            return;
                         }
                     }
                     MessageType::Disconnect => {
@@ -418,7 +1023,7 @@ async fn reader_loop_inner(
         }
 
         // Read from socket with 2s timeout (for liveness checking)
-        let read_result = timeout(Duration::from_secs(2), reader.read(&mut read_buffer)).await;
+        let read_result = timeout(Duration::from_secs(2), parser.read_from(&mut reader)).await;
 
         match read_result {
             Ok(Ok(0)) => {
@@ -428,15 +1033,8 @@ async fn reader_loop_inner(
                 ));
                 return;
             }
-            Ok(Ok(n)) => {
+            Ok(Ok(_)) => {
                 last_recv = Instant::now();
-                if let Err(e) = parser.add_data(&read_buffer[..n]) {
-                    warn!("Buffer error: {}", e);
-                    let _ = status_tx.send(ConnectionStatus::Disconnected(
-                        DisconnectReason::IoError(e.to_string()),
-                    ));
-                    return;
-                }
             }
             Ok(Err(e)) => {
                 warn!("Read error: {}", e);
@@ -526,6 +1124,58 @@ impl EslClient {
         Self::connect_inner(host, port, AuthMethod::User { user, password }, options).await
     }
 
+    /// Authenticate over an existing transport (inbound mode).
+    ///
+    /// Accepts any `AsyncRead + AsyncWrite` stream: a Unix socket to a local
+    /// stunnel or socat, an SSH port-forward channel, `tokio::io::duplex` in
+    /// tests. The stream must be positioned at the start of the session, so
+    /// that FreeSWITCH's `auth/request` is the next thing read.
+    ///
+    /// ```rust,no_run
+    /// use freeswitch_esl_tokio::EslClient;
+    /// use tokio::net::UnixStream;
+    ///
+    /// # async fn example() -> Result<(), freeswitch_esl_tokio::EslError> {
+    /// let stream = UnixStream::connect("/run/esl-tunnel.sock").await?;
+    /// let (client, events) = EslClient::from_stream(stream, "ClueCon").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_stream<S>(stream: S, password: &str) -> EslResult<(Self, EslEventStream)>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        Self::from_stream_with_options(stream, password, EslConnectOptions::default()).await
+    }
+
+    /// [`from_stream`](Self::from_stream) with custom options.
+    ///
+    /// The TLS fields of `options` are ignored; wrap the stream yourself.
+    pub async fn from_stream_with_options<S>(
+        stream: S,
+        password: &str,
+        options: EslConnectOptions,
+    ) -> EslResult<(Self, EslEventStream)>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        Self::authenticate_and_spawn(stream, AuthMethod::Password(password), options).await
+    }
+
+    /// Start a client on a stream that needs no handshake: an accepted
+    /// outbound connection, or an inbound session already authenticated.
+    ///
+    /// Must be called within a Tokio runtime (spawns the reader task).
+    pub fn from_authenticated_stream<S>(
+        stream: S,
+        options: EslConnectOptions,
+    ) -> (Self, EslEventStream)
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        Self::split_and_spawn_with_options(stream, EslParser::new(), options)
+    }
+
     async fn connect_inner(
         host: &str,
         port: u16,
@@ -543,11 +1193,36 @@ impl EslClient {
 
         info!("Connecting to FreeSWITCH at {}:{}", host, port);
 
-        let mut stream = tcp_connect_with_timeout(host, port).await?;
+        let stream = tcp_connect_with_timeout(host, port).await?;
+
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &options.tls {
+            let stream = timeout(
+                Duration::from_millis(DEFAULT_TIMEOUT_MS),
+                tls.connect(host, stream),
+            )
+            .await
+            .map_err(|_| EslError::Timeout {
+                timeout_ms: DEFAULT_TIMEOUT_MS,
+            })??;
+            debug!("[CONNECT] TLS handshake complete");
+            return Self::authenticate_and_spawn(stream, method, options).await;
+        }
+
+        Self::authenticate_and_spawn(stream, method, options).await
+    }
+
+    async fn authenticate_and_spawn<S>(
+        mut stream: S,
+        method: AuthMethod<'_>,
+        options: EslConnectOptions,
+    ) -> EslResult<(Self, EslEventStream)>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
         let mut parser = EslParser::new();
-        let mut read_buffer = [0u8; SOCKET_BUF_SIZE];
 
-        authenticate(&mut stream, &mut parser, &mut read_buffer, method).await?;
+        authenticate(&mut stream, &mut parser, method).await?;
 
         info!("Successfully connected and authenticated to FreeSWITCH");
         Ok(Self::split_and_spawn_with_options(stream, parser, options))
@@ -571,6 +1246,32 @@ impl EslClient {
             .map_err(EslError::Io)?;
         info!("Accepted outbound connection from {}", addr);
 
+        Self::spawn_accepted(stream, options).await
+    }
+
+    /// Start a client on an accepted outbound connection, performing the
+    /// TLS handshake first if `options.tls_accept` is set.
+    pub(crate) async fn spawn_accepted(
+        stream: TcpStream,
+        options: EslConnectOptions,
+    ) -> EslResult<(Self, EslEventStream)> {
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &options.tls_accept {
+            let stream = timeout(
+                Duration::from_millis(DEFAULT_TIMEOUT_MS),
+                tls.accept(stream),
+            )
+            .await
+            .map_err(|_| EslError::Timeout {
+                timeout_ms: DEFAULT_TIMEOUT_MS,
+            })??;
+            return Ok(Self::split_and_spawn_with_options(
+                stream,
+                EslParser::new(),
+                options,
+            ));
+        }
+
         Ok(Self::split_and_spawn_with_options(
             stream,
             EslParser::new(),
@@ -578,28 +1279,50 @@ impl EslClient {
         ))
     }
 
-    fn split_and_spawn_with_options(
-        stream: TcpStream,
+    pub(crate) fn split_and_spawn_with_options<S>(
+        stream: S,
         parser: EslParser,
         options: EslConnectOptions,
-    ) -> (Self, EslEventStream) {
+    ) -> (Self, EslEventStream)
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
         let queue_size = options
             .event_queue_size
             .max(1);
 
-        let (read_half, write_half) = stream.into_split();
+        let (read_half, write_half) = tokio::io::split(stream);
+        let (event_tx, event_rx) = queue::channel(queue_size);
 
         let shared = Arc::new(SharedState {
-            pending_reply: Mutex::new(None),
+            pending_replies: std::sync::Mutex::new(Some(VecDeque::new())),
+            next_command_id: AtomicU64::new(0),
             liveness_timeout_ms: AtomicU64::new(0),
             command_timeout_ms: AtomicU64::new(DEFAULT_COMMAND_TIMEOUT_MS),
+            backpressure: options.backpressure,
+            event_queue: event_tx.monitor(),
+            backpressure_waits: AtomicU64::new(0),
             event_overflow: AtomicBool::new(false),
             dropped_event_count: AtomicU64::new(0),
+            pending_jobs: std::sync::Mutex::new(HashMap::new()),
+            job_timeout_ms: AtomicU64::new(DEFAULT_JOB_TIMEOUT_MS),
+            job_subscription: std::sync::Mutex::new(JobSubscription {
+                format: EventFormat::Plain,
+                user_requested: false,
+                auto_subscribed: false,
+            }),
+            pending_executions: std::sync::Mutex::new(HashMap::new()),
+            subscriptions: std::sync::Mutex::new(SubscriptionState::default()),
+            sessions: std::sync::Mutex::new(Some(HashMap::new())),
+            next_session_id: AtomicU64::new(0),
+            subscribers: std::sync::Mutex::new(Some(Vec::new())),
+            route_queue_size: queue_size,
+            single_channel: AtomicBool::new(false),
+            log_sink: std::sync::Mutex::new(LogSink::Idle),
         });
 
         let (status_tx, status_rx) = watch::channel(ConnectionStatus::Connected);
         let status_rx2 = status_tx.subscribe();
-        let (event_tx, event_rx) = mpsc::channel(queue_size);
 
         tokio::spawn(reader_loop(
             read_half,
@@ -610,7 +1333,7 @@ impl EslClient {
         ));
 
         let client = EslClient {
-            writer: Arc::new(Mutex::new(write_half)),
+            writer: Arc::new(Mutex::new(Box::new(write_half) as EslWriter)),
             shared,
             status_rx,
         };
@@ -625,9 +1348,15 @@ impl EslClient {
 
     /// Send a command and wait for the reply.
     ///
-    /// The writer lock is held through the entire send-and-receive cycle to
-    /// prevent concurrent commands from overwriting the pending reply slot
-    /// (ESL is a sequential request/response protocol).
+    /// The writer lock is held only while the command is written and queued,
+    /// so concurrent commands are pipelined instead of waiting for each
+    /// other's replies. ESL answers commands in order; the reader task hands
+    /// each reply to the oldest queued command.
+    ///
+    /// Cancellation-safe: once the command is written, dropping this future
+    /// or timing out leaves its slot queued and the late reply is discarded
+    /// when it arrives. A reply that cannot belong to the oldest command
+    /// closes the connection with [`EslError::Desync`].
     pub async fn send_command(&self, command: EslCommand) -> EslResult<EslResponse> {
         if !self.is_connected() {
             return Err(EslError::NotConnected);
@@ -642,54 +1371,60 @@ impl EslClient {
             _ => debug!("Sending command: {}", command_str.trim()),
         }
 
-        // Lock writer — serializes concurrent commands and holds through reply.
-        let mut writer = self
-            .writer
-            .lock()
-            .await;
-
-        // Set up reply channel
+        let expects_api_response = matches!(command, EslCommand::Api { .. });
         let (tx, rx) = oneshot::channel();
-        {
-            let mut pending = self
-                .shared
-                .pending_reply
+        let writer = self
+            .writer
+            .clone();
+        let shared = self
+            .shared
+            .clone();
+        // Queue and write in a separate task so that dropping this future
+        // cannot leave a half-written command on the wire. Queue and write
+        // happen under the writer lock so queue order matches wire order; the
+        // entry goes in first because the reply may be read before write_all
+        // returns.
+        let write = tokio::spawn(async move {
+            let mut writer = writer
                 .lock()
                 .await;
-            *pending = Some(tx);
-        }
-
-        // Write command
-        writer
-            .write_all(command_str.as_bytes())
+            let id = shared
+                .next_command_id
+                .fetch_add(1, Ordering::Relaxed);
+            match shared
+                .pending_replies()
+                .as_mut()
+            {
+                Some(queue) => queue.push_back(PendingReply {
+                    id,
+                    expects_api_response,
+                    tx,
+                }),
+                None => return Err(EslError::ConnectionClosed),
+            }
+            if let Err(e) = writer
+                .write_all(command_str.as_bytes())
+                .await
+            {
+                shared.cancel_reply(id);
+                return Err(EslError::Io(e));
+            }
+            Ok(())
+        });
+        write
             .await
-            .map_err(EslError::Io)?;
+            .map_err(|e| EslError::generic(format!("command writer task failed: {}", e)))??;
 
-        // Wait for reply from reader task with command timeout (writer still locked)
         let timeout_ms = self
             .shared
             .command_timeout_ms
             .load(Ordering::Relaxed);
         let message = match timeout(Duration::from_millis(timeout_ms), rx).await {
-            Ok(Ok(message)) => message,
-            Ok(Err(_)) => {
-                drop(writer);
-                return Err(EslError::ConnectionClosed);
-            }
-            Err(_) => {
-                let mut pending = self
-                    .shared
-                    .pending_reply
-                    .lock()
-                    .await;
-                pending.take();
-                drop(writer);
-                return Err(EslError::Timeout { timeout_ms });
-            }
+            Ok(Ok(reply)) => reply?,
+            Ok(Err(_)) => return Err(EslError::ConnectionClosed),
+            Err(_) => return Err(EslError::Timeout { timeout_ms }),
         };
 
-        drop(writer);
-
         let response = message.into_response();
         debug!("Received response: success={}", response.is_success());
         Ok(response)
@@ -737,46 +1472,192 @@ impl EslClient {
     /// # Ok(())
     /// # }
     /// ```
+    ///
+    /// Use [`bgapi_job`](Self::bgapi_job) to have the library do the matching.
     pub async fn bgapi(&self, command: &str) -> EslResult<EslResponse> {
         let cmd = EslCommand::BgApi {
             command: command.to_string(),
+            job_uuid: None,
         };
         self.send_command(cmd)
             .await
     }
 
+    /// Execute background API command and get a future for its result.
+    ///
+    /// The `Job-UUID` is generated client-side and registered before the
+    /// command is sent, so the matching `BACKGROUND_JOB` event can never be
+    /// missed. The reader task hands that event to the returned [`BgJob`],
+    /// which resolves to the job body (e.g. `+OK <uuid>` or `-ERR NO_ANSWER`).
+    ///
+    /// `BACKGROUND_JOB` is subscribed automatically (in the connection's
+    /// current event format) if the application has not done so. Those
+    /// events only reach the [`EslEventStream`] if the application subscribed
+    /// to `BACKGROUND_JOB` or `ALL` itself. If server-side filters are set,
+    /// they must let `BACKGROUND_JOB` through.
+    ///
+    /// The job has its own timeout (default 2 minutes, see
+    /// [`set_job_timeout`](Self::set_job_timeout)), counted from the
+    /// `+OK Job-UUID` reply and independent of the command timeout. Dropping
+    /// the [`BgJob`] stops waiting; the job itself keeps running in
+    /// FreeSWITCH.
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &freeswitch_esl_tokio::EslClient) -> Result<(), freeswitch_esl_tokio::EslError> {
+    /// let job = client.bgapi_job("originate user/1000 &park").await?;
+    /// println!("started job {}", job.job_uuid());
+    /// let body = job.await?;
+    /// println!("originate result: {}", body.trim());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bgapi_job(&self, command: &str) -> EslResult<BgJob> {
+        self.ensure_background_job_subscription()
+            .await?;
+
+        let job_uuid = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.shared
+            .pending_jobs()
+            .insert(job_uuid.clone(), tx);
+
+        let timeout_ms = self
+            .shared
+            .job_timeout_ms
+            .load(Ordering::Relaxed);
+        // Created before sending so an error below unregisters the waiter on drop.
+        let mut job = BgJob {
+            job_uuid: job_uuid.clone(),
+            rx,
+            shared: self
+                .shared
+                .clone(),
+            deadline: Box::pin(tokio::time::sleep(Duration::from_millis(timeout_ms))),
+            timeout_ms,
+        };
+
+        let cmd = EslCommand::BgApi {
+            command: command.to_string(),
+            job_uuid: Some(job_uuid),
+        };
+        self.send_command(cmd)
+            .await?
+            .into_result()?;
+        // The job timeout covers the job itself, not the command reply.
+        job.deadline
+            .as_mut()
+            .reset(Instant::now() + Duration::from_millis(timeout_ms));
+        Ok(job)
+    }
+
+    /// Execute background API command and wait for its result body.
+    ///
+    /// Shorthand for [`bgapi_job`](Self::bgapi_job) followed by `.await`.
+    pub async fn bgapi_wait(&self, command: &str) -> EslResult<String> {
+        self.bgapi_job(command)
+            .await?
+            .await
+    }
+
+    /// Subscribe to `BACKGROUND_JOB` unless already covered.
+    async fn ensure_background_job_subscription(&self) -> EslResult<()> {
+        let format = {
+            let sub = self
+                .shared
+                .job_subscription();
+            if sub.user_requested || sub.auto_subscribed {
+                return Ok(());
+            }
+            sub.format
+        };
+
+        let cmd = EslCommand::Events {
+            format: format.to_string(),
+            events: EslEventType::BackgroundJob.to_string(),
+        };
+        self.send_command_ok(cmd)
+            .await?;
+        debug!("Auto-subscribed to BACKGROUND_JOB for bgapi_job");
+        self.shared
+            .job_subscription()
+            .auto_subscribed = true;
+        Ok(())
+    }
+
+    /// Record an `event` command for `BACKGROUND_JOB` routing and
+    /// [`subscriptions`](Self::subscriptions).
+    fn track_subscribe(&self, format: EventFormat, events: &str) {
+        self.shared
+            .subscriptions()
+            .add_events(format, events);
+        let mut sub = self
+            .shared
+            .job_subscription();
+        sub.format = format;
+        if event_list_has_background_job(events) {
+            sub.user_requested = true;
+        }
+    }
+
+    /// Record a `nixevent`/`noevents` command for `BACKGROUND_JOB` routing
+    /// and [`subscriptions`](Self::subscriptions).
+    fn track_unsubscribe(&self, events: Option<&str>) {
+        match events {
+            Some(list) => self
+                .shared
+                .subscriptions()
+                .remove_events(list),
+            None => self
+                .shared
+                .subscriptions()
+                .clear_events(),
+        }
+        if events.map_or(true, event_list_has_background_job) {
+            let mut sub = self
+                .shared
+                .job_subscription();
+            sub.user_requested = false;
+            sub.auto_subscribed = false;
+        }
+    }
+
     /// Subscribe to events by typed enum variants.
     ///
-    /// For `CUSTOM` event subclasses (e.g., `sofia::register`), use
-    /// [`subscribe_events_raw`](Self::subscribe_events_raw) instead — this method
-    /// sends bare `CUSTOM` which subscribes to **all** custom events:
+    /// Accepts [`EslEventType`]s, [`EventSubclass`](crate::EventSubclass)es,
+    /// or a mix of both as [`EventKind`]s. `CUSTOM` and the subclasses are
+    /// sent last, as FreeSWITCH reads every name after `CUSTOM` as a
+    /// subclass; bare [`EslEventType::Custom`] subscribes to **all** custom
+    /// events.
     ///
     /// ```rust,no_run
     /// # async fn example(client: &freeswitch_esl_tokio::EslClient) -> Result<(), freeswitch_esl_tokio::EslError> {
-    /// use freeswitch_esl_tokio::EventFormat;
-    /// // Subscribe to specific CUSTOM subclasses:
-    /// client.subscribe_events_raw(EventFormat::Plain, "CUSTOM sofia::register sofia::unregister").await?;
+    /// use freeswitch_esl_tokio::{EslEventType, EventFormat, EventKind, EventSubclass};
+    ///
+    /// // event plain CHANNEL_ANSWER CUSTOM sofia::register sofia::unregister
+    /// let events: [EventKind; 3] = [
+    ///     EventSubclass::SofiaRegister.into(),
+    ///     EslEventType::ChannelAnswer.into(),
+    ///     EventSubclass::SofiaUnregister.into(),
+    /// ];
+    /// client.subscribe_events(EventFormat::Plain, &events).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn subscribe_events(
+    pub async fn subscribe_events<E: Copy + Into<EventKind>>(
         &self,
         format: EventFormat,
-        events: &[EslEventType],
+        events: &[E],
     ) -> EslResult<()> {
-        let events_str = if events.contains(&EslEventType::All) {
-            "ALL".to_string()
-        } else {
-            event_types_to_string(events)
-        };
+        let events_str = event_types_to_string(events);
 
         let cmd = EslCommand::Events {
             format: format.to_string(),
-            events: events_str,
+            events: events_str.clone(),
         };
 
         self.send_command_ok(cmd)
             .await?;
+        self.track_subscribe(format, &events_str);
         info!("Subscribed to events with format {:?}", format);
         Ok(())
     }
@@ -801,6 +1682,7 @@ impl EslClient {
 
         self.send_command_ok(cmd)
             .await?;
+        self.track_subscribe(format, events);
         info!(
             "Subscribed to raw events '{}' with format {:?}",
             events, format
@@ -808,6 +1690,107 @@ impl EslClient {
         Ok(())
     }
 
+    /// Subscribe to `events` and open a receiver for those matching `filter`.
+    ///
+    /// The parts of `filter` FreeSWITCH can evaluate
+    /// ([`EventFilter::server_filters`]) are sent as `filter` commands, before
+    /// the `event` subscription, so non-matching events are dropped before
+    /// reaching the client; the returned stream applies the full filter. Server-side filters apply to
+    /// the whole connection, including the main [`EslEventStream`], other
+    /// receivers and `BACKGROUND_JOB` events for [`bgapi`](Self::bgapi);
+    /// remove them with [`filter_delete`](Self::filter_delete).
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &freeswitch_esl_tokio::EslClient) -> Result<(), Box<dyn std::error::Error>> {
+    /// use freeswitch_esl_tokio::{EslEventType, EventFormat};
+    ///
+    /// let filter = "Event-Name in (CHANNEL_ANSWER, CHANNEL_HANGUP) \
+    ///     and variable_sip_from_user =~ /^1[0-9]{3}$/"
+    ///     .parse()?;
+    /// let mut calls = client
+    ///     .subscribe_events_filtered(EventFormat::Plain, &[EslEventType::All], filter)
+    ///     .await?;
+    /// while let Some(Ok(event)) = calls.recv().await {
+    ///     println!("{:?} {:?}", event.event_type(), event.unique_id());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_events_filtered<E: Copy + Into<EventKind>>(
+        &self,
+        format: EventFormat,
+        events: &[E],
+        filter: EventFilter,
+    ) -> EslResult<FilteredEventStream> {
+        let server_filters = filter.server_filters();
+        // Registered first so no matching event slips past
+        let stream = self.subscribe_stream(filter);
+        // Filters go first so no unfiltered event reaches the main stream
+        for (header, value) in &server_filters {
+            self.filter_events(header, value)
+                .await?;
+        }
+        self.subscribe_events(format, events)
+            .await?;
+        Ok(stream)
+    }
+
+    /// Event subscriptions and filters set on this connection so far.
+    ///
+    /// Built from the successful `event`, `nixevent`, `noevents`, `filter`
+    /// and `filter delete` commands sent through this client; commands sent
+    /// with [`send_command`](Self::send_command) and the automatic
+    /// `BACKGROUND_JOB` subscription of [`bgapi_job`](Self::bgapi_job) are
+    /// not included.
+    pub fn subscriptions(&self) -> SubscriptionState {
+        self.shared
+            .subscriptions()
+            .clone()
+    }
+
+    /// Bring the connection's subscriptions and filters to `desired`,
+    /// sending only the commands needed to get there from
+    /// [`subscriptions`](Self::subscriptions).
+    ///
+    /// Filters are added before new events are subscribed, so no unwanted
+    /// events slip through. On error, the commands already sent stay in
+    /// effect and are reflected in [`subscriptions`](Self::subscriptions).
+    /// Not atomic with respect to concurrent subscription commands.
+    pub async fn set_subscriptions(&self, desired: &SubscriptionState) -> EslResult<()> {
+        let changes = self
+            .subscriptions()
+            .diff(desired);
+        for change in changes {
+            match change {
+                SubscriptionChange::Subscribe { format, events } => {
+                    self.subscribe_events_raw(format, &events)
+                        .await?
+                }
+                SubscriptionChange::Unsubscribe(events) => {
+                    self.nixevent_raw(&events)
+                        .await?
+                }
+                SubscriptionChange::NoEvents => {
+                    self.noevents()
+                        .await?
+                }
+                SubscriptionChange::AddFilter(header, value) => {
+                    self.filter_events(&header, &value)
+                        .await?
+                }
+                SubscriptionChange::DeleteFilter(header, value) => {
+                    self.filter_delete(&header, Some(&value))
+                        .await?
+                }
+                SubscriptionChange::DeleteAllFilters => {
+                    self.filter_delete_all()
+                        .await?
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Set event filter
     pub async fn filter_events(&self, header: &str, value: &str) -> EslResult<()> {
         let cmd = EslCommand::Filter {
@@ -817,6 +1800,9 @@ impl EslClient {
 
         self.send_command_ok(cmd)
             .await?;
+        self.shared
+            .subscriptions()
+            .add_filter(header, value);
         debug!("Set event filter: {} = {}", header, value);
         Ok(())
     }
@@ -837,6 +1823,92 @@ impl EslClient {
             .await
     }
 
+    /// Execute an application on a channel and get a future for its completion.
+    ///
+    /// The `sendmsg` carries a client-generated `Event-UUID`, which
+    /// FreeSWITCH reports back as the `Application-UUID` of the
+    /// `CHANNEL_EXECUTE_COMPLETE` event. The waiter is registered before the
+    /// command is sent; the returned [`AppExecution`] resolves to that event
+    /// once the application (and every iteration of `loops`) has finished.
+    ///
+    /// The connection must receive `CHANNEL_EXECUTE_COMPLETE` for the
+    /// channel, e.g. via [`myevents`](Self::myevents) or
+    /// [`subscribe_events`](Self::subscribe_events); unlike `bgapi_job`,
+    /// nothing is subscribed automatically. The events still reach the
+    /// [`EslEventStream`]. There is no timeout unless
+    /// [`AppExecution::timeout`] is set.
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &freeswitch_esl_tokio::EslClient, uuid: &str) -> Result<(), freeswitch_esl_tokio::EslError> {
+    /// use freeswitch_esl_tokio::{ExecuteOptions, PlayAndGetDigits};
+    ///
+    /// let pagd = PlayAndGetDigits {
+    ///     var_name: Some("ext".into()),
+    ///     ..PlayAndGetDigits::new(1, 4, "ivr/ivr-enter_ext.wav")
+    /// };
+    /// let args = pagd.to_string();
+    /// let run = client
+    ///     .execute_and_wait("play_and_get_digits", Some(&args), uuid, ExecuteOptions::default())
+    ///     .await?;
+    /// let done = run.await?;
+    /// println!("digits: {:?}", done.variable("ext"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_and_wait(
+        &self,
+        app: &str,
+        args: Option<&str>,
+        uuid: &str,
+        options: ExecuteOptions,
+    ) -> EslResult<AppExecution> {
+        let application_uuid = uuid::Uuid::new_v4().to_string();
+        let loops = options
+            .loops
+            .max(1);
+
+        let mut event = EslEvent::new();
+        event.set_header("call-command", "execute");
+        event.set_header("execute-app-name", app);
+        if let Some(args) = args {
+            event.set_header("execute-app-arg", args);
+        }
+        event.set_header("Event-UUID", application_uuid.clone());
+        if options.event_lock {
+            event.set_header("event-lock", "true");
+        }
+        if loops > 1 {
+            event.set_header("loops", loops.to_string());
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.shared
+            .pending_executions()
+            .insert(
+                application_uuid.clone(),
+                PendingExecution {
+                    uuid: uuid.to_string(),
+                    remaining: loops,
+                    last: None,
+                    tx,
+                },
+            );
+        // Created before sending so an error below unregisters the waiter on drop.
+        let execution = AppExecution {
+            application_uuid,
+            uuid: uuid.to_string(),
+            rx,
+            client: self.clone(),
+            deadline: None,
+            timeout_ms: 0,
+        };
+
+        self.sendmsg(Some(uuid), event)
+            .await?
+            .into_result()?;
+        Ok(execution)
+    }
+
     /// Send message to channel
     pub async fn sendmsg(&self, uuid: Option<&str>, event: EslEvent) -> EslResult<EslResponse> {
         let cmd = EslCommand::SendMsg {
@@ -867,7 +1939,14 @@ impl EslClient {
             uuid: None,
         };
         self.send_command_ok(cmd)
-            .await
+            .await?;
+        self.shared
+            .job_subscription()
+            .format = format;
+        self.shared
+            .subscriptions()
+            .set_format(format);
+        Ok(())
     }
 
     /// Subscribe to session events for a specific UUID (inbound mode).
@@ -880,7 +1959,14 @@ impl EslClient {
             uuid: Some(uuid.to_string()),
         };
         self.send_command_ok(cmd)
-            .await
+            .await?;
+        self.shared
+            .job_subscription()
+            .format = format;
+        self.shared
+            .subscriptions()
+            .set_format(format);
+        Ok(())
     }
 
     /// Keep the socket open after the channel hangs up (outbound mode).
@@ -922,16 +2008,40 @@ impl EslClient {
     /// The returned [`EslResponse`] contains the channel data as headers.
     /// Use [`EslResponse::header()`] to read individual channel variables
     /// (e.g., `Caller-Caller-ID-Number`, `Channel-Name`).
+    ///
+    /// The `Control` header of the reply is recorded, see
+    /// [`is_single_channel`](Self::is_single_channel).
     pub async fn connect_session(&self) -> EslResult<EslResponse> {
-        self.send_command(EslCommand::Connect)
-            .await
+        let response = self
+            .send_command(EslCommand::Connect)
+            .await?;
+        if let Some(control) = response.header("Control") {
+            self.shared
+                .single_channel
+                .store(control != "full", Ordering::Relaxed);
+        }
+        Ok(response)
+    }
+
+    /// Whether this is an outbound socket without `full` control.
+    ///
+    /// Set by [`connect_session`](Self::connect_session) when FreeSWITCH
+    /// reports `Control: single-channel`, i.e. the `socket` application was
+    /// started without `full`. Such a socket only accepts `connect`,
+    /// `myevents`, `getvar`, `resume`, `filter`, `divert_events` and
+    /// `sendmsg`; `api`, `bgapi`, `event` and `linger` are not available.
+    /// Always `false` in inbound mode.
+    pub fn is_single_channel(&self) -> bool {
+        self.shared
+            .single_channel
+            .load(Ordering::Relaxed)
     }
 
     /// Unsubscribe from specific events by typed enum variants.
     ///
     /// The inverse of [`subscribe_events`](Self::subscribe_events). Accepts
-    /// multiple event types to unsubscribe from at once.
-    pub async fn nixevent(&self, events: &[EslEventType]) -> EslResult<()> {
+    /// multiple event types or subclasses to unsubscribe from at once.
+    pub async fn nixevent<E: Copy + Into<EventKind>>(&self, events: &[E]) -> EslResult<()> {
         self.nixevent_raw(&event_types_to_string(events))
             .await
     }
@@ -945,7 +2055,9 @@ impl EslClient {
             events: events.to_string(),
         };
         self.send_command_ok(cmd)
-            .await
+            .await?;
+        self.track_unsubscribe(Some(events));
+        Ok(())
     }
 
     /// Unsubscribe from all events.
@@ -953,7 +2065,9 @@ impl EslClient {
     /// Clears all event subscriptions. The server flushes any queued events.
     pub async fn noevents(&self) -> EslResult<()> {
         self.send_command_ok(EslCommand::NoEvents)
-            .await
+            .await?;
+        self.track_unsubscribe(None);
+        Ok(())
     }
 
     /// Remove an event filter for a specific header.
@@ -966,7 +2080,11 @@ impl EslClient {
             value: value.map(|v| v.to_string()),
         };
         self.send_command_ok(cmd)
-            .await
+            .await?;
+        self.shared
+            .subscriptions()
+            .remove_filter(header, value);
+        Ok(())
     }
 
     /// Remove all event filters.
@@ -1007,9 +2125,10 @@ impl EslClient {
 
     /// Enable FreeSWITCH log forwarding at the given level.
     ///
-    /// Log messages stream as events with `Content-Type: log/data`.
-    /// Valid levels: `DEBUG`, `INFO`, `NOTICE`, `WARNING`, `ERROR`,
-    /// `CRIT`, `ALERT`, `EMERG` (or numeric 0–7).
+    /// Log lines arrive as `log/data` messages and are delivered on
+    /// [`log_stream`](Self::log_stream), not the event stream. Valid levels:
+    /// `DEBUG`, `INFO`, `NOTICE`, `WARNING`, `ERR`, `CRIT`, `ALERT`,
+    /// `CONSOLE` (or numeric 0–7); see [`LogLevel`](crate::LogLevel).
     pub async fn log(&self, level: &str) -> EslResult<EslResponse> {
         let cmd = EslCommand::Log {
             level: level.to_string(),
@@ -1024,6 +2143,27 @@ impl EslClient {
             .await
     }
 
+    /// Open the stream of log lines forwarded after [`log`](Self::log).
+    ///
+    /// Only one log stream exists per connection: opening a new one ends the
+    /// previous one. Lines received while no stream is open, or while its
+    /// queue is full, are dropped. The stream ends when the connection closes.
+    pub fn log_stream(&self) -> EslLogStream {
+        let (tx, rx) = mpsc::channel(
+            self.shared
+                .route_queue_size,
+        );
+        let mut sink = self
+            .shared
+            .log_sink();
+        // Once the reader task has exited, `tx` is dropped here and the
+        // stream ends immediately.
+        if !matches!(*sink, LogSink::Closed) {
+            *sink = LogSink::Active(tx);
+        }
+        EslLogStream::new(rx)
+    }
+
     /// Send a no-op command (keepalive).
     pub async fn noop(&self) -> EslResult<EslResponse> {
         self.send_command(EslCommand::NoOp)
@@ -1047,6 +2187,22 @@ impl EslClient {
             .load(Ordering::Relaxed)
     }
 
+    /// Current depth and wait count of the event queue.
+    pub fn event_queue_stats(&self) -> EventQueueStats {
+        let queue = &self
+            .shared
+            .event_queue;
+        EventQueueStats {
+            depth: queue.depth(),
+            max_depth: queue.max_depth(),
+            capacity: queue.capacity(),
+            reader_waits: self
+                .shared
+                .backpressure_waits
+                .load(Ordering::Relaxed),
+        }
+    }
+
     /// Set liveness timeout. Any inbound TCP traffic resets the timer.
     /// Set to zero to disable (default).
     pub fn set_liveness_timeout(&self, duration: Duration) {
@@ -1068,6 +2224,127 @@ impl EslClient {
             .store(duration.as_millis() as u64, Ordering::Relaxed);
     }
 
+    /// Open a handle that receives only the events of one channel.
+    ///
+    /// The reader task copies every event whose `Unique-ID` matches `uuid`
+    /// to the returned [`ChannelSession`]; the events still reach the main
+    /// [`EslEventStream`] too, which must keep being drained. The session
+    /// ends after `CHANNEL_DESTROY` or when the connection closes.
+    ///
+    /// This does not subscribe to anything: the connection must already
+    /// receive the channel's events (via [`subscribe_events`](Self::subscribe_events),
+    /// or [`ChannelSession::myevents`]). Several sessions may be open on the
+    /// same UUID; each gets its own copy. When a session falls behind and its
+    /// queue fills up, further copies for it are dropped with a warning; they
+    /// are not counted in [`dropped_event_count`](Self::dropped_event_count),
+    /// since the main stream still receives them.
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &freeswitch_esl_tokio::EslClient) -> Result<(), freeswitch_esl_tokio::EslError> {
+    /// let mut session = client.session("4f5e6d7c-...");
+    /// session.execute("playback", Some("ivr/ivr-welcome.wav")).await?;
+    /// while let Some(event) = session.recv().await {
+    ///     println!("{:?}", event.event_type());
+    /// }
+    /// // CHANNEL_DESTROY received, or connection closed
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn session(&self, uuid: &str) -> ChannelSession {
+        let (tx, rx) = mpsc::channel(
+            self.shared
+                .route_queue_size,
+        );
+        let id = self
+            .shared
+            .next_session_id
+            .fetch_add(1, Ordering::Relaxed);
+        // Once the reader task has exited, `tx` is dropped here and the
+        // session ends immediately.
+        if let Some(sessions) = self
+            .shared
+            .sessions()
+            .as_mut()
+        {
+            sessions
+                .entry(uuid.to_string())
+                .or_default()
+                .push(SessionRoute { id, tx });
+        }
+        ChannelSession::new(self.clone(), uuid.to_string(), id, rx)
+    }
+
+    /// Open an additional event receiver that gets a copy of every event
+    /// matching `filter`.
+    ///
+    /// Each receiver has its own buffer of
+    /// [`event_queue_size`](EslConnectOptions::event_queue_size) events; one
+    /// that falls behind skips events (reported with [`EslError::Lagged`])
+    /// without affecting the main [`EslEventStream`] or other receivers.
+    /// The main stream must stay alive: dropping it stops the reader task.
+    /// Events still need a server-side subscription
+    /// ([`subscribe_events`](Self::subscribe_events)).
+    pub fn subscribe_stream(&self, filter: EventFilter) -> FilteredEventStream {
+        self.subscribe_stream_with_capacity(
+            filter,
+            self.shared
+                .route_queue_size,
+        )
+    }
+
+    /// [`subscribe_stream`](Self::subscribe_stream) with a buffer of
+    /// `capacity` events.
+    pub fn subscribe_stream_with_capacity(
+        &self,
+        filter: EventFilter,
+        capacity: usize,
+    ) -> FilteredEventStream {
+        // One extra slot for the `Lagged` notice, see `route_to_subscribers`
+        let (tx, rx) = mpsc::channel(capacity.max(1) + 1);
+        let skipped = Arc::new(AtomicU64::new(0));
+        // Once the reader task has exited, `tx` is dropped here and the
+        // stream ends immediately.
+        if let Some(routes) = self
+            .shared
+            .subscribers()
+            .as_mut()
+        {
+            routes.push(SubscriberRoute {
+                filter: filter.clone(),
+                tx,
+                missed: 0,
+                skipped: skipped.clone(),
+            });
+        }
+        FilteredEventStream::new(rx, filter, skipped)
+    }
+
+    /// Remove a session route, called when a [`ChannelSession`] is dropped.
+    pub(crate) fn close_session(&self, uuid: &str, id: u64) {
+        let mut guard = self
+            .shared
+            .sessions();
+        let Some(sessions) = guard.as_mut() else {
+            return;
+        };
+        if let Some(routes) = sessions.get_mut(uuid) {
+            routes.retain(|route| route.id != id);
+            if routes.is_empty() {
+                sessions.remove(uuid);
+            }
+        }
+    }
+
+    /// Set the result timeout for [`bgapi_job`](Self::bgapi_job) (default: 2 minutes).
+    ///
+    /// Independent of the command timeout, which only covers the initial
+    /// `+OK Job-UUID` reply. Applies to jobs started after the call.
+    pub fn set_job_timeout(&self, duration: Duration) {
+        self.shared
+            .job_timeout_ms
+            .store(duration.as_millis() as u64, Ordering::Relaxed);
+    }
+
     /// Whether the connection is alive (not yet disconnected).
     pub fn is_connected(&self) -> bool {
         matches!(
@@ -1131,6 +2408,269 @@ impl EslEventStream {
     }
 }
 
+/// Pending result of a [`bgapi_job`](EslClient::bgapi_job) command.
+///
+/// Resolves to the `BACKGROUND_JOB` body, [`EslError::Timeout`] when the job
+/// timeout expires first, or [`EslError::ConnectionClosed`] if the connection
+/// drops while waiting. Dropping it unregisters the waiter.
+#[must_use = "a BgJob does nothing unless awaited"]
+pub struct BgJob {
+    job_uuid: String,
+    rx: oneshot::Receiver<EslEvent>,
+    shared: Arc<SharedState>,
+    deadline: Pin<Box<tokio::time::Sleep>>,
+    timeout_ms: u64,
+}
+
+impl std::fmt::Debug for BgJob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BgJob")
+            .field("job_uuid", &self.job_uuid)
+            .field("timeout_ms", &self.timeout_ms)
+            .finish()
+    }
+}
+
+impl BgJob {
+    /// The client-generated `Job-UUID` of this job.
+    pub fn job_uuid(&self) -> &str {
+        &self.job_uuid
+    }
+
+    /// Override the client's job timeout for this job, counted from now.
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.timeout_ms = duration.as_millis() as u64;
+        self.deadline = Box::pin(tokio::time::sleep(duration));
+        self
+    }
+
+    /// Wait for the full `BACKGROUND_JOB` event instead of just its body.
+    pub async fn event(mut self) -> EslResult<EslEvent> {
+        std::future::poll_fn(|cx| self.poll_event(cx)).await
+    }
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<EslResult<EslEvent>> {
+        if let Poll::Ready(result) = Pin::new(&mut self.rx).poll(cx) {
+            return Poll::Ready(result.map_err(|_| EslError::ConnectionClosed));
+        }
+        if self
+            .deadline
+            .as_mut()
+            .poll(cx)
+            .is_ready()
+        {
+            return Poll::Ready(Err(EslError::Timeout {
+                timeout_ms: self.timeout_ms,
+            }));
+        }
+        Poll::Pending
+    }
+}
+
+impl Future for BgJob {
+    type Output = EslResult<String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.poll_event(cx)
+            .map(|result| {
+                result.map(|event| {
+                    event
+                        .body()
+                        .unwrap_or("")
+                        .to_string()
+                })
+            })
+    }
+}
+
+impl Drop for BgJob {
+    fn drop(&mut self) {
+        self.shared
+            .pending_jobs()
+            .remove(&self.job_uuid);
+    }
+}
+
+/// Pending completion of an [`execute_and_wait`](EslClient::execute_and_wait) application.
+///
+/// Resolves to the [`ExecuteComplete`] event, [`EslError::Timeout`] when a
+/// [`timeout`](Self::timeout) expires first, [`EslError::Generic`] if the
+/// channel is destroyed without a completion, or
+/// [`EslError::ConnectionClosed`] if the connection drops while waiting.
+/// Dropping it unregisters the waiter; the application keeps running.
+#[must_use = "an AppExecution does nothing unless awaited"]
+pub struct AppExecution {
+    application_uuid: String,
+    uuid: String,
+    rx: oneshot::Receiver<EslResult<EslEvent>>,
+    client: EslClient,
+    deadline: Option<Pin<Box<tokio::time::Sleep>>>,
+    timeout_ms: u64,
+}
+
+impl std::fmt::Debug for AppExecution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppExecution")
+            .field("application_uuid", &self.application_uuid)
+            .field("uuid", &self.uuid)
+            .finish()
+    }
+}
+
+impl AppExecution {
+    /// The client-generated `Application-UUID` of this execution.
+    pub fn application_uuid(&self) -> &str {
+        &self.application_uuid
+    }
+
+    /// The channel the application runs on.
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    /// Stop waiting after `duration`, counted from now.
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.timeout_ms = duration.as_millis() as u64;
+        self.deadline = Some(Box::pin(tokio::time::sleep(duration)));
+        self
+    }
+
+    /// Interrupt the application.
+    ///
+    /// Uses `api uuid_break`, or executes the `break` application through
+    /// `sendmsg` on an outbound socket without `full` control. The execution
+    /// then resolves with the next completion event, even if iterations of
+    /// `loops` were left.
+    pub async fn cancel(&self) -> EslResult<()> {
+        if let Some(waiter) = self
+            .client
+            .shared
+            .pending_executions()
+            .get_mut(&self.application_uuid)
+        {
+            waiter.remaining = 1;
+        }
+        if self
+            .client
+            .is_single_channel()
+        {
+            let mut event = EslEvent::new();
+            event.set_header("call-command", "execute");
+            event.set_header("execute-app-name", "break");
+            return self
+                .client
+                .sendmsg(Some(&self.uuid), event)
+                .await?
+                .into_result()
+                .map(|_| ());
+        }
+        let cmd = UuidBreak {
+            uuid: self
+                .uuid
+                .clone(),
+            all: false,
+        };
+        api_result(
+            self.client
+                .api(&cmd.to_string())
+                .await?,
+        )
+        .map(|_| ())
+    }
+}
+
+impl Future for AppExecution {
+    type Output = EslResult<ExecuteComplete>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(result) = Pin::new(&mut self.rx).poll(cx) {
+            return Poll::Ready(match result {
+                Ok(result) => result.map(|event| ExecuteComplete { event }),
+                Err(_) => Err(EslError::ConnectionClosed),
+            });
+        }
+        let timeout_ms = self.timeout_ms;
+        if let Some(deadline) = self
+            .deadline
+            .as_mut()
+        {
+            if deadline
+                .as_mut()
+                .poll(cx)
+                .is_ready()
+            {
+                return Poll::Ready(Err(EslError::Timeout { timeout_ms }));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for AppExecution {
+    fn drop(&mut self) {
+        self.client
+            .shared
+            .pending_executions()
+            .remove(&self.application_uuid);
+    }
+}
+
+/// The `CHANNEL_EXECUTE_COMPLETE` event of an [`AppExecution`].
+///
+/// Carries the application's response and the channel variables as they
+/// were when it finished, such as the digits `play_and_get_digits` collected.
+#[derive(Debug, Clone)]
+pub struct ExecuteComplete {
+    event: EslEvent,
+}
+
+impl ExecuteComplete {
+    /// Name of the application that ran (`Application`).
+    pub fn application(&self) -> Option<&str> {
+        self.event
+            .header("Application")
+    }
+
+    /// Arguments the application ran with (`Application-Data`).
+    pub fn application_data(&self) -> Option<&str> {
+        self.event
+            .header("Application-Data")
+    }
+
+    /// The application's result (`Application-Response`); `None` when it set none.
+    pub fn response(&self) -> Option<&str> {
+        self.event
+            .header(HEADER_APPLICATION_RESPONSE)
+            .filter(|r| *r != "_none_")
+    }
+
+    /// Look up a channel variable (`variable_{name}`).
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        self.event
+            .variable(name)
+    }
+
+    /// All channel variables, without the `variable_` prefix.
+    pub fn variables(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.event
+            .headers()
+            .filter_map(|(name, value)| {
+                name.strip_prefix("variable_")
+                    .map(|name| (name, value))
+            })
+    }
+
+    /// The full completion event.
+    pub fn event(&self) -> &EslEvent {
+        &self.event
+    }
+
+    /// Take the full completion event.
+    pub fn into_event(self) -> EslEvent {
+        self.event
+    }
+}
+
 impl futures_util::Stream for EslEventStream {
     type Item = Result<EslEvent, EslError>;
 
@@ -1144,6 +2684,32 @@ impl futures_util::Stream for EslEventStream {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_event_types_to_string_custom_last() {
+        assert_eq!(
+            event_types_to_string(&[EslEventType::Custom, EslEventType::ChannelAnswer]),
+            "CHANNEL_ANSWER CUSTOM"
+        );
+        let events: [EventKind; 3] = [
+            crate::EventSubclass::FifoInfo.into(),
+            EslEventType::Heartbeat.into(),
+            EslEventType::Custom.into(),
+        ];
+        assert_eq!(
+            event_types_to_string(&events),
+            "HEARTBEAT CUSTOM fifo::info"
+        );
+        assert_eq!(
+            event_types_to_string(&[EslEventType::Heartbeat, EslEventType::All]),
+            "ALL"
+        );
+        let events: [EventKind; 2] = [
+            EslEventType::All.into(),
+            crate::EventSubclass::FifoInfo.into(),
+        ];
+        assert_eq!(event_types_to_string(&events), "ALL CUSTOM fifo::info");
+    }
+
     #[tokio::test]
     async fn test_connection_mode() {
         assert_eq!(ConnectionMode::Inbound, ConnectionMode::Inbound);
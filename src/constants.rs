@@ -29,6 +29,7 @@ pub const CONTENT_TYPE_API_RESPONSE: &str = "api/response";
 pub const CONTENT_TYPE_TEXT_EVENT_PLAIN: &str = "text/event-plain";
 pub const CONTENT_TYPE_TEXT_EVENT_JSON: &str = "text/event-json";
 pub const CONTENT_TYPE_TEXT_EVENT_XML: &str = "text/event-xml";
+pub const CONTENT_TYPE_LOG_DATA: &str = "log/data";
 
 /// Header names
 pub const HEADER_CONTENT_TYPE: &str = "Content-Type";
@@ -38,6 +39,8 @@ pub const HEADER_EVENT_NAME: &str = "Event-Name";
 pub const HEADER_UNIQUE_ID: &str = "Unique-ID";
 pub const HEADER_CALLER_UUID: &str = "Caller-Unique-ID";
 pub const HEADER_JOB_UUID: &str = "Job-UUID";
+pub const HEADER_APPLICATION_UUID: &str = "Application-UUID";
+pub const HEADER_APPLICATION_RESPONSE: &str = "Application-Response";
 
 /// Channel state headers
 pub const HEADER_CHANNEL_STATE: &str = "Channel-State";
@@ -111,6 +111,14 @@ pub enum EslError {
     #[error("Connection closed by FreeSWITCH")]
     ConnectionClosed,
 
+    /// Replies no longer line up with the commands sent; the connection was
+    /// closed because later replies could reach the wrong caller.
+    #[error("Reply stream out of sync: {message}")]
+    Desync {
+        /// What did not match (unsolicited reply or wrong reply type).
+        message: String,
+    },
+
     /// Heartbeat/liveness timeout expired
     #[error("Heartbeat expired after {interval_ms}ms without traffic")]
     HeartbeatExpired {
@@ -129,6 +137,15 @@ pub enum EslError {
     #[error("Event queue is full - dropping events")]
     QueueFull,
 
+    /// A [`FilteredEventStream`](crate::FilteredEventStream) or
+    /// [`ChannelChangeStream`](crate::ChannelChangeStream) fell behind and
+    /// skipped items
+    #[error("Subscriber lagged - skipped {count} events")]
+    Lagged {
+        /// Number of events skipped since the previous notice.
+        count: u64,
+    },
+
     /// Generic error with custom message
     #[error("ESL error: {message}")]
     Generic {
@@ -136,6 +153,13 @@ pub enum EslError {
         message: String,
     },
 
+    /// TLS configuration error (invalid certificate, key or server name)
+    #[error("TLS error: {message}")]
+    Tls {
+        /// What was rejected.
+        message: String,
+    },
+
     /// Originate command builder error
     #[error("Originate error: {0}")]
     Originate(#[from] OriginateError),
@@ -156,6 +180,13 @@ impl EslError {
         }
     }
 
+    /// Construct a TLS configuration error.
+    pub fn tls(message: impl Into<String>) -> Self {
+        Self::Tls {
+            message: message.into(),
+        }
+    }
+
     /// Construct an authentication failure with a reason.
     pub fn auth_failed(reason: impl Into<String>) -> Self {
         Self::AuthenticationFailed {
@@ -165,7 +196,8 @@ impl EslError {
 
     /// `true` if the connection is still usable and the caller can retry.
     ///
-    /// Recoverable: `Timeout`, `CommandFailed`, `UnexpectedReply`, `QueueFull`.
+    /// Recoverable: `Timeout`, `CommandFailed`, `UnexpectedReply`, `QueueFull`,
+    /// `Lagged`.
     /// Non-recoverable errors (I/O, auth, disconnect) mean the connection is dead
     /// and the caller should reconnect.
     pub fn is_recoverable(&self) -> bool {
@@ -175,17 +207,20 @@ impl EslError {
             EslError::ConnectionClosed => false,
             EslError::AuthenticationFailed { .. } => false,
             EslError::HeartbeatExpired { .. } => false,
+            EslError::Desync { .. } => false,
             EslError::Timeout { .. } => true,
             EslError::CommandFailed { .. } => true,
             EslError::UnexpectedReply { .. } => true,
             EslError::QueueFull => true,
+            EslError::Lagged { .. } => true,
             _ => false,
         }
     }
 
     /// `true` if the TCP session is dead and the caller should reconnect.
     ///
-    /// Matches: `Io`, `NotConnected`, `ConnectionClosed`, `HeartbeatExpired`.
+    /// Matches: `Io`, `NotConnected`, `ConnectionClosed`, `HeartbeatExpired`,
+    /// `Desync`.
     pub fn is_connection_error(&self) -> bool {
         matches!(
             self,
@@ -193,6 +228,7 @@ impl EslError {
                 | EslError::NotConnected
                 | EslError::ConnectionClosed
                 | EslError::HeartbeatExpired { .. }
+                | EslError::Desync { .. }
         )
     }
 }
@@ -1,16 +1,56 @@
 //! ESL event types and structures
 
-use crate::channel::{AnswerState, CallDirection, CallState, ChannelState};
+use crate::channel::{AnswerState, CallDirection, CallState, ChannelState, HangupCause};
 use crate::constants::{
     HEADER_ANSWER_STATE, HEADER_CALLER_UUID, HEADER_CALL_DIRECTION, HEADER_CHANNEL_CALL_STATE,
     HEADER_CHANNEL_STATE, HEADER_CHANNEL_STATE_NUMBER, HEADER_UNIQUE_ID,
 };
 use crate::variables::EslArray;
-use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use indexmap::IndexMap;
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, CONTROLS};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
 use std::fmt;
+use std::ops::Range;
 use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+
+/// Bytes `switch_url_encode()` escapes in header values: controls,
+/// non-ASCII bytes, space and the punctuation below.
+const URL_UNSAFE: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b':')
+    .add(b';')
+    .add(b'<')
+    .add(b'=')
+    .add(b'>')
+    .add(b'?')
+    .add(b'@')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// Percent-decode a header value, borrowing when there is nothing to decode.
+///
+/// A value that does not decode to UTF-8 is returned raw.
+pub(crate) fn decode_header_value(raw: &str) -> Cow<'_, str> {
+    if !raw.contains('%') {
+        return Cow::Borrowed(raw);
+    }
+    percent_decode_str(raw)
+        .decode_utf8()
+        .unwrap_or(Cow::Borrowed(raw))
+}
 
 /// Event format types supported by FreeSWITCH ESL
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -246,6 +286,136 @@ impl fmt::Display for ParseEventTypeError {
 
 impl std::error::Error for ParseEventTypeError {}
 
+/// Common `CUSTOM` event subclasses (the `Event-Subclass` header).
+///
+/// Pass them to [`EslClient::subscribe_events`](crate::EslClient::subscribe_events),
+/// which sends the `CUSTOM <subclass> ...` list FreeSWITCH expects. Subclasses
+/// not listed here are still available as strings through
+/// [`EslEvent::event_subclass`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EventSubclass {
+    /// `sofia::register`: a SIP registration succeeded.
+    SofiaRegister,
+    /// `sofia::unregister`: a SIP registration was removed or expired.
+    SofiaUnregister,
+    /// `sofia::gateway_state`: an outbound gateway changed registration state.
+    SofiaGatewayState,
+    /// `conference::maintenance`: conference and member activity.
+    ConferenceMaintenance,
+    /// `callcenter::info`: mod_callcenter queue, agent and member activity.
+    CallcenterInfo,
+    /// `fifo::info`: mod_fifo queue activity.
+    FifoInfo,
+    /// `valet_parking::info`: calls parked in or retrieved from a valet lot.
+    ValetParkingInfo,
+    /// `spandsp::txfaxresult`: outcome of a sent fax.
+    SpandspTxFaxResult,
+}
+
+impl EventSubclass {
+    /// Every known subclass.
+    pub const ALL: [EventSubclass; 8] = [
+        EventSubclass::SofiaRegister,
+        EventSubclass::SofiaUnregister,
+        EventSubclass::SofiaGatewayState,
+        EventSubclass::ConferenceMaintenance,
+        EventSubclass::CallcenterInfo,
+        EventSubclass::FifoInfo,
+        EventSubclass::ValetParkingInfo,
+        EventSubclass::SpandspTxFaxResult,
+    ];
+
+    /// Wire name, e.g. `sofia::register`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventSubclass::SofiaRegister => "sofia::register",
+            EventSubclass::SofiaUnregister => "sofia::unregister",
+            EventSubclass::SofiaGatewayState => "sofia::gateway_state",
+            EventSubclass::ConferenceMaintenance => "conference::maintenance",
+            EventSubclass::CallcenterInfo => "callcenter::info",
+            EventSubclass::FifoInfo => "fifo::info",
+            EventSubclass::ValetParkingInfo => "valet_parking::info",
+            EventSubclass::SpandspTxFaxResult => "spandsp::txfaxresult",
+        }
+    }
+
+    /// Event list for an `event` command: `CUSTOM` followed by the subclasses.
+    pub fn event_list(subclasses: &[EventSubclass]) -> String {
+        let mut list = String::from("CUSTOM");
+        for subclass in subclasses {
+            list.push(' ');
+            list.push_str(subclass.as_str());
+        }
+        list
+    }
+}
+
+impl fmt::Display for EventSubclass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Entry of an `event` or `nixevent` list: an event type or a `CUSTOM`
+/// subclass.
+///
+/// Both convert with `From`, so a list can mix them:
+///
+/// ```
+/// use freeswitch_esl_tokio::{EslEventType, EventKind, EventSubclass};
+///
+/// let events: [EventKind; 2] = [
+///     EslEventType::ChannelAnswer.into(),
+///     EventSubclass::SofiaRegister.into(),
+/// ];
+/// # let _ = events;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EventKind {
+    /// An event type; [`EslEventType::Custom`] alone means every subclass.
+    Type(EslEventType),
+    /// A single `CUSTOM` subclass.
+    Subclass(EventSubclass),
+}
+
+impl From<EslEventType> for EventKind {
+    fn from(event_type: EslEventType) -> Self {
+        EventKind::Type(event_type)
+    }
+}
+
+impl From<EventSubclass> for EventKind {
+    fn from(subclass: EventSubclass) -> Self {
+        EventKind::Subclass(subclass)
+    }
+}
+
+/// Error returned when parsing an unknown event subclass string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEventSubclassError(pub String);
+
+impl fmt::Display for ParseEventSubclassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown event subclass: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseEventSubclassError {}
+
+impl FromStr for EventSubclass {
+    type Err = ParseEventSubclassError;
+
+    /// Exact wire name; FreeSWITCH subclass names are case-sensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EventSubclass::ALL
+            .into_iter()
+            .find(|subclass| subclass.as_str() == s)
+            .ok_or_else(|| ParseEventSubclassError(s.to_string()))
+    }
+}
+
 /// Event priority levels matching FreeSWITCH `esl_priority_t`
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -296,11 +466,34 @@ impl FromStr for EslEventPriority {
     }
 }
 
+/// Stored header value.
+#[derive(Debug, Clone)]
+enum HeaderValue {
+    /// Set through the API, already decoded.
+    Decoded(String),
+    /// Span of the event's wire text, percent-decoded on first read if
+    /// `encoded`.
+    Wire {
+        span: Range<usize>,
+        encoded: bool,
+        decoded: OnceLock<String>,
+    },
+}
+
 /// ESL Event structure containing headers and optional body
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Parsed plain and XML events share one copy of their header text; each
+/// value is a view into it, percent-decoded the first time it is read.
+///
+/// Serializes as the flat object FreeSWITCH sends for `text/event-json`:
+/// one key per header, `ARRAY::` values as lists, and the body under `_body`
+/// with a matching `Content-Length`.
+#[derive(Clone)]
 pub struct EslEvent {
     event_type: Option<EslEventType>,
-    headers: HashMap<String, String>,
+    headers: IndexMap<String, HeaderValue>,
+    /// Header text that [`HeaderValue::Wire`] spans point into.
+    wire: Option<Arc<str>>,
     body: Option<String>,
 }
 
@@ -309,7 +502,8 @@ impl EslEvent {
     pub fn new() -> Self {
         Self {
             event_type: None,
-            headers: HashMap::new(),
+            headers: IndexMap::new(),
+            wire: None,
             body: None,
         }
     }
@@ -318,8 +512,64 @@ impl EslEvent {
     pub fn with_type(event_type: EslEventType) -> Self {
         Self {
             event_type: Some(event_type),
-            headers: HashMap::new(),
-            body: None,
+            ..Self::new()
+        }
+    }
+
+    /// Create an event whose headers are spans of `wire`, added with
+    /// [`push_wire_header`](Self::push_wire_header).
+    pub(crate) fn from_wire(wire: Arc<str>) -> Self {
+        Self {
+            wire: Some(wire),
+            ..Self::new()
+        }
+    }
+
+    /// Add a header whose percent-encoded value is `span` of the wire text.
+    ///
+    /// A repeated header is decoded and folded into an `ARRAY::` value, as
+    /// [`push_header`](Self::push_header) does.
+    pub(crate) fn push_wire_header(&mut self, name: &str, span: Range<usize>) {
+        let wire = self
+            .wire
+            .clone()
+            .unwrap_or_else(|| Arc::from(""));
+        let raw = &wire[span.clone()];
+        if self
+            .headers
+            .contains_key(name)
+        {
+            self.push_header(name, &decode_header_value(raw));
+            return;
+        }
+        let value = HeaderValue::Wire {
+            span,
+            encoded: raw.contains('%'),
+            decoded: OnceLock::new(),
+        };
+        self.headers
+            .insert(name.to_string(), value);
+    }
+
+    /// Text of a stored value, decoding a wire span on first use.
+    fn value<'a>(&'a self, value: &'a HeaderValue) -> &'a str {
+        match value {
+            HeaderValue::Decoded(value) => value,
+            HeaderValue::Wire {
+                span,
+                encoded,
+                decoded,
+            } => {
+                let raw = &self
+                    .wire
+                    .as_deref()
+                    .unwrap_or_default()[span.clone()];
+                if *encoded {
+                    decoded.get_or_init(|| decode_header_value(raw).into_owned())
+                } else {
+                    raw
+                }
+            }
         }
     }
 
@@ -337,24 +587,33 @@ impl EslEvent {
     pub fn header(&self, name: &str) -> Option<&str> {
         self.headers
             .get(name)
-            .map(|s| s.as_str())
+            .map(|value| self.value(value))
     }
 
-    /// All headers as a map.
-    pub fn headers(&self) -> &HashMap<String, String> {
-        &self.headers
+    /// All headers, in wire (insertion) order.
+    pub fn headers(&self) -> impl ExactSizeIterator<Item = (&str, &str)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), self.value(value)))
     }
 
-    /// Set or overwrite a header.
+    /// Set or overwrite a header. An overwritten header keeps its position.
     pub fn set_header(&mut self, name: impl Into<String>, value: impl Into<String>) {
         self.headers
-            .insert(name.into(), value.into());
+            .insert(name.into(), HeaderValue::Decoded(value.into()));
     }
 
     /// Remove a header, returning its value if it existed.
     pub fn del_header(&mut self, name: &str) -> Option<String> {
-        self.headers
-            .remove(name)
+        let value = self
+            .headers
+            .shift_remove(name)?;
+        Some(match value {
+            HeaderValue::Decoded(value) => value,
+            wire => self
+                .value(&wire)
+                .to_string(),
+        })
     }
 
     /// Event body (the content after the blank line in plain-text events).
@@ -416,17 +675,14 @@ impl EslEvent {
     }
 
     fn stack_header(&mut self, name: &str, value: &str, op: fn(&mut EslArray, String)) {
-        match self
-            .headers
-            .get(name)
-        {
+        match self.header(name) {
             None => {
                 self.set_header(name, value);
             }
             Some(existing) => {
                 let mut arr = match EslArray::parse(existing) {
                     Some(arr) => arr,
-                    None => EslArray::new(vec![existing.clone()]),
+                    None => EslArray::new(vec![existing.to_string()]),
                 };
                 op(&mut arr, value.into());
                 self.set_header(name, arr.to_string());
@@ -460,9 +716,12 @@ impl EslEvent {
         self.header("Caller-Caller-ID-Name")
     }
 
-    /// `Hangup-Cause` header (e.g. `NORMAL_CLEARING`, `USER_BUSY`).
-    pub fn hangup_cause(&self) -> Option<&str> {
+    /// Parse the `Hangup-Cause` header into a [`HangupCause`].
+    ///
+    /// Names this crate does not know give [`HangupCause::Unknown`].
+    pub fn hangup_cause(&self) -> Option<HangupCause> {
         self.header("Hangup-Cause")
+            .map(HangupCause::from_header)
     }
 
     /// Parse the `Channel-State` header into a [`ChannelState`].
@@ -525,6 +784,18 @@ impl EslEvent {
         self.header("Event-Subclass")
     }
 
+    /// [`event_subclass`](Self::event_subclass) as a known [`EventSubclass`].
+    pub fn subclass(&self) -> Option<EventSubclass> {
+        self.event_subclass()?
+            .parse()
+            .ok()
+    }
+
+    /// Check whether this is a `CUSTOM` event of the given subclass.
+    pub fn is_subclass(&self, subclass: EventSubclass) -> bool {
+        self.event_subclass() == Some(subclass.as_str())
+    }
+
     /// Look up a channel variable by name.
     ///
     /// Checks the `variable_{name}` header, which is how FreeSWITCH exposes
@@ -545,37 +816,20 @@ impl EslEvent {
     /// be fed back through the parser to reconstruct an equivalent `EslEvent`
     /// (round-trip).
     ///
-    /// `Event-Name` is emitted first, remaining headers are sorted alphabetically
-    /// for deterministic output. `Content-Length` from stored headers is skipped
-    /// and recomputed from the body if present.
+    /// Headers are emitted in insertion order and encoded like
+    /// `switch_url_encode()`, so a parsed FreeSWITCH event re-serializes
+    /// byte-identically. `Content-Length` from stored headers is skipped and
+    /// recomputed from the body if present, as the last header.
     pub fn to_plain_format(&self) -> String {
         use std::fmt::Write;
         let mut result = String::new();
 
-        if let Some(event_name) = self
-            .headers
-            .get("Event-Name")
-        {
-            let _ = writeln!(
-                result,
-                "Event-Name: {}",
-                percent_encode(event_name.as_bytes(), NON_ALPHANUMERIC)
-            );
-        }
-
-        let mut sorted_headers: Vec<_> = self
-            .headers
-            .iter()
-            .filter(|(k, _)| k.as_str() != "Event-Name" && k.as_str() != "Content-Length")
-            .collect();
-        sorted_headers.sort_by_key(|(k, _)| k.as_str());
-
-        for (key, value) in sorted_headers {
+        for (key, value) in self.wire_headers() {
             let _ = writeln!(
                 result,
                 "{}: {}",
                 key,
-                percent_encode(value.as_bytes(), NON_ALPHANUMERIC)
+                percent_encode(value.as_bytes(), URL_UNSAFE)
             );
         }
 
@@ -589,6 +843,167 @@ impl EslEvent {
 
         result
     }
+
+    /// Serialize to the `text/event-json` body format.
+    ///
+    /// This is the inverse of `EslParser::parse_json_event()`; see the
+    /// [`Serialize`] impl for the shape. Headers keep insertion order.
+    pub fn to_json(&self) -> String {
+        // A map of strings and string lists always serializes
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Serialize to the `text/event-xml` body format.
+    ///
+    /// This is the inverse of `EslParser::parse_xml_event()`. Header values
+    /// are percent-encoded and `ARRAY::` values repeat the element once per
+    /// item, as `switch_event_xmlize()` does; the body follows `<headers>`
+    /// with a `Content-length` element.
+    pub fn to_xml(&self) -> String {
+        use std::fmt::Write;
+        let mut result = String::from("<event>\n  <headers>\n");
+
+        for (key, value) in self.wire_headers() {
+            let items = match EslArray::parse(value) {
+                Some(array) => array
+                    .items()
+                    .to_vec(),
+                None => vec![value.to_string()],
+            };
+            for item in items {
+                let _ = writeln!(
+                    result,
+                    "    <{key}>{}</{key}>",
+                    percent_encode(item.as_bytes(), URL_UNSAFE)
+                );
+            }
+        }
+        result.push_str("  </headers>\n");
+
+        if let Some(body) = &self.body {
+            let _ = writeln!(result, "  <Content-length>{}</Content-length>", body.len());
+            let _ = writeln!(result, "  <body>{}</body>", quick_xml::escape::escape(body));
+        }
+        result.push_str("</event>");
+        result
+    }
+
+    /// Headers in output order, without `Content-Length` (it is recomputed
+    /// from the body).
+    fn wire_headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers()
+            .filter(|(k, _)| *k != "Content-Length")
+    }
+}
+
+impl fmt::Debug for EslEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EslEvent")
+            .field("event_type", &self.event_type)
+            .field(
+                "headers",
+                &self
+                    .headers()
+                    .collect::<IndexMap<_, _>>(),
+            )
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+/// Header order is ignored, as for an [`IndexMap`].
+impl PartialEq for EslEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.event_type == other.event_type
+            && self.body == other.body
+            && self
+                .headers
+                .len()
+                == other
+                    .headers
+                    .len()
+            && self
+                .headers()
+                .all(|(name, value)| other.header(name) == Some(value))
+    }
+}
+
+impl Eq for EslEvent {}
+
+impl Serialize for EslEvent {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        for (key, value) in self.wire_headers() {
+            match EslArray::parse(value) {
+                Some(array) => map.serialize_entry(key, array.items())?,
+                None => map.serialize_entry(key, value)?,
+            }
+        }
+        if let Some(body) = &self.body {
+            map.serialize_entry(
+                "Content-Length",
+                &body
+                    .len()
+                    .to_string(),
+            )?;
+            map.serialize_entry("_body", body)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for EslEvent {
+    /// Accepts the `text/event-json` shape: string values become headers,
+    /// lists become `ARRAY::` values, other scalars are stored as their JSON
+    /// text, and `_body` becomes the body. Key order is kept and a repeated
+    /// key is folded into an `ARRAY::` value. The event type comes from
+    /// `Event-Name`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(EventVisitor)
+    }
+}
+
+struct EventVisitor;
+
+impl<'de> serde::de::Visitor<'de> for EventVisitor {
+    type Value = EslEvent;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of event headers")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<EslEvent, A::Error> {
+        use serde_json::Value;
+
+        let mut event = EslEvent::new();
+        while let Some((key, value)) = map.next_entry::<String, Value>()? {
+            let text = match value {
+                Value::String(s) => s,
+                Value::Array(items) => EslArray::new(
+                    items
+                        .into_iter()
+                        .map(|item| match item {
+                            Value::String(s) => s,
+                            other => other.to_string(),
+                        })
+                        .collect(),
+                )
+                .to_string(),
+                other => other.to_string(),
+            };
+            if key == "_body" {
+                event.set_body(text);
+            } else {
+                event.push_header(&key, &text);
+            }
+        }
+        if let Some(event_name) = event.header("Event-Name") {
+            event.event_type = EslEventType::parse_event_type(event_name);
+        }
+        Ok(event)
+    }
 }
 
 impl Default for EslEvent {
@@ -597,6 +1012,19 @@ impl Default for EslEvent {
     }
 }
 
+#[cfg(test)]
+impl EslEvent {
+    /// Event of `event_type` with its `Event-Name` and then `headers`, in order.
+    pub(crate) fn test_event(event_type: EslEventType, headers: &[(&str, &str)]) -> Self {
+        let mut event = EslEvent::with_type(event_type);
+        event.set_header("Event-Name", event_type.to_string());
+        for (name, value) in headers {
+            event.set_header(*name, *value);
+        }
+        event
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -673,6 +1101,44 @@ mod tests {
             .is_err());
     }
 
+    #[test]
+    fn test_wire_headers_decode_on_first_read() {
+        let wire: Arc<str> = "Plain: value\nEncoded: a%20b\nBad: %ZZ".into();
+        let mut event = EslEvent::from_wire(wire.clone());
+        event.push_wire_header("Plain", 7..12);
+        event.push_wire_header("Encoded", 22..27);
+        event.push_wire_header("Bad", 33..36);
+
+        let decoded = |event: &EslEvent, name: &str| match &event.headers[name] {
+            HeaderValue::Wire { decoded, .. } => decoded
+                .get()
+                .cloned(),
+            HeaderValue::Decoded(_) => panic!("{} was decoded eagerly", name),
+        };
+        assert_eq!(decoded(&event, "Encoded"), None);
+
+        let plain = event
+            .header("Plain")
+            .unwrap();
+        assert_eq!(plain, "value");
+        assert!(std::ptr::eq(plain.as_ptr(), wire[7..].as_ptr()));
+        assert_eq!(event.header("Encoded"), Some("a b"));
+        assert_eq!(decoded(&event, "Encoded").as_deref(), Some("a b"));
+        assert_eq!(event.header("Bad"), Some("%ZZ"));
+    }
+
+    #[test]
+    fn test_repeated_wire_header_folds() {
+        let mut event = EslEvent::from_wire("a%20b c".into());
+        event.push_wire_header("X-Route", 0..5);
+        event.push_wire_header("X-Route", 6..7);
+        assert_eq!(event.header("X-Route"), Some("ARRAY::a b|:c"));
+        assert_eq!(
+            event.del_header("X-Route"),
+            Some("ARRAY::a b|:c".to_string())
+        );
+    }
+
     #[test]
     fn test_del_header() {
         let mut event = EslEvent::new();
@@ -744,36 +1210,54 @@ mod tests {
         let msg1 = EslMessage::new(
             MessageType::Event,
             {
-                let mut h = HashMap::new();
+                let mut h = IndexMap::new();
                 h.insert("Content-Type".to_string(), "text/event-plain".to_string());
                 h
             },
-            Some(plain1.clone()),
+            Some(
+                plain1
+                    .clone()
+                    .into(),
+            ),
         );
         let parsed1 = EslParser::new()
             .parse_event(msg1, crate::event::EventFormat::Plain)
             .unwrap();
 
         assert_eq!(parsed1.event_type, original.event_type);
-        assert_eq!(parsed1.headers, original.headers);
+        assert_eq!(
+            parsed1
+                .headers()
+                .collect::<Vec<_>>(),
+            original
+                .headers()
+                .collect::<Vec<_>>()
+        );
         assert_eq!(parsed1.body, original.body);
 
         let plain2 = parsed1.to_plain_format();
         let msg2 = EslMessage::new(
             MessageType::Event,
             {
-                let mut h = HashMap::new();
+                let mut h = IndexMap::new();
                 h.insert("Content-Type".to_string(), "text/event-plain".to_string());
                 h
             },
-            Some(plain2),
+            Some(plain2.into()),
         );
         let parsed2 = EslParser::new()
             .parse_event(msg2, crate::event::EventFormat::Plain)
             .unwrap();
 
         assert_eq!(parsed2.event_type, original.event_type);
-        assert_eq!(parsed2.headers, original.headers);
+        assert_eq!(
+            parsed2
+                .headers()
+                .collect::<Vec<_>>(),
+            original
+                .headers()
+                .collect::<Vec<_>>()
+        );
         assert_eq!(parsed2.body, original.body);
     }
 
@@ -797,21 +1281,146 @@ mod tests {
         let msg = EslMessage::new(
             MessageType::Event,
             {
-                let mut h = HashMap::new();
+                let mut h = IndexMap::new();
                 h.insert("Content-Type".to_string(), "text/event-plain".to_string());
                 h
             },
-            Some(plain),
+            Some(plain.into()),
         );
         let parsed = EslParser::new()
             .parse_event(msg, crate::event::EventFormat::Plain)
             .unwrap();
 
         assert_eq!(parsed.event_type, original.event_type);
-        assert_eq!(parsed.headers, original.headers);
+        assert_eq!(
+            parsed
+                .headers()
+                .collect::<Vec<_>>(),
+            original
+                .headers()
+                .collect::<Vec<_>>()
+        );
         assert_eq!(parsed.body, original.body);
     }
 
+    /// A BACKGROUND_JOB event with a multi-value header and a body that needs
+    /// escaping in every format.
+    fn sample_job_event() -> EslEvent {
+        let body_text = "+OK <done> & \"quoted\"\nLine 2\n";
+        let mut event = EslEvent::with_type(EslEventType::BackgroundJob);
+        event.set_header("Event-Name", "BACKGROUND_JOB");
+        event.set_header("Job-UUID", "job-789");
+        event.set_header("Job-Command-Arg", "1000 &park()");
+        event.set_header("variable_codecs", "ARRAY::PCMU|:PCMA");
+        event.set_header(
+            "Content-Length",
+            body_text
+                .len()
+                .to_string(),
+        );
+        event.set_body(body_text.to_string());
+        event
+    }
+
+    fn reparse(body: String, format: EventFormat) -> EslEvent {
+        use crate::protocol::{EslMessage, EslParser, MessageType};
+
+        let msg = EslMessage::new(MessageType::Event, IndexMap::new(), Some(body.into()));
+        EslParser::new()
+            .parse_event(msg, format)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_to_json_shape() {
+        let json: serde_json::Value = serde_json::from_str(&sample_job_event().to_json()).unwrap();
+        assert_eq!(json["Event-Name"], "BACKGROUND_JOB");
+        assert_eq!(json["variable_codecs"], serde_json::json!(["PCMU", "PCMA"]));
+        assert_eq!(json["Content-Length"], "29");
+        assert_eq!(json["_body"], "+OK <done> & \"quoted\"\nLine 2\n");
+        assert!(json
+            .get("body")
+            .is_none());
+    }
+
+    #[test]
+    fn test_to_json_round_trip() {
+        let original = sample_job_event();
+        let parsed = reparse(original.to_json(), EventFormat::Json);
+        assert_eq!(parsed, original);
+
+        let event: EslEvent = serde_json::from_str(&original.to_json()).unwrap();
+        assert_eq!(event, original);
+    }
+
+    #[test]
+    fn test_json_keeps_header_order() {
+        let json = r#"{"Event-Name":"CUSTOM","Zeta":"1","Alpha":"2","Mid":"3"}"#;
+        let event: EslEvent = serde_json::from_str(json).unwrap();
+        let names: Vec<&str> = event
+            .headers()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["Event-Name", "Zeta", "Alpha", "Mid"]);
+        assert_eq!(event.to_json(), json);
+    }
+
+    #[test]
+    fn test_del_header_keeps_order() {
+        let mut event = EslEvent::new();
+        event.set_header("A", "1");
+        event.set_header("B", "2");
+        event.set_header("C", "3");
+        event.del_header("B");
+        event.set_header("A", "updated");
+        let headers: Vec<(&str, &str)> = event
+            .headers()
+            .collect();
+        assert_eq!(headers, [("A", "updated"), ("C", "3")]);
+    }
+
+    #[test]
+    fn test_to_xml_shape() {
+        let xml = sample_job_event().to_xml();
+        assert!(
+            xml.starts_with("<event>\n  <headers>\n    <Event-Name>BACKGROUND_JOB</Event-Name>\n")
+        );
+        assert!(xml.contains("<Job-Command-Arg>1000%20%26park()</Job-Command-Arg>"));
+        assert!(xml.contains("<variable_codecs>PCMU</variable_codecs>"));
+        assert!(xml.contains("<variable_codecs>PCMA</variable_codecs>"));
+        assert!(xml.contains("  <Content-length>29</Content-length>\n"));
+        assert!(xml.contains("<body>+OK &lt;done&gt; &amp; &quot;quoted&quot;\nLine 2\n</body>"));
+        assert!(xml.ends_with("</event>"));
+    }
+
+    #[test]
+    fn test_to_xml_round_trip() {
+        let mut original = sample_job_event();
+        let parsed = reparse(original.to_xml(), EventFormat::Xml);
+        // The XML parser takes Content-length from the body, not the headers
+        original.del_header("Content-Length");
+        assert_eq!(parsed, original);
+    }
+
+    #[test]
+    fn test_to_xml_without_body() {
+        let mut original = EslEvent::with_type(EslEventType::Heartbeat);
+        original.set_header("Event-Name", "HEARTBEAT");
+        original.set_header("Up-Time", "0 years, 0 days, 1 hour");
+        let xml = original.to_xml();
+        assert!(!xml.contains("<body>"));
+        assert_eq!(reparse(xml, EventFormat::Xml), original);
+    }
+
+    #[test]
+    fn test_plain_round_trip_all_formats_agree() {
+        let original = sample_job_event();
+        let plain = reparse(original.to_plain_format(), EventFormat::Plain);
+        let json = reparse(original.to_json(), EventFormat::Json);
+        assert_eq!(plain, json);
+        assert_eq!(plain.to_json(), original.to_json());
+    }
+
     #[test]
     fn test_set_priority_normal() {
         let mut event = EslEvent::new();
@@ -929,12 +1538,41 @@ mod tests {
         );
         assert_eq!(event.caller_id_number(), Some("1000"));
         assert_eq!(event.caller_id_name(), Some("Alice"));
-        assert_eq!(event.hangup_cause(), Some("NORMAL_CLEARING"));
+        assert_eq!(event.hangup_cause(), Some(HangupCause::NormalClearing));
         assert_eq!(event.event_subclass(), Some("sofia::register"));
         assert_eq!(event.variable("sip_from_display"), Some("Bob"));
         assert_eq!(event.variable("nonexistent"), None);
     }
 
+    #[test]
+    fn test_event_subclass_round_trip() {
+        for subclass in EventSubclass::ALL {
+            assert_eq!(
+                subclass
+                    .to_string()
+                    .parse::<EventSubclass>(),
+                Ok(subclass)
+            );
+        }
+        assert!("Sofia::Register"
+            .parse::<EventSubclass>()
+            .is_err());
+        assert_eq!(
+            EventSubclass::event_list(&[
+                EventSubclass::SofiaRegister,
+                EventSubclass::SofiaUnregister
+            ]),
+            "CUSTOM sofia::register sofia::unregister"
+        );
+
+        let mut event = EslEvent::with_type(EslEventType::Custom);
+        event.set_header("Event-Subclass", "valet_parking::info");
+        assert_eq!(event.subclass(), Some(EventSubclass::ValetParkingInfo));
+        assert!(event.is_subclass(EventSubclass::ValetParkingInfo));
+        event.set_header("Event-Subclass", "mod_custom::thing");
+        assert_eq!(event.subclass(), None);
+    }
+
     #[test]
     fn test_event_format_from_str() {
         assert_eq!("plain".parse::<EventFormat>(), Ok(EventFormat::Plain));
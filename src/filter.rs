@@ -0,0 +1,858 @@
+//! Client-side event filtering and fan-out streams.
+//!
+//! [`EslClient::subscribe_stream`] opens an extra receiver that gets a copy of
+//! every event matching its [`EventFilter`], independently of the main
+//! [`EslEventStream`](crate::EslEventStream) and of other receivers. Each
+//! receiver has its own buffer; a receiver that falls behind skips events and
+//! is told how many with [`EslError::Lagged`], without slowing the others.
+//!
+//! Filters here run in the client, after FreeSWITCH has delivered the event;
+//! the connection still has to be subscribed to the event types with
+//! [`EslClient::subscribe_events`]. [`EslClient::subscribe_events_filtered`]
+//! does both, and also sends FreeSWITCH the parts of the filter it can
+//! evaluate itself ([`EventFilter::server_filters`]) so fewer events cross
+//! the wire.
+//!
+//! ```rust,no_run
+//! use freeswitch_esl_tokio::{EslClient, EslEventType, EventFilter, EventFormat};
+//!
+//! # async fn example() -> Result<(), freeswitch_esl_tokio::EslError> {
+//! let (client, _events) = EslClient::connect("localhost", 8021, "ClueCon").await?;
+//! client
+//!     .subscribe_events(EventFormat::Plain, &[EslEventType::All])
+//!     .await?;
+//!
+//! let mut cdr = client.subscribe_stream(
+//!     EventFilter::new().event_types(&[EslEventType::ChannelHangupComplete]),
+//! );
+//! let mut registrations = client.subscribe_stream(
+//!     EventFilter::new().header("Event-Subclass", "sofia::register"),
+//! );
+//!
+//! tokio::spawn(async move {
+//!     while let Some(Ok(event)) = cdr.recv().await {
+//!         println!("CDR for {:?}", event.unique_id());
+//!     }
+//! });
+//! while let Some(item) = registrations.recv().await {
+//!     println!("{:?}", item.map(|e| e.header("from-user").map(str::to_string)));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use regex::Regex;
+use tokio::sync::mpsc;
+
+use crate::{
+    error::EslError,
+    event::{EslEvent, EslEventType},
+};
+
+#[cfg(doc)]
+use crate::EslClient;
+
+/// Client-side event predicate.
+///
+/// An empty filter matches every event. Each added condition must hold:
+/// the event type must be one of [`event_types`](Self::event_types), every
+/// [`header`](Self::header) must match exactly, and the channel must be
+/// [`uuid`](Self::uuid).
+///
+/// Filters can also be written as expressions, see [`parse`](Self::parse):
+///
+/// ```rust
+/// use freeswitch_esl_tokio::EventFilter;
+///
+/// let filter: EventFilter = "Event-Name in (CHANNEL_ANSWER, CHANNEL_HANGUP) \
+///     and variable_sip_from_user =~ /^1[0-9]{3}$/"
+///     .parse()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// All must hold.
+    conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    EventTypes(Vec<EslEventType>),
+    Uuid(String),
+    /// Header present and equal to one of the values.
+    Header {
+        name: String,
+        values: Vec<String>,
+    },
+    Regex {
+        name: String,
+        regex: Regex,
+    },
+    Exists(String),
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    fn matches(&self, event: &EslEvent) -> bool {
+        match self {
+            Self::EventTypes(types) => event
+                .event_type()
+                .is_some_and(|t| types.contains(&t)),
+            Self::Uuid(uuid) => event.unique_id() == Some(uuid.as_str()),
+            Self::Header { name, values } => event
+                .header(name)
+                .is_some_and(|v| {
+                    values
+                        .iter()
+                        .any(|value| value == v)
+                }),
+            Self::Regex { name, regex } => event
+                .header(name)
+                .is_some_and(|v| regex.is_match(v)),
+            Self::Exists(name) => event
+                .header(name)
+                .is_some(),
+            Self::Not(inner) => !inner.matches(event),
+            Self::All(conditions) => conditions
+                .iter()
+                .all(|c| c.matches(event)),
+            Self::Any(conditions) => conditions
+                .iter()
+                .any(|c| c.matches(event)),
+        }
+    }
+
+    /// ESL filters whose union lets through every event this condition
+    /// matches, or `None` if the server cannot narrow it down.
+    fn server_filters(&self) -> Option<Vec<(String, String)>> {
+        match self {
+            Self::EventTypes(types) => {
+                if types.contains(&EslEventType::All) {
+                    return None;
+                }
+                Some(
+                    types
+                        .iter()
+                        .map(|t| ("Event-Name".to_string(), t.to_string()))
+                        .collect(),
+                )
+            }
+            Self::Header { name, values } => values
+                .iter()
+                .map(|value| {
+                    // FreeSWITCH reads a leading `/` as a regex and a leading
+                    // `+`, `-` or space as a filter flag.
+                    let literal = !value.is_empty()
+                        && !value.starts_with(['/', '+', '-', ' '])
+                        && !value.contains(['\n', '\r']);
+                    literal.then(|| (name.clone(), value.clone()))
+                })
+                .collect(),
+            Self::Regex { name, regex } => {
+                let pattern = regex.as_str();
+                (!pattern.contains(['\n', '\r']))
+                    .then(|| vec![(name.clone(), format!("/{}/", pattern))])
+            }
+            Self::Uuid(_) | Self::Exists(_) | Self::Not(_) => None,
+            // Any one conjunct is enough; take the narrowest.
+            Self::All(conditions) => conditions
+                .iter()
+                .filter_map(Self::server_filters)
+                .min_by_key(Vec::len),
+            Self::Any(conditions) => conditions
+                .iter()
+                .map(Self::server_filters)
+                .collect::<Option<Vec<_>>>()
+                .map(|sets| sets.concat()),
+        }
+    }
+}
+
+impl EventFilter {
+    /// Filter matching every event.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a filter expression.
+    ///
+    /// Conditions on headers:
+    ///
+    /// | Syntax | Matches when the header |
+    /// |---|---|
+    /// | `Header == value` | equals `value` |
+    /// | `Header != value` | is absent or differs from `value` |
+    /// | `Header in (a, b, ...)` | equals one of the values |
+    /// | `Header =~ /regex/` | matches the regex (`\/` for a literal slash) |
+    /// | `Header !~ /regex/` | is absent or does not match |
+    /// | `Header exists` | is present |
+    ///
+    /// Conditions combine with `and`, `or`, `not` and parentheses; `and`
+    /// binds tighter than `or`. Keywords are case-insensitive, values are
+    /// not. Values containing spaces, parentheses, commas, `=` or `!` must
+    /// be double-quoted (`"..."`, with `\"` and `\\` escapes).
+    ///
+    /// Regexes use the [`regex`](https://docs.rs/regex) crate syntax.
+    pub fn parse(expr: &str) -> Result<Self, ParseFilterError> {
+        let mut parser = Parser {
+            tokens: tokenize(expr)?,
+            pos: 0,
+            end: expr.len(),
+        };
+        let condition = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(ParseFilterError::new(
+                token.offset,
+                format!("unexpected {}", token.kind),
+            ));
+        }
+        Ok(Self {
+            conditions: vec![condition],
+        })
+    }
+
+    /// Only events of these types. Repeated calls extend the set.
+    pub fn event_types(mut self, types: &[EslEventType]) -> Self {
+        let existing = self
+            .conditions
+            .iter_mut()
+            .find_map(|c| match c {
+                Condition::EventTypes(list) => Some(list),
+                _ => None,
+            });
+        match existing {
+            Some(list) => list.extend_from_slice(types),
+            None => self
+                .conditions
+                .push(Condition::EventTypes(types.to_vec())),
+        }
+        self
+    }
+
+    /// Only events whose header `name` equals `value`.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.conditions
+            .push(Condition::Header {
+                name: name.into(),
+                values: vec![value.into()],
+            });
+        self
+    }
+
+    /// Only events for this channel (`Unique-ID`, or `Caller-Unique-ID`).
+    pub fn uuid(mut self, uuid: impl Into<String>) -> Self {
+        self.conditions
+            .retain(|c| !matches!(c, Condition::Uuid(_)));
+        self.conditions
+            .push(Condition::Uuid(uuid.into()));
+        self
+    }
+
+    /// Whether `event` passes the filter.
+    pub fn matches(&self, event: &EslEvent) -> bool {
+        self.conditions
+            .iter()
+            .all(|c| c.matches(event))
+    }
+
+    /// `(header, value)` pairs for ESL `filter` commands that let through
+    /// at least every event this filter matches.
+    ///
+    /// FreeSWITCH passes an event if it matches *any* filter on the
+    /// connection, so only a necessary condition can be pushed down: an
+    /// `==`/`in` or regex condition that every match must satisfy, or an
+    /// `or` of them. The client still applies the full filter. Empty when
+    /// nothing can be pushed down, e.g. for `not`, `!=` or `exists`.
+    /// Server-side regexes are PCRE, which agrees with the
+    /// [`regex`](https://docs.rs/regex) crate on common patterns.
+    pub fn server_filters(&self) -> Vec<(String, String)> {
+        let mut filters = Condition::All(
+            self.conditions
+                .clone(),
+        )
+        .server_filters()
+        .unwrap_or_default();
+        let mut seen = std::collections::HashSet::new();
+        filters.retain(|f| seen.insert(f.clone()));
+        filters
+    }
+}
+
+impl FromStr for EventFilter {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Error from [`EventFilter::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFilterError {
+    /// Byte offset in the expression where the problem was found.
+    pub offset: usize,
+    /// What was wrong.
+    pub message: String,
+}
+
+impl ParseFilterError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid event filter at offset {}: {}",
+            self.offset, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseFilterError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    LParen,
+    RParen,
+    Comma,
+    Eq,
+    Ne,
+    Match,
+    NotMatch,
+    /// Bare word: header name, keyword or unquoted value.
+    Word(String),
+    Quoted(String),
+    Regex(String),
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LParen => f.write_str("'('"),
+            Self::RParen => f.write_str("')'"),
+            Self::Comma => f.write_str("','"),
+            Self::Eq => f.write_str("'=='"),
+            Self::Ne => f.write_str("'!='"),
+            Self::Match => f.write_str("'=~'"),
+            Self::NotMatch => f.write_str("'!~'"),
+            Self::Word(w) => write!(f, "'{}'", w),
+            Self::Quoted(s) => write!(f, "\"{}\"", s),
+            Self::Regex(r) => write!(f, "/{}/", r),
+        }
+    }
+}
+
+struct Token {
+    kind: TokenKind,
+    offset: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | ',' | '"' | '=' | '!')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseFilterError> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = input
+        .char_indices()
+        .peekable();
+    while let Some((offset, c)) = chars.next() {
+        let after_match_op = tokens
+            .last()
+            .is_some_and(|t| matches!(t.kind, TokenKind::Match | TokenKind::NotMatch));
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            ',' => TokenKind::Comma,
+            '=' | '!' => {
+                let next = chars
+                    .peek()
+                    .map(|&(_, n)| n);
+                let kind = match (c, next) {
+                    ('=', Some('=')) => TokenKind::Eq,
+                    ('=', Some('~')) => TokenKind::Match,
+                    ('!', Some('=')) => TokenKind::Ne,
+                    ('!', Some('~')) => TokenKind::NotMatch,
+                    _ => {
+                        return Err(ParseFilterError::new(
+                            offset,
+                            "expected '==', '!=', '=~' or '!~'",
+                        ))
+                    }
+                };
+                chars.next();
+                kind
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, e @ ('"' | '\\'))) => value.push(e),
+                            Some((_, e)) => {
+                                value.push('\\');
+                                value.push(e);
+                            }
+                            None => {
+                                return Err(ParseFilterError::new(offset, "unterminated string"))
+                            }
+                        },
+                        Some((_, ch)) => value.push(ch),
+                        None => return Err(ParseFilterError::new(offset, "unterminated string")),
+                    }
+                }
+                TokenKind::Quoted(value)
+            }
+            '/' if after_match_op => {
+                let mut pattern = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '/')) => break,
+                        Some((_, '\\'))
+                            if chars
+                                .peek()
+                                .map(|&(_, n)| n)
+                                == Some('/') =>
+                        {
+                            chars.next();
+                            pattern.push('/');
+                        }
+                        Some((_, ch)) => pattern.push(ch),
+                        None => return Err(ParseFilterError::new(offset, "unterminated regex")),
+                    }
+                }
+                TokenKind::Regex(pattern)
+            }
+            c => {
+                let mut word = String::from(c);
+                while let Some(&(_, n)) = chars.peek() {
+                    if !is_word_char(n) {
+                        break;
+                    }
+                    word.push(n);
+                    chars.next();
+                }
+                TokenKind::Word(word)
+            }
+        };
+        tokens.push(Token { kind, offset });
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Offset reported for errors at the end of input.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens
+            .get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, ParseFilterError> {
+        let Some(token) = self
+            .tokens
+            .get(self.pos)
+        else {
+            return Err(ParseFilterError::new(self.end, "unexpected end of filter"));
+        };
+        self.pos += 1;
+        Ok(Token {
+            kind: token
+                .kind
+                .clone(),
+            offset: token.offset,
+        })
+    }
+
+    /// Consume the next token if it is the given keyword.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(
+            self.peek(),
+            Some(Token { kind: TokenKind::Word(w), .. }) if w.eq_ignore_ascii_case(keyword)
+        );
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), ParseFilterError> {
+        let token = self.next()?;
+        if token.kind != kind {
+            return Err(ParseFilterError::new(
+                token.offset,
+                format!("expected {}, found {}", kind, token.kind),
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<Condition, ParseFilterError> {
+        let mut terms = vec![self.parse_and()?];
+        while self.keyword("or") {
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Condition::Any(terms)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Condition, ParseFilterError> {
+        let mut terms = vec![self.parse_unary()?];
+        while self.keyword("and") {
+            terms.push(self.parse_unary()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Condition::All(terms)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Condition, ParseFilterError> {
+        if self.keyword("not") {
+            return Ok(Condition::Not(Box::new(self.parse_unary()?)));
+        }
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::LParen => {
+                let inner = self.parse_or()?;
+                self.expect(TokenKind::RParen)?;
+                Ok(inner)
+            }
+            TokenKind::Word(name) => self.parse_condition(name),
+            other => Err(ParseFilterError::new(
+                token.offset,
+                format!("expected a header name, found {}", other),
+            )),
+        }
+    }
+
+    fn parse_condition(&mut self, name: String) -> Result<Condition, ParseFilterError> {
+        if self.keyword("exists") {
+            return Ok(Condition::Exists(name));
+        }
+        if self.keyword("in") {
+            self.expect(TokenKind::LParen)?;
+            let mut values = vec![self.parse_value()?];
+            loop {
+                let token = self.next()?;
+                match token.kind {
+                    TokenKind::Comma => values.push(self.parse_value()?),
+                    TokenKind::RParen => break,
+                    other => {
+                        return Err(ParseFilterError::new(
+                            token.offset,
+                            format!("expected ',' or ')', found {}", other),
+                        ))
+                    }
+                }
+            }
+            return Ok(Condition::Header { name, values });
+        }
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::Eq => Ok(Condition::Header {
+                name,
+                values: vec![self.parse_value()?],
+            }),
+            TokenKind::Ne => Ok(Condition::Not(Box::new(Condition::Header {
+                name,
+                values: vec![self.parse_value()?],
+            }))),
+            TokenKind::Match => self.parse_regex(name),
+            TokenKind::NotMatch => Ok(Condition::Not(Box::new(self.parse_regex(name)?))),
+            other => Err(ParseFilterError::new(
+                token.offset,
+                format!(
+                    "expected '==', '!=', '=~', '!~', 'in' or 'exists' after '{}', found {}",
+                    name, other
+                ),
+            )),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<String, ParseFilterError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::Word(value) | TokenKind::Quoted(value) => Ok(value),
+            other => Err(ParseFilterError::new(
+                token.offset,
+                format!("expected a value, found {}", other),
+            )),
+        }
+    }
+
+    fn parse_regex(&mut self, name: String) -> Result<Condition, ParseFilterError> {
+        let token = self.next()?;
+        let TokenKind::Regex(pattern) = token.kind else {
+            return Err(ParseFilterError::new(
+                token.offset,
+                format!("expected /regex/, found {}", token.kind),
+            ));
+        };
+        let regex = Regex::new(&pattern)
+            .map_err(|e| ParseFilterError::new(token.offset, format!("invalid regex: {}", e)))?;
+        Ok(Condition::Regex { name, regex })
+    }
+}
+
+/// Fan-out receiver from [`EslClient::subscribe_stream`] (!Clone).
+///
+/// Yields `Err(EslError::Lagged { count })` in place of events skipped
+/// because this receiver's buffer was full. Ends when the connection closes.
+/// Dropping it unregisters the receiver.
+pub struct FilteredEventStream {
+    rx: mpsc::Receiver<Result<EslEvent, EslError>>,
+    filter: EventFilter,
+    skipped: Arc<AtomicU64>,
+}
+
+impl std::fmt::Debug for FilteredEventStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilteredEventStream")
+            .field("filter", &self.filter)
+            .field("skipped", &self.skipped_count())
+            .finish()
+    }
+}
+
+impl FilteredEventStream {
+    pub(crate) fn new(
+        rx: mpsc::Receiver<Result<EslEvent, EslError>>,
+        filter: EventFilter,
+        skipped: Arc<AtomicU64>,
+    ) -> Self {
+        Self {
+            rx,
+            filter,
+            skipped,
+        }
+    }
+
+    /// Receive the next matching event, or `None` once the connection closed.
+    pub async fn recv(&mut self) -> Option<Result<EslEvent, EslError>> {
+        self.rx
+            .recv()
+            .await
+    }
+
+    /// The filter this receiver was opened with.
+    pub fn filter(&self) -> &EventFilter {
+        &self.filter
+    }
+
+    /// Matching events skipped so far because the buffer was full.
+    pub fn skipped_count(&self) -> u64 {
+        self.skipped
+            .load(Ordering::Relaxed)
+    }
+}
+
+impl futures_util::Stream for FilteredEventStream {
+    type Item = Result<EslEvent, EslError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx
+            .poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        let filter = EventFilter::new();
+        assert!(filter.matches(&EslEvent::test_event(EslEventType::Heartbeat, &[])));
+        assert!(filter.matches(&EslEvent::new()));
+    }
+
+    #[test]
+    fn test_event_types_any_of() {
+        let filter = EventFilter::new()
+            .event_types(&[EslEventType::ChannelAnswer])
+            .event_types(&[EslEventType::ChannelHangup]);
+        assert!(filter.matches(&EslEvent::test_event(EslEventType::ChannelAnswer, &[])));
+        assert!(filter.matches(&EslEvent::test_event(EslEventType::ChannelHangup, &[])));
+        assert!(!filter.matches(&EslEvent::test_event(EslEventType::Heartbeat, &[])));
+        assert!(!filter.matches(&EslEvent::new()));
+    }
+
+    #[test]
+    fn test_headers_and_uuid_all_required() {
+        let filter = EventFilter::new()
+            .uuid("abc")
+            .header("Call-Direction", "inbound");
+        assert!(filter.matches(&EslEvent::test_event(
+            EslEventType::ChannelAnswer,
+            &[("Unique-ID", "abc"), ("Call-Direction", "inbound")]
+        )));
+        assert!(!filter.matches(&EslEvent::test_event(
+            EslEventType::ChannelAnswer,
+            &[("Unique-ID", "abc"), ("Call-Direction", "outbound")]
+        )));
+        assert!(!filter.matches(&EslEvent::test_event(
+            EslEventType::ChannelAnswer,
+            &[("Unique-ID", "xyz"), ("Call-Direction", "inbound")]
+        )));
+        assert!(filter.matches(&EslEvent::test_event(
+            EslEventType::ChannelAnswer,
+            &[("Caller-Unique-ID", "abc"), ("Call-Direction", "inbound")]
+        )));
+    }
+
+    fn parsed(expr: &str) -> EventFilter {
+        expr.parse()
+            .unwrap_or_else(|e| panic!("{}: {}", expr, e))
+    }
+
+    #[test]
+    fn test_parse_in_and_regex() {
+        let filter = parsed(
+            "Event-Name in (CHANNEL_ANSWER, CHANNEL_HANGUP) \
+             and variable_sip_from_user =~ /^1[0-9]{3}$/",
+        );
+        let answer = |from: &str| {
+            EslEvent::test_event(
+                EslEventType::ChannelAnswer,
+                &[("variable_sip_from_user", from)],
+            )
+        };
+        assert!(filter.matches(&answer("1001")));
+        assert!(!filter.matches(&answer("2001")));
+        assert!(!filter.matches(&answer("10011")));
+        assert!(!filter.matches(&EslEvent::test_event(
+            EslEventType::Heartbeat,
+            &[("variable_sip_from_user", "1001")]
+        )));
+        assert!(!filter.matches(&EslEvent::test_event(EslEventType::ChannelAnswer, &[])));
+    }
+
+    #[test]
+    fn test_parse_operators() {
+        let direction = |value: &str| {
+            EslEvent::test_event(
+                EslEventType::ChannelCreate,
+                &[("Call-Direction", value), ("Caller-Context", "public")],
+            )
+        };
+        let none = EslEvent::new();
+
+        let ne = parsed("Call-Direction != inbound");
+        assert!(!ne.matches(&direction("inbound")));
+        assert!(ne.matches(&direction("outbound")));
+        assert!(ne.matches(&none));
+
+        let not_match = parsed(r"Caller-Context !~ /^pub/");
+        assert!(!not_match.matches(&direction("inbound")));
+        assert!(not_match.matches(&none));
+
+        let exists = parsed("Caller-Context EXISTS");
+        assert!(exists.matches(&direction("inbound")));
+        assert!(!exists.matches(&none));
+
+        // `and` binds tighter than `or`
+        let precedence = parsed(
+            "Call-Direction == outbound or Call-Direction == inbound and Caller-Context == x",
+        );
+        assert!(precedence.matches(&direction("outbound")));
+        assert!(!precedence.matches(&direction("inbound")));
+        let grouped = parsed(
+            "(Call-Direction == outbound or Call-Direction == inbound) and not Caller-Context == x",
+        );
+        assert!(grouped.matches(&direction("inbound")));
+
+        let quoted = parsed(r#"Channel-Name == "sofia/internal/1000@example.com" and x !~ /a\/b/"#);
+        assert!(quoted.matches(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[
+                ("Channel-Name", "sofia/internal/1000@example.com"),
+                ("x", "a/c")
+            ]
+        )));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |expr: &str| {
+            expr.parse::<EventFilter>()
+                .unwrap_err()
+        };
+        assert_eq!(err("").offset, 0);
+        assert_eq!(err("Call-Direction").offset, 14);
+        assert_eq!(err("Call-Direction = inbound").offset, 15);
+        assert_eq!(err("a == b and").offset, 10);
+        assert_eq!(err("a == b c").offset, 7);
+        assert_eq!(err("a in (b, c").offset, 10);
+        assert_eq!(err("a =~ b").offset, 5);
+        assert_eq!(err("a =~ /(/").offset, 5);
+        assert_eq!(err(r#"a == "open"#).offset, 5);
+        assert!(err("a =~ /(/")
+            .to_string()
+            .starts_with("invalid event filter at offset 5: invalid regex"));
+    }
+
+    #[test]
+    fn test_server_filters() {
+        let pairs = |expr: &str| parsed(expr).server_filters();
+        let pair = |h: &str, v: &str| (h.to_string(), v.to_string());
+
+        assert_eq!(
+            pairs("Event-Name in (CHANNEL_ANSWER, CHANNEL_HANGUP) and x =~ /^1/"),
+            vec![pair("x", "/^1/")]
+        );
+        assert_eq!(
+            pairs("Event-Name in (CHANNEL_ANSWER, CHANNEL_HANGUP) and x exists"),
+            vec![
+                pair("Event-Name", "CHANNEL_ANSWER"),
+                pair("Event-Name", "CHANNEL_HANGUP")
+            ]
+        );
+        assert_eq!(
+            pairs("a == 1 or (b == 2 and c != 3) or a == 1"),
+            vec![pair("a", "1"), pair("b", "2")]
+        );
+        // One side of the `or` cannot be narrowed, so neither can the whole
+        assert!(pairs("a == 1 or b != 2").is_empty());
+        assert!(pairs("not a == 1").is_empty());
+        // Would be read by FreeSWITCH as a filter flag
+        assert!(pairs("a == +1555").is_empty());
+
+        let built = EventFilter::new()
+            .uuid("abc")
+            .event_types(&[EslEventType::ChannelAnswer]);
+        assert_eq!(
+            built.server_filters(),
+            vec![pair("Event-Name", "CHANNEL_ANSWER")]
+        );
+        assert!(EventFilter::new()
+            .server_filters()
+            .is_empty());
+    }
+}
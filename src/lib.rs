@@ -125,32 +125,69 @@
 //! ```
 
 pub mod app;
+pub mod calls;
+pub mod cdr;
 pub mod channel;
 pub mod commands;
 pub mod connection;
 pub mod error;
 pub mod event;
+pub mod filter;
+pub mod log;
+pub mod outbound;
+pub mod pool;
+pub mod reconnect;
+pub mod session;
+pub mod subscription;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod tracker;
+pub mod typed;
 pub mod variables;
 
 pub(crate) mod buffer;
 pub(crate) mod command;
 pub(crate) mod constants;
 pub(crate) mod protocol;
+pub(crate) mod queue;
 
-pub use app::dptools::AppCommand;
-pub use channel::{AnswerState, CallDirection, CallState, ChannelState, ChannelTimetable};
+pub use app::dptools::{
+    AppCommand, BindDigitAction, BindMetaApp, ConferenceJoin, PlayAndGetDigits, ReadDigits, Record,
+    Say, ToneDetect,
+};
+pub use calls::{Call, CallGraph, CallHistoryEntry, CallHistoryKind, CallLeg, LegRole};
+pub use cdr::{CallDetailRecord, CdrTemplate, ParseCdrTemplateError};
+pub use channel::{
+    AnswerState, CallDirection, CallState, ChannelState, ChannelTimetable, HangupCause,
+    ParseHangupCauseError,
+};
 pub use command::{CommandBuilder, EslResponse, ReplyStatus};
 pub use commands::{
     Application, ApplicationList, ConferenceDtmf, ConferenceHold, ConferenceMute, DialplanType,
-    Endpoint, HoldAction, MuteAction, Originate, OriginateError, UuidAnswer, UuidBridge,
+    Endpoint, HoldAction, MuteAction, Originate, OriginateError, UuidAnswer, UuidBreak, UuidBridge,
     UuidDeflect, UuidGetVar, UuidHold, UuidKill, UuidSendDtmf, UuidSetVar, UuidTransfer, Variables,
     VariablesType,
 };
 pub use connection::{
-    ConnectionMode, ConnectionStatus, DisconnectReason, EslClient, EslConnectOptions,
-    EslEventStream,
+    AppExecution, BackpressurePolicy, BgJob, ConnectionMode, ConnectionStatus, DisconnectReason,
+    EslClient, EslConnectOptions, EslEventStream, EventQueueStats, ExecuteComplete, ExecuteOptions,
 };
 pub use constants::DEFAULT_ESL_PORT;
 pub use error::{EslError, EslResult};
-pub use event::{EslEvent, EslEventPriority, EslEventType, EventFormat};
+pub use event::{EslEvent, EslEventPriority, EslEventType, EventFormat, EventKind, EventSubclass};
+pub use filter::{EventFilter, FilteredEventStream, ParseFilterError};
+pub use log::{EslLogStream, LogLevel, LogLine, ParseLogLevelError};
+pub use outbound::{OutboundCall, OutboundOptions, OutboundServer, ShutdownHandle};
+pub use pool::{
+    EslPool, EslPoolEventStream, LoadBalance, PoolEvent, PoolNode, PoolNodeInfo, PoolOptions,
+};
+pub use reconnect::{
+    ReconnectOptions, ReconnectingClient, ReconnectingEventStream, SupervisedEvent,
+};
+pub use session::ChannelSession;
+pub use subscription::SubscriptionState;
+pub use tracker::{
+    ChannelChange, ChannelChangeStream, ChannelTracker, TrackedChannel, TrackerOptions,
+};
+pub use typed::{TypedEvent, TypedEventError};
 pub use variables::{EslArray, MultipartBody, MultipartItem};
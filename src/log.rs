@@ -0,0 +1,324 @@
+//! FreeSWITCH log forwarding (`log` command, `log/data` messages).
+//!
+//! After [`EslClient::log`](crate::EslClient::log), FreeSWITCH sends every
+//! log line at or above the requested level as a `log/data` message. The
+//! reader task parses them into [`LogLine`] values and delivers them on the
+//! stream returned by [`EslClient::log_stream`](crate::EslClient::log_stream),
+//! separate from events.
+//!
+//! ```rust,no_run
+//! use freeswitch_esl_tokio::{EslClient, LogLevel};
+//!
+//! # async fn example() -> Result<(), freeswitch_esl_tokio::EslError> {
+//! let (client, _events) = EslClient::connect("localhost", 8021, "ClueCon").await?;
+//! let mut logs = client.log_stream();
+//! client.log(&LogLevel::Info.to_string()).await?;
+//!
+//! while let Some(line) = logs.recv().await {
+//!     println!("[{}] {:?} {}", line.level, line.uuid(), line.text);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use indexmap::IndexMap;
+use std::fmt;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::error::{EslError, EslResult};
+
+/// FreeSWITCH log level (`switch_log_level_t`).
+///
+/// Lower numbers are more severe, so `Error < Warning` holds and a level
+/// filter reads `line.level <= LogLevel::Warning`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+#[repr(u8)]
+pub enum LogLevel {
+    /// Console output (0).
+    Console = 0,
+    /// Alert (1).
+    Alert = 1,
+    /// Critical (2).
+    Crit = 2,
+    /// Error (3).
+    Error = 3,
+    /// Warning (4).
+    Warning = 4,
+    /// Notice (5).
+    Notice = 5,
+    /// Informational (6).
+    Info = 6,
+    /// Debug (7).
+    Debug = 7,
+}
+
+impl LogLevel {
+    /// Parse from the numeric level used in the `Log-Level` header.
+    pub fn from_number(n: u8) -> Option<Self> {
+        match n {
+            0 => Some(Self::Console),
+            1 => Some(Self::Alert),
+            2 => Some(Self::Crit),
+            3 => Some(Self::Error),
+            4 => Some(Self::Warning),
+            5 => Some(Self::Notice),
+            6 => Some(Self::Info),
+            7 => Some(Self::Debug),
+            _ => None,
+        }
+    }
+
+    /// Numeric level.
+    pub fn as_number(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Console => "CONSOLE",
+            Self::Alert => "ALERT",
+            Self::Crit => "CRIT",
+            Self::Error => "ERR",
+            Self::Warning => "WARNING",
+            Self::Notice => "NOTICE",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Error returned when parsing an invalid log level string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLogLevelError(pub String);
+
+impl fmt::Display for ParseLogLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown log level: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseLogLevelError {}
+
+impl FromStr for LogLevel {
+    type Err = ParseLogLevelError;
+
+    /// Case-insensitive name (`ERROR` is accepted for `ERR`) or number 0–7.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let level = match s
+            .to_uppercase()
+            .as_str()
+        {
+            "CONSOLE" => Some(Self::Console),
+            "ALERT" => Some(Self::Alert),
+            "CRIT" => Some(Self::Crit),
+            "ERR" | "ERROR" => Some(Self::Error),
+            "WARNING" => Some(Self::Warning),
+            "NOTICE" => Some(Self::Notice),
+            "INFO" => Some(Self::Info),
+            "DEBUG" => Some(Self::Debug),
+            other => other
+                .parse()
+                .ok()
+                .and_then(Self::from_number),
+        };
+        level.ok_or_else(|| ParseLogLevelError(s.to_string()))
+    }
+}
+
+/// One log line forwarded by FreeSWITCH in a `log/data` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LogLine {
+    /// `Log-Level`.
+    pub level: LogLevel,
+    /// `Log-File`: source file, e.g. `switch_core_state_machine.c`.
+    pub file: Option<String>,
+    /// `Log-Line`: line number in the source file.
+    pub line: Option<u32>,
+    /// `Log-Func`: function that logged.
+    pub function: Option<String>,
+    /// `User-Data`: the channel UUID for session logs, absent otherwise.
+    pub user_data: Option<String>,
+    /// Message text, without the trailing newline.
+    pub text: String,
+}
+
+impl LogLine {
+    /// Build from the headers and body of a `log/data` message.
+    pub(crate) fn from_parts(
+        headers: &IndexMap<String, String>,
+        body: Option<&str>,
+    ) -> EslResult<Self> {
+        let non_empty = |name: &str| {
+            headers
+                .get(name)
+                .filter(|v| !v.is_empty())
+                .cloned()
+        };
+        let level_str = headers
+            .get("Log-Level")
+            .ok_or_else(|| EslError::protocol_error("log/data message missing Log-Level"))?;
+        let level = level_str
+            .parse()
+            .map_err(|_| EslError::InvalidHeader {
+                header: format!("Log-Level: {}", level_str),
+            })?;
+        Ok(Self {
+            level,
+            file: non_empty("Log-File"),
+            line: headers
+                .get("Log-Line")
+                .and_then(|v| {
+                    v.trim()
+                        .parse()
+                        .ok()
+                }),
+            function: non_empty("Log-Func"),
+            user_data: non_empty("User-Data"),
+            text: body
+                .unwrap_or("")
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+        })
+    }
+
+    /// Channel UUID the line belongs to (the `User-Data` header).
+    pub fn uuid(&self) -> Option<&str> {
+        self.user_data
+            .as_deref()
+    }
+}
+
+/// Log line receiver from [`EslClient::log_stream`](crate::EslClient::log_stream) (!Clone).
+///
+/// Ends when the connection closes or another log stream replaces it.
+pub struct EslLogStream {
+    rx: mpsc::Receiver<LogLine>,
+}
+
+impl fmt::Debug for EslLogStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EslLogStream")
+            .finish_non_exhaustive()
+    }
+}
+
+impl EslLogStream {
+    pub(crate) fn new(rx: mpsc::Receiver<LogLine>) -> Self {
+        Self { rx }
+    }
+
+    /// Receive the next log line, or `None` once the stream has ended.
+    pub async fn recv(&mut self) -> Option<LogLine> {
+        self.rx
+            .recv()
+            .await
+    }
+}
+
+impl futures_util::Stream for EslLogStream {
+    type Item = LogLine;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx
+            .poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_headers(level: &str) -> IndexMap<String, String> {
+        let mut headers = IndexMap::new();
+        headers.insert("Content-Type".to_string(), "log/data".to_string());
+        headers.insert("Log-Level".to_string(), level.to_string());
+        headers.insert("Text-Channel".to_string(), "3".to_string());
+        headers.insert("Log-File".to_string(), "switch_ivr.c".to_string());
+        headers.insert("Log-Func".to_string(), "switch_ivr_park".to_string());
+        headers.insert("Log-Line".to_string(), "1042".to_string());
+        headers
+    }
+
+    #[test]
+    fn test_log_level_from_str() {
+        assert_eq!("debug".parse::<LogLevel>(), Ok(LogLevel::Debug));
+        assert_eq!("ERROR".parse::<LogLevel>(), Ok(LogLevel::Error));
+        assert_eq!("err".parse::<LogLevel>(), Ok(LogLevel::Error));
+        assert_eq!("4".parse::<LogLevel>(), Ok(LogLevel::Warning));
+        assert!("8"
+            .parse::<LogLevel>()
+            .is_err());
+        assert!("LOUD"
+            .parse::<LogLevel>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_log_level_round_trip_and_order() {
+        for n in 0..=7 {
+            let level = LogLevel::from_number(n).unwrap();
+            assert_eq!(level.as_number(), n);
+            assert_eq!(
+                level
+                    .to_string()
+                    .parse::<LogLevel>(),
+                Ok(level)
+            );
+        }
+        assert!(LogLevel::Error < LogLevel::Warning);
+        assert!(LogLevel::Debug > LogLevel::Info);
+    }
+
+    #[test]
+    fn test_log_line_from_parts() {
+        let mut headers = log_headers("6");
+        headers.insert(
+            "User-Data".to_string(),
+            "a1b2c3d4-0000-1111-2222-333344445555".to_string(),
+        );
+        let line = LogLine::from_parts(&headers, Some("Channel parked\n")).unwrap();
+        assert_eq!(line.level, LogLevel::Info);
+        assert_eq!(
+            line.file
+                .as_deref(),
+            Some("switch_ivr.c")
+        );
+        assert_eq!(line.line, Some(1042));
+        assert_eq!(
+            line.function
+                .as_deref(),
+            Some("switch_ivr_park")
+        );
+        assert_eq!(line.uuid(), Some("a1b2c3d4-0000-1111-2222-333344445555"));
+        assert_eq!(line.text, "Channel parked");
+    }
+
+    #[test]
+    fn test_log_line_without_user_data() {
+        let mut headers = log_headers("3");
+        headers.insert("User-Data".to_string(), String::new());
+        let line = LogLine::from_parts(&headers, None).unwrap();
+        assert_eq!(line.level, LogLevel::Error);
+        assert_eq!(line.uuid(), None);
+        assert_eq!(line.text, "");
+    }
+
+    #[test]
+    fn test_log_line_invalid_level() {
+        let headers = log_headers("loud");
+        assert!(LogLine::from_parts(&headers, Some("x")).is_err());
+        let mut headers = log_headers("7");
+        headers.shift_remove("Log-Level");
+        assert!(LogLine::from_parts(&headers, Some("x")).is_err());
+    }
+}
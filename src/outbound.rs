@@ -0,0 +1,343 @@
+//! Outbound socket server running one handler task per call.
+//!
+//! In outbound mode FreeSWITCH connects to the application from the
+//! `socket` dialplan application. [`OutboundServer`] owns the listener and
+//! the accept loop; for each connection it sends `connect`, optionally
+//! `myevents` and `linger`, then runs the handler with a ready
+//! [`OutboundCall`].
+//!
+//! ```rust,no_run
+//! use freeswitch_esl_tokio::outbound::{OutboundOptions, OutboundServer};
+//! use freeswitch_esl_tokio::AppCommand;
+//!
+//! # async fn example() -> Result<(), freeswitch_esl_tokio::EslError> {
+//! let server = OutboundServer::bind("0.0.0.0:8040", OutboundOptions::default()).await?;
+//! // Call shutdown.shutdown() from a signal handler to drain and stop
+//! let shutdown = server.shutdown_handle();
+//!
+//! server
+//!     .serve(|call| async move {
+//!         println!("call from {:?}", call.channel_data.header("Caller-Caller-ID-Number"));
+//!         call.client.send_command(AppCommand::answer()).await?;
+//!         call.client.send_command(AppCommand::playback("ivr/ivr-welcome.wav")).await?;
+//!         Ok(())
+//!     })
+//!     .await
+//! # }
+//! ```
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    command::EslResponse,
+    connection::{EslClient, EslConnectOptions, EslEventStream},
+    error::{EslError, EslResult},
+    event::EventFormat,
+};
+
+/// Settings for [`OutboundServer`].
+#[derive(Debug, Clone)]
+pub struct OutboundOptions {
+    /// Maximum number of calls handled at once. Connections beyond the limit
+    /// are closed immediately, so the `socket` application fails and the
+    /// dialplan continues. Default: 1000.
+    pub max_calls: usize,
+    /// Send `myevents <format>` before running the handler. Default: `Some(Plain)`.
+    pub myevents: Option<EventFormat>,
+    /// Send `linger` before running the handler, so events after hangup are
+    /// still delivered. Default: `true`.
+    ///
+    /// Only honoured with `&socket(host:port async full)`: on a
+    /// single-channel socket FreeSWITCH refuses `linger`, so it is skipped
+    /// (see [`EslClient::is_single_channel`]). A failed `linger` is logged
+    /// and the call still runs.
+    pub linger: bool,
+    /// How long [`OutboundServer::serve`] waits for running calls after
+    /// shutdown before aborting them. `None` waits indefinitely. Default: `None`.
+    pub drain_timeout: Option<Duration>,
+    /// Options applied to every call's connection.
+    pub connect_options: EslConnectOptions,
+}
+
+impl Default for OutboundOptions {
+    fn default() -> Self {
+        Self {
+            max_calls: 1000,
+            myevents: Some(EventFormat::Plain),
+            linger: true,
+            drain_timeout: None,
+            connect_options: EslConnectOptions::default(),
+        }
+    }
+}
+
+/// A call handed to the [`OutboundServer`] handler, with the session
+/// already established.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct OutboundCall {
+    /// Client bound to the call's socket.
+    pub client: EslClient,
+    /// Events for the call.
+    pub events: EslEventStream,
+    /// Reply to `connect`: the channel data as headers
+    /// (`Unique-ID`, `Channel-Name`, `Caller-Caller-ID-Number`, variables, ...).
+    pub channel_data: EslResponse,
+    /// Address of the FreeSWITCH host that connected.
+    pub peer: SocketAddr,
+}
+
+impl OutboundCall {
+    /// The channel UUID from the channel data.
+    pub fn uuid(&self) -> Option<&str> {
+        self.channel_data
+            .header("Unique-ID")
+    }
+}
+
+/// Stops an [`OutboundServer`] from another task (Clone).
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl ShutdownHandle {
+    /// Stop accepting calls; [`OutboundServer::serve`] returns once running
+    /// calls have finished (or the drain timeout expired).
+    pub fn shutdown(&self) {
+        self.tx
+            .send_replace(true);
+    }
+}
+
+/// Accept loop for outbound ESL connections.
+#[derive(Debug)]
+pub struct OutboundServer {
+    listener: TcpListener,
+    options: OutboundOptions,
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+impl OutboundServer {
+    /// Bind a listener on `addr`.
+    pub async fn bind(addr: impl ToSocketAddrs, options: OutboundOptions) -> EslResult<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(EslError::Io)?;
+        Ok(Self::from_listener(listener, options))
+    }
+
+    /// Use an already bound listener.
+    pub fn from_listener(listener: TcpListener, options: OutboundOptions) -> Self {
+        let (tx, _) = watch::channel(false);
+        Self {
+            listener,
+            options,
+            shutdown: Arc::new(tx),
+        }
+    }
+
+    /// Address the listener is bound to.
+    pub fn local_addr(&self) -> EslResult<SocketAddr> {
+        self.listener
+            .local_addr()
+            .map_err(EslError::Io)
+    }
+
+    /// Handle to stop [`serve`](Self::serve) from another task.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            tx: self
+                .shutdown
+                .clone(),
+        }
+    }
+
+    /// Accept calls and run `handler` for each in its own task.
+    ///
+    /// Handler errors and panics are logged and only end that call. Returns
+    /// after [`ShutdownHandle::shutdown`] once in-flight calls have drained.
+    pub async fn serve<F, Fut>(self, handler: F) -> EslResult<()>
+    where
+        F: Fn(OutboundCall) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = EslResult<()>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        let limit = Arc::new(Semaphore::new(
+            self.options
+                .max_calls,
+        ));
+        let mut shutdown_rx = self
+            .shutdown
+            .subscribe();
+        let mut calls = JoinSet::new();
+        // shutdown() may have been called before serve() subscribed
+        let mut stopping = *shutdown_rx.borrow();
+
+        info!(
+            "Outbound server listening on {:?}",
+            self.listener
+                .local_addr()
+        );
+
+        while !stopping {
+            tokio::select! {
+                accepted = self.listener.accept() => {
+                    let (stream, peer) = match accepted {
+                        Ok(pair) => pair,
+                        Err(e) => {
+                            // e.g. EMFILE: back off instead of spinning
+                            warn!("Accept failed: {}", e);
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                            continue;
+                        }
+                    };
+                    let Ok(permit) = limit.clone().try_acquire_owned() else {
+                        warn!(
+                            "Rejecting outbound connection from {}: {} calls in progress",
+                            peer, self.options.max_calls
+                        );
+                        drop(stream);
+                        continue;
+                    };
+                    debug!("Accepted outbound connection from {}", peer);
+                    let options = self.options.clone();
+                    let handler = handler.clone();
+                    calls.spawn(async move {
+                        let _permit = permit;
+                        // Any TLS handshake runs here, off the accept loop
+                        let (client, events) = match EslClient::spawn_accepted(
+                            stream,
+                            options.connect_options.clone(),
+                        )
+                        .await
+                        {
+                            Ok(pair) => pair,
+                            Err(e) => {
+                                warn!("Outbound connection from {} failed: {}", peer, e);
+                                return;
+                            }
+                        };
+                        run_call(client, events, peer, &options, handler.as_ref()).await;
+                    });
+                }
+                Some(joined) = calls.join_next(), if !calls.is_empty() => {
+                    log_join(joined);
+                }
+                _ = shutdown_rx.changed() => {
+                    stopping = *shutdown_rx.borrow();
+                }
+            }
+        }
+
+        info!("Outbound server draining {} call(s)", calls.len());
+        drop(self.listener);
+        let drain = async {
+            while let Some(joined) = calls
+                .join_next()
+                .await
+            {
+                log_join(joined);
+            }
+        };
+        match self
+            .options
+            .drain_timeout
+        {
+            Some(limit) => {
+                if tokio::time::timeout(limit, drain)
+                    .await
+                    .is_err()
+                {
+                    warn!("Drain timeout expired, aborting {} call(s)", calls.len());
+                    calls
+                        .shutdown()
+                        .await;
+                }
+            }
+            None => drain.await,
+        }
+        info!("Outbound server stopped");
+        Ok(())
+    }
+}
+
+/// Establish the session and run the handler for one call.
+async fn run_call<F, Fut>(
+    client: EslClient,
+    events: EslEventStream,
+    peer: SocketAddr,
+    options: &OutboundOptions,
+    handler: &F,
+) where
+    F: Fn(OutboundCall) -> Fut,
+    Fut: Future<Output = EslResult<()>>,
+{
+    let channel_data = match setup_session(&client, options).await {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Outbound session setup with {} failed: {}", peer, e);
+            let _ = client
+                .disconnect()
+                .await;
+            return;
+        }
+    };
+
+    let call = OutboundCall {
+        client,
+        events,
+        channel_data,
+        peer,
+    };
+    let uuid = call
+        .uuid()
+        .unwrap_or("(unknown)")
+        .to_string();
+    debug!("Running handler for call {}", uuid);
+    if let Err(e) = handler(call).await {
+        warn!("Handler for call {} failed: {}", uuid, e);
+    }
+}
+
+/// `connect`, then `myevents` and `linger` as configured.
+///
+/// Only `connect` and `myevents` are fatal; `linger` is best effort.
+async fn setup_session(client: &EslClient, options: &OutboundOptions) -> EslResult<EslResponse> {
+    let channel_data = client
+        .connect_session()
+        .await?
+        .into_result()?;
+    if let Some(format) = options.myevents {
+        client
+            .myevents(format)
+            .await?;
+    }
+    if options.linger {
+        if client.is_single_channel() {
+            debug!("Skipping linger: socket application was started without full");
+        } else if let Err(e) = client
+            .linger(None)
+            .await
+        {
+            warn!("linger failed, events after hangup may be lost: {}", e);
+        }
+    }
+    Ok(channel_data)
+}
+
+fn log_join(joined: Result<(), tokio::task::JoinError>) {
+    if let Err(e) = joined {
+        if e.is_panic() {
+            error!("Outbound call handler panicked: {}", e);
+        }
+    }
+}
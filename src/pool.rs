@@ -0,0 +1,832 @@
+//! Connection pool spread over several FreeSWITCH nodes.
+//!
+//! [`EslPool`] keeps [`PoolOptions::connections_per_node`] supervised
+//! connections ([`ReconnectingClient`]) to every node. `api`/`bgapi` calls go
+//! to a healthy connection chosen by the [`LoadBalance`] strategy; calls for
+//! a channel go to the node that owns it. Events from every node arrive on
+//! one [`EslPoolEventStream`], tagged with the node they came from.
+//!
+//! Only the first connection of each node carries event subscriptions, so
+//! events are not duplicated per connection.
+//!
+//! ```rust,no_run
+//! use freeswitch_esl_tokio::pool::{EslPool, PoolNode, PoolOptions};
+//! use freeswitch_esl_tokio::{EslEventType, EventFormat, SupervisedEvent};
+//!
+//! # async fn example() -> Result<(), freeswitch_esl_tokio::EslError> {
+//! let nodes = vec![
+//!     PoolNode::new("fs1.example.com", 8021, "ClueCon"),
+//!     PoolNode::new("fs2.example.com", 8021, "ClueCon"),
+//! ];
+//! let (pool, mut events) = EslPool::connect(nodes, PoolOptions::default()).await?;
+//! pool.subscribe_events(EventFormat::Plain, &[EslEventType::ChannelAnswer]).await?;
+//!
+//! let status = pool.api("status").await?;
+//!
+//! while let Some(item) = events.recv().await {
+//!     if let SupervisedEvent::Event(event) = &item.event {
+//!         // Channel commands go to the node that reported the channel
+//!         if let Some(uuid) = event.unique_id() {
+//!             pool.api_for_channel(uuid, &format!("uuid_getvar {} sip_call_id", uuid)).await?;
+//!         }
+//!         println!("{:?} on {:?}", event.event_type(), item.node.hostname);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::future::try_join_all;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::{
+    command::{EslCommand, EslResponse},
+    connection::BgJob,
+    constants::HEADER_UNIQUE_ID,
+    error::{EslError, EslResult},
+    event::{EslEvent, EslEventType, EventFormat, EventKind},
+    reconnect::{ReconnectOptions, ReconnectingClient, ReconnectingEventStream, SupervisedEvent},
+};
+
+/// How [`EslPool`] picks a connection for calls not tied to a channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum LoadBalance {
+    /// Rotate through healthy connections.
+    #[default]
+    RoundRobin,
+    /// The healthy connection with the fewest commands in flight.
+    LeastLoaded,
+}
+
+/// Settings for [`EslPool`].
+#[derive(Debug, Clone)]
+pub struct PoolOptions {
+    /// Connections opened to each node. Default: 2.
+    pub connections_per_node: usize,
+    /// Connection choice for `api`/`bgapi`. Default: [`LoadBalance::RoundRobin`].
+    pub strategy: LoadBalance,
+    /// Subscribe every connection to `HEARTBEAT` and drop it after this long
+    /// without traffic, so a hung node is taken out of rotation. `HEARTBEAT`
+    /// events then also appear on the event stream. Default: `None`.
+    ///
+    /// FreeSWITCH fires `HEARTBEAT` every 20 seconds by default
+    /// (`event-heartbeat-interval` in `switch.conf.xml`), so an idle
+    /// connection sees no traffic for that long. Use a value well above the
+    /// interval, e.g. 60 seconds; anything at or below it makes idle
+    /// connections flap, and [`EslPool::connect`] logs a warning.
+    pub liveness_timeout: Option<Duration>,
+    /// Reconnect and connection settings applied to every connection.
+    pub reconnect: ReconnectOptions,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            connections_per_node: 2,
+            strategy: LoadBalance::RoundRobin,
+            liveness_timeout: None,
+            reconnect: ReconnectOptions::default(),
+        }
+    }
+}
+
+/// Address and credentials of one FreeSWITCH node.
+#[derive(Clone)]
+pub struct PoolNode {
+    host: String,
+    port: u16,
+    user: Option<String>,
+    password: String,
+}
+
+impl PoolNode {
+    /// Node using password authentication.
+    pub fn new(host: impl Into<String>, port: u16, password: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            user: None,
+            password: password.into(),
+        }
+    }
+
+    /// Node using user authentication (`user@domain`).
+    pub fn with_user(
+        host: impl Into<String>,
+        port: u16,
+        user: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            user: Some(user.into()),
+            ..Self::new(host, port, password)
+        }
+    }
+
+    fn addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    async fn open(
+        &self,
+        options: ReconnectOptions,
+    ) -> EslResult<(ReconnectingClient, ReconnectingEventStream)> {
+        match &self.user {
+            Some(user) => {
+                ReconnectingClient::connect_with_user(
+                    &self.host,
+                    self.port,
+                    user,
+                    &self.password,
+                    options,
+                )
+                .await
+            }
+            None => {
+                ReconnectingClient::connect(&self.host, self.port, &self.password, options).await
+            }
+        }
+    }
+}
+
+impl fmt::Debug for PoolNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoolNode")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("user", &self.user)
+            .field("password", &"[REDACTED]")
+            .finish()
+    }
+}
+
+/// Identity of a pool node.
+///
+/// `core_uuid` and `hostname` are learned from the `Core-UUID` and
+/// `FreeSWITCH-Hostname` headers of the node's events; they are `None`
+/// until the first event arrives, and `core_uuid` changes when the node
+/// restarts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PoolNodeInfo {
+    /// Position of the node in the list given to [`EslPool::connect`].
+    pub index: usize,
+    /// `host:port` the pool connects to.
+    pub addr: String,
+    /// `Core-UUID` of the running FreeSWITCH instance.
+    pub core_uuid: Option<String>,
+    /// `FreeSWITCH-Hostname`.
+    pub hostname: Option<String>,
+}
+
+/// Item from [`EslPoolEventStream`]: a supervised event and its node.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct PoolEvent {
+    /// Node the item came from.
+    pub node: Arc<PoolNodeInfo>,
+    /// Event, error or connection state change on that node's event connection.
+    pub event: SupervisedEvent,
+}
+
+/// Merged event stream of every node (!Clone).
+///
+/// Ends once every node's supervisor has stopped.
+pub struct EslPoolEventStream {
+    rx: mpsc::Receiver<PoolEvent>,
+}
+
+impl fmt::Debug for EslPoolEventStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EslPoolEventStream")
+            .finish_non_exhaustive()
+    }
+}
+
+impl EslPoolEventStream {
+    /// Receive the next item, or `None` once every node has stopped.
+    pub async fn recv(&mut self) -> Option<PoolEvent> {
+        self.rx
+            .recv()
+            .await
+    }
+}
+
+impl futures_util::Stream for EslPoolEventStream {
+    type Item = PoolEvent;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx
+            .poll_recv(cx)
+    }
+}
+
+/// One supervised connection and its load.
+struct PooledConnection {
+    client: ReconnectingClient,
+    in_flight: AtomicUsize,
+}
+
+/// Decrements the in-flight count when a call finishes or is cancelled.
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl PooledConnection {
+    fn is_healthy(&self) -> bool {
+        self.client
+            .is_connected()
+    }
+
+    fn begin(&self) -> InFlight<'_> {
+        self.in_flight
+            .fetch_add(1, Ordering::Relaxed);
+        InFlight(&self.in_flight)
+    }
+}
+
+struct Node {
+    info: Mutex<Arc<PoolNodeInfo>>,
+    /// The first connection carries event subscriptions.
+    connections: Vec<PooledConnection>,
+}
+
+impl Node {
+    fn info(&self) -> Arc<PoolNodeInfo> {
+        self.info
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn events(&self) -> &ReconnectingClient {
+        &self.connections[0].client
+    }
+
+    /// Record `Core-UUID`/`FreeSWITCH-Hostname` from an event, returning the
+    /// current identity.
+    fn learn_identity(&self, event: &EslEvent) -> Arc<PoolNodeInfo> {
+        let mut info = self
+            .info
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let core_uuid = event.header("Core-UUID");
+        let hostname = event.header("FreeSWITCH-Hostname");
+        let changed = core_uuid.is_some_and(|v| {
+            info.core_uuid
+                .as_deref()
+                != Some(v)
+        }) || hostname.is_some_and(|v| {
+            info.hostname
+                .as_deref()
+                != Some(v)
+        });
+        if changed {
+            let mut updated = PoolNodeInfo::clone(&info);
+            if let Some(v) = core_uuid {
+                updated.core_uuid = Some(v.to_string());
+            }
+            if let Some(v) = hostname {
+                updated.hostname = Some(v.to_string());
+            }
+            *info = Arc::new(updated);
+        }
+        info.clone()
+    }
+}
+
+/// FreeSWITCH's default `event-heartbeat-interval`.
+const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(20);
+
+struct PoolInner {
+    nodes: Vec<Node>,
+    strategy: LoadBalance,
+    next: AtomicUsize,
+    /// Channel UUID to owning node index.
+    owners: Mutex<HashMap<String, usize>>,
+}
+
+impl PoolInner {
+    fn owners(&self) -> std::sync::MutexGuard<'_, HashMap<String, usize>> {
+        self.owners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Track channel ownership from a node's events.
+    fn observe(&self, node: usize, event: &EslEvent) {
+        let Some(uuid) = event.header(HEADER_UNIQUE_ID) else {
+            return;
+        };
+        match event.event_type() {
+            Some(EslEventType::ChannelCreate) => {
+                self.owners()
+                    .insert(uuid.to_string(), node);
+            }
+            Some(EslEventType::ChannelDestroy) => {
+                self.owners()
+                    .remove(uuid);
+            }
+            _ => {}
+        }
+    }
+
+    /// Drop every channel pinned to `node`, e.g. after it reconnected and a
+    /// `CHANNEL_DESTROY` may have been missed.
+    fn forget_node(&self, node: usize) {
+        self.owners()
+            .retain(|_, owner| *owner != node);
+    }
+
+    /// Pick a healthy connection among `candidates` by the pool strategy.
+    fn pick<'a>(&self, candidates: &[&'a PooledConnection]) -> EslResult<&'a PooledConnection> {
+        let start = self
+            .next
+            .fetch_add(1, Ordering::Relaxed);
+        let healthy = (0..candidates.len())
+            .map(|i| candidates[(start + i) % candidates.len()])
+            .filter(|c| c.is_healthy());
+        let chosen = match self.strategy {
+            LoadBalance::RoundRobin => healthy
+                .into_iter()
+                .next(),
+            LoadBalance::LeastLoaded => healthy.min_by_key(|c| {
+                c.in_flight
+                    .load(Ordering::Relaxed)
+            }),
+        };
+        chosen.ok_or(EslError::NotConnected)
+    }
+
+    fn any_connection(&self) -> EslResult<&PooledConnection> {
+        let all: Vec<&PooledConnection> = self
+            .nodes
+            .iter()
+            .flat_map(|n| {
+                n.connections
+                    .iter()
+            })
+            .collect();
+        self.pick(&all)
+    }
+
+    fn node_connection(&self, node: usize) -> EslResult<&PooledConnection> {
+        let node = self
+            .nodes
+            .get(node)
+            .ok_or_else(|| EslError::generic(format!("no pool node {}", node)))?;
+        let all: Vec<&PooledConnection> = node
+            .connections
+            .iter()
+            .collect();
+        self.pick(&all)
+    }
+}
+
+/// Pool of supervised connections to several FreeSWITCH nodes (Clone + Send).
+#[derive(Clone)]
+pub struct EslPool {
+    inner: Arc<PoolInner>,
+}
+
+impl fmt::Debug for EslPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EslPool")
+            .field(
+                "nodes",
+                &self
+                    .inner
+                    .nodes
+                    .len(),
+            )
+            .field(
+                "strategy",
+                &self
+                    .inner
+                    .strategy,
+            )
+            .finish()
+    }
+}
+
+impl EslPool {
+    /// Open every connection to every node.
+    ///
+    /// As with [`ReconnectingClient::connect`], each initial connection is
+    /// attempted once and the first error is returned; later failures are
+    /// retried in the background while the pool routes around them.
+    pub async fn connect(
+        nodes: Vec<PoolNode>,
+        options: PoolOptions,
+    ) -> EslResult<(Self, EslPoolEventStream)> {
+        if nodes.is_empty() {
+            return Err(EslError::generic("EslPool needs at least one node"));
+        }
+        let per_node = options
+            .connections_per_node
+            .max(1);
+
+        let opened = try_join_all(
+            nodes
+                .iter()
+                .map(|node| {
+                    let reconnect = options
+                        .reconnect
+                        .clone();
+                    async move {
+                        try_join_all((0..per_node).map(|_| node.open(reconnect.clone()))).await
+                    }
+                }),
+        )
+        .await?;
+
+        let queue_size = options
+            .reconnect
+            .connect_options
+            .event_queue_size
+            .max(1);
+        let (tx, rx) = mpsc::channel(queue_size);
+
+        let mut pool_nodes = Vec::with_capacity(nodes.len());
+        let mut event_streams = Vec::with_capacity(nodes.len());
+        for (index, (node, connections)) in nodes
+            .iter()
+            .zip(opened)
+            .enumerate()
+        {
+            let mut clients = Vec::with_capacity(per_node);
+            // Only the first connection's events are forwarded; dropping the
+            // other streams lets their supervisors discard events.
+            for (i, (client, events)) in connections
+                .into_iter()
+                .enumerate()
+            {
+                if i == 0 {
+                    event_streams.push(events);
+                }
+                clients.push(PooledConnection {
+                    client,
+                    in_flight: AtomicUsize::new(0),
+                });
+            }
+            pool_nodes.push(Node {
+                info: Mutex::new(Arc::new(PoolNodeInfo {
+                    index,
+                    addr: node.addr(),
+                    core_uuid: None,
+                    hostname: None,
+                })),
+                connections: clients,
+            });
+        }
+
+        let pool = Self {
+            inner: Arc::new(PoolInner {
+                nodes: pool_nodes,
+                strategy: options.strategy,
+                next: AtomicUsize::new(0),
+                owners: Mutex::new(HashMap::new()),
+            }),
+        };
+
+        if let Some(timeout) = options.liveness_timeout {
+            if timeout <= DEFAULT_HEARTBEAT_INTERVAL {
+                warn!(
+                    "liveness_timeout {:?} is not above the default HEARTBEAT interval ({:?}); \
+                     idle connections will be dropped",
+                    timeout, DEFAULT_HEARTBEAT_INTERVAL
+                );
+            }
+            for connection in pool
+                .inner
+                .nodes
+                .iter()
+                .flat_map(|n| {
+                    n.connections
+                        .iter()
+                })
+            {
+                connection
+                    .client
+                    .subscribe_events(EventFormat::Plain, &[EslEventType::Heartbeat])
+                    .await?;
+                connection
+                    .client
+                    .set_liveness_timeout(timeout);
+            }
+        }
+
+        for (index, events) in event_streams
+            .into_iter()
+            .enumerate()
+        {
+            tokio::spawn(forward_events(
+                Arc::downgrade(&pool.inner),
+                index,
+                events,
+                tx.clone(),
+            ));
+        }
+
+        Ok((pool, EslPoolEventStream { rx }))
+    }
+
+    /// Identity of every node, in the order given to [`connect`](Self::connect).
+    pub fn nodes(&self) -> Vec<Arc<PoolNodeInfo>> {
+        self.inner
+            .nodes
+            .iter()
+            .map(Node::info)
+            .collect()
+    }
+
+    /// Number of connections to `node` that are currently up.
+    pub fn healthy_connections(&self, node: usize) -> usize {
+        self.inner
+            .nodes
+            .get(node)
+            .map_or(0, |n| {
+                n.connections
+                    .iter()
+                    .filter(|c| c.is_healthy())
+                    .count()
+            })
+    }
+
+    /// Whether at least one connection in the pool is up.
+    pub fn is_connected(&self) -> bool {
+        (0..self
+            .inner
+            .nodes
+            .len())
+            .any(|i| self.healthy_connections(i) > 0)
+    }
+
+    /// Send a raw command on a connection chosen by the load-balancing strategy.
+    pub async fn send_command(&self, command: EslCommand) -> EslResult<EslResponse> {
+        let connection = self
+            .inner
+            .any_connection()?;
+        let _load = connection.begin();
+        connection
+            .client
+            .send_command(command)
+            .await
+    }
+
+    /// Execute an API command on any healthy node.
+    pub async fn api(&self, command: &str) -> EslResult<EslResponse> {
+        let connection = self
+            .inner
+            .any_connection()?;
+        let _load = connection.begin();
+        connection
+            .client
+            .api(command)
+            .await
+    }
+
+    /// Execute a background API command on any healthy node.
+    pub async fn bgapi(&self, command: &str) -> EslResult<EslResponse> {
+        let connection = self
+            .inner
+            .any_connection()?;
+        let _load = connection.begin();
+        connection
+            .client
+            .bgapi(command)
+            .await
+    }
+
+    /// Execute a background API command on any healthy node and get a
+    /// future for its result. See [`EslClient::bgapi_job`](crate::EslClient::bgapi_job).
+    pub async fn bgapi_job(&self, command: &str) -> EslResult<BgJob> {
+        let connection = self
+            .inner
+            .any_connection()?;
+        let _load = connection.begin();
+        connection
+            .client
+            .bgapi_job(command)
+            .await
+    }
+
+    /// Execute an API command on a specific node.
+    pub async fn api_on(&self, node: usize, command: &str) -> EslResult<EslResponse> {
+        let connection = self
+            .inner
+            .node_connection(node)?;
+        let _load = connection.begin();
+        connection
+            .client
+            .api(command)
+            .await
+    }
+
+    /// Pin a channel to a node, e.g. after originating it there.
+    pub fn pin_channel(&self, uuid: &str, node: usize) {
+        self.inner
+            .owners()
+            .insert(uuid.to_string(), node);
+    }
+
+    /// Forget the node of a channel.
+    pub fn unpin_channel(&self, uuid: &str) {
+        self.inner
+            .owners()
+            .remove(uuid);
+    }
+
+    /// Node that owns channel `uuid`.
+    ///
+    /// Known from `CHANNEL_CREATE` events (if subscribed) or
+    /// [`pin_channel`](Self::pin_channel); otherwise every node is asked with
+    /// `uuid_exists` and the answer is remembered until `CHANNEL_DESTROY`.
+    /// When a node's event connection reconnects, its channels are forgotten
+    /// (their `CHANNEL_DESTROY` may have been missed) and looked up again on
+    /// next use.
+    pub async fn channel_node(&self, uuid: &str) -> EslResult<usize> {
+        if let Some(&node) = self
+            .inner
+            .owners()
+            .get(uuid)
+        {
+            return Ok(node);
+        }
+        for node in 0..self
+            .inner
+            .nodes
+            .len()
+        {
+            let exists = match self
+                .api_on(node, &format!("uuid_exists {}", uuid))
+                .await
+            {
+                Ok(response) => response
+                    .body()
+                    .is_some_and(|b| b.trim() == "true"),
+                Err(e) => {
+                    debug!("uuid_exists on node {} failed: {}", node, e);
+                    false
+                }
+            };
+            if exists {
+                self.pin_channel(uuid, node);
+                return Ok(node);
+            }
+        }
+        Err(EslError::generic(format!(
+            "channel {} not found on any node",
+            uuid
+        )))
+    }
+
+    /// Execute an API command on the node that owns channel `uuid`.
+    pub async fn api_for_channel(&self, uuid: &str, command: &str) -> EslResult<EslResponse> {
+        let node = self
+            .channel_node(uuid)
+            .await?;
+        self.api_on(node, command)
+            .await
+    }
+
+    /// Send a raw command (e.g. `sendmsg`) on the node that owns channel `uuid`.
+    pub async fn send_command_for_channel(
+        &self,
+        uuid: &str,
+        command: EslCommand,
+    ) -> EslResult<EslResponse> {
+        let node = self
+            .channel_node(uuid)
+            .await?;
+        let connection = self
+            .inner
+            .node_connection(node)?;
+        let _load = connection.begin();
+        connection
+            .client
+            .send_command(command)
+            .await
+    }
+
+    /// Subscribe every node's event connection. Recorded and replayed on
+    /// reconnect.
+    pub async fn subscribe_events<E: Copy + Into<EventKind>>(
+        &self,
+        format: EventFormat,
+        events: &[E],
+    ) -> EslResult<()> {
+        for node in &self
+            .inner
+            .nodes
+        {
+            node.events()
+                .subscribe_events(format, events)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Subscribe every node's event connection using a raw event list.
+    pub async fn subscribe_events_raw(&self, format: EventFormat, events: &str) -> EslResult<()> {
+        for node in &self
+            .inner
+            .nodes
+        {
+            node.events()
+                .subscribe_events_raw(format, events)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Add an event filter on every node's event connection.
+    pub async fn filter_events(&self, header: &str, value: &str) -> EslResult<()> {
+        for node in &self
+            .inner
+            .nodes
+        {
+            node.events()
+                .filter_events(header, value)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Close every connection and stop reconnecting.
+    pub async fn disconnect(&self) -> EslResult<()> {
+        for connection in self
+            .inner
+            .nodes
+            .iter()
+            .flat_map(|n| {
+                n.connections
+                    .iter()
+            })
+        {
+            if let Err(e) = connection
+                .client
+                .disconnect()
+                .await
+            {
+                warn!("Pool disconnect failed: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Forward one node's events to the merged stream, tagging them and
+/// tracking channel ownership.
+async fn forward_events(
+    pool: std::sync::Weak<PoolInner>,
+    index: usize,
+    mut events: ReconnectingEventStream,
+    tx: mpsc::Sender<PoolEvent>,
+) {
+    while let Some(item) = events
+        .recv()
+        .await
+    {
+        let Some(inner) = pool.upgrade() else {
+            return;
+        };
+        let node = &inner.nodes[index];
+        let info = match &item {
+            SupervisedEvent::Event(event) => {
+                inner.observe(index, event);
+                node.learn_identity(event)
+            }
+            SupervisedEvent::Reconnected { .. } => {
+                debug!("Node {} reconnected, forgetting its channels", index);
+                inner.forget_node(index);
+                node.info()
+            }
+            _ => node.info(),
+        };
+        drop(inner);
+        // Keep tracking ownership even if the application dropped the stream.
+        let _ = tx
+            .send(PoolEvent {
+                node: info,
+                event: item,
+            })
+            .await;
+    }
+}
@@ -5,10 +5,14 @@ use crate::{
     command::EslResponse,
     constants::*,
     error::{EslError, EslResult},
-    event::{EslEvent, EslEventType, EventFormat},
+    event::{decode_header_value, EslEvent, EslEventType, EventFormat},
+    variables::EslArray,
 };
-use percent_encoding::percent_decode_str;
-use std::collections::HashMap;
+use bytes::Bytes;
+use indexmap::IndexMap;
+use std::ops::Range;
+use std::sync::Arc;
+use tokio::io::AsyncRead;
 
 /// ESL message types
 #[derive(Debug, Clone, PartialEq)]
@@ -21,6 +25,8 @@ pub enum MessageType {
     ApiResponse,
     /// Event message
     Event,
+    /// Forwarded log line (`log/data`)
+    Log,
     /// Disconnect notice
     Disconnect,
     /// Unknown message type
@@ -36,8 +42,8 @@ impl MessageType {
             CONTENT_TYPE_API_RESPONSE => MessageType::ApiResponse,
             CONTENT_TYPE_TEXT_EVENT_PLAIN
             | CONTENT_TYPE_TEXT_EVENT_JSON
-            | CONTENT_TYPE_TEXT_EVENT_XML
-            | "log/data" => MessageType::Event,
+            | CONTENT_TYPE_TEXT_EVENT_XML => MessageType::Event,
+            CONTENT_TYPE_LOG_DATA => MessageType::Log,
             "text/disconnect-notice" => MessageType::Disconnect,
             _ => MessageType::Unknown(content_type.to_string()),
         }
@@ -50,17 +56,17 @@ pub struct EslMessage {
     /// Message type
     pub message_type: MessageType,
     /// Message headers
-    pub headers: HashMap<String, String>,
-    /// Message body (optional)
-    pub body: Option<String>,
+    pub headers: IndexMap<String, String>,
+    /// Message body (optional), shared with the read buffer
+    pub body: Option<Bytes>,
 }
 
 impl EslMessage {
     /// Create new message
     pub fn new(
         message_type: MessageType,
-        headers: HashMap<String, String>,
-        body: Option<String>,
+        headers: IndexMap<String, String>,
+        body: Option<Bytes>,
     ) -> Self {
         Self {
             message_type,
@@ -69,19 +75,108 @@ impl EslMessage {
         }
     }
 
+    /// Body as UTF-8 text.
+    pub fn body_str(&self) -> EslResult<Option<&str>> {
+        self.body
+            .as_deref()
+            .map(|b| {
+                std::str::from_utf8(b)
+                    .map_err(|_| EslError::protocol_error("Invalid UTF-8 in body"))
+            })
+            .transpose()
+    }
+
     /// Convert to EslResponse
+    ///
+    /// Invalid UTF-8 in the body is replaced rather than rejected: the frame
+    /// boundaries are intact, so there is no reason to drop the connection.
     pub fn into_response(self) -> EslResponse {
-        EslResponse::new(self.headers, self.body)
+        let body = self
+            .body
+            .map(|b| String::from_utf8_lossy(&b).into_owned());
+        EslResponse::new(self.headers, body)
+    }
+}
+
+/// `Name: value` lines of a header block.
+///
+/// Keys borrow from the block; values are returned as the span of their raw,
+/// still percent-encoded text, for [`decode_header_value`] or
+/// [`EslEvent::push_wire_header`]. Lines without a colon yield
+/// [`EslError::InvalidHeader`].
+pub(crate) struct HeaderLines<'a> {
+    block: &'a str,
+    pos: usize,
+}
+
+impl<'a> HeaderLines<'a> {
+    pub(crate) fn new(block: &'a str) -> Self {
+        Self { block, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for HeaderLines<'a> {
+    type Item = EslResult<(&'a str, Range<usize>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos
+            < self
+                .block
+                .len()
+        {
+            let start = self.pos;
+            let end = self.block[start..]
+                .find('\n')
+                .map_or(
+                    self.block
+                        .len(),
+                    |i| start + i,
+                );
+            self.pos = end + 1;
+
+            let line = trim_span(self.block, start..end);
+            if line.is_empty() {
+                continue;
+            }
+
+            return Some(match self.block[line.clone()].find(':') {
+                Some(colon) => {
+                    let key = trim_span(self.block, line.start..line.start + colon);
+                    let value = trim_span(self.block, line.start + colon + 1..line.end);
+                    Ok((&self.block[key], value))
+                }
+                None => Err(EslError::InvalidHeader {
+                    header: self.block[line].to_string(),
+                }),
+            });
+        }
+        None
     }
 }
 
+/// Narrow `span` of `text` to exclude surrounding whitespace.
+fn trim_span(text: &str, span: Range<usize>) -> Range<usize> {
+    let slice = &text[span.clone()];
+    let start = span.start
+        + (slice.len()
+            - slice
+                .trim_start()
+                .len());
+    let end = span.end
+        - (slice.len()
+            - slice
+                .trim_end()
+                .len());
+    start..end.max(start)
+}
+
 /// Parser state for handling incomplete messages
 #[derive(Debug)]
 enum ParseState {
     WaitingForHeaders,
     WaitingForBody {
         message_type: MessageType,
-        headers: std::collections::HashMap<String, String>,
+        headers: IndexMap<String, String>,
         body_length: usize,
     },
 }
@@ -102,6 +197,7 @@ impl EslParser {
     }
 
     /// Add data to the parser buffer
+    #[cfg(test)]
     pub fn add_data(&mut self, data: &[u8]) -> EslResult<()> {
         self.buffer
             .extend_from_slice(data);
@@ -110,6 +206,19 @@ impl EslParser {
         Ok(())
     }
 
+    /// Read from `reader` directly into the parser buffer.
+    ///
+    /// Returns the number of bytes read; `0` means EOF. Cancel-safe.
+    pub async fn read_from<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> EslResult<usize> {
+        let n = self
+            .buffer
+            .read_from(reader)
+            .await?;
+        self.buffer
+            .check_size_limits()?;
+        Ok(n)
+    }
+
     /// Try to parse a complete message from the buffer
     pub fn parse_message(&mut self) -> EslResult<Option<EslMessage>> {
         match &self.state {
@@ -121,15 +230,7 @@ impl EslParser {
                     .buffer
                     .extract_until_pattern(terminator)
                 {
-                    // Compact buffer to free consumed header data
-                    self.buffer
-                        .compact();
-
-                    // Parse headers
-                    let headers_str = String::from_utf8(headers_data)
-                        .map_err(|_| EslError::protocol_error("Invalid UTF-8 in headers"))?;
-
-                    let headers = self.parse_headers(&headers_str)?;
+                    let headers = self.parse_headers(&headers_data)?;
 
                     // Every ESL message must have Content-Type. Missing means
                     // protocol desync (e.g. from a corrupted Content-Length).
@@ -185,26 +286,26 @@ impl EslParser {
                     Ok(None)
                 }
             }
-            ParseState::WaitingForBody {
-                message_type,
-                headers,
-                body_length,
-            } => {
+            ParseState::WaitingForBody { body_length, .. } => {
                 if let Some(body_data) = self
                     .buffer
                     .extract_bytes(*body_length)
                 {
-                    // Compact buffer to free consumed body data
-                    self.buffer
-                        .compact();
-
-                    let body_str = String::from_utf8(body_data)
-                        .map_err(|_| EslError::protocol_error("Invalid UTF-8 in body"))?;
-
-                    let message =
-                        EslMessage::new(message_type.clone(), headers.clone(), Some(body_str));
-                    self.state = ParseState::WaitingForHeaders;
-                    Ok(Some(message))
+                    // The body is not UTF-8 validated here; each consumer
+                    // validates it once as it parses.
+                    let ParseState::WaitingForBody {
+                        message_type,
+                        headers,
+                        ..
+                    } = std::mem::replace(&mut self.state, ParseState::WaitingForHeaders)
+                    else {
+                        unreachable!()
+                    };
+                    Ok(Some(EslMessage::new(
+                        message_type,
+                        headers,
+                        Some(body_data),
+                    )))
                 } else {
                     // Not enough body data yet
                     Ok(None)
@@ -213,30 +314,28 @@ impl EslParser {
         }
     }
 
-    /// Parse headers from string
-    fn parse_headers(&self, headers_str: &str) -> EslResult<HashMap<String, String>> {
-        let mut headers = HashMap::new();
-
-        for line in headers_str.lines() {
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-
-            if let Some(colon_pos) = line.find(':') {
-                let key = line[..colon_pos]
-                    .trim()
-                    .to_string();
-                let raw_value = line[colon_pos + 1..].trim();
-                let value = percent_decode_str(raw_value)
-                    .decode_utf8()
-                    .map(|s| s.into_owned())
-                    .unwrap_or_else(|_| raw_value.to_string());
-                headers.insert(key, value);
-            } else {
-                return Err(EslError::InvalidHeader {
-                    header: line.to_string(),
-                });
+    /// Parse envelope headers, folding repeated headers into an array value
+    fn parse_headers(&self, block: &[u8]) -> EslResult<IndexMap<String, String>> {
+        let block = std::str::from_utf8(block)
+            .map_err(|_| EslError::protocol_error("Invalid UTF-8 in headers"))?;
+        let mut headers: IndexMap<String, String> = IndexMap::new();
+
+        // Envelope headers are few and end up owned in an EslResponse, so
+        // they are decoded right away
+        for line in HeaderLines::new(block) {
+            let (key, span) = line?;
+            let value = decode_header_value(&block[span]);
+            match headers.get_mut(key) {
+                // A repeated header carries several values
+                Some(existing) => {
+                    let mut array = EslArray::parse(existing)
+                        .unwrap_or_else(|| EslArray::new(vec![existing.clone()]));
+                    array.push(value.into_owned());
+                    *existing = array.to_string();
+                }
+                None => {
+                    headers.insert(key.to_string(), value.into_owned());
+                }
             }
         }
 
@@ -270,41 +369,32 @@ impl EslParser {
             .as_deref()
             .ok_or_else(|| EslError::protocol_error("Plain event missing body"))?;
 
-        let mut event = EslEvent::new();
-
         // Split event body into headers and optional inner body.
         // Event headers are terminated by \n\n; anything after is the inner body.
-        let (header_section, inner_body) = if let Some(pos) = body.find("\n\n") {
-            (&body[..pos], Some(&body[pos + 2..]))
-        } else {
-            (body, None)
+        let (header_section, inner_body) = match body
+            .windows(2)
+            .position(|w| w == b"\n\n")
+        {
+            Some(pos) => (&body[..pos], &body[pos + 2..]),
+            None => (body, &[][..]),
         };
-
-        // Parse event headers from the body, percent-decoding values
-        for line in header_section.lines() {
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-            if let Some(colon_pos) = line.find(':') {
-                let key = line[..colon_pos]
-                    .trim()
-                    .to_string();
-                let raw_value = line[colon_pos + 1..].trim();
-                let value = percent_decode_str(raw_value)
-                    .decode_utf8()
-                    .map(|s| s.into_owned())
-                    .unwrap_or_else(|_| raw_value.to_string());
-                event.set_header(key, value);
-            }
+        let header_section: Arc<str> = std::str::from_utf8(header_section)
+            .map_err(|_| EslError::protocol_error("Invalid UTF-8 in headers"))?
+            .into();
+
+        // The event keeps the header text; values are views into it, decoded
+        // when read. Lines without a colon are skipped
+        let mut event = EslEvent::from_wire(header_section.clone());
+        for (key, span) in HeaderLines::new(&header_section).flatten() {
+            event.push_wire_header(key, span);
         }
 
         // If the event headers contain their own Content-Length, the inner body
         // is that many bytes after the header section
-        if let Some(ib) = inner_body {
-            if !ib.is_empty() {
-                event.set_body(ib.to_string());
-            }
+        if !inner_body.is_empty() {
+            let inner_body = std::str::from_utf8(inner_body)
+                .map_err(|_| EslError::protocol_error("Invalid UTF-8 in event body"))?;
+            event.set_body(inner_body.to_string());
         }
 
         if let Some(event_name) = event
@@ -318,34 +408,16 @@ impl EslParser {
     }
 
     /// Parse JSON event
+    ///
+    /// FreeSWITCH sends a flat object: headers as strings, multi-value
+    /// headers as lists, and the body under `_body`. See the
+    /// [`Deserialize`](serde::Deserialize) impl of [`EslEvent`].
     fn parse_json_event(&self, message: EslMessage) -> EslResult<EslEvent> {
         let body = message
             .body
             .ok_or_else(|| EslError::protocol_error("JSON event missing body"))?;
 
-        // Parse JSON body
-        let json_value: serde_json::Value = serde_json::from_str(&body)?;
-
-        let mut event = EslEvent::new();
-
-        if let Some(obj) = json_value.as_object() {
-            for (key, value) in obj {
-                let value_str = match value {
-                    serde_json::Value::String(s) => s.clone(),
-                    _ => value.to_string(),
-                };
-                event.set_header(key.clone(), value_str);
-            }
-
-            if let Some(event_name) = event
-                .header("Event-Name")
-                .map(|s| s.to_string())
-            {
-                event.set_event_type(EslEventType::parse_event_type(&event_name));
-            }
-        }
-
-        Ok(event)
+        Ok(serde_json::from_slice(&body)?)
     }
 
     /// Parse XML event using quick_xml.
@@ -360,6 +432,9 @@ impl EslParser {
     ///   <body>...</body>
     /// </event>
     /// ```
+    ///
+    /// Header values are percent-encoded; a header repeated once per item is
+    /// a multi-value header and is folded into an `ARRAY::` value.
     fn parse_xml_event(&self, message: EslMessage) -> EslResult<EslEvent> {
         use quick_xml::events::Event as XmlEvent;
         use quick_xml::Reader;
@@ -368,8 +443,14 @@ impl EslParser {
             .body
             .ok_or_else(|| EslError::protocol_error("XML event missing body"))?;
 
-        let mut reader = Reader::from_str(&body);
-        let mut event = EslEvent::new();
+        let body = std::str::from_utf8(&body)
+            .map_err(|_| EslError::protocol_error("Invalid UTF-8 in XML event"))?;
+        let mut reader = Reader::from_str(body);
+        // Unescaped header values are appended to one string the event keeps;
+        // percent-decoding waits until a value is read
+        let mut wire = String::new();
+        let mut headers: Vec<(String, Range<usize>)> = Vec::new();
+        let mut event_body = None;
         let mut in_headers = false;
         let mut current_tag: Option<String> = None;
         let mut in_body = false;
@@ -403,13 +484,13 @@ impl EslParser {
                     }
                 }
                 Ok(XmlEvent::Text(ref e)) => {
-                    let text = e
-                        .unescape()?
-                        .to_string();
+                    let text = e.unescape()?;
                     if in_body {
-                        event.set_body(text);
+                        event_body = Some(text.into_owned());
                     } else if let Some(ref tag) = current_tag {
-                        event.set_header(tag.clone(), text);
+                        let start = wire.len();
+                        wire.push_str(&text);
+                        headers.push((tag.clone(), start..wire.len()));
                     }
                 }
                 Ok(XmlEvent::Eof) => break,
@@ -418,6 +499,14 @@ impl EslParser {
             }
         }
 
+        let mut event = EslEvent::from_wire(wire.into());
+        for (tag, span) in headers {
+            event.push_wire_header(&tag, span);
+        }
+        if let Some(text) = event_body {
+            event.set_body(text);
+        }
+
         if let Some(event_name) = event
             .header(HEADER_EVENT_NAME)
             .map(|s| s.to_string())
@@ -444,7 +533,7 @@ mod tests {
         let parser = EslParser::new();
         let headers_str = "Content-Type: auth/request\r\nContent-Length: 0";
         let headers = parser
-            .parse_headers(headers_str)
+            .parse_headers(headers_str.as_bytes())
             .unwrap();
 
         assert_eq!(
@@ -494,7 +583,41 @@ mod tests {
             .unwrap();
 
         assert_eq!(message.message_type, MessageType::ApiResponse);
-        assert_eq!(message.body, Some("OK".to_string()));
+        assert_eq!(
+            message
+                .body_str()
+                .unwrap(),
+            Some("OK")
+        );
+    }
+
+    #[test]
+    fn test_parse_log_data() {
+        let mut parser = EslParser::new();
+        let data = b"Content-Type: log/data\nContent-Length: 15\nLog-Level: 6\nLog-File: switch_ivr.c\nUser-Data: \n\nChannel parked\n";
+
+        parser
+            .add_data(data)
+            .unwrap();
+        let message = parser
+            .parse_message()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(message.message_type, MessageType::Log);
+        assert_eq!(
+            message
+                .headers
+                .get("Log-Level")
+                .map(String::as_str),
+            Some("6")
+        );
+        assert_eq!(
+            message
+                .body_str()
+                .unwrap(),
+            Some("Channel parked\n")
+        );
     }
 
     #[test]
@@ -666,6 +789,137 @@ mod tests {
         assert_eq!(event.header("Up-Time"), Some("0 years, 1 day"));
     }
 
+    fn parse_plain_body(body: &str) -> EslEvent {
+        let message = EslMessage::new(
+            MessageType::Event,
+            [("Content-Type".to_string(), "text/event-plain".to_string())].into(),
+            Some(Bytes::copy_from_slice(body.as_bytes())),
+        );
+        EslParser::new()
+            .parse_event(message, EventFormat::Plain)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_plain_event_reserializes_byte_identical() {
+        // As switch_event_serialize() writes it: wire order, Content-Length last
+        let wire = "\
+Event-Name: CHANNEL_EXECUTE_COMPLETE
+Core-UUID: 6f2c1a8e-52d6-4a93-8d11-0b5e0d6f7a10
+FreeSWITCH-Hostname: fs1.example.com
+Event-Date-Local: 2025-01-15%2012%3A00%3A00
+Unique-ID: a1b2c3d4-0000-1111-2222-333344445555
+Caller-Caller-ID-Name: Jane%20Doe
+Application: bridge
+Application-Data: %7Borigination_caller_id_number%3D1000%7Dsofia/gateway/gw1/5551234
+variable_sip_h_X-Trace: a%7Cb
+variable_codecs: ARRAY%3A%3APCMU%7C%3APCMA
+Content-Length: 4
+
++OK
+";
+        let event = parse_plain_body(wire);
+        let names: Vec<&str> = event
+            .headers()
+            .map(|(name, _)| name)
+            .take(3)
+            .collect();
+        assert_eq!(names, ["Event-Name", "Core-UUID", "FreeSWITCH-Hostname"]);
+        assert_eq!(event.header("Caller-Caller-ID-Name"), Some("Jane Doe"));
+        assert_eq!(event.to_plain_format(), wire);
+    }
+
+    #[test]
+    fn test_plain_event_repeated_header_folded() {
+        let event =
+            parse_plain_body("Event-Name: CUSTOM\nX-Route: first\nX-Other: 1\nX-Route: second\n\n");
+        assert_eq!(event.header("X-Route"), Some("ARRAY::first|:second"));
+        let names: Vec<&str> = event
+            .headers()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["Event-Name", "X-Route", "X-Other"]);
+    }
+
+    #[test]
+    fn test_envelope_headers_keep_order() {
+        let mut parser = EslParser::new();
+        parser
+            .add_data(b"Content-Type: command/reply\nReply-Text: +OK\nJob-UUID: abc\n\n")
+            .unwrap();
+        let message = parser
+            .parse_message()
+            .unwrap()
+            .unwrap();
+        let names: Vec<&str> = message
+            .headers
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(names, ["Content-Type", "Reply-Text", "Job-UUID"]);
+    }
+
+    #[test]
+    fn test_parse_event_json_body_and_arrays() {
+        let mut parser = EslParser::new();
+        let json_body = r#"{"Event-Name":"BACKGROUND_JOB","Job-UUID":"def-456","variable_codecs":["PCMU","PCMA"],"Content-Length":"16","_body":"+OK result data\n"}"#;
+        let data = format!(
+            "Content-Length: {}\nContent-Type: text/event-json\n\n{}",
+            json_body.len(),
+            json_body
+        );
+
+        parser
+            .add_data(data.as_bytes())
+            .unwrap();
+        let message = parser
+            .parse_message()
+            .unwrap()
+            .unwrap();
+        let event = parser
+            .parse_event(message, EventFormat::Json)
+            .unwrap();
+
+        assert_eq!(event.event_type(), Some(EslEventType::BackgroundJob));
+        assert_eq!(event.header("Job-UUID"), Some("def-456"));
+        assert_eq!(event.header("variable_codecs"), Some("ARRAY::PCMU|:PCMA"));
+        assert_eq!(event.header("_body"), None);
+        assert_eq!(event.body(), Some("+OK result data\n"));
+    }
+
+    #[test]
+    fn test_parse_event_xml_repeated_header_and_encoding() {
+        let mut parser = EslParser::new();
+        let xml_body = "\
+<event>\n\
+  <headers>\n\
+    <Event-Name>CHANNEL_CREATE</Event-Name>\n\
+    <Caller-Caller-ID-Name>Jane%20Doe</Caller-Caller-ID-Name>\n\
+    <variable_codecs>PCMU</variable_codecs>\n\
+    <variable_codecs>PCMA</variable_codecs>\n\
+  </headers>\n\
+</event>";
+        let data = format!(
+            "Content-Length: {}\nContent-Type: text/event-xml\n\n{}",
+            xml_body.len(),
+            xml_body
+        );
+
+        parser
+            .add_data(data.as_bytes())
+            .unwrap();
+        let message = parser
+            .parse_message()
+            .unwrap()
+            .unwrap();
+        let event = parser
+            .parse_event(message, EventFormat::Xml)
+            .unwrap();
+
+        assert_eq!(event.header("Caller-Caller-ID-Name"), Some("Jane Doe"));
+        assert_eq!(event.header("variable_codecs"), Some("ARRAY::PCMU|:PCMA"));
+    }
+
     #[test]
     fn test_parse_event_xml_with_body() {
         let mut parser = EslParser::new();
@@ -779,7 +1033,12 @@ mod tests {
             .unwrap()
             .unwrap();
         assert_eq!(message.message_type, MessageType::ApiResponse);
-        assert_eq!(message.body, Some("He".to_string()));
+        assert_eq!(
+            message
+                .body_str()
+                .unwrap(),
+            Some("He")
+        );
 
         // Leftover "llo" is now junk in the buffer — next parse finds nothing
         let next = parser
@@ -807,7 +1066,12 @@ mod tests {
             .parse_message()
             .unwrap()
             .unwrap();
-        assert_eq!(first.body, Some("He".to_string()));
+        assert_eq!(
+            first
+                .body_str()
+                .unwrap(),
+            Some("He")
+        );
 
         parser
             .add_data(msg2)
@@ -843,7 +1107,7 @@ mod tests {
     fn test_parse_headers_percent_decodes_values() {
         let parser = EslParser::new();
         let headers = parser
-            .parse_headers("Content-Type: command%2Freply\nReply-Text: %2BOK")
+            .parse_headers(b"Content-Type: command%2Freply\nReply-Text: %2BOK")
             .unwrap();
 
         assert_eq!(
@@ -864,7 +1128,7 @@ mod tests {
     fn test_parse_headers_noop_for_plain_values() {
         let parser = EslParser::new();
         let headers = parser
-            .parse_headers("Content-Type: command/reply\nReply-Text: +OK")
+            .parse_headers(b"Content-Type: command/reply\nReply-Text: +OK")
             .unwrap();
 
         assert_eq!(
@@ -885,7 +1149,7 @@ mod tests {
     fn test_parse_headers_invalid_percent_sequence() {
         let parser = EslParser::new();
         let headers = parser
-            .parse_headers("X-Bad: %ZZinvalid\nX-Good: clean")
+            .parse_headers(b"X-Bad: %ZZinvalid\nX-Good: clean")
             .unwrap();
 
         assert_eq!(
@@ -903,6 +1167,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_header_lines_return_raw_spans() {
+        let block = "Plain: value\r\nEncoded: a%20b\n\n Bad : %ZZ\nNo colon";
+        let lines: Vec<_> = HeaderLines::new(block).collect();
+
+        assert_eq!(lines.len(), 4);
+        let spans: Vec<(&str, &str)> = lines[..3]
+            .iter()
+            .map(|line| {
+                let (key, span) = line
+                    .as_ref()
+                    .unwrap();
+                (*key, &block[span.clone()])
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            [("Plain", "value"), ("Encoded", "a%20b"), ("Bad", "%ZZ")]
+        );
+        assert!(matches!(lines[3], Err(EslError::InvalidHeader { .. })));
+    }
+
+    #[test]
+    fn test_invalid_utf8_body_is_lossy_in_response() {
+        let mut parser = EslParser::new();
+        parser
+            .add_data(b"Content-Type: api/response\nContent-Length: 3\n\nO\xffK")
+            .unwrap();
+        let message = parser
+            .parse_message()
+            .unwrap()
+            .unwrap();
+
+        assert!(message
+            .body_str()
+            .is_err());
+        assert_eq!(
+            message
+                .into_response()
+                .body(),
+            Some("O\u{fffd}K")
+        );
+    }
+
     #[test]
     fn test_parse_connect_response() {
         use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
@@ -0,0 +1,362 @@
+//! Bounded event queue between the reader task and [`EslEventStream`](crate::EslEventStream).
+//!
+//! Unlike `tokio::sync::mpsc`, the producer can evict queued items, which the
+//! drop-oldest and priority [`BackpressurePolicy`](crate::BackpressurePolicy)
+//! variants need, and the queue depth can be read from other tasks.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use tokio::sync::Notify;
+
+struct State<T> {
+    items: VecDeque<T>,
+    rx_waker: Option<Waker>,
+    sender_alive: bool,
+    receiver_alive: bool,
+    max_depth: usize,
+}
+
+struct Inner<T> {
+    state: Mutex<State<T>>,
+    /// Signalled when the receiver pops an item or goes away.
+    space: Notify,
+    capacity: usize,
+}
+
+impl<T> Inner<T> {
+    fn state(&self) -> MutexGuard<'_, State<T>> {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Why an item could not be queued; the item is handed back.
+pub(crate) enum PushError<T> {
+    Full(T),
+    Closed(T),
+}
+
+/// Producer half, owned by the reader task.
+pub(crate) struct QueueSender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// Consumer half, owned by the event stream.
+pub(crate) struct QueueReceiver<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// Read-only view of the queue for metrics.
+pub(crate) struct QueueMonitor<T> {
+    inner: Arc<Inner<T>>,
+}
+
+pub(crate) fn channel<T>(capacity: usize) -> (QueueSender<T>, QueueReceiver<T>) {
+    let inner = Arc::new(Inner {
+        state: Mutex::new(State {
+            items: VecDeque::with_capacity(capacity.min(1024)),
+            rx_waker: None,
+            sender_alive: true,
+            receiver_alive: true,
+            max_depth: 0,
+        }),
+        space: Notify::new(),
+        capacity: capacity.max(1),
+    });
+    (
+        QueueSender {
+            inner: inner.clone(),
+        },
+        QueueReceiver { inner },
+    )
+}
+
+/// Append `item` and take the receiver's waker, to be woken after unlocking.
+fn enqueue<T>(state: &mut State<T>, item: T) -> Option<Waker> {
+    state
+        .items
+        .push_back(item);
+    state.max_depth = state
+        .max_depth
+        .max(
+            state
+                .items
+                .len(),
+        );
+    state
+        .rx_waker
+        .take()
+}
+
+impl<T> QueueSender<T> {
+    /// Queue `item` if there is room.
+    pub(crate) fn try_push(&self, item: T) -> Result<(), PushError<T>> {
+        self.push_evicting(item, |_| false)
+            .map(|_| ())
+    }
+
+    /// Queue `item`; when full, make room by removing the oldest queued item
+    /// matching `evict` and return it. Gives `item` back if nothing matched.
+    pub(crate) fn push_evicting(
+        &self,
+        item: T,
+        evict: impl Fn(&T) -> bool,
+    ) -> Result<Option<T>, PushError<T>> {
+        let mut state = self
+            .inner
+            .state();
+        if !state.receiver_alive {
+            return Err(PushError::Closed(item));
+        }
+        let mut evicted = None;
+        if state
+            .items
+            .len()
+            >= self
+                .inner
+                .capacity
+        {
+            let Some(index) = state
+                .items
+                .iter()
+                .position(evict)
+            else {
+                return Err(PushError::Full(item));
+            };
+            evicted = state
+                .items
+                .remove(index);
+        }
+        let waker = enqueue(&mut state, item);
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(evicted)
+    }
+
+    /// Queue `item`, waiting for the receiver to make room.
+    pub(crate) async fn push(&self, mut item: T) -> Result<(), T> {
+        loop {
+            // Created before the check so a pop in between is not missed
+            let space = self
+                .inner
+                .space
+                .notified();
+            match self.try_push(item) {
+                Ok(()) => return Ok(()),
+                Err(PushError::Closed(rejected)) => return Err(rejected),
+                Err(PushError::Full(rejected)) => item = rejected,
+            }
+            space.await;
+        }
+    }
+
+    pub(crate) fn monitor(&self) -> QueueMonitor<T> {
+        QueueMonitor {
+            inner: self
+                .inner
+                .clone(),
+        }
+    }
+}
+
+impl<T> Drop for QueueSender<T> {
+    fn drop(&mut self) {
+        let mut state = self
+            .inner
+            .state();
+        state.sender_alive = false;
+        let waker = state
+            .rx_waker
+            .take();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> QueueReceiver<T> {
+    /// Next item, or `None` once the sender is gone and the queue is drained.
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut state = self
+            .inner
+            .state();
+        if let Some(item) = state
+            .items
+            .pop_front()
+        {
+            drop(state);
+            self.inner
+                .space
+                .notify_one();
+            return Poll::Ready(Some(item));
+        }
+        if !state.sender_alive {
+            return Poll::Ready(None);
+        }
+        state.rx_waker = Some(
+            cx.waker()
+                .clone(),
+        );
+        Poll::Pending
+    }
+
+    pub(crate) async fn recv(&mut self) -> Option<T> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+}
+
+impl<T> Drop for QueueReceiver<T> {
+    fn drop(&mut self) {
+        let mut state = self
+            .inner
+            .state();
+        state.receiver_alive = false;
+        state
+            .items
+            .clear();
+        drop(state);
+        self.inner
+            .space
+            .notify_one();
+    }
+}
+
+impl<T> QueueMonitor<T> {
+    pub(crate) fn depth(&self) -> usize {
+        self.inner
+            .state()
+            .items
+            .len()
+    }
+
+    pub(crate) fn max_depth(&self) -> usize {
+        self.inner
+            .state()
+            .max_depth
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.inner
+            .capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_fifo_and_close() {
+        let (tx, mut rx) = channel(4);
+        assert!(tx
+            .try_push(1)
+            .is_ok());
+        assert!(tx
+            .try_push(2)
+            .is_ok());
+        drop(tx);
+        assert_eq!(
+            rx.recv()
+                .await,
+            Some(1)
+        );
+        assert_eq!(
+            rx.recv()
+                .await,
+            Some(2)
+        );
+        assert_eq!(
+            rx.recv()
+                .await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_full_and_evict() {
+        let (tx, mut rx) = channel(2);
+        tx.try_push(1)
+            .ok();
+        tx.try_push(2)
+            .ok();
+        assert!(matches!(tx.try_push(3), Err(PushError::Full(3))));
+
+        // Evict the first even item
+        match tx.push_evicting(3, |v| v % 2 == 0) {
+            Ok(evicted) => assert_eq!(evicted, Some(2)),
+            Err(_) => panic!("expected eviction"),
+        }
+        assert!(matches!(
+            tx.push_evicting(5, |v| v % 2 == 0),
+            Err(PushError::Full(5))
+        ));
+
+        let monitor = tx.monitor();
+        assert_eq!(monitor.depth(), 2);
+        assert_eq!(monitor.max_depth(), 2);
+        assert_eq!(
+            rx.recv()
+                .await,
+            Some(1)
+        );
+        assert_eq!(
+            rx.recv()
+                .await,
+            Some(3)
+        );
+        assert_eq!(monitor.depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_push_waits_for_space() {
+        let (tx, mut rx) = channel(1);
+        tx.try_push(1)
+            .ok();
+        let pusher = tokio::spawn(async move {
+            tx.push(2)
+                .await
+                .is_ok()
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!pusher.is_finished());
+        assert_eq!(
+            rx.recv()
+                .await,
+            Some(1)
+        );
+        assert!(pusher
+            .await
+            .unwrap());
+        assert_eq!(
+            rx.recv()
+                .await,
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_push_fails_when_receiver_dropped() {
+        let (tx, rx) = channel(1);
+        tx.try_push(1)
+            .ok();
+        let pusher = tokio::spawn(async move {
+            tx.push(2)
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(rx);
+        assert_eq!(
+            pusher
+                .await
+                .unwrap(),
+            Err(2)
+        );
+    }
+}
@@ -0,0 +1,988 @@
+//! Supervised client that reconnects automatically and replays subscriptions.
+//!
+//! [`EslClient`] never reconnects on its own — once the reader task exits, the
+//! handle is dead. [`ReconnectingClient`] wraps it with a supervisor task that
+//! watches the connection, reconnects with exponential backoff and jitter,
+//! re-authenticates, and replays the recorded `event`, `filter`, `log` and
+//! `divert_events` state so the event stream keeps flowing across FreeSWITCH
+//! restarts.
+//!
+//! Gaps are explicit: the [`ReconnectingEventStream`] yields
+//! [`SupervisedEvent::Disconnected`] when the connection drops and
+//! [`SupervisedEvent::Reconnected`] once a new session is ready. Events fired
+//! by FreeSWITCH in between are lost.
+//!
+//! ```rust,no_run
+//! use freeswitch_esl_tokio::{EslEventType, EventFormat};
+//! use freeswitch_esl_tokio::reconnect::{ReconnectingClient, ReconnectOptions, SupervisedEvent};
+//!
+//! # async fn example() -> Result<(), freeswitch_esl_tokio::EslError> {
+//! let (client, mut events) =
+//!     ReconnectingClient::connect("localhost", 8021, "ClueCon", ReconnectOptions::default())
+//!         .await?;
+//!
+//! // Recorded and replayed after every reconnect
+//! client.subscribe_events(EventFormat::Plain, &[EslEventType::ChannelAnswer]).await?;
+//!
+//! while let Some(item) = events.recv().await {
+//!     match item {
+//!         SupervisedEvent::Event(event) => println!("{:?}", event.event_type()),
+//!         SupervisedEvent::Disconnected(reason) => println!("gap started: {}", reason),
+//!         SupervisedEvent::Reconnected { .. } => println!("gap ended, resync state"),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, info, warn};
+
+use crate::{
+    command::{EslCommand, EslResponse},
+    connection::{
+        AppExecution, BgJob, ConnectionStatus, DisconnectReason, EslClient, EslConnectOptions,
+        EslEventStream, ExecuteOptions,
+    },
+    constants::MAX_EVENT_QUEUE_SIZE,
+    error::{EslError, EslResult},
+    event::{EslEvent, EventFormat, EventKind},
+    filter::{EventFilter, FilteredEventStream},
+    log::EslLogStream,
+    session::ChannelSession,
+    subscription::SubscriptionState,
+};
+
+/// Backoff and connection settings for [`ReconnectingClient`].
+///
+/// The delay before attempt `n` is `initial_delay * multiplier^(n-1)`, capped
+/// at `max_delay`, then randomized by ±`jitter` (a fraction, `0.2` = ±20%).
+#[derive(Debug, Clone)]
+pub struct ReconnectOptions {
+    /// Delay before the first reconnect attempt. Default: 500ms.
+    pub initial_delay: Duration,
+    /// Upper bound for the backoff delay. Default: 30s.
+    pub max_delay: Duration,
+    /// Growth factor applied after each failed attempt. Default: 2.0.
+    pub multiplier: f64,
+    /// Random spread applied to each delay, as a fraction clamped to `0.0..=1.0`;
+    /// a NaN or infinite value disables it. Default: 0.2.
+    pub jitter: f64,
+    /// Give up after this many consecutive failed attempts. Default: `None` (retry forever).
+    pub max_attempts: Option<u32>,
+    /// Options applied to every underlying connection.
+    pub connect_options: EslConnectOptions,
+}
+
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: None,
+            connect_options: EslConnectOptions::default(),
+        }
+    }
+}
+
+impl ReconnectOptions {
+    /// Backoff delay before attempt `attempt` (1-based), without jitter.
+    fn base_delay(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1) as i32;
+        let secs = self
+            .initial_delay
+            .as_secs_f64()
+            * self
+                .multiplier
+                .max(1.0)
+                .powi(exp);
+        let capped = secs.min(
+            self.max_delay
+                .as_secs_f64(),
+        );
+        // Near Duration::MAX the f64 round trip overflows
+        Duration::try_from_secs_f64(capped.max(0.0)).unwrap_or(self.max_delay)
+    }
+
+    /// Backoff delay before attempt `attempt` (1-based), with jitter applied.
+    fn delay(&self, attempt: u32) -> Duration {
+        let base = self.base_delay(attempt);
+        // A NaN or infinite jitter disables it
+        let jitter = if self
+            .jitter
+            .is_finite()
+        {
+            self.jitter
+                .clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        if jitter == 0.0 {
+            return base;
+        }
+        // Uniform in [-1, 1)
+        let r = random_unit() * 2.0 - 1.0;
+        Duration::try_from_secs_f64(base.as_secs_f64() * (1.0 + jitter * r)).unwrap_or(base)
+    }
+}
+
+/// Cheap non-cryptographic random number in `[0, 1)` for backoff jitter.
+fn random_unit() -> f64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static STATE: AtomicU64 = AtomicU64::new(0);
+
+    let mut x = STATE.load(Ordering::Relaxed);
+    if x == 0 {
+        x = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x9E37_79B9_7F4A_7C15)
+            | 1;
+    }
+    // xorshift64
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    STATE.store(x, Ordering::Relaxed);
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Item delivered by [`ReconnectingEventStream`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SupervisedEvent {
+    /// An event received on the current connection.
+    Event(EslEvent),
+    /// Error surfaced by the current connection's event stream (e.g.
+    /// [`EslError::QueueFull`] or a parse error).
+    Error(EslError),
+    /// The connection dropped. Events fired from now until
+    /// [`Reconnected`](Self::Reconnected) are lost.
+    Disconnected(DisconnectReason),
+    /// About to sleep `delay` before reconnect attempt number `attempt` (1-based).
+    Reconnecting {
+        /// Consecutive attempt number since the connection dropped.
+        attempt: u32,
+        /// Backoff delay before this attempt.
+        delay: Duration,
+    },
+    /// A new session is authenticated and the recorded subscriptions,
+    /// filters and log level have been replayed.
+    Reconnected {
+        /// Number of attempts it took.
+        attempts: u32,
+    },
+}
+
+/// Login credentials kept for re-authentication.
+#[derive(Clone)]
+enum Credentials {
+    Password(String),
+    User { user: String, password: String },
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Credentials::Password(_) => f
+                .debug_tuple("Password")
+                .field(&"[REDACTED]")
+                .finish(),
+            Credentials::User { user, .. } => f
+                .debug_struct("User")
+                .field("user", user)
+                .field("password", &"[REDACTED]")
+                .finish(),
+        }
+    }
+}
+
+/// Session state recorded from successful commands and replayed on reconnect.
+///
+/// Subscriptions and filters are not recorded here: they are taken from
+/// [`EslClient::subscriptions`] of the connection that dropped.
+#[derive(Debug, Clone, Default)]
+struct ReplayState {
+    log_level: Option<String>,
+    divert_events: Option<bool>,
+    liveness_timeout: Option<Duration>,
+    command_timeout: Option<Duration>,
+    job_timeout: Option<Duration>,
+}
+
+impl ReplayState {
+    /// Apply the recorded state to a freshly authenticated client.
+    async fn replay(&self, client: &EslClient, subscriptions: &SubscriptionState) -> EslResult<()> {
+        if let Some(timeout) = self.liveness_timeout {
+            client.set_liveness_timeout(timeout);
+        }
+        if let Some(timeout) = self.command_timeout {
+            client.set_command_timeout(timeout);
+        }
+        if let Some(timeout) = self.job_timeout {
+            client.set_job_timeout(timeout);
+        }
+        if let Some(list) = subscriptions.event_list() {
+            let format = subscriptions
+                .event_format()
+                .unwrap_or(EventFormat::Plain);
+            client
+                .subscribe_events_raw(format, &list)
+                .await?;
+            let excluded = subscriptions.excluded_events();
+            if !excluded.is_empty() {
+                client
+                    .nixevent_raw(&excluded.join(" "))
+                    .await?;
+            }
+        }
+        for (header, value) in subscriptions.filters() {
+            client
+                .filter_events(header, value)
+                .await?;
+        }
+        if let Some(level) = &self.log_level {
+            client
+                .log(level)
+                .await?
+                .into_result()?;
+        }
+        if let Some(on) = self.divert_events {
+            client
+                .divert_events(on)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Shared between all [`ReconnectingClient`] clones.
+struct Inner {
+    current: watch::Receiver<Option<EslClient>>,
+    replay: Arc<Mutex<ReplayState>>,
+    /// Subscriptions of the connection that dropped, reported while reconnecting
+    last_subscriptions: Arc<Mutex<SubscriptionState>>,
+    shutdown: watch::Sender<bool>,
+}
+
+/// ESL client handle that survives reconnects (Clone + Send).
+///
+/// Wraps the [`EslClient`] of the current connection. Methods that change
+/// session state (`subscribe_events`, `filter_events`, `log`, ...) are
+/// recorded on success and replayed after every reconnect. While the
+/// connection is down, commands fail with [`EslError::NotConnected`].
+///
+/// Dropping every clone (or calling [`disconnect`](Self::disconnect)) stops
+/// the supervisor and ends the event stream.
+#[derive(Clone)]
+pub struct ReconnectingClient {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for ReconnectingClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectingClient")
+            .field("connected", &self.is_connected())
+            .finish()
+    }
+}
+
+/// Event stream that spans reconnects (!Clone).
+///
+/// Returns `None` once the supervisor stops: after
+/// [`ReconnectingClient::disconnect`], when every client handle is dropped, or
+/// when [`ReconnectOptions::max_attempts`] is exhausted.
+pub struct ReconnectingEventStream {
+    rx: mpsc::Receiver<SupervisedEvent>,
+}
+
+impl std::fmt::Debug for ReconnectingEventStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectingEventStream")
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReconnectingEventStream {
+    /// Receive the next item, or `None` once the supervisor has stopped.
+    pub async fn recv(&mut self) -> Option<SupervisedEvent> {
+        self.rx
+            .recv()
+            .await
+    }
+}
+
+impl futures_util::Stream for ReconnectingEventStream {
+    type Item = SupervisedEvent;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx
+            .poll_recv(cx)
+    }
+}
+
+/// Everything the supervisor task needs to rebuild a connection.
+struct Supervisor {
+    host: String,
+    port: u16,
+    credentials: Credentials,
+    options: ReconnectOptions,
+    replay: Arc<Mutex<ReplayState>>,
+    last_subscriptions: Arc<Mutex<SubscriptionState>>,
+    current_tx: watch::Sender<Option<EslClient>>,
+    item_tx: mpsc::Sender<SupervisedEvent>,
+    shutdown_rx: watch::Receiver<bool>,
+}
+
+impl Supervisor {
+    async fn open(&self) -> EslResult<(EslClient, EslEventStream)> {
+        let options = self
+            .options
+            .connect_options
+            .clone();
+        match &self.credentials {
+            Credentials::Password(password) => {
+                EslClient::connect_with_options(&self.host, self.port, password, options).await
+            }
+            Credentials::User { user, password } => {
+                EslClient::connect_with_user_and_options(
+                    &self.host, self.port, user, password, options,
+                )
+                .await
+            }
+        }
+    }
+
+    fn is_shutdown(&self) -> bool {
+        *self
+            .shutdown_rx
+            .borrow()
+    }
+
+    /// Wait for shutdown. Also resolves when every client handle is dropped.
+    async fn shutdown_requested(rx: &mut watch::Receiver<bool>) {
+        while !*rx.borrow() {
+            if rx
+                .changed()
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    async fn emit(&self, item: SupervisedEvent) {
+        // The application may have dropped the stream and only kept the client.
+        let _ = self
+            .item_tx
+            .send(item)
+            .await;
+    }
+
+    /// Reconnect with backoff until a session is up and replayed.
+    ///
+    /// Returns `None` on shutdown or when `max_attempts` is exhausted.
+    async fn reconnect(
+        &mut self,
+        subscriptions: &SubscriptionState,
+    ) -> Option<(EslClient, EslEventStream, u32)> {
+        let mut attempt = 0u32;
+        loop {
+            attempt += 1;
+            if let Some(max) = self
+                .options
+                .max_attempts
+            {
+                if attempt > max {
+                    warn!("Giving up after {} reconnect attempts", max);
+                    return None;
+                }
+            }
+
+            let delay = self
+                .options
+                .delay(attempt);
+            self.emit(SupervisedEvent::Reconnecting { attempt, delay })
+                .await;
+            debug!("Reconnect attempt {} in {:?}", attempt, delay);
+
+            let mut shutdown_rx = self
+                .shutdown_rx
+                .clone();
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = Self::shutdown_requested(&mut shutdown_rx) => return None,
+            }
+
+            let (client, events) = match self
+                .open()
+                .await
+            {
+                Ok(pair) => pair,
+                Err(e) => {
+                    warn!("Reconnect attempt {} failed: {}", attempt, e);
+                    continue;
+                }
+            };
+
+            let state = self
+                .replay
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone();
+            if let Err(e) = state
+                .replay(&client, subscriptions)
+                .await
+            {
+                warn!("Replaying session state failed: {}", e);
+                let _ = client
+                    .disconnect()
+                    .await;
+                continue;
+            }
+
+            return Some((client, events, attempt));
+        }
+    }
+
+    async fn run(mut self, client: EslClient, events: EslEventStream) {
+        let mut session = Some((client, events));
+
+        while let Some((client, mut events)) = session.take() {
+            self.current_tx
+                .send_replace(Some(client.clone()));
+
+            let mut shutdown_rx = self
+                .shutdown_rx
+                .clone();
+            let stopping = loop {
+                tokio::select! {
+                    item = events.recv() => match item {
+                        Some(Ok(event)) => self.emit(SupervisedEvent::Event(event)).await,
+                        Some(Err(e)) => self.emit(SupervisedEvent::Error(e)).await,
+                        None => break false,
+                    },
+                    _ = Self::shutdown_requested(&mut shutdown_rx) => break true,
+                }
+            };
+
+            self.current_tx
+                .send_replace(None);
+
+            if stopping
+                || self.is_shutdown()
+                || shutdown_rx
+                    .has_changed()
+                    .is_err()
+            {
+                info!("Supervisor stopping on client request");
+                let _ = client
+                    .disconnect()
+                    .await;
+                return;
+            }
+
+            let reason = match events.status() {
+                ConnectionStatus::Disconnected(reason) => reason,
+                ConnectionStatus::Connected => DisconnectReason::ConnectionClosed,
+            };
+            warn!("Connection lost ({}), reconnecting", reason);
+            let subscriptions = client.subscriptions();
+            *self
+                .last_subscriptions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = subscriptions.clone();
+            self.emit(SupervisedEvent::Disconnected(reason))
+                .await;
+
+            if let Some((client, events, attempts)) = self
+                .reconnect(&subscriptions)
+                .await
+            {
+                info!("Reconnected after {} attempt(s)", attempts);
+                self.emit(SupervisedEvent::Reconnected { attempts })
+                    .await;
+                session = Some((client, events));
+            }
+        }
+    }
+}
+
+impl ReconnectingClient {
+    /// Connect with password authentication and start supervising the connection.
+    ///
+    /// The initial connection is attempted once; an error here is returned
+    /// directly so configuration mistakes surface immediately. Later failures
+    /// are retried according to `options`.
+    pub async fn connect(
+        host: &str,
+        port: u16,
+        password: &str,
+        options: ReconnectOptions,
+    ) -> EslResult<(Self, ReconnectingEventStream)> {
+        Self::start(
+            host,
+            port,
+            Credentials::Password(password.to_string()),
+            options,
+        )
+        .await
+    }
+
+    /// Connect with user authentication (`user@domain`) and start supervising.
+    pub async fn connect_with_user(
+        host: &str,
+        port: u16,
+        user: &str,
+        password: &str,
+        options: ReconnectOptions,
+    ) -> EslResult<(Self, ReconnectingEventStream)> {
+        Self::start(
+            host,
+            port,
+            Credentials::User {
+                user: user.to_string(),
+                password: password.to_string(),
+            },
+            options,
+        )
+        .await
+    }
+
+    async fn start(
+        host: &str,
+        port: u16,
+        credentials: Credentials,
+        options: ReconnectOptions,
+    ) -> EslResult<(Self, ReconnectingEventStream)> {
+        let queue_size = options
+            .connect_options
+            .event_queue_size
+            .clamp(1, MAX_EVENT_QUEUE_SIZE.max(1));
+        let (current_tx, current_rx) = watch::channel(None);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (item_tx, item_rx) = mpsc::channel(queue_size);
+        let replay = Arc::new(Mutex::new(ReplayState::default()));
+        let last_subscriptions = Arc::new(Mutex::new(SubscriptionState::default()));
+
+        let supervisor = Supervisor {
+            host: host.to_string(),
+            port,
+            credentials,
+            options,
+            replay: replay.clone(),
+            last_subscriptions: last_subscriptions.clone(),
+            current_tx,
+            item_tx,
+            shutdown_rx,
+        };
+
+        let (client, events) = supervisor
+            .open()
+            .await?;
+        // Publish before returning so commands work immediately.
+        supervisor
+            .current_tx
+            .send_replace(Some(client.clone()));
+        tokio::spawn(supervisor.run(client, events));
+
+        let handle = Self {
+            inner: Arc::new(Inner {
+                current: current_rx,
+                replay,
+                last_subscriptions,
+                shutdown: shutdown_tx,
+            }),
+        };
+        Ok((handle, ReconnectingEventStream { rx: item_rx }))
+    }
+
+    /// The [`EslClient`] of the current connection, or `None` while reconnecting.
+    ///
+    /// Subscriptions and filters set through it are replayed like those set
+    /// through this handle; log level and `divert_events` are not.
+    pub fn client(&self) -> Option<EslClient> {
+        self.inner
+            .current
+            .borrow()
+            .clone()
+    }
+
+    fn current(&self) -> EslResult<EslClient> {
+        self.client()
+            .ok_or(EslError::NotConnected)
+    }
+
+    fn record(&self, f: impl FnOnce(&mut ReplayState)) {
+        let mut state = self
+            .inner
+            .replay
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        f(&mut state);
+    }
+
+    /// Whether a connection is currently established.
+    pub fn is_connected(&self) -> bool {
+        self.client()
+            .is_some_and(|c| c.is_connected())
+    }
+
+    /// Send a raw command on the current connection. Not recorded for replay.
+    pub async fn send_command(&self, command: EslCommand) -> EslResult<EslResponse> {
+        self.current()?
+            .send_command(command)
+            .await
+    }
+
+    /// Execute an API command on the current connection.
+    pub async fn api(&self, command: &str) -> EslResult<EslResponse> {
+        self.current()?
+            .api(command)
+            .await
+    }
+
+    /// Execute a background API command on the current connection.
+    ///
+    /// A job still running when the connection drops never reports back:
+    /// its `BACKGROUND_JOB` event is lost with the old session.
+    pub async fn bgapi(&self, command: &str) -> EslResult<EslResponse> {
+        self.current()?
+            .bgapi(command)
+            .await
+    }
+
+    /// Execute a background API command and get a future for its result.
+    ///
+    /// See [`EslClient::bgapi_job`]. The job fails with
+    /// [`EslError::ConnectionClosed`] if the connection drops first.
+    pub async fn bgapi_job(&self, command: &str) -> EslResult<BgJob> {
+        self.current()?
+            .bgapi_job(command)
+            .await
+    }
+
+    /// Execute a background API command and wait for its result body.
+    pub async fn bgapi_wait(&self, command: &str) -> EslResult<String> {
+        self.bgapi_job(command)
+            .await?
+            .await
+    }
+
+    /// Set the `bgapi_job` timeout on this and every future connection.
+    pub fn set_job_timeout(&self, duration: Duration) {
+        self.record(|s| s.job_timeout = Some(duration));
+        if let Some(client) = self.client() {
+            client.set_job_timeout(duration);
+        }
+    }
+
+    /// Subscribe to event types or `CUSTOM` subclasses; see
+    /// [`EslClient::subscribe_events`]. Replayed after reconnect.
+    pub async fn subscribe_events<E: Copy + Into<EventKind>>(
+        &self,
+        format: EventFormat,
+        events: &[E],
+    ) -> EslResult<()> {
+        self.current()?
+            .subscribe_events(format, events)
+            .await
+    }
+
+    /// Subscribe to events using raw event name strings. Replayed after reconnect.
+    pub async fn subscribe_events_raw(&self, format: EventFormat, events: &str) -> EslResult<()> {
+        self.current()?
+            .subscribe_events_raw(format, events)
+            .await
+    }
+
+    /// Unsubscribe from specific events. Replayed after reconnect.
+    pub async fn nixevent<E: Copy + Into<EventKind>>(&self, events: &[E]) -> EslResult<()> {
+        self.current()?
+            .nixevent(events)
+            .await
+    }
+
+    /// Unsubscribe from events using raw event name strings. Replayed after reconnect.
+    pub async fn nixevent_raw(&self, events: &str) -> EslResult<()> {
+        self.current()?
+            .nixevent_raw(events)
+            .await
+    }
+
+    /// Unsubscribe from all events. Replayed after reconnect.
+    pub async fn noevents(&self) -> EslResult<()> {
+        self.current()?
+            .noevents()
+            .await
+    }
+
+    /// Bring subscriptions and filters to `desired`; see
+    /// [`EslClient::set_subscriptions`]. Replayed after reconnect.
+    pub async fn set_subscriptions(&self, desired: &SubscriptionState) -> EslResult<()> {
+        self.current()?
+            .set_subscriptions(desired)
+            .await
+    }
+
+    /// Subscriptions and filters that are replayed after a reconnect.
+    ///
+    /// [`EslClient::subscriptions`] of the current connection; while
+    /// reconnecting, those of the connection that dropped.
+    pub fn subscriptions(&self) -> SubscriptionState {
+        match self.client() {
+            Some(client) => client.subscriptions(),
+            None => self
+                .inner
+                .last_subscriptions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        }
+    }
+
+    /// Set an event filter. Replayed after reconnect.
+    pub async fn filter_events(&self, header: &str, value: &str) -> EslResult<()> {
+        self.current()?
+            .filter_events(header, value)
+            .await
+    }
+
+    /// Remove an event filter. Replayed after reconnect.
+    pub async fn filter_delete(&self, header: &str, value: Option<&str>) -> EslResult<()> {
+        self.current()?
+            .filter_delete(header, value)
+            .await
+    }
+
+    /// Remove all event filters. Replayed after reconnect.
+    pub async fn filter_delete_all(&self) -> EslResult<()> {
+        self.filter_delete("all", None)
+            .await
+    }
+
+    /// Enable log forwarding at the given level. Recorded for replay.
+    pub async fn log(&self, level: &str) -> EslResult<EslResponse> {
+        let response = self
+            .current()?
+            .log(level)
+            .await?;
+        if response.is_success() {
+            self.record(|s| s.log_level = Some(level.to_string()));
+        }
+        Ok(response)
+    }
+
+    /// Disable log forwarding. Recorded for replay.
+    pub async fn nolog(&self) -> EslResult<EslResponse> {
+        let response = self
+            .current()?
+            .nolog()
+            .await?;
+        if response.is_success() {
+            self.record(|s| s.log_level = None);
+        }
+        Ok(response)
+    }
+
+    /// Redirect session events to the ESL connection. Recorded for replay.
+    pub async fn divert_events(&self, on: bool) -> EslResult<()> {
+        self.current()?
+            .divert_events(on)
+            .await?;
+        self.record(|s| s.divert_events = Some(on));
+        Ok(())
+    }
+
+    /// Receive the current connection's `log/data` lines; see [`EslClient::log_stream`].
+    ///
+    /// The stream ends when the connection drops. The log level is replayed,
+    /// but lines are dropped until this is called again after
+    /// [`SupervisedEvent::Reconnected`].
+    pub fn log_stream(&self) -> EslResult<EslLogStream> {
+        Ok(self
+            .current()?
+            .log_stream())
+    }
+
+    /// Open a [`ChannelSession`] on the current connection; see [`EslClient::session`].
+    ///
+    /// The session ends when the connection drops.
+    pub fn session(&self, uuid: &str) -> EslResult<ChannelSession> {
+        Ok(self
+            .current()?
+            .session(uuid))
+    }
+
+    /// Copy matching events of the current connection to a separate stream;
+    /// see [`EslClient::subscribe_stream`].
+    ///
+    /// The stream ends when the connection drops.
+    pub fn subscribe_stream(&self, filter: EventFilter) -> EslResult<FilteredEventStream> {
+        Ok(self
+            .current()?
+            .subscribe_stream(filter))
+    }
+
+    /// Execute an application on a channel.
+    pub async fn execute(
+        &self,
+        app: &str,
+        args: Option<&str>,
+        uuid: Option<&str>,
+    ) -> EslResult<EslResponse> {
+        self.current()?
+            .execute(app, args, uuid)
+            .await
+    }
+
+    /// Execute an application and get a future for its completion; see
+    /// [`EslClient::execute_and_wait`].
+    ///
+    /// Fails with [`EslError::ConnectionClosed`] if the connection drops first.
+    pub async fn execute_and_wait(
+        &self,
+        app: &str,
+        args: Option<&str>,
+        uuid: &str,
+        options: ExecuteOptions,
+    ) -> EslResult<AppExecution> {
+        self.current()?
+            .execute_and_wait(app, args, uuid, options)
+            .await
+    }
+
+    /// Send a message to a channel.
+    pub async fn sendmsg(&self, uuid: Option<&str>, event: EslEvent) -> EslResult<EslResponse> {
+        self.current()?
+            .sendmsg(uuid, event)
+            .await
+    }
+
+    /// Fire an event into FreeSWITCH's event bus.
+    pub async fn sendevent(&self, event: EslEvent) -> EslResult<EslResponse> {
+        self.current()?
+            .sendevent(event)
+            .await
+    }
+
+    /// Set the liveness timeout on this and every future connection.
+    pub fn set_liveness_timeout(&self, duration: Duration) {
+        self.record(|s| s.liveness_timeout = Some(duration));
+        if let Some(client) = self.client() {
+            client.set_liveness_timeout(duration);
+        }
+    }
+
+    /// Set the command timeout on this and every future connection.
+    pub fn set_command_timeout(&self, duration: Duration) {
+        self.record(|s| s.command_timeout = Some(duration));
+        if let Some(client) = self.client() {
+            client.set_command_timeout(duration);
+        }
+    }
+
+    /// Stop supervising and close the current connection.
+    ///
+    /// The event stream ends once the supervisor has shut down.
+    pub async fn disconnect(&self) -> EslResult<()> {
+        info!("Client requested disconnect, stopping supervisor");
+        self.inner
+            .shutdown
+            .send_replace(true);
+        match self.client() {
+            Some(client) => {
+                client
+                    .disconnect()
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        let options = ReconnectOptions {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(options.delay(1), Duration::from_millis(100));
+        assert_eq!(options.delay(2), Duration::from_millis(200));
+        assert_eq!(options.delay(3), Duration::from_millis(400));
+        assert_eq!(options.delay(5), Duration::from_secs(1));
+        assert_eq!(options.delay(50), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn backoff_jitter_within_bounds() {
+        let options = ReconnectOptions {
+            initial_delay: Duration::from_millis(1000),
+            jitter: 0.25,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let d = options.delay(1);
+            assert!(d >= Duration::from_millis(750), "{:?}", d);
+            assert!(d <= Duration::from_millis(1250), "{:?}", d);
+        }
+    }
+
+    #[test]
+    fn backoff_caps_at_duration_max() {
+        let options = ReconnectOptions {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::MAX,
+            multiplier: 10.0,
+            ..Default::default()
+        };
+        assert_eq!(options.base_delay(1), Duration::from_secs(1));
+        assert_eq!(options.base_delay(500), Duration::MAX);
+        for attempt in [30, 500, u32::MAX] {
+            options.delay(attempt);
+        }
+    }
+
+    #[test]
+    fn backoff_ignores_nan_jitter() {
+        let options = ReconnectOptions {
+            initial_delay: Duration::from_millis(100),
+            jitter: f64::NAN,
+            ..Default::default()
+        };
+        assert_eq!(options.delay(1), Duration::from_millis(100));
+        let options = ReconnectOptions {
+            jitter: f64::INFINITY,
+            ..options
+        };
+        assert_eq!(options.delay(1), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn credentials_debug_redacts_password() {
+        let creds = Credentials::User {
+            user: "admin@default".into(),
+            password: "secret".into(),
+        };
+        let debug = format!("{:?}", creds);
+        assert!(!debug.contains("secret"));
+        assert!(debug.contains("admin@default"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Channel-scoped event stream and command helpers.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    channel::HangupCause,
+    command::{api_result, EslResponse},
+    commands::{UuidGetVar, UuidKill, UuidSetVar},
+    connection::{AppExecution, EslClient, ExecuteOptions},
+    error::EslResult,
+    event::{EslEvent, EventFormat},
+};
+
+/// Value `uuid_getvar` returns for an unset variable.
+const UNDEFINED_VAR: &str = "_undef_";
+
+/// Handle on a single channel, obtained from [`EslClient::session`] (!Clone).
+///
+/// Receives a copy of every event carrying the channel's `Unique-ID`, and
+/// exposes helpers bound to that UUID. The stream ends after
+/// `CHANNEL_DESTROY` or when the connection closes. Dropping the handle
+/// stops the routing.
+pub struct ChannelSession {
+    client: EslClient,
+    uuid: String,
+    id: u64,
+    rx: mpsc::Receiver<EslEvent>,
+}
+
+impl std::fmt::Debug for ChannelSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelSession")
+            .field("uuid", &self.uuid)
+            .finish()
+    }
+}
+
+impl ChannelSession {
+    pub(crate) fn new(
+        client: EslClient,
+        uuid: String,
+        id: u64,
+        rx: mpsc::Receiver<EslEvent>,
+    ) -> Self {
+        Self {
+            client,
+            uuid,
+            id,
+            rx,
+        }
+    }
+
+    /// The channel UUID this session is bound to.
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    /// The underlying client, for commands not covered by the helpers.
+    pub fn client(&self) -> &EslClient {
+        &self.client
+    }
+
+    /// Receive the next event for this channel.
+    ///
+    /// Returns `None` after `CHANNEL_DESTROY` has been delivered, or once the
+    /// connection has closed.
+    pub async fn recv(&mut self) -> Option<EslEvent> {
+        self.rx
+            .recv()
+            .await
+    }
+
+    /// Subscribe the connection to this channel's events (`myevents <uuid>`).
+    ///
+    /// On an inbound connection FreeSWITCH then delivers **only** this
+    /// channel's events for the rest of the connection, so this suits a
+    /// connection dedicated to one call. On a shared connection, subscribe
+    /// to the needed event types with [`EslClient::subscribe_events`] instead.
+    pub async fn myevents(&self, format: EventFormat) -> EslResult<()> {
+        self.client
+            .myevents_uuid(&self.uuid, format)
+            .await
+    }
+
+    /// Execute a dialplan application on this channel.
+    pub async fn execute(&self, app: &str, args: Option<&str>) -> EslResult<EslResponse> {
+        self.client
+            .execute(app, args, Some(&self.uuid))
+            .await
+    }
+
+    /// Execute an application on this channel and get a future for its completion.
+    ///
+    /// See [`EslClient::execute_and_wait`].
+    pub async fn execute_and_wait(
+        &self,
+        app: &str,
+        args: Option<&str>,
+        options: ExecuteOptions,
+    ) -> EslResult<AppExecution> {
+        self.client
+            .execute_and_wait(app, args, &self.uuid, options)
+            .await
+    }
+
+    /// Send a message to this channel (`sendmsg <uuid>`).
+    pub async fn sendmsg(&self, event: EslEvent) -> EslResult<EslResponse> {
+        self.client
+            .sendmsg(Some(&self.uuid), event)
+            .await
+    }
+
+    /// Read a channel variable.
+    ///
+    /// Returns `None` if the variable is not set. Uses `api uuid_getvar`, or
+    /// the plain `getvar` command on an outbound socket without `full`
+    /// control (see [`EslClient::is_single_channel`]), where `api` is not
+    /// available. `getvar` cannot tell an unset variable from an empty one,
+    /// so both return `None` there.
+    pub async fn getvar(&self, name: &str) -> EslResult<Option<String>> {
+        if self
+            .client
+            .is_single_channel()
+        {
+            let value = self
+                .client
+                .getvar(name)
+                .await?;
+            return Ok(Some(value).filter(|v| !v.is_empty()));
+        }
+        let cmd = UuidGetVar {
+            uuid: self
+                .uuid
+                .clone(),
+            key: name.to_string(),
+        };
+        let value = api_result(
+            self.client
+                .api(&cmd.to_string())
+                .await?,
+        )?;
+        if value == UNDEFINED_VAR {
+            Ok(None)
+        } else {
+            Ok(Some(value))
+        }
+    }
+
+    /// Set a channel variable.
+    ///
+    /// Uses `api uuid_setvar`, or executes the `set` application on an
+    /// outbound socket without `full` control.
+    pub async fn setvar(&self, name: &str, value: &str) -> EslResult<()> {
+        if self
+            .client
+            .is_single_channel()
+        {
+            return self
+                .execute("set", Some(&format!("{}={}", name, value)))
+                .await?
+                .into_result()
+                .map(|_| ());
+        }
+        let cmd = UuidSetVar {
+            uuid: self
+                .uuid
+                .clone(),
+            key: name.to_string(),
+            value: value.to_string(),
+        };
+        api_result(
+            self.client
+                .api(&cmd.to_string())
+                .await?,
+        )
+        .map(|_| ())
+    }
+
+    /// Hang up the channel.
+    ///
+    /// Uses `api uuid_kill`, or a `sendmsg` with `call-command: hangup` on an
+    /// outbound socket without `full` control. With `None`, FreeSWITCH uses
+    /// `NORMAL_CLEARING`.
+    pub async fn hangup(&self, cause: Option<HangupCause>) -> EslResult<()> {
+        if self
+            .client
+            .is_single_channel()
+        {
+            let mut event = EslEvent::new();
+            event.set_header("call-command", "hangup");
+            if let Some(cause) = cause {
+                event.set_header("hangup-cause", cause.to_string());
+            }
+            return self
+                .sendmsg(event)
+                .await?
+                .into_result()
+                .map(|_| ());
+        }
+        let cmd = UuidKill {
+            uuid: self
+                .uuid
+                .clone(),
+            cause,
+        };
+        api_result(
+            self.client
+                .api(&cmd.to_string())
+                .await?,
+        )
+        .map(|_| ())
+    }
+}
+
+impl Drop for ChannelSession {
+    fn drop(&mut self) {
+        self.client
+            .close_session(&self.uuid, self.id);
+    }
+}
+
+impl futures_util::Stream for ChannelSession {
+    type Item = EslEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx
+            .poll_recv(cx)
+    }
+}
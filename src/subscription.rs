@@ -0,0 +1,690 @@
+//! Event subscriptions and filters held by a connection.
+//!
+//! FreeSWITCH has no command to list what a connection is subscribed to, so
+//! [`EslClient`] records every successful `event`, `nixevent`, `noevents`,
+//! `filter` and `filter delete` in a [`SubscriptionState`], readable with
+//! [`EslClient::subscriptions`]. [`EslClient::set_subscriptions`] takes the
+//! desired state instead of individual commands and sends only what differs:
+//!
+//! ```rust,no_run
+//! use freeswitch_esl_tokio::{
+//!     EslClient, EslEventType, EventFormat, EventSubclass, SubscriptionState,
+//! };
+//!
+//! # async fn example(client: &EslClient) -> Result<(), freeswitch_esl_tokio::EslError> {
+//! let desired = SubscriptionState::new()
+//!     .format(EventFormat::Plain)
+//!     .events(&[EslEventType::ChannelAnswer, EslEventType::ChannelHangup])
+//!     .custom_events(&[EventSubclass::SofiaRegister])
+//!     .filter("Call-Direction", "inbound");
+//! client.set_subscriptions(&desired).await?;
+//!
+//! // Only `nixevent CHANNEL_HANGUP` is sent
+//! let desired = desired.without_events(&[EslEventType::ChannelHangup]);
+//! client.set_subscriptions(&desired).await?;
+//! println!("{:?}", client.subscriptions());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::event::{EslEventType, EventFormat, EventSubclass};
+
+#[cfg(doc)]
+use crate::EslClient;
+
+/// Event subscriptions and filters of a connection, or a desired set of them.
+///
+/// Event names are kept uppercase; tokens after `CUSTOM` are subclass names,
+/// as `mod_event_socket` parses them. Comparison ignores order.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionState {
+    format: Option<EventFormat>,
+    all: bool,
+    /// Names removed from `ALL` with `nixevent`
+    excluded: Vec<String>,
+    events: Vec<String>,
+    subclasses: Vec<String>,
+    filters: Vec<(String, String)>,
+}
+
+impl PartialEq for SubscriptionState {
+    fn eq(&self, other: &Self) -> bool {
+        fn same<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|x| b.contains(x))
+        }
+        self.format == other.format
+            && self.all == other.all
+            && same(&self.excluded, &other.excluded)
+            && same(&self.events, &other.events)
+            && same(&self.subclasses, &other.subclasses)
+            && same(&self.filters, &other.filters)
+    }
+}
+
+impl Eq for SubscriptionState {}
+
+/// One command of a [`SubscriptionState::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SubscriptionChange {
+    Subscribe { format: EventFormat, events: String },
+    Unsubscribe(String),
+    NoEvents,
+    AddFilter(String, String),
+    DeleteFilter(String, String),
+    DeleteAllFilters,
+}
+
+impl SubscriptionState {
+    /// No events and no filters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Event format to subscribe with. Without one, the connection keeps its
+    /// current format (`plain` if none was ever set).
+    pub fn format(mut self, format: EventFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Add event types. [`EslEventType::All`] subscribes to everything.
+    pub fn events(mut self, events: &[EslEventType]) -> Self {
+        for event in events {
+            self.add_tokens(&event.to_string());
+        }
+        self
+    }
+
+    /// Add events by name, `CUSTOM` followed by subclass names included.
+    pub fn events_raw(mut self, events: &str) -> Self {
+        self.add_tokens(events);
+        self
+    }
+
+    /// Add `CUSTOM` subclasses.
+    pub fn custom_events(mut self, subclasses: &[EventSubclass]) -> Self {
+        self.add_tokens(&EventSubclass::event_list(subclasses));
+        self
+    }
+
+    /// Remove event types. With [`EslEventType::All`] subscribed, the
+    /// removed types are excluded from it, as `nixevent` does.
+    pub fn without_events(mut self, events: &[EslEventType]) -> Self {
+        for event in events {
+            self.remove_events(&event.to_string());
+        }
+        self
+    }
+
+    /// Add a `filter <header> <value>`; `/regex/` values are regexes.
+    pub fn filter(mut self, header: impl Into<String>, value: impl Into<String>) -> Self {
+        self.add_filter(&header.into(), &value.into());
+        self
+    }
+
+    /// Format of the last `event`/`myevents` command.
+    pub fn event_format(&self) -> Option<EventFormat> {
+        self.format
+    }
+
+    /// Subscribed to `ALL`, minus any [`excluded_events`](Self::excluded_events).
+    pub fn is_all(&self) -> bool {
+        self.all
+    }
+
+    /// Event names removed from `ALL` with `nixevent`.
+    ///
+    /// FreeSWITCH turns `nixevent X` on an `ALL` subscription into every
+    /// event type except `X`.
+    pub fn excluded_events(&self) -> &[String] {
+        &self.excluded
+    }
+
+    /// Event names, `CUSTOM` included if custom events were subscribed.
+    pub fn event_names(&self) -> &[String] {
+        &self.events
+    }
+
+    /// `CUSTOM` subclasses, e.g. `sofia::register`.
+    pub fn subclasses(&self) -> &[String] {
+        &self.subclasses
+    }
+
+    /// `(header, value)` filters.
+    pub fn filters(&self) -> &[(String, String)] {
+        &self.filters
+    }
+
+    /// The list for an `event` command, with subclasses after `CUSTOM`;
+    /// `None` if nothing is subscribed.
+    ///
+    /// With `ALL`, the list is `ALL` and the subclasses; the
+    /// [`excluded_events`](Self::excluded_events) need a `nixevent` after it.
+    pub fn event_list(&self) -> Option<String> {
+        let mut tokens: Vec<&str> = if self.all {
+            vec!["ALL"]
+        } else {
+            self.events
+                .iter()
+                .filter(|e| e.as_str() != "CUSTOM")
+                .map(|e| e.as_str())
+                .collect()
+        };
+        if (!self.all
+            && self
+                .events
+                .iter()
+                .any(|e| e == "CUSTOM"))
+            || !self
+                .subclasses
+                .is_empty()
+        {
+            tokens.push("CUSTOM");
+            tokens.extend(
+                self.subclasses
+                    .iter()
+                    .map(|s| s.as_str()),
+            );
+        }
+        if tokens.is_empty() {
+            None
+        } else {
+            Some(tokens.join(" "))
+        }
+    }
+
+    fn has_events(&self) -> bool {
+        self.all
+            || !self
+                .events
+                .is_empty()
+            || !self
+                .subclasses
+                .is_empty()
+    }
+
+    /// Record an `event <format> <list>` command.
+    pub(crate) fn add_events(&mut self, format: EventFormat, list: &str) {
+        self.format = Some(format);
+        self.add_tokens(list);
+    }
+
+    pub(crate) fn set_format(&mut self, format: EventFormat) {
+        self.format = Some(format);
+    }
+
+    fn add_tokens(&mut self, list: &str) {
+        let mut custom = false;
+        for token in list.split_whitespace() {
+            if custom {
+                push_unique(&mut self.subclasses, token);
+            } else if token.eq_ignore_ascii_case("ALL") {
+                self.all = true;
+                self.excluded
+                    .clear();
+            } else if token.eq_ignore_ascii_case("CUSTOM") {
+                custom = true;
+                push_unique(&mut self.events, "CUSTOM");
+            } else {
+                let name = token.to_uppercase();
+                self.excluded
+                    .retain(|e| *e != name);
+                push_unique(&mut self.events, &name);
+            }
+        }
+    }
+
+    /// Record a `nixevent <list>` command.
+    pub(crate) fn remove_events(&mut self, list: &str) {
+        let mut custom = false;
+        for token in list.split_whitespace() {
+            if custom {
+                self.subclasses
+                    .retain(|s| s != token);
+            } else if token.eq_ignore_ascii_case("ALL") {
+                self.clear_events();
+            } else {
+                if token.eq_ignore_ascii_case("CUSTOM") {
+                    custom = true;
+                } else if self.all {
+                    push_unique(&mut self.excluded, &token.to_uppercase());
+                }
+                self.events
+                    .retain(|e| !e.eq_ignore_ascii_case(token));
+            }
+        }
+    }
+
+    /// Record a `noevents` command.
+    pub(crate) fn clear_events(&mut self) {
+        self.all = false;
+        self.excluded
+            .clear();
+        self.events
+            .clear();
+        self.subclasses
+            .clear();
+    }
+
+    /// Record a `filter` command.
+    pub(crate) fn add_filter(&mut self, header: &str, value: &str) {
+        let entry = (header.to_string(), value.to_string());
+        if !self
+            .filters
+            .contains(&entry)
+        {
+            self.filters
+                .push(entry);
+        }
+    }
+
+    /// Record a `filter delete` command.
+    pub(crate) fn remove_filter(&mut self, header: &str, value: Option<&str>) {
+        if header == "all" {
+            self.filters
+                .clear();
+            return;
+        }
+        self.filters
+            .retain(|(h, v)| !(h == header && value.map_or(true, |value| v == value)));
+    }
+
+    /// Commands that turn this state into `desired`.
+    ///
+    /// Unsubscribes and filters come before new subscriptions, so events the
+    /// filters would reject are not let through in between. The exception is
+    /// `ALL` with exclusions, which is sent as `event ALL` then `nixevent`.
+    pub(crate) fn diff(&self, desired: &SubscriptionState) -> Vec<SubscriptionChange> {
+        let mut changes = Vec::new();
+        let mut current = self.clone();
+        let format = desired
+            .format
+            .or(current.format)
+            .unwrap_or(EventFormat::Plain);
+        // The format applies to the whole connection; resend everything.
+        let resubscribe = current.format != Some(format);
+
+        if !desired.has_events() || (current.all && !desired.all) {
+            if current.has_events() {
+                changes.push(SubscriptionChange::NoEvents);
+                current.clear_events();
+            }
+        } else if current.all && !resubscribe {
+            let excluded = missing(&desired.excluded, &current.excluded);
+            if !excluded.is_empty() {
+                let list = excluded.join(" ");
+                current.remove_events(&list);
+                changes.push(SubscriptionChange::Unsubscribe(list));
+            }
+        } else if !current.all && !desired.all {
+            let stale = SubscriptionState {
+                events: missing(&current.events, &desired.events),
+                subclasses: missing(&current.subclasses, &desired.subclasses),
+                ..Default::default()
+            };
+            if let Some(list) = stale.event_list() {
+                // `nixevent CUSTOM <subclass>` is recorded as dropping plain
+                // `CUSTOM` too; when `desired` still has `CUSTOM`, it is then
+                // missing from `current` and subscribed again below.
+                current.remove_events(&list);
+                changes.push(SubscriptionChange::Unsubscribe(list));
+            }
+        }
+
+        for (header, value) in &desired.filters {
+            if !current
+                .filters
+                .contains(&(header.clone(), value.clone()))
+            {
+                changes.push(SubscriptionChange::AddFilter(header.clone(), value.clone()));
+            }
+        }
+        let stale_filters = missing(&current.filters, &desired.filters);
+        if desired
+            .filters
+            .is_empty()
+            && !stale_filters.is_empty()
+        {
+            changes.push(SubscriptionChange::DeleteAllFilters);
+        } else {
+            for (header, value) in stale_filters {
+                changes.push(SubscriptionChange::DeleteFilter(header, value));
+            }
+        }
+
+        let Some(full_list) = desired.event_list() else {
+            return changes;
+        };
+        let events = if resubscribe || (desired.all && !current.all) {
+            Some(full_list)
+        } else if current.all {
+            // Types excluded so far but wanted again
+            let readded = missing(&current.excluded, &desired.excluded);
+            (!readded.is_empty()).then(|| readded.join(" "))
+        } else {
+            SubscriptionState {
+                events: missing(&desired.events, &current.events),
+                subclasses: missing(&desired.subclasses, &current.subclasses),
+                ..Default::default()
+            }
+            .event_list()
+        };
+        let sent_all = desired.all && (resubscribe || !current.all);
+        if let Some(events) = events {
+            changes.push(SubscriptionChange::Subscribe { format, events });
+        }
+        if sent_all
+            && !desired
+                .excluded
+                .is_empty()
+        {
+            changes.push(SubscriptionChange::Unsubscribe(
+                desired
+                    .excluded
+                    .join(" "),
+            ));
+        }
+        changes
+    }
+}
+
+/// Items of `list` that are not in `other`.
+fn missing<T: PartialEq + Clone>(list: &[T], other: &[T]) -> Vec<T> {
+    list.iter()
+        .filter(|item| !other.contains(item))
+        .cloned()
+        .collect()
+}
+
+fn push_unique(list: &mut Vec<String>, value: &str) {
+    if !list
+        .iter()
+        .any(|v| v == value)
+    {
+        list.push(value.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscribe(format: EventFormat, events: &str) -> SubscriptionChange {
+        SubscriptionChange::Subscribe {
+            format,
+            events: events.to_string(),
+        }
+    }
+
+    #[test]
+    fn event_list_keeps_custom_last() {
+        let mut state = SubscriptionState::default();
+        state.add_events(EventFormat::Plain, "CHANNEL_CREATE");
+        state.add_events(EventFormat::Plain, "CUSTOM sofia::register");
+        state.add_events(EventFormat::Plain, "HEARTBEAT");
+        assert_eq!(
+            state
+                .event_list()
+                .as_deref(),
+            Some("CHANNEL_CREATE HEARTBEAT CUSTOM sofia::register")
+        );
+    }
+
+    #[test]
+    fn event_list_dedups() {
+        let mut state = SubscriptionState::default();
+        state.add_events(EventFormat::Json, "HEARTBEAT channel_answer");
+        state.add_events(EventFormat::Json, "HEARTBEAT");
+        assert_eq!(
+            state
+                .event_list()
+                .as_deref(),
+            Some("HEARTBEAT CHANNEL_ANSWER")
+        );
+        assert_eq!(state.format, Some(EventFormat::Json));
+    }
+
+    #[test]
+    fn nixevent_and_noevents() {
+        let mut state = SubscriptionState::default();
+        state.add_events(
+            EventFormat::Plain,
+            "HEARTBEAT CHANNEL_ANSWER CUSTOM a::b c::d",
+        );
+        state.remove_events("CHANNEL_ANSWER CUSTOM c::d");
+        assert_eq!(
+            state
+                .event_list()
+                .as_deref(),
+            Some("HEARTBEAT CUSTOM a::b")
+        );
+        state.clear_events();
+        assert_eq!(state.event_list(), None);
+    }
+
+    #[test]
+    fn all_overrides_list() {
+        let mut state = SubscriptionState::default();
+        state.add_events(EventFormat::Plain, "HEARTBEAT");
+        state.add_events(EventFormat::Plain, "ALL");
+        assert_eq!(
+            state
+                .event_list()
+                .as_deref(),
+            Some("ALL")
+        );
+    }
+
+    #[test]
+    fn filters() {
+        let mut state = SubscriptionState::default();
+        state.add_filter("Event-Name", "CHANNEL_ANSWER");
+        state.add_filter("Event-Name", "CHANNEL_ANSWER");
+        state.add_filter("Event-Name", "HEARTBEAT");
+        state.add_filter("Unique-ID", "abc");
+        assert_eq!(
+            state
+                .filters
+                .len(),
+            3
+        );
+
+        state.remove_filter("Event-Name", Some("HEARTBEAT"));
+        assert_eq!(
+            state
+                .filters
+                .len(),
+            2
+        );
+        state.remove_filter("Event-Name", None);
+        assert_eq!(
+            state.filters,
+            vec![("Unique-ID".to_string(), "abc".to_string())]
+        );
+        state.remove_filter("all", None);
+        assert!(state
+            .filters
+            .is_empty());
+    }
+
+    #[test]
+    fn diff_sends_only_changes() {
+        let current = SubscriptionState::new()
+            .format(EventFormat::Plain)
+            .events_raw("HEARTBEAT CHANNEL_ANSWER CUSTOM a::b")
+            .filter("Call-Direction", "inbound");
+        let desired = SubscriptionState::new()
+            .events_raw("CHANNEL_ANSWER CHANNEL_HANGUP CUSTOM a::b")
+            .filter("Call-Direction", "inbound");
+        assert_eq!(
+            current.diff(&desired),
+            vec![
+                SubscriptionChange::Unsubscribe("HEARTBEAT".to_string()),
+                subscribe(EventFormat::Plain, "CHANNEL_HANGUP"),
+            ]
+        );
+        assert!(desired
+            .clone()
+            .format(EventFormat::Plain)
+            .diff(&desired)
+            .is_empty());
+    }
+
+    #[test]
+    fn diff_removing_subclass_keeps_custom() {
+        let current = SubscriptionState::new()
+            .format(EventFormat::Plain)
+            .events_raw("CUSTOM a::b c::d");
+        let desired = SubscriptionState::new().events_raw("CUSTOM a::b");
+        assert_eq!(
+            current.diff(&desired),
+            vec![
+                SubscriptionChange::Unsubscribe("CUSTOM c::d".to_string()),
+                subscribe(EventFormat::Plain, "CUSTOM"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_removing_last_subclass_drops_custom() {
+        let current = SubscriptionState::new()
+            .format(EventFormat::Plain)
+            .events_raw("HEARTBEAT CUSTOM a::b");
+        let desired = SubscriptionState::new().events_raw("HEARTBEAT");
+        assert_eq!(
+            current.diff(&desired),
+            vec![SubscriptionChange::Unsubscribe("CUSTOM a::b".to_string())]
+        );
+
+        // Plain CUSTOM still wanted: subscribed again after the nixevent
+        let desired = SubscriptionState::new().events_raw("HEARTBEAT CUSTOM");
+        assert_eq!(
+            current.diff(&desired),
+            vec![
+                SubscriptionChange::Unsubscribe("CUSTOM a::b".to_string()),
+                subscribe(EventFormat::Plain, "CUSTOM"),
+            ]
+        );
+    }
+
+    #[test]
+    fn nixevent_on_all_excludes() {
+        let mut state = SubscriptionState::default();
+        state.add_events(EventFormat::Plain, "ALL");
+        state.remove_events("heartbeat CHANNEL_ANSWER");
+        assert!(state.is_all());
+        assert_eq!(state.excluded_events(), ["HEARTBEAT", "CHANNEL_ANSWER"]);
+        assert_ne!(
+            state,
+            SubscriptionState::new()
+                .format(EventFormat::Plain)
+                .events(&[EslEventType::All])
+        );
+
+        state.add_events(EventFormat::Plain, "HEARTBEAT");
+        assert_eq!(state.excluded_events(), ["CHANNEL_ANSWER"]);
+        state.add_events(EventFormat::Plain, "ALL");
+        assert!(state
+            .excluded_events()
+            .is_empty());
+        state.remove_events("CHANNEL_ANSWER");
+        state.clear_events();
+        assert!(!state.is_all());
+        assert!(state
+            .excluded_events()
+            .is_empty());
+    }
+
+    #[test]
+    fn diff_all_with_exclusions() {
+        let all = SubscriptionState::new()
+            .format(EventFormat::Plain)
+            .events(&[EslEventType::All]);
+        let all_but_heartbeat = all
+            .clone()
+            .without_events(&[EslEventType::Heartbeat]);
+        assert_eq!(
+            all.diff(&all_but_heartbeat),
+            vec![SubscriptionChange::Unsubscribe("HEARTBEAT".to_string())]
+        );
+        assert_eq!(
+            all_but_heartbeat.diff(&all),
+            vec![subscribe(EventFormat::Plain, "HEARTBEAT")]
+        );
+        assert!(all_but_heartbeat
+            .diff(&all_but_heartbeat)
+            .is_empty());
+        assert_eq!(
+            SubscriptionState::new().diff(&all_but_heartbeat),
+            vec![
+                subscribe(EventFormat::Plain, "ALL"),
+                SubscriptionChange::Unsubscribe("HEARTBEAT".to_string()),
+            ]
+        );
+        assert_eq!(
+            all_but_heartbeat.diff(
+                &all_but_heartbeat
+                    .clone()
+                    .format(EventFormat::Json)
+            ),
+            vec![
+                subscribe(EventFormat::Json, "ALL"),
+                SubscriptionChange::Unsubscribe("HEARTBEAT".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_format_change_resubscribes() {
+        let current = SubscriptionState::new()
+            .format(EventFormat::Plain)
+            .events(&[EslEventType::Heartbeat]);
+        let desired = SubscriptionState::new()
+            .format(EventFormat::Json)
+            .events(&[EslEventType::Heartbeat, EslEventType::ChannelAnswer]);
+        assert_eq!(
+            current.diff(&desired),
+            vec![subscribe(EventFormat::Json, "HEARTBEAT CHANNEL_ANSWER")]
+        );
+    }
+
+    #[test]
+    fn diff_all_and_clearing() {
+        let all = SubscriptionState::new()
+            .format(EventFormat::Plain)
+            .events(&[EslEventType::All])
+            .filter("a", "1")
+            .filter("b", "2");
+        let some = SubscriptionState::new().events(&[EslEventType::Heartbeat]);
+        assert_eq!(
+            all.diff(&some),
+            vec![
+                SubscriptionChange::NoEvents,
+                SubscriptionChange::DeleteAllFilters,
+                subscribe(EventFormat::Plain, "HEARTBEAT"),
+            ]
+        );
+        assert_eq!(
+            some.clone()
+                .format(EventFormat::Plain)
+                .diff(&all),
+            vec![
+                SubscriptionChange::AddFilter("a".to_string(), "1".to_string()),
+                SubscriptionChange::AddFilter("b".to_string(), "2".to_string()),
+                subscribe(EventFormat::Plain, "ALL"),
+            ]
+        );
+        assert_eq!(
+            all.diff(&SubscriptionState::new().filter("b", "2")),
+            vec![
+                SubscriptionChange::NoEvents,
+                SubscriptionChange::DeleteFilter("a".to_string(), "1".to_string()),
+            ]
+        );
+        assert!(SubscriptionState::new()
+            .diff(&SubscriptionState::new())
+            .is_empty());
+    }
+}
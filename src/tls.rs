@@ -0,0 +1,244 @@
+//! TLS transport (requires the `tls` feature).
+//!
+//! FreeSWITCH's event socket speaks plain TCP; TLS is usually terminated by
+//! stunnel or a similar proxy in front of it. Set
+//! [`EslConnectOptions::tls`](crate::EslConnectOptions::tls) to connect
+//! through such a proxy, and
+//! [`EslConnectOptions::tls_accept`](crate::EslConnectOptions::tls_accept)
+//! to accept outbound connections that arrive over TLS.
+//!
+//! ```rust,no_run
+//! use freeswitch_esl_tokio::tls::{TlsConnectOptions, TlsIdentity};
+//! use freeswitch_esl_tokio::{EslClient, EslConnectOptions};
+//!
+//! # async fn example() -> Result<(), freeswitch_esl_tokio::EslError> {
+//! let identity = TlsIdentity::from_pem(
+//!     &std::fs::read("client.pem")?,
+//!     &std::fs::read("client.key")?,
+//! )?;
+//! let tls = TlsConnectOptions::new(&std::fs::read("ca.pem")?, Some(identity))?
+//!     .server_name("esl.example.com");
+//!
+//! let options = EslConnectOptions {
+//!     tls: Some(tls),
+//!     ..Default::default()
+//! };
+//! let (client, _events) =
+//!     EslClient::connect_with_options("10.0.0.5", 8021, "ClueCon", options).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream as ClientTlsStream;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+use tokio_rustls::server::TlsStream as ServerTlsStream;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+use crate::error::{EslError, EslResult};
+
+/// The `rustls` version used by this crate, for building custom configs.
+pub use tokio_rustls::rustls;
+
+/// A certificate chain and its private key, from PEM.
+pub struct TlsIdentity {
+    certs: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+}
+
+impl TlsIdentity {
+    /// Parse a PEM certificate chain (leaf first) and a PEM private key
+    /// (PKCS#8, PKCS#1 or SEC1).
+    pub fn from_pem(cert_chain_pem: &[u8], key_pem: &[u8]) -> EslResult<Self> {
+        let certs = parse_certs(cert_chain_pem)?;
+        let key = PrivateKeyDer::from_pem_slice(key_pem)
+            .map_err(|e| EslError::tls(format!("invalid private key: {}", e)))?;
+        Ok(Self { certs, key })
+    }
+}
+
+impl Clone for TlsIdentity {
+    fn clone(&self) -> Self {
+        Self {
+            certs: self
+                .certs
+                .clone(),
+            key: self
+                .key
+                .clone_key(),
+        }
+    }
+}
+
+impl fmt::Debug for TlsIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsIdentity")
+            .field(
+                "certs",
+                &self
+                    .certs
+                    .len(),
+            )
+            .field("key", &"[REDACTED]")
+            .finish()
+    }
+}
+
+/// TLS settings for inbound connections ([`EslClient::connect_with_options`](crate::EslClient::connect_with_options)).
+#[derive(Clone)]
+pub struct TlsConnectOptions {
+    config: Arc<ClientConfig>,
+    server_name: Option<String>,
+}
+
+impl TlsConnectOptions {
+    /// Trust the CA certificates in `ca_pem`, optionally presenting a client
+    /// certificate.
+    pub fn new(ca_pem: &[u8], identity: Option<TlsIdentity>) -> EslResult<Self> {
+        let builder = ClientConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(EslError::from)?
+            .with_root_certificates(root_store(ca_pem)?);
+        let config = match identity {
+            Some(identity) => builder
+                .with_client_auth_cert(identity.certs, identity.key)
+                .map_err(EslError::from)?,
+            None => builder.with_no_client_auth(),
+        };
+        Ok(Self::from_config(Arc::new(config)))
+    }
+
+    /// Use a prebuilt rustls client config.
+    pub fn from_config(config: Arc<ClientConfig>) -> Self {
+        Self {
+            config,
+            server_name: None,
+        }
+    }
+
+    /// Name sent as SNI and checked against the server certificate.
+    /// Defaults to the host passed to `connect`.
+    pub fn server_name(mut self, name: impl Into<String>) -> Self {
+        self.server_name = Some(name.into());
+        self
+    }
+
+    pub(crate) async fn connect(
+        &self,
+        host: &str,
+        stream: TcpStream,
+    ) -> EslResult<ClientTlsStream<TcpStream>> {
+        let name = self
+            .server_name
+            .as_deref()
+            .unwrap_or(host);
+        let name = ServerName::try_from(name.to_string())
+            .map_err(|_| EslError::tls(format!("invalid server name: {}", name)))?;
+        TlsConnector::from(
+            self.config
+                .clone(),
+        )
+        .connect(name, stream)
+        .await
+        .map_err(EslError::Io)
+    }
+}
+
+impl fmt::Debug for TlsConnectOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConnectOptions")
+            .field("server_name", &self.server_name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// TLS settings for accepted outbound connections
+/// ([`EslClient::accept_outbound_with_options`](crate::EslClient::accept_outbound_with_options),
+/// [`OutboundServer`](crate::OutboundServer)).
+#[derive(Clone)]
+pub struct TlsAcceptOptions {
+    acceptor: TlsAcceptor,
+}
+
+impl TlsAcceptOptions {
+    /// Present `identity` to connecting peers. With `client_ca_pem`, peers
+    /// must present a certificate signed by one of those CAs.
+    pub fn new(identity: TlsIdentity, client_ca_pem: Option<&[u8]>) -> EslResult<Self> {
+        let builder = ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(EslError::from)?;
+        let builder = match client_ca_pem {
+            Some(ca_pem) => {
+                let verifier = WebPkiClientVerifier::builder_with_provider(
+                    Arc::new(root_store(ca_pem)?),
+                    provider(),
+                )
+                .build()
+                .map_err(|e| EslError::tls(e.to_string()))?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let config = builder
+            .with_single_cert(identity.certs, identity.key)
+            .map_err(EslError::from)?;
+        Ok(Self::from_config(Arc::new(config)))
+    }
+
+    /// Use a prebuilt rustls server config.
+    pub fn from_config(config: Arc<ServerConfig>) -> Self {
+        Self {
+            acceptor: TlsAcceptor::from(config),
+        }
+    }
+
+    pub(crate) async fn accept(&self, stream: TcpStream) -> EslResult<ServerTlsStream<TcpStream>> {
+        self.acceptor
+            .accept(stream)
+            .await
+            .map_err(EslError::Io)
+    }
+}
+
+impl fmt::Debug for TlsAcceptOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsAcceptOptions")
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<rustls::Error> for EslError {
+    fn from(e: rustls::Error) -> Self {
+        EslError::tls(e.to_string())
+    }
+}
+
+fn provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn parse_certs(pem: &[u8]) -> EslResult<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_slice_iter(pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| EslError::tls(format!("invalid certificate: {}", e)))?;
+    if certs.is_empty() {
+        return Err(EslError::tls("no certificate found in PEM data"));
+    }
+    Ok(certs)
+}
+
+fn root_store(ca_pem: &[u8]) -> EslResult<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for cert in parse_certs(ca_pem)? {
+        roots
+            .add(cert)
+            .map_err(EslError::from)?;
+    }
+    Ok(roots)
+}
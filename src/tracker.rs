@@ -0,0 +1,1016 @@
+//! Live table of active channels built from channel events.
+//!
+//! [`ChannelTracker`] takes over a connection's event stream, subscribes to
+//! the channel lifecycle events ([`ChannelTracker::EVENTS`]) and keeps one
+//! [`TrackedChannel`] per `Unique-ID`. Every event header is merged into the
+//! channel, so typed state ([`ChannelState`], [`CallState`], [`AnswerState`])
+//! always reflects the latest event.
+//!
+//! On start, channels that already exist are loaded with
+//! `show channels as json`, and each one is completed with `uuid_dump` (sent
+//! with `bgapi` so event processing never waits on it). With
+//! [`ChannelTracker::start_reconnecting`] the same bootstrap runs again after
+//! every reconnect, since events fired while disconnected are lost.
+//!
+//! The same events feed a [`CallGraph`] that groups channels into calls and
+//! follows their bridges, see [`ChannelTracker::call_for`].
+//!
+//! ```rust,no_run
+//! use freeswitch_esl_tokio::{ChannelChange, ChannelTracker, EslClient, TrackerOptions};
+//!
+//! # async fn example() -> Result<(), freeswitch_esl_tokio::EslError> {
+//! let (client, events) = EslClient::connect("localhost", 8021, "ClueCon").await?;
+//! let tracker = ChannelTracker::start(client, events, TrackerOptions::default()).await?;
+//!
+//! for channel in tracker.by_caller_number("1000") {
+//!     println!("{} {:?}", channel.uuid(), channel.call_state());
+//! }
+//!
+//! let mut changes = tracker.changes();
+//! while let Some(Ok(change)) = changes.recv().await {
+//!     if let ChannelChange::Removed(channel) = change {
+//!         println!("{} gone: {:?}", channel.uuid(), channel.hangup_cause());
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use percent_encoding::percent_decode_str;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::{
+    calls::{Call, CallGraph},
+    channel::{AnswerState, CallDirection, CallState, ChannelState, HangupCause},
+    command::EslResponse,
+    connection::{BgJob, EslClient, EslEventStream},
+    constants::{
+        HEADER_CALL_DIRECTION, HEADER_CHANNEL_CALL_STATE, HEADER_CHANNEL_STATE, HEADER_UNIQUE_ID,
+    },
+    error::{EslError, EslResult},
+    event::{EslEvent, EslEventType, EventFormat},
+    reconnect::{ReconnectingClient, ReconnectingEventStream, SupervisedEvent},
+};
+
+/// Mapping from `show channels as json` field names to ESL event header names,
+/// so bootstrap rows and live events share the same merge path.
+const DB_TO_EVENT: &[(&str, &str)] = &[
+    ("uuid", HEADER_UNIQUE_ID),
+    ("name", "Channel-Name"),
+    ("state", HEADER_CHANNEL_STATE),
+    ("callstate", HEADER_CHANNEL_CALL_STATE),
+    ("direction", HEADER_CALL_DIRECTION),
+    ("cid_name", "Caller-Caller-ID-Name"),
+    ("cid_num", "Caller-Caller-ID-Number"),
+    ("initial_cid_name", "Caller-Orig-Caller-ID-Name"),
+    ("initial_cid_num", "Caller-Orig-Caller-ID-Number"),
+    ("callee_name", "Caller-Callee-ID-Name"),
+    ("callee_num", "Caller-Callee-ID-Number"),
+    ("dest", "Caller-Destination-Number"),
+    ("call_uuid", "Channel-Call-UUID"),
+];
+
+/// Settings for [`ChannelTracker`].
+#[derive(Debug, Clone)]
+pub struct TrackerOptions {
+    /// Complete every new channel with a `bgapi uuid_dump`, which adds all
+    /// channel variables. Costs one background job per channel. Default: `true`.
+    pub uuid_dump: bool,
+    /// Changes buffered per [`ChannelChangeStream`] before a slow reader
+    /// starts skipping (at least 1). Default: 1024.
+    pub changes_capacity: usize,
+}
+
+impl Default for TrackerOptions {
+    fn default() -> Self {
+        Self {
+            uuid_dump: true,
+            changes_capacity: 1024,
+        }
+    }
+}
+
+/// Snapshot of one channel: every header seen in its events and `uuid_dump`.
+///
+/// Typed accessors parse on demand from the stored headers.
+#[derive(Debug, Clone)]
+pub struct TrackedChannel {
+    pub(crate) data: EslEvent,
+}
+
+impl TrackedChannel {
+    fn from_event(event: &EslEvent) -> Self {
+        let mut channel = Self {
+            data: EslEvent::new(),
+        };
+        channel.merge_event(event);
+        channel
+    }
+
+    /// Merge all event headers into the channel.
+    fn merge_event(&mut self, event: &EslEvent) {
+        for (key, value) in event.headers() {
+            self.data
+                .set_header(key, value);
+        }
+    }
+
+    /// Merge a `uuid_dump` body (`Key: Value` lines, percent-encoded values).
+    fn merge_dump(&mut self, body: &str) {
+        for line in body.lines() {
+            if let Some((key, value)) = line.split_once(": ") {
+                let decoded = percent_decode_str(value)
+                    .decode_utf8_lossy()
+                    .into_owned();
+                self.data
+                    .set_header(key, decoded);
+            }
+        }
+    }
+
+    /// Channel UUID (`Unique-ID`).
+    pub fn uuid(&self) -> &str {
+        self.data
+            .unique_id()
+            .unwrap_or_default()
+    }
+
+    /// `Channel-Call-UUID` header, shared by the legs of a bridged call.
+    pub fn call_uuid(&self) -> Option<&str> {
+        self.header("Channel-Call-UUID")
+    }
+
+    /// `Channel-Name` header (e.g. `sofia/internal/1000@domain`).
+    pub fn channel_name(&self) -> Option<&str> {
+        self.data
+            .channel_name()
+    }
+
+    /// `Caller-Caller-ID-Number` header.
+    pub fn caller_id_number(&self) -> Option<&str> {
+        self.data
+            .caller_id_number()
+    }
+
+    /// `Caller-Caller-ID-Name` header.
+    pub fn caller_id_name(&self) -> Option<&str> {
+        self.data
+            .caller_id_name()
+    }
+
+    /// `Caller-Destination-Number` header.
+    pub fn destination_number(&self) -> Option<&str> {
+        self.header("Caller-Destination-Number")
+    }
+
+    /// `Other-Leg-Unique-ID` header while the channel is bridged.
+    pub fn other_leg_uuid(&self) -> Option<&str> {
+        self.header("Other-Leg-Unique-ID")
+    }
+
+    /// Parse the `Channel-State` header into a [`ChannelState`].
+    pub fn channel_state(&self) -> Option<ChannelState> {
+        self.data
+            .channel_state()
+    }
+
+    /// Parse the `Channel-Call-State` header into a [`CallState`].
+    pub fn call_state(&self) -> Option<CallState> {
+        self.data
+            .call_state()
+    }
+
+    /// Parse the `Answer-State` header into an [`AnswerState`].
+    pub fn answer_state(&self) -> Option<AnswerState> {
+        self.data
+            .answer_state()
+    }
+
+    /// Parse the `Call-Direction` header into a [`CallDirection`].
+    pub fn call_direction(&self) -> Option<CallDirection> {
+        self.data
+            .call_direction()
+    }
+
+    /// Parse the `Hangup-Cause` header into a [`HangupCause`].
+    pub fn hangup_cause(&self) -> Option<HangupCause> {
+        self.data
+            .hangup_cause()
+    }
+
+    /// Look up a stored header by name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.data
+            .header(name)
+    }
+
+    /// Look up a channel variable (`variable_{name}` header).
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        self.data
+            .variable(name)
+    }
+
+    /// All stored headers.
+    pub fn headers(&self) -> impl ExactSizeIterator<Item = (&str, &str)> {
+        self.data
+            .headers()
+    }
+}
+
+/// Change delivered by [`ChannelChangeStream`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ChannelChange {
+    /// A channel appeared, from `CHANNEL_CREATE` or a bootstrap.
+    Created(TrackedChannel),
+    /// A tracked channel was updated.
+    Updated {
+        /// Event that caused the update; `None` for a bootstrap or
+        /// `uuid_dump` refresh.
+        event_type: Option<EslEventType>,
+        /// The channel after the update.
+        channel: TrackedChannel,
+    },
+    /// The channel is gone: `CHANNEL_DESTROY`, or no longer listed by
+    /// FreeSWITCH after a reconnect.
+    Removed(TrackedChannel),
+    /// The channel table was reloaded from `show channels`, at start or
+    /// after a reconnect.
+    Synchronized {
+        /// Number of channels tracked after the reload.
+        channels: usize,
+    },
+}
+
+/// Receiver of [`ChannelChange`]s, from [`ChannelTracker::changes`] (!Clone).
+///
+/// Each stream has its own buffer. A stream that falls behind skips changes
+/// and is told how many with [`EslError::Lagged`]; the tracker table itself
+/// stays complete.
+pub struct ChannelChangeStream {
+    rx: broadcast::Receiver<ChannelChange>,
+}
+
+impl std::fmt::Debug for ChannelChangeStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelChangeStream")
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChannelChangeStream {
+    /// Receive the next change, or `None` once the tracker has stopped.
+    pub async fn recv(&mut self) -> Option<Result<ChannelChange, EslError>> {
+        match self
+            .rx
+            .recv()
+            .await
+        {
+            Ok(change) => Some(Ok(change)),
+            Err(broadcast::error::RecvError::Lagged(count)) => {
+                Some(Err(EslError::Lagged { count }))
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    }
+}
+
+/// Connection the tracker sends its bootstrap commands on.
+#[derive(Clone)]
+enum Commands {
+    Client(EslClient),
+    Reconnecting(ReconnectingClient),
+}
+
+impl Commands {
+    async fn api(&self, command: &str) -> EslResult<EslResponse> {
+        match self {
+            Commands::Client(client) => {
+                client
+                    .api(command)
+                    .await
+            }
+            Commands::Reconnecting(client) => {
+                client
+                    .api(command)
+                    .await
+            }
+        }
+    }
+
+    async fn bgapi_job(&self, command: &str) -> EslResult<BgJob> {
+        match self {
+            Commands::Client(client) => {
+                client
+                    .bgapi_job(command)
+                    .await
+            }
+            Commands::Reconnecting(client) => {
+                client
+                    .bgapi_job(command)
+                    .await
+            }
+        }
+    }
+}
+
+/// Shared between all [`ChannelTracker`] clones and the tracking task.
+struct Inner {
+    channels: Mutex<HashMap<String, TrackedChannel>>,
+    /// Locked after `channels`
+    calls: Mutex<CallGraph>,
+    /// `None` once the tracking task has exited
+    changes: Mutex<Option<broadcast::Sender<ChannelChange>>>,
+    changes_capacity: usize,
+}
+
+impl Inner {
+    fn channels(&self) -> std::sync::MutexGuard<'_, HashMap<String, TrackedChannel>> {
+        self.channels
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn calls(&self) -> std::sync::MutexGuard<'_, CallGraph> {
+        self.calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn changes(&self) -> std::sync::MutexGuard<'_, Option<broadcast::Sender<ChannelChange>>> {
+        self.changes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Publish a change. Called with the channel table locked so streams see
+    /// changes in table order.
+    fn notify(&self, change: ChannelChange) {
+        if let Some(tx) = self
+            .changes()
+            .as_ref()
+        {
+            let _ = tx.send(change);
+        }
+    }
+
+    /// Apply a live event. Returns the UUID of a newly created channel.
+    fn apply_event(&self, event: &EslEvent) -> Option<String> {
+        let event_type = event.event_type()?;
+        if !ChannelTracker::EVENTS.contains(&event_type) {
+            return None;
+        }
+        let uuid = event.unique_id()?;
+        let mut channels = self.channels();
+        self.calls()
+            .apply(event);
+
+        match event_type {
+            EslEventType::ChannelCreate => {
+                if let Some(channel) = channels.get_mut(uuid) {
+                    channel.merge_event(event);
+                    self.notify(ChannelChange::Updated {
+                        event_type: Some(event_type),
+                        channel: channel.clone(),
+                    });
+                    return None;
+                }
+                let channel = TrackedChannel::from_event(event);
+                self.notify(ChannelChange::Created(channel.clone()));
+                channels.insert(uuid.to_string(), channel);
+                Some(uuid.to_string())
+            }
+            EslEventType::ChannelDestroy => {
+                let Some(mut channel) = channels.remove(uuid) else {
+                    debug!("CHANNEL_DESTROY for untracked channel {}", uuid);
+                    return None;
+                };
+                channel.merge_event(event);
+                self.notify(ChannelChange::Removed(channel));
+                None
+            }
+            _ => {
+                let channel = channels.get_mut(uuid)?;
+                channel.merge_event(event);
+                if event_type == EslEventType::ChannelUnbridge {
+                    channel
+                        .data
+                        .del_header("Other-Leg-Unique-ID");
+                }
+                self.notify(ChannelChange::Updated {
+                    event_type: Some(event_type),
+                    channel: channel.clone(),
+                });
+                None
+            }
+        }
+    }
+
+    /// Replace the table with a `show channels as json` listing. Channels no
+    /// longer listed are removed. Returns the listed UUIDs.
+    ///
+    /// A listing that does not parse leaves the table untouched.
+    fn synchronize(&self, body: &str) -> EslResult<Vec<String>> {
+        let rows = parse_channel_rows(body)?;
+        let listed: HashSet<&str> = rows
+            .iter()
+            .filter_map(|e| e.unique_id())
+            .collect();
+        let mut channels = self.channels();
+
+        let stale: Vec<String> = channels
+            .keys()
+            .filter(|uuid| !listed.contains(uuid.as_str()))
+            .cloned()
+            .collect();
+        for uuid in stale {
+            if let Some(channel) = channels.remove(&uuid) {
+                self.notify(ChannelChange::Removed(channel));
+            }
+            self.calls()
+                .end_leg(&uuid);
+        }
+
+        let mut uuids = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let Some(uuid) = row.unique_id() else {
+                continue;
+            };
+            self.calls()
+                .apply(row);
+            if let Some(channel) = channels.get_mut(uuid) {
+                channel.merge_event(row);
+                self.notify(ChannelChange::Updated {
+                    event_type: None,
+                    channel: channel.clone(),
+                });
+            } else {
+                let channel = TrackedChannel::from_event(row);
+                self.notify(ChannelChange::Created(channel.clone()));
+                channels.insert(uuid.to_string(), channel);
+            }
+            uuids.push(uuid.to_string());
+        }
+        self.notify(ChannelChange::Synchronized {
+            channels: channels.len(),
+        });
+        info!("Channel tracker synchronized {} channels", channels.len());
+        Ok(uuids)
+    }
+
+    fn apply_dump(&self, uuid: &str, body: &str) {
+        if body.starts_with("-ERR") {
+            debug!("uuid_dump {} failed: {}", uuid, body.trim_end());
+            return;
+        }
+        let mut channels = self.channels();
+        if let Some(channel) = channels.get_mut(uuid) {
+            channel.merge_dump(body);
+            // The dump carries `originator` and loopback variables missing from `show channels`
+            self.calls()
+                .apply(&channel.data);
+            self.notify(ChannelChange::Updated {
+                event_type: None,
+                channel: channel.clone(),
+            });
+        }
+    }
+}
+
+/// Parse `show channels as json` into one fake `CHANNEL_CREATE` per row.
+fn parse_channel_rows(body: &str) -> EslResult<Vec<EslEvent>> {
+    let json: serde_json::Value = serde_json::from_str(body)?;
+    let rows = match json.get("rows") {
+        Some(rows) => rows
+            .as_array()
+            .ok_or_else(|| EslError::protocol_error("show channels: rows is not an array"))?,
+        // `{"row_count":0}` has no rows array
+        None if json
+            .get("row_count")
+            .and_then(|v| v.as_u64())
+            == Some(0) =>
+        {
+            return Ok(Vec::new())
+        }
+        None => return Err(EslError::protocol_error("show channels: no rows")),
+    };
+    Ok(rows
+        .iter()
+        .filter_map(fake_channel_create)
+        .collect())
+}
+
+/// Build a fake `CHANNEL_CREATE` event from a `show channels as json` row.
+fn fake_channel_create(row: &serde_json::Value) -> Option<EslEvent> {
+    row.get("uuid")?
+        .as_str()?;
+
+    let mut event = EslEvent::with_type(EslEventType::ChannelCreate);
+    for (json_key, header_name) in DB_TO_EVENT {
+        if let Some(val) = row
+            .get(json_key)
+            .and_then(|v| v.as_str())
+        {
+            if !val.is_empty() {
+                event.set_header(*header_name, val);
+            }
+        }
+    }
+    Some(event)
+}
+
+/// Table of active channels kept up to date from channel events (Clone + Send).
+///
+/// Created with [`start`](Self::start) or
+/// [`start_reconnecting`](Self::start_reconnecting), which take over the
+/// connection's event stream. Other consumers on the same connection can use
+/// [`EslClient::subscribe_stream`]. Queries return snapshots; the table keeps
+/// the last state after the connection closes.
+#[derive(Clone)]
+pub struct ChannelTracker {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for ChannelTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelTracker")
+            .field("channels", &self.len())
+            .finish()
+    }
+}
+
+impl ChannelTracker {
+    /// Event types the tracker subscribes to and applies.
+    pub const EVENTS: &'static [EslEventType] = &[
+        EslEventType::ChannelCreate,
+        EslEventType::ChannelDestroy,
+        EslEventType::ChannelState,
+        EslEventType::ChannelCallstate,
+        EslEventType::ChannelAnswer,
+        EslEventType::ChannelHangup,
+        EslEventType::ChannelHangupComplete,
+        EslEventType::ChannelExecute,
+        EslEventType::ChannelExecuteComplete,
+        EslEventType::ChannelHold,
+        EslEventType::ChannelUnhold,
+        EslEventType::ChannelBridge,
+        EslEventType::ChannelUnbridge,
+        EslEventType::ChannelProgress,
+        EslEventType::ChannelProgressMedia,
+        EslEventType::ChannelOutgoing,
+        EslEventType::ChannelPark,
+        EslEventType::ChannelUnpark,
+        EslEventType::ChannelApplication,
+        EslEventType::ChannelOriginate,
+        EslEventType::ChannelUuid,
+        EslEventType::CallSecure,
+        EslEventType::CallUpdate,
+    ];
+
+    /// Subscribe to [`EVENTS`](Self::EVENTS), load the existing channels and
+    /// track them until `events` ends.
+    ///
+    /// Subscribing happens before the listing, so channels created meanwhile
+    /// are not missed.
+    pub async fn start(
+        client: EslClient,
+        events: EslEventStream,
+        options: TrackerOptions,
+    ) -> EslResult<Self> {
+        client
+            .subscribe_events(EventFormat::Plain, Self::EVENTS)
+            .await?;
+        let (tracker, task) = Self::bootstrap(Commands::Client(client), options).await?;
+        tokio::spawn(async move {
+            let mut events = events;
+            while let Some(result) = events
+                .recv()
+                .await
+            {
+                match result {
+                    Ok(event) => task.handle_event(&event),
+                    Err(e) => debug!("Channel tracker event error: {}", e),
+                }
+            }
+            task.stop();
+        });
+        Ok(tracker)
+    }
+
+    /// Like [`start`](Self::start), on a connection that survives reconnects.
+    ///
+    /// The subscription is replayed by the [`ReconnectingClient`]; after every
+    /// [`SupervisedEvent::Reconnected`] the channel list is loaded again and
+    /// channels that ended while disconnected are removed.
+    pub async fn start_reconnecting(
+        client: ReconnectingClient,
+        events: ReconnectingEventStream,
+        options: TrackerOptions,
+    ) -> EslResult<Self> {
+        client
+            .subscribe_events(EventFormat::Plain, Self::EVENTS)
+            .await?;
+        let (tracker, task) = Self::bootstrap(Commands::Reconnecting(client), options).await?;
+        tokio::spawn(async move {
+            let mut events = events;
+            while let Some(item) = events
+                .recv()
+                .await
+            {
+                match item {
+                    SupervisedEvent::Event(event) => task.handle_event(&event),
+                    SupervisedEvent::Reconnected { .. } => {
+                        if let Err(e) = task
+                            .synchronize()
+                            .await
+                        {
+                            warn!("Channel tracker resync failed: {}", e);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            task.stop();
+        });
+        Ok(tracker)
+    }
+
+    async fn bootstrap(
+        commands: Commands,
+        options: TrackerOptions,
+    ) -> EslResult<(Self, TrackerTask)> {
+        let (tx, _) = broadcast::channel(
+            options
+                .changes_capacity
+                .max(1),
+        );
+        let inner = Arc::new(Inner {
+            channels: Mutex::new(HashMap::new()),
+            calls: Mutex::new(CallGraph::new()),
+            changes: Mutex::new(Some(tx)),
+            changes_capacity: options.changes_capacity,
+        });
+        let task = TrackerTask {
+            inner: inner.clone(),
+            commands,
+            uuid_dump: options.uuid_dump,
+        };
+        task.synchronize()
+            .await?;
+        Ok((Self { inner }, task))
+    }
+
+    /// Receive every change from now on.
+    ///
+    /// Ends once the tracker stops (its event stream ended). To start from a
+    /// consistent view, open the stream before reading
+    /// [`channels`](Self::channels).
+    pub fn changes(&self) -> ChannelChangeStream {
+        let rx = match self
+            .inner
+            .changes()
+            .as_ref()
+        {
+            Some(tx) => tx.subscribe(),
+            // Sender dropped at once: the stream ends immediately
+            None => {
+                broadcast::channel(
+                    self.inner
+                        .changes_capacity
+                        .max(1),
+                )
+                .1
+            }
+        };
+        ChannelChangeStream { rx }
+    }
+
+    /// Channel with the given `Unique-ID`.
+    pub fn get(&self, uuid: &str) -> Option<TrackedChannel> {
+        self.inner
+            .channels()
+            .get(uuid)
+            .cloned()
+    }
+
+    /// Channels whose `Channel-Call-UUID` is `call_uuid` (both legs of a call).
+    pub fn by_call_uuid(&self, call_uuid: &str) -> Vec<TrackedChannel> {
+        self.find(|channel| channel.call_uuid() == Some(call_uuid))
+    }
+
+    /// Channels whose `Caller-Caller-ID-Number` is `number`.
+    pub fn by_caller_number(&self, number: &str) -> Vec<TrackedChannel> {
+        self.find(|channel| channel.caller_id_number() == Some(number))
+    }
+
+    /// Channels matching a predicate.
+    pub fn find(&self, mut predicate: impl FnMut(&TrackedChannel) -> bool) -> Vec<TrackedChannel> {
+        self.inner
+            .channels()
+            .values()
+            .filter(|channel| predicate(channel))
+            .cloned()
+            .collect()
+    }
+
+    /// Call holding the channel `uuid`, with its legs and bridge history.
+    ///
+    /// Unlike [`by_call_uuid`](Self::by_call_uuid), this follows
+    /// `originator`, loopback pairs and bridges made after the channels
+    /// were created (`uuid_bridge`, `att_xfer`), see [`CallGraph`].
+    pub fn call_for(&self, uuid: &str) -> Option<Call> {
+        self.inner
+            .calls()
+            .call_for_leg(uuid)
+            .cloned()
+    }
+
+    /// All calls, in no particular order.
+    pub fn calls(&self) -> Vec<Call> {
+        self.inner
+            .calls()
+            .calls()
+            .cloned()
+            .collect()
+    }
+
+    /// All tracked channels, in no particular order.
+    pub fn channels(&self) -> Vec<TrackedChannel> {
+        self.find(|_| true)
+    }
+
+    /// Number of tracked channels.
+    pub fn len(&self) -> usize {
+        self.inner
+            .channels()
+            .len()
+    }
+
+    /// `true` when no channel is tracked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `true` until the event stream the tracker consumes has ended.
+    pub fn is_running(&self) -> bool {
+        self.inner
+            .changes()
+            .is_some()
+    }
+}
+
+/// The tracking task's side: applies events and runs bootstrap commands.
+struct TrackerTask {
+    inner: Arc<Inner>,
+    commands: Commands,
+    uuid_dump: bool,
+}
+
+impl TrackerTask {
+    fn handle_event(&self, event: &EslEvent) {
+        if let Some(uuid) = self
+            .inner
+            .apply_event(event)
+        {
+            self.request_dump(uuid);
+        }
+    }
+
+    /// Load `show channels as json` into the table.
+    async fn synchronize(&self) -> EslResult<()> {
+        let response = self
+            .commands
+            .api("show channels as json")
+            .await?;
+        let body = response.body_string();
+        if body.starts_with("-ERR") {
+            return Err(EslError::CommandFailed {
+                reply_text: body
+                    .trim_end()
+                    .to_string(),
+            });
+        }
+        for uuid in self
+            .inner
+            .synchronize(&body)?
+        {
+            self.request_dump(uuid);
+        }
+        Ok(())
+    }
+
+    /// Fetch `uuid_dump` in the background and merge it when it arrives.
+    fn request_dump(&self, uuid: String) {
+        if !self.uuid_dump {
+            return;
+        }
+        let inner = self
+            .inner
+            .clone();
+        let commands = self
+            .commands
+            .clone();
+        tokio::spawn(async move {
+            let result = match commands
+                .bgapi_job(&format!("uuid_dump {}", uuid))
+                .await
+            {
+                Ok(job) => job.await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(body) => inner.apply_dump(&uuid, &body),
+                Err(e) => debug!("uuid_dump {} failed: {}", uuid, e),
+            }
+        });
+    }
+
+    /// Close every change stream.
+    fn stop(&self) {
+        self.inner
+            .changes()
+            .take();
+        debug!("Channel tracker stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_inner() -> Inner {
+        let (tx, _) = broadcast::channel(16);
+        Inner {
+            channels: Mutex::new(HashMap::new()),
+            calls: Mutex::new(CallGraph::new()),
+            changes: Mutex::new(Some(tx)),
+            changes_capacity: 16,
+        }
+    }
+
+    #[test]
+    fn test_synchronize_maps_show_channels_rows() {
+        let inner = test_inner();
+        let body = r#"{"row_count":2,"rows":[
+            {"uuid":"a","name":"sofia/internal/1000@pbx","state":"CS_EXECUTE","callstate":"ACTIVE",
+             "direction":"inbound","cid_num":"1000","dest":"2000","call_uuid":"a"},
+            {"uuid":"b","name":"sofia/internal/2000@pbx","state":"CS_EXCHANGE_MEDIA",
+             "callstate":"RINGING","direction":"outbound","cid_num":"","call_uuid":"a"}
+        ]}"#;
+        let mut uuids = inner
+            .synchronize(body)
+            .unwrap();
+        uuids.sort();
+        assert_eq!(uuids, ["a", "b"]);
+
+        let channels = inner.channels();
+        let a = &channels["a"];
+        assert_eq!(a.channel_state(), Some(ChannelState::CsExecute));
+        assert_eq!(a.call_state(), Some(CallState::Active));
+        assert_eq!(a.call_direction(), Some(CallDirection::Inbound));
+        assert_eq!(a.caller_id_number(), Some("1000"));
+        assert_eq!(a.destination_number(), Some("2000"));
+        let b = &channels["b"];
+        assert_eq!(b.call_uuid(), Some("a"));
+        assert_eq!(b.caller_id_number(), None);
+    }
+
+    #[test]
+    fn test_synchronize_removes_unlisted_channels() {
+        let inner = test_inner();
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "gone")],
+        ));
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "kept")],
+        ));
+        let mut rx = inner
+            .changes()
+            .as_ref()
+            .unwrap()
+            .subscribe();
+
+        inner
+            .synchronize(r#"{"row_count":1,"rows":[{"uuid":"kept"}]}"#)
+            .unwrap();
+        assert!(matches!(rx.try_recv(), Ok(ChannelChange::Removed(c)) if c.uuid() == "gone"));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ChannelChange::Updated { event_type: None, channel }) if channel.uuid() == "kept"
+        ));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ChannelChange::Synchronized { channels: 1 })
+        ));
+
+        inner
+            .synchronize(r#"{"row_count":0}"#)
+            .unwrap();
+        assert!(inner
+            .channels()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_synchronize_rejects_invalid_listing() {
+        let inner = test_inner();
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "a")],
+        ));
+
+        for body in [
+            "-ERR no reply\n",
+            r#"{"row_count":1}"#,
+            r#"{"rows":"oops"}"#,
+        ] {
+            assert!(inner
+                .synchronize(body)
+                .is_err());
+        }
+        assert!(inner
+            .channels()
+            .contains_key("a"));
+    }
+
+    #[test]
+    fn test_apply_event_lifecycle() {
+        let inner = test_inner();
+        assert_eq!(
+            inner.apply_event(&EslEvent::test_event(
+                EslEventType::ChannelCreate,
+                &[("Unique-ID", "a"), ("Channel-State", "CS_INIT")]
+            )),
+            Some("a".to_string())
+        );
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::ChannelAnswer,
+            &[
+                ("Unique-ID", "a"),
+                ("Answer-State", "answered"),
+                ("Channel-Call-State", "ACTIVE"),
+            ],
+        ));
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::ChannelBridge,
+            &[("Unique-ID", "a"), ("Other-Leg-Unique-ID", "b")],
+        ));
+        {
+            let channels = inner.channels();
+            assert_eq!(channels["a"].answer_state(), Some(AnswerState::Answered));
+            assert_eq!(channels["a"].other_leg_uuid(), Some("b"));
+        }
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::ChannelUnbridge,
+            &[("Unique-ID", "a"), ("Other-Leg-Unique-ID", "b")],
+        ));
+        assert_eq!(inner.channels()["a"].other_leg_uuid(), None);
+
+        // Events for unknown channels and non-channel events are ignored
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::ChannelAnswer,
+            &[("Unique-ID", "x")],
+        ));
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::Custom,
+            &[("Unique-ID", "a"), ("X", "1")],
+        ));
+        assert_eq!(inner.channels()["a"].header("X"), None);
+        assert_eq!(
+            inner
+                .channels()
+                .len(),
+            1
+        );
+
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::ChannelDestroy,
+            &[("Unique-ID", "a"), ("Hangup-Cause", "NORMAL_CLEARING")],
+        ));
+        assert!(inner
+            .channels()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_apply_dump_decodes_variables() {
+        let inner = test_inner();
+        inner.apply_event(&EslEvent::test_event(
+            EslEventType::ChannelCreate,
+            &[("Unique-ID", "a")],
+        ));
+        inner.apply_dump(
+            "a",
+            "Unique-ID: a\nvariable_sip_call_id: abc%40host\nvariable_empty: \n",
+        );
+        inner.apply_dump("a", "-ERR No such channel!\n");
+        let channels = inner.channels();
+        assert_eq!(channels["a"].variable("sip_call_id"), Some("abc@host"));
+    }
+}
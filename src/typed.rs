@@ -0,0 +1,1016 @@
+//! Strongly typed views of the core channel and system events, and of the
+//! common `CUSTOM` subclasses ([`EventSubclass`]).
+//!
+//! Each struct is built from an [`EslEvent`] with `TryFrom<&EslEvent>`,
+//! parsing the headers it needs. [`TypedEvent`] wraps them in an enum so a
+//! consumer can `match` instead of comparing header strings:
+//!
+//! ```
+//! use freeswitch_esl_tokio::typed::TypedEvent;
+//! # use freeswitch_esl_tokio::{EslEvent, EslEventType};
+//! # let mut event = EslEvent::with_type(EslEventType::Dtmf);
+//! # event.set_header("Unique-ID", "abc");
+//! # event.set_header("DTMF-Digit", "5");
+//! match TypedEvent::try_from(&event)? {
+//!     TypedEvent::Dtmf(dtmf) => println!("{} pressed {}", dtmf.channel.unique_id, dtmf.digit),
+//!     TypedEvent::ChannelHangupComplete(hangup) => println!("{:?}", hangup.hangup_cause),
+//!     _ => {}
+//! }
+//! # Ok::<(), freeswitch_esl_tokio::typed::TypedEventError>(())
+//! ```
+//!
+//! Optional headers stay `Option`; a header that is present but unparseable
+//! is treated as absent, matching the [`EslEvent`] accessors. Only the
+//! headers an event cannot be identified without (`Unique-ID`, `Job-UUID`,
+//! `DTMF-Digit`, and the profile, gateway, conference, queue or lot name of
+//! `CUSTOM` events) are required.
+
+use std::str::FromStr;
+
+use crate::{
+    channel::{AnswerState, CallDirection, CallState, ChannelState, ChannelTimetable, HangupCause},
+    event::{EslEvent, EslEventType, EventSubclass},
+};
+
+/// Errors converting an [`EslEvent`] into a typed event.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum TypedEventError {
+    /// The event is not of the type the struct represents.
+    #[error("expected {expected} event, got {actual:?}")]
+    WrongEventType {
+        /// Event type the conversion expected.
+        expected: EslEventType,
+        /// Event type of the event (None if unknown).
+        actual: Option<EslEventType>,
+    },
+    /// The `CUSTOM` event is not of the subclass the struct represents.
+    #[error("expected {expected} event, got subclass {actual:?}")]
+    WrongSubclass {
+        /// Subclass the conversion expected.
+        expected: EventSubclass,
+        /// `Event-Subclass` of the event, if any.
+        actual: Option<String>,
+    },
+    /// A required header is missing.
+    #[error("missing required header {0}")]
+    MissingHeader(&'static str),
+    /// A required header is present but cannot be parsed.
+    #[error("invalid value for header {header}: {value:?}")]
+    InvalidHeader {
+        /// Header name.
+        header: &'static str,
+        /// Raw header value.
+        value: String,
+    },
+}
+
+fn expect_type(event: &EslEvent, expected: EslEventType) -> Result<(), TypedEventError> {
+    if event.is_event_type(expected) {
+        Ok(())
+    } else {
+        Err(TypedEventError::WrongEventType {
+            expected,
+            actual: event.event_type(),
+        })
+    }
+}
+
+fn expect_subclass(event: &EslEvent, expected: EventSubclass) -> Result<(), TypedEventError> {
+    expect_type(event, EslEventType::Custom)?;
+    if event.is_subclass(expected) {
+        Ok(())
+    } else {
+        Err(TypedEventError::WrongSubclass {
+            expected,
+            actual: event
+                .event_subclass()
+                .map(str::to_string),
+        })
+    }
+}
+
+fn required(event: &EslEvent, header: &'static str) -> Result<String, TypedEventError> {
+    event
+        .header(header)
+        .map(|s| s.to_string())
+        .ok_or(TypedEventError::MissingHeader(header))
+}
+
+fn optional(event: &EslEvent, header: &str) -> Option<String> {
+    event
+        .header(header)
+        .map(|s| s.to_string())
+}
+
+fn parsed<T: FromStr>(event: &EslEvent, header: &str) -> Option<T> {
+    event
+        .header(header)?
+        .parse()
+        .ok()
+}
+
+/// Headers common to every channel event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChannelData {
+    /// `Unique-ID` (falls back to `Caller-Unique-ID`).
+    pub unique_id: String,
+    /// `Channel-Name`, e.g. `sofia/internal/1000@example.com`.
+    pub channel_name: Option<String>,
+    /// `Channel-State`.
+    pub channel_state: Option<ChannelState>,
+    /// `Channel-Call-State`.
+    pub call_state: Option<CallState>,
+    /// `Answer-State`.
+    pub answer_state: Option<AnswerState>,
+    /// `Call-Direction`.
+    pub call_direction: Option<CallDirection>,
+    /// `Caller-Caller-ID-Number`.
+    pub caller_id_number: Option<String>,
+    /// `Caller-Caller-ID-Name`.
+    pub caller_id_name: Option<String>,
+    /// `Caller-Destination-Number`.
+    pub destination_number: Option<String>,
+    /// `Caller-*-Time` timestamps.
+    pub timetable: Option<ChannelTimetable>,
+    /// `Event-Date-Timestamp`, epoch microseconds.
+    pub event_timestamp: Option<i64>,
+}
+
+impl ChannelData {
+    /// Extract the common channel headers, regardless of event type.
+    pub fn from_event(event: &EslEvent) -> Result<Self, TypedEventError> {
+        Ok(Self {
+            unique_id: event
+                .unique_id()
+                .ok_or(TypedEventError::MissingHeader("Unique-ID"))?
+                .to_string(),
+            channel_name: optional(event, "Channel-Name"),
+            channel_state: event.channel_state(),
+            call_state: event.call_state(),
+            answer_state: event.answer_state(),
+            call_direction: event.call_direction(),
+            caller_id_number: optional(event, "Caller-Caller-ID-Number"),
+            caller_id_name: optional(event, "Caller-Caller-ID-Name"),
+            destination_number: optional(event, "Caller-Destination-Number"),
+            timetable: event.caller_timetable(),
+            event_timestamp: parsed(event, "Event-Date-Timestamp"),
+        })
+    }
+}
+
+/// `CHANNEL_CREATE`: a new channel exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChannelCreate {
+    /// Channel headers.
+    pub channel: ChannelData,
+}
+
+impl TryFrom<&EslEvent> for ChannelCreate {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_type(event, EslEventType::ChannelCreate)?;
+        Ok(Self {
+            channel: ChannelData::from_event(event)?,
+        })
+    }
+}
+
+/// `CHANNEL_ANSWER`: the channel was answered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChannelAnswer {
+    /// Channel headers.
+    pub channel: ChannelData,
+}
+
+impl TryFrom<&EslEvent> for ChannelAnswer {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_type(event, EslEventType::ChannelAnswer)?;
+        Ok(Self {
+            channel: ChannelData::from_event(event)?,
+        })
+    }
+}
+
+/// `CHANNEL_HANGUP_COMPLETE`: the channel hung up and its variables are final.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChannelHangupComplete {
+    /// Channel headers.
+    pub channel: ChannelData,
+    /// `Hangup-Cause`.
+    pub hangup_cause: Option<HangupCause>,
+    /// `variable_duration`: seconds from creation to hangup.
+    pub duration: Option<u64>,
+    /// `variable_billsec`: seconds from answer to hangup.
+    pub billsec: Option<u64>,
+}
+
+impl TryFrom<&EslEvent> for ChannelHangupComplete {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_type(event, EslEventType::ChannelHangupComplete)?;
+        Ok(Self {
+            channel: ChannelData::from_event(event)?,
+            hangup_cause: event.hangup_cause(),
+            duration: parsed(event, "variable_duration"),
+            billsec: parsed(event, "variable_billsec"),
+        })
+    }
+}
+
+/// `CHANNEL_BRIDGE`: two channels were bridged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChannelBridge {
+    /// Channel headers (the A leg).
+    pub channel: ChannelData,
+    /// `Other-Leg-Unique-ID` (falls back to `Bridge-B-Unique-ID`).
+    pub other_leg_unique_id: Option<String>,
+    /// `Other-Leg-Channel-Name`.
+    pub other_leg_channel_name: Option<String>,
+    /// `Other-Leg-*-Time` timestamps.
+    pub other_leg_timetable: Option<ChannelTimetable>,
+}
+
+impl TryFrom<&EslEvent> for ChannelBridge {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_type(event, EslEventType::ChannelBridge)?;
+        Ok(Self {
+            channel: ChannelData::from_event(event)?,
+            other_leg_unique_id: optional(event, "Other-Leg-Unique-ID")
+                .or_else(|| optional(event, "Bridge-B-Unique-ID")),
+            other_leg_channel_name: optional(event, "Other-Leg-Channel-Name"),
+            other_leg_timetable: event.other_leg_timetable(),
+        })
+    }
+}
+
+/// `DTMF`: a digit was received on the channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Dtmf {
+    /// Channel headers.
+    pub channel: ChannelData,
+    /// `DTMF-Digit` (`0`-`9`, `*`, `#`, `A`-`D`).
+    pub digit: char,
+    /// `DTMF-Duration` in samples.
+    pub duration: Option<u32>,
+    /// `DTMF-Source`, e.g. `RTP` or `INBAND_AUDIO`.
+    pub source: Option<String>,
+}
+
+impl TryFrom<&EslEvent> for Dtmf {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_type(event, EslEventType::Dtmf)?;
+        let raw = required(event, "DTMF-Digit")?;
+        let mut chars = raw.chars();
+        let digit = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            _ => {
+                return Err(TypedEventError::InvalidHeader {
+                    header: "DTMF-Digit",
+                    value: raw,
+                })
+            }
+        };
+        Ok(Self {
+            channel: ChannelData::from_event(event)?,
+            digit,
+            duration: parsed(event, "DTMF-Duration"),
+            source: optional(event, "DTMF-Source"),
+        })
+    }
+}
+
+/// `BACKGROUND_JOB`: the result of a `bgapi` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BackgroundJob {
+    /// `Job-UUID`, as returned by `bgapi`.
+    pub job_uuid: String,
+    /// `Job-Command`, e.g. `originate`.
+    pub job_command: Option<String>,
+    /// `Job-Command-Arg`.
+    pub job_command_arg: Option<String>,
+    /// Command output (`+OK ...` or `-ERR ...`).
+    pub body: String,
+}
+
+impl BackgroundJob {
+    /// `true` if the body starts with `-ERR`.
+    pub fn is_error(&self) -> bool {
+        self.body
+            .starts_with("-ERR")
+    }
+}
+
+impl TryFrom<&EslEvent> for BackgroundJob {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_type(event, EslEventType::BackgroundJob)?;
+        Ok(Self {
+            job_uuid: required(event, "Job-UUID")?,
+            job_command: optional(event, "Job-Command"),
+            job_command_arg: optional(event, "Job-Command-Arg"),
+            body: event
+                .body()
+                .unwrap_or("")
+                .to_string(),
+        })
+    }
+}
+
+/// `HEARTBEAT`: periodic system status.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Heartbeat {
+    /// `Core-UUID`.
+    pub core_uuid: Option<String>,
+    /// `FreeSWITCH-Hostname`.
+    pub hostname: Option<String>,
+    /// `Up-Time`, human readable.
+    pub up_time: Option<String>,
+    /// `Uptime-msec`.
+    pub uptime_msec: Option<u64>,
+    /// `Session-Count`: active sessions.
+    pub session_count: Option<u64>,
+    /// `Max-Sessions`.
+    pub max_sessions: Option<u64>,
+    /// `Session-Per-Sec`: configured session rate limit.
+    pub session_per_sec: Option<u64>,
+    /// `Idle-CPU` percentage.
+    pub idle_cpu: Option<f64>,
+    /// `Event-Date-Timestamp`, epoch microseconds.
+    pub event_timestamp: Option<i64>,
+}
+
+impl TryFrom<&EslEvent> for Heartbeat {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_type(event, EslEventType::Heartbeat)?;
+        Ok(Self {
+            core_uuid: optional(event, "Core-UUID"),
+            hostname: optional(event, "FreeSWITCH-Hostname"),
+            up_time: optional(event, "Up-Time"),
+            uptime_msec: parsed(event, "Uptime-msec"),
+            session_count: parsed(event, "Session-Count"),
+            max_sessions: parsed(event, "Max-Sessions"),
+            session_per_sec: parsed(event, "Session-Per-Sec"),
+            idle_cpu: parsed(event, "Idle-CPU"),
+            event_timestamp: parsed(event, "Event-Date-Timestamp"),
+        })
+    }
+}
+
+/// `CUSTOM sofia::register`: a SIP endpoint registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SofiaRegister {
+    /// `profile-name`, the Sofia profile that took the registration.
+    pub profile_name: String,
+    /// `from-user`.
+    pub from_user: Option<String>,
+    /// `from-host`.
+    pub from_host: Option<String>,
+    /// `contact`: the registered Contact URI.
+    pub contact: Option<String>,
+    /// `call-id` of the REGISTER.
+    pub call_id: Option<String>,
+    /// `expires`, seconds.
+    pub expires: Option<u32>,
+    /// `user-agent`.
+    pub user_agent: Option<String>,
+    /// `network-ip` the REGISTER came from.
+    pub network_ip: Option<String>,
+    /// `network-port` the REGISTER came from.
+    pub network_port: Option<u16>,
+    /// `realm` used for authentication.
+    pub realm: Option<String>,
+}
+
+impl TryFrom<&EslEvent> for SofiaRegister {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_subclass(event, EventSubclass::SofiaRegister)?;
+        Ok(Self {
+            profile_name: required(event, "profile-name")?,
+            from_user: optional(event, "from-user"),
+            from_host: optional(event, "from-host"),
+            contact: optional(event, "contact"),
+            call_id: optional(event, "call-id"),
+            expires: parsed(event, "expires"),
+            user_agent: optional(event, "user-agent"),
+            network_ip: optional(event, "network-ip"),
+            network_port: parsed(event, "network-port"),
+            realm: optional(event, "realm"),
+        })
+    }
+}
+
+/// `CUSTOM sofia::unregister`: a registration was removed or expired.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SofiaUnregister {
+    /// `profile-name`.
+    pub profile_name: String,
+    /// `from-user`.
+    pub from_user: Option<String>,
+    /// `from-host`.
+    pub from_host: Option<String>,
+    /// `contact`.
+    pub contact: Option<String>,
+    /// `call-id` of the registration.
+    pub call_id: Option<String>,
+}
+
+impl TryFrom<&EslEvent> for SofiaUnregister {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_subclass(event, EventSubclass::SofiaUnregister)?;
+        Ok(Self {
+            profile_name: required(event, "profile-name")?,
+            from_user: optional(event, "from-user"),
+            from_host: optional(event, "from-host"),
+            contact: optional(event, "contact"),
+            call_id: optional(event, "call-id"),
+        })
+    }
+}
+
+/// `CUSTOM sofia::gateway_state`: a gateway's registration state changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SofiaGatewayState {
+    /// `Gateway` name.
+    pub gateway: String,
+    /// `State`, e.g. `REGED`, `TRYING`, `FAIL_WAIT`, `NOREG`.
+    pub state: Option<String>,
+    /// `Ping-Status`: `UP`, `DOWN` or `INVALID`.
+    pub ping_status: Option<String>,
+    /// `Status`: SIP response code of the last REGISTER, when failed.
+    pub status: Option<u16>,
+    /// `Phrase`: SIP reason phrase of the last REGISTER, when failed.
+    pub phrase: Option<String>,
+}
+
+impl TryFrom<&EslEvent> for SofiaGatewayState {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_subclass(event, EventSubclass::SofiaGatewayState)?;
+        Ok(Self {
+            gateway: required(event, "Gateway")?,
+            state: optional(event, "State"),
+            ping_status: optional(event, "Ping-Status"),
+            status: parsed(event, "Status"),
+            phrase: optional(event, "Phrase"),
+        })
+    }
+}
+
+/// `CUSTOM conference::maintenance`: conference and member activity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ConferenceMaintenance {
+    /// `Conference-Name`.
+    pub conference_name: String,
+    /// `Conference-Unique-ID`.
+    pub conference_uuid: Option<String>,
+    /// `Conference-Size`: current member count.
+    pub conference_size: Option<u32>,
+    /// `Action`, e.g. `add-member`, `del-member`, `start-talking`, `mute-member`.
+    pub action: Option<String>,
+    /// `Member-ID`, for member actions.
+    pub member_id: Option<u32>,
+    /// `Unique-ID` of the member's channel, for member actions.
+    pub unique_id: Option<String>,
+}
+
+impl TryFrom<&EslEvent> for ConferenceMaintenance {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_subclass(event, EventSubclass::ConferenceMaintenance)?;
+        Ok(Self {
+            conference_name: required(event, "Conference-Name")?,
+            conference_uuid: optional(event, "Conference-Unique-ID"),
+            conference_size: parsed(event, "Conference-Size"),
+            action: optional(event, "Action"),
+            member_id: parsed(event, "Member-ID"),
+            unique_id: optional(event, "Unique-ID"),
+        })
+    }
+}
+
+/// `CUSTOM callcenter::info`: mod_callcenter queue, agent and member activity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CallcenterInfo {
+    /// `CC-Action`, e.g. `agent-status-change`, `member-queue-start`, `bridge-agent-start`.
+    pub action: String,
+    /// `CC-Queue`.
+    pub queue: Option<String>,
+    /// `CC-Agent`.
+    pub agent: Option<String>,
+    /// `CC-Agent-Status`, e.g. `Available`, `On Break`.
+    pub agent_status: Option<String>,
+    /// `CC-Agent-State`, e.g. `Waiting`, `In a queue call`.
+    pub agent_state: Option<String>,
+    /// `CC-Member-UUID`: the member's queue entry.
+    pub member_uuid: Option<String>,
+    /// `CC-Member-Session-UUID`: the member's channel.
+    pub member_session_uuid: Option<String>,
+}
+
+impl TryFrom<&EslEvent> for CallcenterInfo {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_subclass(event, EventSubclass::CallcenterInfo)?;
+        Ok(Self {
+            action: required(event, "CC-Action")?,
+            queue: optional(event, "CC-Queue"),
+            agent: optional(event, "CC-Agent"),
+            agent_status: optional(event, "CC-Agent-Status"),
+            agent_state: optional(event, "CC-Agent-State"),
+            member_uuid: optional(event, "CC-Member-UUID"),
+            member_session_uuid: optional(event, "CC-Member-Session-UUID"),
+        })
+    }
+}
+
+/// `CUSTOM fifo::info`: mod_fifo queue activity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FifoInfo {
+    /// `FIFO-Name`.
+    pub fifo_name: String,
+    /// `FIFO-Action`, e.g. `push`, `pop`, `abort`, `consumer_start`.
+    pub action: Option<String>,
+    /// `Unique-ID` of the caller or consumer channel.
+    pub unique_id: Option<String>,
+}
+
+impl TryFrom<&EslEvent> for FifoInfo {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_subclass(event, EventSubclass::FifoInfo)?;
+        Ok(Self {
+            fifo_name: required(event, "FIFO-Name")?,
+            action: optional(event, "FIFO-Action"),
+            unique_id: optional(event, "Unique-ID"),
+        })
+    }
+}
+
+/// `CUSTOM valet_parking::info`: a call was parked in or left a valet lot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ValetParkingInfo {
+    /// `Valet-Lot-Name`.
+    pub lot_name: String,
+    /// `Valet-Extension`: the parking slot.
+    pub extension: Option<String>,
+    /// `Action`: `hold`, `bridge` or `exit`.
+    pub action: Option<String>,
+    /// `Unique-ID` of the parked channel.
+    pub unique_id: Option<String>,
+}
+
+impl TryFrom<&EslEvent> for ValetParkingInfo {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_subclass(event, EventSubclass::ValetParkingInfo)?;
+        Ok(Self {
+            lot_name: required(event, "Valet-Lot-Name")?,
+            extension: optional(event, "Valet-Extension"),
+            action: optional(event, "Action"),
+            unique_id: optional(event, "Unique-ID"),
+        })
+    }
+}
+
+/// `CUSTOM spandsp::txfaxresult`: outcome of a sent fax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SpandspTxFaxResult {
+    /// `Unique-ID` of the sending channel.
+    pub unique_id: Option<String>,
+    /// `fax-success`: `1` on success.
+    pub success: Option<bool>,
+    /// `fax-result-code`: spandsp `T30_ERR_*` code, `0` on success.
+    pub result_code: Option<i32>,
+    /// `fax-result-text`.
+    pub result_text: Option<String>,
+    /// `fax-document-transferred-pages`.
+    pub transferred_pages: Option<u32>,
+    /// `fax-document-total-pages`.
+    pub total_pages: Option<u32>,
+    /// `fax-remote-station-id`.
+    pub remote_station_id: Option<String>,
+    /// `fax-transfer-rate`, bits per second.
+    pub transfer_rate: Option<u32>,
+}
+
+impl TryFrom<&EslEvent> for SpandspTxFaxResult {
+    type Error = TypedEventError;
+
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        expect_subclass(event, EventSubclass::SpandspTxFaxResult)?;
+        Ok(Self {
+            unique_id: optional(event, "Unique-ID"),
+            success: event
+                .header("fax-success")
+                .map(|v| v == "1"),
+            result_code: parsed(event, "fax-result-code"),
+            result_text: optional(event, "fax-result-text"),
+            transferred_pages: parsed(event, "fax-document-transferred-pages"),
+            total_pages: parsed(event, "fax-document-total-pages"),
+            remote_station_id: optional(event, "fax-remote-station-id"),
+            transfer_rate: parsed(event, "fax-transfer-rate"),
+        })
+    }
+}
+
+/// Typed view of an event, one variant per supported event type.
+///
+/// Deliberately exhaustive so a `match` can cover every variant; event types
+/// without a typed struct arrive as [`Other`](Self::Other).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedEvent {
+    /// `CHANNEL_CREATE`
+    ChannelCreate(ChannelCreate),
+    /// `CHANNEL_ANSWER`
+    ChannelAnswer(ChannelAnswer),
+    /// `CHANNEL_HANGUP_COMPLETE`
+    ChannelHangupComplete(ChannelHangupComplete),
+    /// `CHANNEL_BRIDGE`
+    ChannelBridge(ChannelBridge),
+    /// `DTMF`
+    Dtmf(Dtmf),
+    /// `BACKGROUND_JOB`
+    BackgroundJob(BackgroundJob),
+    /// `HEARTBEAT`
+    Heartbeat(Heartbeat),
+    /// `CUSTOM sofia::register`
+    SofiaRegister(SofiaRegister),
+    /// `CUSTOM sofia::unregister`
+    SofiaUnregister(SofiaUnregister),
+    /// `CUSTOM sofia::gateway_state`
+    SofiaGatewayState(SofiaGatewayState),
+    /// `CUSTOM conference::maintenance`
+    ConferenceMaintenance(ConferenceMaintenance),
+    /// `CUSTOM callcenter::info`
+    CallcenterInfo(CallcenterInfo),
+    /// `CUSTOM fifo::info`
+    FifoInfo(FifoInfo),
+    /// `CUSTOM valet_parking::info`
+    ValetParkingInfo(ValetParkingInfo),
+    /// `CUSTOM spandsp::txfaxresult`
+    SpandspTxFaxResult(SpandspTxFaxResult),
+    /// Any event type or `CUSTOM` subclass without a typed struct.
+    Other(EslEvent),
+}
+
+impl TryFrom<&EslEvent> for TypedEvent {
+    type Error = TypedEventError;
+
+    /// Fails only if the event is of a supported type but lacks a required header.
+    fn try_from(event: &EslEvent) -> Result<Self, Self::Error> {
+        Ok(match event.event_type() {
+            Some(EslEventType::ChannelCreate) => Self::ChannelCreate(event.try_into()?),
+            Some(EslEventType::ChannelAnswer) => Self::ChannelAnswer(event.try_into()?),
+            Some(EslEventType::ChannelHangupComplete) => {
+                Self::ChannelHangupComplete(event.try_into()?)
+            }
+            Some(EslEventType::ChannelBridge) => Self::ChannelBridge(event.try_into()?),
+            Some(EslEventType::Dtmf) => Self::Dtmf(event.try_into()?),
+            Some(EslEventType::BackgroundJob) => Self::BackgroundJob(event.try_into()?),
+            Some(EslEventType::Heartbeat) => Self::Heartbeat(event.try_into()?),
+            Some(EslEventType::Custom) => match event.subclass() {
+                Some(EventSubclass::SofiaRegister) => Self::SofiaRegister(event.try_into()?),
+                Some(EventSubclass::SofiaUnregister) => Self::SofiaUnregister(event.try_into()?),
+                Some(EventSubclass::SofiaGatewayState) => {
+                    Self::SofiaGatewayState(event.try_into()?)
+                }
+                Some(EventSubclass::ConferenceMaintenance) => {
+                    Self::ConferenceMaintenance(event.try_into()?)
+                }
+                Some(EventSubclass::CallcenterInfo) => Self::CallcenterInfo(event.try_into()?),
+                Some(EventSubclass::FifoInfo) => Self::FifoInfo(event.try_into()?),
+                Some(EventSubclass::ValetParkingInfo) => Self::ValetParkingInfo(event.try_into()?),
+                Some(EventSubclass::SpandspTxFaxResult) => {
+                    Self::SpandspTxFaxResult(event.try_into()?)
+                }
+                None => Self::Other(event.clone()),
+            },
+            _ => Self::Other(event.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel_event(event_type: EslEventType) -> EslEvent {
+        let mut event = EslEvent::with_type(event_type);
+        event.set_header("Event-Name", event_type.to_string());
+        event.set_header("Unique-ID", "abc-123");
+        event.set_header("Channel-Name", "sofia/internal/1000@example.com");
+        event.set_header("Channel-State", "CS_EXECUTE");
+        event.set_header("Channel-Call-State", "ACTIVE");
+        event.set_header("Answer-State", "answered");
+        event.set_header("Call-Direction", "inbound");
+        event.set_header("Caller-Caller-ID-Number", "1000");
+        event.set_header("Caller-Caller-ID-Name", "Alice");
+        event.set_header("Caller-Destination-Number", "2000");
+        event.set_header("Caller-Channel-Created-Time", "1700000000000000");
+        event.set_header("Event-Date-Timestamp", "1700000001000000");
+        event
+    }
+
+    #[test]
+    fn channel_data_fields() {
+        let event = channel_event(EslEventType::ChannelCreate);
+        let create = ChannelCreate::try_from(&event).unwrap();
+        let ch = create.channel;
+        assert_eq!(ch.unique_id, "abc-123");
+        assert_eq!(
+            ch.channel_name
+                .as_deref(),
+            Some("sofia/internal/1000@example.com")
+        );
+        assert_eq!(ch.channel_state, Some(ChannelState::CsExecute));
+        assert_eq!(ch.call_state, Some(CallState::Active));
+        assert_eq!(ch.answer_state, Some(AnswerState::Answered));
+        assert_eq!(ch.call_direction, Some(CallDirection::Inbound));
+        assert_eq!(
+            ch.caller_id_number
+                .as_deref(),
+            Some("1000")
+        );
+        assert_eq!(
+            ch.destination_number
+                .as_deref(),
+            Some("2000")
+        );
+        assert_eq!(
+            ch.timetable
+                .unwrap()
+                .created,
+            Some(1700000000000000)
+        );
+        assert_eq!(ch.event_timestamp, Some(1700000001000000));
+    }
+
+    #[test]
+    fn wrong_event_type_rejected() {
+        let event = channel_event(EslEventType::ChannelAnswer);
+        assert_eq!(
+            ChannelCreate::try_from(&event),
+            Err(TypedEventError::WrongEventType {
+                expected: EslEventType::ChannelCreate,
+                actual: Some(EslEventType::ChannelAnswer),
+            })
+        );
+    }
+
+    #[test]
+    fn missing_unique_id_rejected() {
+        let event = EslEvent::with_type(EslEventType::ChannelAnswer);
+        assert_eq!(
+            ChannelAnswer::try_from(&event),
+            Err(TypedEventError::MissingHeader("Unique-ID"))
+        );
+    }
+
+    #[test]
+    fn hangup_complete_fields() {
+        let mut event = channel_event(EslEventType::ChannelHangupComplete);
+        event.set_header("Hangup-Cause", "NORMAL_CLEARING");
+        event.set_header("variable_duration", "42");
+        event.set_header("variable_billsec", "30");
+        let hangup = ChannelHangupComplete::try_from(&event).unwrap();
+        assert_eq!(hangup.hangup_cause, Some(HangupCause::NormalClearing));
+        assert_eq!(hangup.duration, Some(42));
+        assert_eq!(hangup.billsec, Some(30));
+    }
+
+    #[test]
+    fn bridge_other_leg() {
+        let mut event = channel_event(EslEventType::ChannelBridge);
+        event.set_header("Bridge-B-Unique-ID", "def-456");
+        event.set_header("Other-Leg-Channel-Created-Time", "1700000000500000");
+        let bridge = ChannelBridge::try_from(&event).unwrap();
+        assert_eq!(
+            bridge
+                .other_leg_unique_id
+                .as_deref(),
+            Some("def-456")
+        );
+        assert_eq!(
+            bridge
+                .other_leg_timetable
+                .unwrap()
+                .created,
+            Some(1700000000500000)
+        );
+    }
+
+    #[test]
+    fn dtmf_digit_and_duration() {
+        let mut event = channel_event(EslEventType::Dtmf);
+        event.set_header("DTMF-Digit", "#");
+        event.set_header("DTMF-Duration", "2000");
+        event.set_header("DTMF-Source", "RTP");
+        let dtmf = Dtmf::try_from(&event).unwrap();
+        assert_eq!(dtmf.digit, '#');
+        assert_eq!(dtmf.duration, Some(2000));
+        assert_eq!(
+            dtmf.source
+                .as_deref(),
+            Some("RTP")
+        );
+
+        event.set_header("DTMF-Digit", "12");
+        assert!(matches!(
+            Dtmf::try_from(&event),
+            Err(TypedEventError::InvalidHeader {
+                header: "DTMF-Digit",
+                ..
+            })
+        ));
+        event.del_header("DTMF-Digit");
+        assert_eq!(
+            Dtmf::try_from(&event),
+            Err(TypedEventError::MissingHeader("DTMF-Digit"))
+        );
+    }
+
+    #[test]
+    fn background_job_body() {
+        let mut event = EslEvent::with_type(EslEventType::BackgroundJob);
+        event.set_header("Job-UUID", "job-1");
+        event.set_header("Job-Command", "originate");
+        event.set_body("-ERR NO_ANSWER\n".to_string());
+        let job = BackgroundJob::try_from(&event).unwrap();
+        assert_eq!(job.job_uuid, "job-1");
+        assert_eq!(
+            job.job_command
+                .as_deref(),
+            Some("originate")
+        );
+        assert!(job.is_error());
+    }
+
+    #[test]
+    fn heartbeat_fields() {
+        let mut event = EslEvent::with_type(EslEventType::Heartbeat);
+        event.set_header("Session-Count", "5");
+        event.set_header("Max-Sessions", "1000");
+        event.set_header("Idle-CPU", "97.5");
+        event.set_header("Uptime-msec", "not-a-number");
+        let hb = Heartbeat::try_from(&event).unwrap();
+        assert_eq!(hb.session_count, Some(5));
+        assert_eq!(hb.max_sessions, Some(1000));
+        assert_eq!(hb.idle_cpu, Some(97.5));
+        assert_eq!(hb.uptime_msec, None);
+    }
+
+    #[test]
+    fn typed_event_dispatch() {
+        let event = channel_event(EslEventType::ChannelAnswer);
+        assert!(matches!(
+            TypedEvent::try_from(&event),
+            Ok(TypedEvent::ChannelAnswer(_))
+        ));
+
+        let event = channel_event(EslEventType::ChannelPark);
+        match TypedEvent::try_from(&event).unwrap() {
+            TypedEvent::Other(raw) => assert_eq!(raw.unique_id(), Some("abc-123")),
+            other => panic!("expected Other, got {:?}", other),
+        }
+
+        let event = EslEvent::with_type(EslEventType::Dtmf);
+        assert!(TypedEvent::try_from(&event).is_err());
+    }
+
+    fn custom_event(subclass: &str) -> EslEvent {
+        let mut event = EslEvent::with_type(EslEventType::Custom);
+        event.set_header("Event-Name", "CUSTOM");
+        event.set_header("Event-Subclass", subclass);
+        event
+    }
+
+    #[test]
+    fn sofia_register_fields() {
+        let mut event = custom_event("sofia::register");
+        event.set_header("profile-name", "internal");
+        event.set_header("from-user", "1000");
+        event.set_header("from-host", "example.com");
+        event.set_header("expires", "3600");
+        event.set_header("network-port", "5060");
+        let reg = SofiaRegister::try_from(&event).unwrap();
+        assert_eq!(reg.profile_name, "internal");
+        assert_eq!(
+            reg.from_user
+                .as_deref(),
+            Some("1000")
+        );
+        assert_eq!(reg.expires, Some(3600));
+        assert_eq!(reg.network_port, Some(5060));
+
+        assert_eq!(
+            SofiaUnregister::try_from(&event),
+            Err(TypedEventError::WrongSubclass {
+                expected: EventSubclass::SofiaUnregister,
+                actual: Some("sofia::register".to_string()),
+            })
+        );
+        event.del_header("profile-name");
+        assert_eq!(
+            SofiaRegister::try_from(&event),
+            Err(TypedEventError::MissingHeader("profile-name"))
+        );
+    }
+
+    #[test]
+    fn subclass_requires_custom_event() {
+        let mut event = channel_event(EslEventType::ChannelAnswer);
+        event.set_header("Event-Subclass", "fifo::info");
+        assert!(matches!(
+            FifoInfo::try_from(&event),
+            Err(TypedEventError::WrongEventType {
+                expected: EslEventType::Custom,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn fax_result_fields() {
+        let mut event = custom_event("spandsp::txfaxresult");
+        event.set_header("fax-success", "0");
+        event.set_header("fax-result-code", "49");
+        event.set_header("fax-result-text", "The call dropped prematurely");
+        event.set_header("fax-document-transferred-pages", "1");
+        event.set_header("fax-document-total-pages", "3");
+        let fax = SpandspTxFaxResult::try_from(&event).unwrap();
+        assert_eq!(fax.success, Some(false));
+        assert_eq!(fax.result_code, Some(49));
+        assert_eq!(fax.transferred_pages, Some(1));
+        assert_eq!(fax.total_pages, Some(3));
+    }
+
+    #[test]
+    fn typed_event_dispatch_custom() {
+        let mut event = custom_event("conference::maintenance");
+        event.set_header("Conference-Name", "3000");
+        event.set_header("Action", "add-member");
+        event.set_header("Member-ID", "7");
+        match TypedEvent::try_from(&event).unwrap() {
+            TypedEvent::ConferenceMaintenance(conf) => {
+                assert_eq!(conf.conference_name, "3000");
+                assert_eq!(conf.member_id, Some(7));
+            }
+            other => panic!("expected ConferenceMaintenance, got {:?}", other),
+        }
+
+        let mut event = custom_event("valet_parking::info");
+        event.set_header("Valet-Lot-Name", "lot1");
+        assert!(matches!(
+            TypedEvent::try_from(&event),
+            Ok(TypedEvent::ValetParkingInfo(_))
+        ));
+
+        let event = custom_event("mod_custom::thing");
+        assert!(matches!(
+            TypedEvent::try_from(&event),
+            Ok(TypedEvent::Other(_))
+        ));
+        let event = custom_event("callcenter::info");
+        assert_eq!(
+            TypedEvent::try_from(&event),
+            Err(TypedEventError::MissingHeader("CC-Action"))
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Event queue backpressure policy tests using mock ESL server
+
+#[allow(dead_code)]
+mod mock_server;
+
+use freeswitch_esl_tokio::{
+    BackpressurePolicy, EslClient, EslConnectOptions, EslError, EslEventStream, EslEventType,
+};
+use mock_server::{MockClient, MockEslServer};
+use std::collections::HashMap;
+use std::time::Duration;
+
+async fn connect(
+    server: &MockEslServer,
+    event_queue_size: usize,
+    backpressure: BackpressurePolicy,
+) -> (MockClient, EslClient, EslEventStream) {
+    // TLS fields only exist with the `tls` feature
+    #[allow(clippy::needless_update)]
+    let options = EslConnectOptions {
+        event_queue_size,
+        backpressure,
+        ..Default::default()
+    };
+    let (mock, result) = tokio::join!(
+        server.accept(),
+        EslClient::connect_with_options("127.0.0.1", server.port(), "ClueCon", options)
+    );
+    let (client, events) = result.expect("connect failed");
+    (mock, client, events)
+}
+
+/// Send events tagged with a `Sequence` header.
+async fn send_events(mock: &mut MockClient, events: &[(&str, u32)]) {
+    for (name, seq) in events {
+        let mut headers = HashMap::new();
+        headers.insert("Sequence".to_string(), seq.to_string());
+        mock.send_event_plain(name, &headers)
+            .await;
+    }
+}
+
+/// Round-trip a command so every event sent before it has been dispatched.
+async fn sync(mock: &mut MockClient, client: &EslClient) {
+    let (result, _) = tokio::join!(client.api("status"), async {
+        mock.read_command()
+            .await;
+        mock.reply_api("OK")
+            .await;
+    });
+    result.unwrap();
+}
+
+async fn recv_seq(events: &mut EslEventStream) -> Result<u32, EslError> {
+    let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("timeout")
+        .expect("stream ended")?;
+    Ok(event
+        .header("Sequence")
+        .unwrap()
+        .parse()
+        .unwrap())
+}
+
+#[tokio::test]
+async fn test_drop_newest_reports_queue_full() {
+    let server = MockEslServer::start("ClueCon").await;
+    let (mut mock, client, mut events) = connect(&server, 2, BackpressurePolicy::DropNewest).await;
+
+    send_events(
+        &mut mock,
+        &[("HEARTBEAT", 1), ("HEARTBEAT", 2), ("HEARTBEAT", 3)],
+    )
+    .await;
+    sync(&mut mock, &client).await;
+    assert_eq!(client.dropped_event_count(), 1);
+
+    assert_eq!(
+        recv_seq(&mut events)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        recv_seq(&mut events)
+            .await
+            .unwrap(),
+        2
+    );
+
+    send_events(&mut mock, &[("HEARTBEAT", 4)]).await;
+    assert!(matches!(
+        recv_seq(&mut events).await,
+        Err(EslError::QueueFull)
+    ));
+    assert_eq!(
+        recv_seq(&mut events)
+            .await
+            .unwrap(),
+        4
+    );
+}
+
+#[tokio::test]
+async fn test_drop_oldest_keeps_latest_events() {
+    let server = MockEslServer::start("ClueCon").await;
+    let (mut mock, client, mut events) = connect(&server, 2, BackpressurePolicy::DropOldest).await;
+
+    send_events(
+        &mut mock,
+        &[("HEARTBEAT", 1), ("HEARTBEAT", 2), ("HEARTBEAT", 3)],
+    )
+    .await;
+    sync(&mut mock, &client).await;
+
+    let stats = client.event_queue_stats();
+    assert_eq!(stats.depth, 2);
+    assert_eq!(stats.max_depth, 2);
+    assert_eq!(stats.capacity, 2);
+    assert_eq!(client.dropped_event_count(), 1);
+
+    assert_eq!(
+        recv_seq(&mut events)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        recv_seq(&mut events)
+            .await
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        client
+            .event_queue_stats()
+            .depth,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_block_loses_nothing() {
+    let server = MockEslServer::start("ClueCon").await;
+    let (mut mock, client, mut events) = connect(&server, 1, BackpressurePolicy::Block).await;
+
+    send_events(
+        &mut mock,
+        &[("HEARTBEAT", 1), ("HEARTBEAT", 2), ("HEARTBEAT", 3)],
+    )
+    .await;
+
+    // The reader stalls on the second event until the application drains
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client
+            .event_queue_stats()
+            .reader_waits
+            == 0
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("reader never waited");
+    assert_eq!(
+        client
+            .event_queue_stats()
+            .depth,
+        1
+    );
+
+    for seq in 1..=3 {
+        assert_eq!(
+            recv_seq(&mut events)
+                .await
+                .unwrap(),
+            seq
+        );
+    }
+    sync(&mut mock, &client).await;
+    assert_eq!(client.dropped_event_count(), 0);
+    assert!(client.is_connected());
+}
+
+#[tokio::test]
+async fn test_priority_never_drops_critical_events() {
+    let server = MockEslServer::start("ClueCon").await;
+    let policy = BackpressurePolicy::Priority {
+        critical: vec![EslEventType::ChannelHangupComplete],
+    };
+    let (mut mock, client, mut events) = connect(&server, 2, policy).await;
+
+    send_events(
+        &mut mock,
+        &[
+            ("HEARTBEAT", 1),
+            ("HEARTBEAT", 2),
+            // Evicts heartbeat 1
+            ("CHANNEL_HANGUP_COMPLETE", 3),
+            // Queue full: dropped
+            ("HEARTBEAT", 4),
+            // Evicts heartbeat 2
+            ("CHANNEL_HANGUP_COMPLETE", 5),
+        ],
+    )
+    .await;
+    sync(&mut mock, &client).await;
+    assert_eq!(client.dropped_event_count(), 3);
+
+    assert_eq!(
+        recv_seq(&mut events)
+            .await
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        recv_seq(&mut events)
+            .await
+            .unwrap(),
+        5
+    );
+
+    // With only critical events queued, the reader waits instead of dropping
+    send_events(
+        &mut mock,
+        &[
+            ("CHANNEL_HANGUP_COMPLETE", 6),
+            ("CHANNEL_HANGUP_COMPLETE", 7),
+            ("CHANNEL_HANGUP_COMPLETE", 8),
+        ],
+    )
+    .await;
+    // QueueFull notice from the earlier drops comes first
+    assert!(matches!(
+        recv_seq(&mut events).await,
+        Err(EslError::QueueFull)
+    ));
+    for seq in 6..=8 {
+        assert_eq!(
+            recv_seq(&mut events)
+                .await
+                .unwrap(),
+            seq
+        );
+    }
+    assert_eq!(client.dropped_event_count(), 3);
+    assert!(
+        client
+            .event_queue_stats()
+            .reader_waits
+            >= 1
+    );
+}
@@ -3,8 +3,9 @@
 mod mock_server;
 
 use freeswitch_esl_tokio::{
-    ConnectionStatus, DisconnectReason, EslClient, EslError, EslEvent, EslEventStream,
-    EslEventType, EventFormat,
+    ChannelSession, ConnectionStatus, DisconnectReason, EslClient, EslConnectOptions, EslError,
+    EslEvent, EslEventStream, EslEventType, EventFormat, EventKind, EventSubclass, ExecuteOptions,
+    HangupCause, LogLevel, SubscriptionState,
 };
 use mock_server::{setup_connected_pair, MockClient, MockEslServer};
 use std::collections::HashMap;
@@ -418,7 +419,7 @@ async fn test_command_timeout_cleanup() {
         .await;
     assert!(matches!(result, Err(EslError::Timeout { .. })));
 
-    // Second command should still work — pending_reply slot was cleaned up
+    // Second command should still work — the timed-out slot stays queued
     let api_task = tokio::spawn({
         let client = client.clone();
         async move {
@@ -435,13 +436,213 @@ async fn test_command_timeout_cleanup() {
     let _cmd2 = mock
         .read_command()
         .await;
+    // The late reply to the timed-out command must not reach the new caller
+    mock.reply_api("UP 0 years")
+        .await;
     mock.reply_api("1.0")
         .await;
 
     let result = api_task
         .await
         .unwrap();
-    assert!(result.is_ok());
+    assert_eq!(
+        result
+            .unwrap()
+            .body(),
+        Some("1.0")
+    );
+}
+
+#[tokio::test]
+async fn test_commands_pipelined_past_slow_reply() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let slow = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .api("show calls")
+                .await
+        }
+    });
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "api show calls\n\n"
+    );
+
+    // Written while the first command still waits for its reply
+    let fast = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .api("status")
+                .await
+        }
+    });
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "api status\n\n"
+    );
+
+    // Replies arrive in command order and go to their own callers
+    mock.reply_api("0 total.")
+        .await;
+    mock.reply_api("UP 0 years")
+        .await;
+    assert_eq!(
+        slow.await
+            .unwrap()
+            .unwrap()
+            .body(),
+        Some("0 total.")
+    );
+    assert_eq!(
+        fast.await
+            .unwrap()
+            .unwrap()
+            .body(),
+        Some("UP 0 years")
+    );
+}
+
+#[tokio::test]
+async fn test_in_flight_command_fails_on_disconnect() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let pending = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .api("status")
+                .await
+        }
+    });
+    mock.read_command()
+        .await;
+    mock.drop_connection()
+        .await;
+
+    assert!(matches!(
+        pending
+            .await
+            .unwrap(),
+        Err(EslError::ConnectionClosed)
+    ));
+}
+
+#[tokio::test]
+async fn test_dropped_command_reply_is_discarded() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    // Poll the command once, then drop it before its reply arrives
+    tokio::select! {
+        biased;
+        _ = client.api("status") => panic!("command completed without a reply"),
+        _ = std::future::ready(()) => {}
+    }
+
+    // The dropped command still reaches the wire in full
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "api status\n\n"
+    );
+
+    let next = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .api("version")
+                .await
+        }
+    });
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "api version\n\n"
+    );
+    mock.reply_api("UP 0 years")
+        .await;
+    mock.reply_api("1.0")
+        .await;
+
+    assert_eq!(
+        next.await
+            .unwrap()
+            .unwrap()
+            .body(),
+        Some("1.0")
+    );
+    assert!(client.is_connected());
+}
+
+#[tokio::test]
+async fn test_unsolicited_reply_closes_connection() {
+    let (mut mock, client, mut events) = setup_connected_pair("ClueCon").await;
+
+    mock.reply_ok()
+        .await;
+
+    let result = tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("timeout");
+    assert!(result.is_none());
+    assert!(matches!(
+        client.status(),
+        ConnectionStatus::Disconnected(DisconnectReason::Desync(_))
+    ));
+    assert!(matches!(
+        client
+            .api("status")
+            .await,
+        Err(EslError::NotConnected)
+    ));
+}
+
+#[tokio::test]
+async fn test_wrong_reply_type_is_desync() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let first = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .api("status")
+                .await
+        }
+    });
+    mock.read_command()
+        .await;
+    let second = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .bgapi("status")
+                .await
+        }
+    });
+    mock.read_command()
+        .await;
+
+    // A command/reply cannot answer `api`; every queued command fails
+    mock.reply_ok()
+        .await;
+
+    let err = first
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(err, EslError::Desync { .. }), "got {:?}", err);
+    assert!(err.is_connection_error());
+    assert!(matches!(
+        second
+            .await
+            .unwrap(),
+        Err(EslError::Desync { .. })
+    ));
+    assert!(!client.is_connected());
 }
 
 #[tokio::test]
@@ -626,6 +827,113 @@ async fn test_resume_command() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_subscribe_events_puts_custom_last() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .subscribe_events(
+                    EventFormat::Plain,
+                    &[EslEventType::Custom, EslEventType::ChannelAnswer],
+                )
+                .await
+        }
+    });
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "event plain CHANNEL_ANSWER CUSTOM\n\n");
+    mock.reply_ok()
+        .await;
+
+    task.await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_subscribe_events_with_subclasses() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            let events: [EventKind; 3] = [
+                EventSubclass::SofiaRegister.into(),
+                EslEventType::ChannelHangup.into(),
+                EventSubclass::SofiaGatewayState.into(),
+            ];
+            client
+                .subscribe_events(EventFormat::Plain, &events)
+                .await
+        }
+    });
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(
+        cmd,
+        "event plain CHANNEL_HANGUP CUSTOM sofia::register sofia::gateway_state\n\n"
+    );
+    mock.reply_ok()
+        .await;
+
+    task.await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        client
+            .subscriptions()
+            .subclasses(),
+        ["sofia::register", "sofia::gateway_state"]
+    );
+}
+
+#[tokio::test]
+async fn test_subscribe_subclasses_only() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .subscribe_events(
+                    EventFormat::Plain,
+                    &[
+                        EventSubclass::SofiaRegister,
+                        EventSubclass::SofiaGatewayState,
+                    ],
+                )
+                .await
+        }
+    });
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(
+        cmd,
+        "event plain CUSTOM sofia::register sofia::gateway_state\n\n"
+    );
+    mock.reply_ok()
+        .await;
+
+    task.await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        client
+            .subscriptions()
+            .subclasses(),
+        ["sofia::register", "sofia::gateway_state"]
+    );
+}
+
 #[tokio::test]
 async fn test_nixevent_command() {
     let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
@@ -701,6 +1009,92 @@ async fn test_filter_delete_command() {
         .unwrap();
 }
 
+/// Run `set_subscriptions`, answering each command with +OK, and return the
+/// commands sent.
+async fn apply_subscriptions(
+    mock: &mut MockClient,
+    client: &EslClient,
+    desired: &SubscriptionState,
+    expected_commands: usize,
+) -> Vec<String> {
+    let (result, commands) = tokio::join!(client.set_subscriptions(desired), async {
+        let mut commands = Vec::new();
+        for _ in 0..expected_commands {
+            commands.push(
+                mock.read_command()
+                    .await,
+            );
+            mock.reply_ok()
+                .await;
+        }
+        commands
+    });
+    result.unwrap();
+    commands
+}
+
+#[tokio::test]
+async fn test_set_subscriptions_sends_diff() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let desired = SubscriptionState::new()
+        .format(EventFormat::Plain)
+        .events(&[EslEventType::ChannelAnswer, EslEventType::Heartbeat])
+        .filter("Call-Direction", "inbound");
+    assert_eq!(
+        apply_subscriptions(&mut mock, &client, &desired, 2).await,
+        vec![
+            "filter Call-Direction inbound\n\n",
+            "event plain CHANNEL_ANSWER HEARTBEAT\n\n"
+        ]
+    );
+    assert_eq!(client.subscriptions(), desired);
+
+    // Already applied: nothing to send
+    assert!(apply_subscriptions(&mut mock, &client, &desired, 0)
+        .await
+        .is_empty());
+
+    let desired = desired
+        .without_events(&[EslEventType::Heartbeat])
+        .events_raw("CUSTOM sofia::register");
+    assert_eq!(
+        apply_subscriptions(&mut mock, &client, &desired, 2).await,
+        vec![
+            "nixevent HEARTBEAT\n\n",
+            "event plain CUSTOM sofia::register\n\n"
+        ]
+    );
+
+    // Commands sent individually are tracked too
+    let (result, _) = tokio::join!(client.filter_delete_all(), async {
+        mock.read_command()
+            .await;
+        mock.reply_ok()
+            .await;
+    });
+    result.unwrap();
+    let state = client.subscriptions();
+    assert!(state
+        .filters()
+        .is_empty());
+    assert_eq!(
+        state
+            .event_list()
+            .as_deref(),
+        Some("CHANNEL_ANSWER CUSTOM sofia::register")
+    );
+
+    assert_eq!(
+        apply_subscriptions(&mut mock, &client, &SubscriptionState::new(), 1).await,
+        vec!["noevents\n\n"]
+    );
+    assert_eq!(
+        client.subscriptions(),
+        SubscriptionState::new().format(EventFormat::Plain)
+    );
+}
+
 #[tokio::test]
 async fn test_divert_events_command() {
     let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
@@ -818,3 +1212,846 @@ async fn test_outbound_connect_session() {
     assert_eq!(response.header("Socket-Mode"), Some("async"));
     assert_eq!(response.header("Control"), Some("full"));
 }
+
+#[tokio::test]
+async fn test_from_stream_over_duplex() {
+    let (client_side, server_side) = tokio::io::duplex(64 * 1024);
+    let mut mock = MockClient::from_stream(server_side);
+
+    let (_, result) = tokio::join!(
+        mock.serve_auth("ClueCon"),
+        EslClient::from_stream(client_side, "ClueCon")
+    );
+    let (client, mut events) = result.unwrap();
+    assert!(client.is_connected());
+
+    let (response, _) = tokio::join!(client.api("status"), async {
+        assert_eq!(
+            mock.read_command()
+                .await,
+            "api status\n\n"
+        );
+        mock.reply_api("UP 0 years")
+            .await;
+    });
+    assert_eq!(
+        response
+            .unwrap()
+            .body(),
+        Some("UP 0 years")
+    );
+
+    mock.send_heartbeat()
+        .await;
+    assert_eq!(
+        recv_event(&mut events)
+            .await
+            .event_type(),
+        Some(EslEventType::Heartbeat)
+    );
+}
+
+#[tokio::test]
+async fn test_from_stream_auth_failure() {
+    let (client_side, server_side) = tokio::io::duplex(64 * 1024);
+    let mut mock = MockClient::from_stream(server_side);
+
+    let (_, result) = tokio::join!(
+        mock.serve_auth("correct_password"),
+        EslClient::from_stream(client_side, "wrong_password")
+    );
+    assert!(matches!(result, Err(EslError::AuthenticationFailed { .. })));
+}
+
+#[tokio::test]
+async fn test_from_authenticated_stream_skips_handshake() {
+    let (client_side, server_side) = tokio::io::duplex(64 * 1024);
+    let mut mock = MockClient::from_stream(server_side);
+    let (client, _events) =
+        EslClient::from_authenticated_stream(client_side, EslConnectOptions::default());
+
+    let (response, _) = tokio::join!(client.api("version"), async {
+        assert_eq!(
+            mock.read_command()
+                .await,
+            "api version\n\n"
+        );
+        mock.reply_api("FreeSWITCH Version 1.10")
+            .await;
+    });
+    assert!(response
+        .unwrap()
+        .is_success());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_from_stream_over_unix_socket() {
+    use tokio::net::{UnixListener, UnixStream};
+
+    let path = std::env::temp_dir().join(format!("esl-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let mock = async {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .unwrap();
+        let mut mock = MockClient::from_stream(stream);
+        mock.serve_auth("ClueCon")
+            .await;
+        mock
+    };
+    let client = async {
+        let stream = UnixStream::connect(&path)
+            .await
+            .unwrap();
+        EslClient::from_stream(stream, "ClueCon").await
+    };
+    let (_mock, result) = tokio::join!(mock, client);
+    let _ = std::fs::remove_file(&path);
+    assert!(result
+        .unwrap()
+        .0
+        .is_connected());
+}
+
+/// Read a `bgapi` command from the mock and return its Job-UUID header.
+async fn read_bgapi_job_uuid(mock: &mut MockClient) -> String {
+    let cmd = mock
+        .read_command()
+        .await;
+    assert!(cmd.starts_with("bgapi status\n"), "got: {:?}", cmd);
+    cmd.lines()
+        .find_map(|l| l.strip_prefix("Job-UUID: "))
+        .expect("bgapi_job sends a Job-UUID header")
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_bgapi_job_resolves_without_leaking_event() {
+    let (mut mock, client, mut events) = setup_connected_pair("ClueCon").await;
+
+    let job_task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .bgapi_wait("status")
+                .await
+        }
+    });
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "event plain BACKGROUND_JOB\n\n");
+    mock.reply_ok()
+        .await;
+
+    let job_uuid = read_bgapi_job_uuid(&mut mock).await;
+    mock.reply_raw_text(&format!("+OK Job-UUID: {}", job_uuid))
+        .await;
+    mock.send_background_job(&job_uuid, "+OK UP 0 years\n")
+        .await;
+    mock.send_heartbeat()
+        .await;
+
+    let body = job_task
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(body, "+OK UP 0 years\n");
+
+    // The BACKGROUND_JOB was consumed; the next event on the stream is the heartbeat
+    let event = recv_event(&mut events).await;
+    assert_eq!(event.event_type(), Some(EslEventType::Heartbeat));
+}
+
+#[tokio::test]
+async fn test_bgapi_job_user_subscription_still_sees_event() {
+    let (mut mock, client, mut events) = setup_connected_pair("ClueCon").await;
+
+    let job_task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .subscribe_events(EventFormat::Json, &[EslEventType::BackgroundJob])
+                .await
+                .unwrap();
+            client
+                .bgapi_job("status")
+                .await
+                .unwrap()
+                .event()
+                .await
+        }
+    });
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "event json BACKGROUND_JOB\n\n");
+    mock.reply_ok()
+        .await;
+
+    // No auto-subscription since the application asked for BACKGROUND_JOB
+    let job_uuid = read_bgapi_job_uuid(&mut mock).await;
+    mock.reply_raw_text(&format!("+OK Job-UUID: {}", job_uuid))
+        .await;
+    mock.send_background_job(&job_uuid, "+OK\n")
+        .await;
+
+    let job_event = job_task
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job_event.job_uuid(), Some(job_uuid.as_str()));
+
+    let event = recv_event(&mut events).await;
+    assert_eq!(event.event_type(), Some(EslEventType::BackgroundJob));
+    assert_eq!(event.job_uuid(), Some(job_uuid.as_str()));
+}
+
+#[tokio::test]
+async fn test_bgapi_job_timeout() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+    client.set_job_timeout(Duration::from_millis(100));
+
+    let job_task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .bgapi_wait("status")
+                .await
+        }
+    });
+
+    mock.read_command()
+        .await;
+    mock.reply_ok()
+        .await;
+    let job_uuid = read_bgapi_job_uuid(&mut mock).await;
+    mock.reply_raw_text(&format!("+OK Job-UUID: {}", job_uuid))
+        .await;
+
+    match job_task
+        .await
+        .unwrap()
+    {
+        Err(EslError::Timeout { timeout_ms }) => assert_eq!(timeout_ms, 100),
+        other => panic!("expected Timeout, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_bgapi_job_timeout_starts_after_reply() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+    client.set_job_timeout(Duration::from_millis(150));
+
+    let job_task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .bgapi_wait("status")
+                .await
+        }
+    });
+
+    mock.read_command()
+        .await;
+    mock.reply_ok()
+        .await;
+    let job_uuid = read_bgapi_job_uuid(&mut mock).await;
+    // A slow command reply is not charged against the job timeout
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    mock.reply_raw_text(&format!("+OK Job-UUID: {}", job_uuid))
+        .await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    mock.send_background_job(&job_uuid, "+OK\n")
+        .await;
+
+    assert_eq!(
+        job_task
+            .await
+            .unwrap()
+            .unwrap(),
+        "+OK\n"
+    );
+}
+
+#[tokio::test]
+async fn test_bgapi_job_fails_on_disconnect() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let job_task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .bgapi_wait("status")
+                .await
+        }
+    });
+
+    mock.read_command()
+        .await;
+    mock.reply_ok()
+        .await;
+    let job_uuid = read_bgapi_job_uuid(&mut mock).await;
+    mock.reply_raw_text(&format!("+OK Job-UUID: {}", job_uuid))
+        .await;
+    mock.drop_connection()
+        .await;
+
+    assert!(matches!(
+        job_task
+            .await
+            .unwrap(),
+        Err(EslError::ConnectionClosed)
+    ));
+}
+
+/// Read an `execute_and_wait` sendmsg from the mock; returns its Event-UUID and the command.
+async fn read_execute_uuid(mock: &mut MockClient, app: &str) -> (String, String) {
+    let cmd = mock
+        .read_command()
+        .await;
+    assert!(cmd.starts_with("sendmsg uuid-a\n"), "got: {:?}", cmd);
+    assert!(
+        cmd.contains(&format!("execute-app-name: {}\n", app)),
+        "got: {:?}",
+        cmd
+    );
+    let app_uuid = cmd
+        .lines()
+        .find_map(|l| l.strip_prefix("Event-UUID: "))
+        .expect("execute_and_wait sends an Event-UUID header")
+        .to_string();
+    (app_uuid, cmd)
+}
+
+async fn send_execute_complete(
+    mock: &mut MockClient,
+    app_uuid: &str,
+    response: &str,
+    variables: &[(&str, &str)],
+) {
+    let mut headers = channel_headers("uuid-a");
+    headers.insert("Application".to_string(), "play_and_get_digits".to_string());
+    headers.insert("Application-UUID".to_string(), app_uuid.to_string());
+    headers.insert("Application-Response".to_string(), response.to_string());
+    for (name, value) in variables {
+        headers.insert(format!("variable_{}", name), value.to_string());
+    }
+    mock.send_event_plain("CHANNEL_EXECUTE_COMPLETE", &headers)
+        .await;
+}
+
+#[tokio::test]
+async fn test_execute_and_wait_resolves_on_matching_completion() {
+    let (mut mock, client, mut events) = setup_connected_pair("ClueCon").await;
+
+    let task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            let options = ExecuteOptions {
+                event_lock: true,
+                ..Default::default()
+            };
+            client
+                .execute_and_wait(
+                    "play_and_get_digits",
+                    Some("1 4 3 5000 # ivr/ivr-enter_ext.wav '' ext"),
+                    "uuid-a",
+                    options,
+                )
+                .await
+                .unwrap()
+                .await
+        }
+    });
+
+    let (app_uuid, cmd) = read_execute_uuid(&mut mock, "play_and_get_digits").await;
+    assert!(cmd.contains("event-lock: true\n"), "got: {:?}", cmd);
+    assert!(!cmd.contains("loops:"), "got: {:?}", cmd);
+    mock.reply_ok()
+        .await;
+
+    send_execute_complete(&mut mock, "other-app", "_none_", &[]).await;
+    send_execute_complete(&mut mock, &app_uuid, "_none_", &[("ext", "1234")]).await;
+
+    let done = task
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(done.application(), Some("play_and_get_digits"));
+    assert_eq!(done.response(), None);
+    assert_eq!(done.variable("ext"), Some("1234"));
+    assert!(done
+        .variables()
+        .any(|(name, value)| name == "ext" && value == "1234"));
+
+    // Completion events still reach the stream
+    for _ in 0..2 {
+        let event = recv_event(&mut events).await;
+        assert_eq!(
+            event.event_type(),
+            Some(EslEventType::ChannelExecuteComplete)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_execute_and_wait_loops_until_last_completion() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            let options = ExecuteOptions {
+                loops: 2,
+                ..Default::default()
+            };
+            client
+                .execute_and_wait("playback", Some("ivr/ivr-welcome.wav"), "uuid-a", options)
+                .await
+                .unwrap()
+                .await
+        }
+    });
+
+    let (app_uuid, cmd) = read_execute_uuid(&mut mock, "playback").await;
+    assert!(cmd.contains("loops: 2\n"), "got: {:?}", cmd);
+    assert!(!cmd.contains("event-lock"), "got: {:?}", cmd);
+    mock.reply_ok()
+        .await;
+
+    send_execute_complete(&mut mock, &app_uuid, "FILE PLAYED", &[]).await;
+    send_execute_complete(&mut mock, &app_uuid, "FILE NOT FOUND", &[]).await;
+
+    let done = task
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(done.response(), Some("FILE NOT FOUND"));
+}
+
+#[tokio::test]
+async fn test_execute_and_wait_cancel_sends_uuid_break() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+    let session = client.session("uuid-a");
+
+    let task = tokio::spawn(async move {
+        let options = ExecuteOptions {
+            loops: 3,
+            ..Default::default()
+        };
+        let run = session
+            .execute_and_wait("playback", Some("local_stream://moh"), options)
+            .await
+            .unwrap();
+        run.cancel()
+            .await
+            .unwrap();
+        run.await
+    });
+
+    let (app_uuid, _) = read_execute_uuid(&mut mock, "playback").await;
+    mock.reply_ok()
+        .await;
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "api uuid_break uuid-a\n\n");
+    mock.reply_api("+OK\n")
+        .await;
+
+    // One completion is enough once cancelled
+    send_execute_complete(&mut mock, &app_uuid, "FILE PLAYED", &[]).await;
+    let done = task
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(done.response(), Some("FILE PLAYED"));
+}
+
+#[tokio::test]
+async fn test_execute_and_wait_cancel_single_channel_executes_break() {
+    let (mut mock, client, _events) = setup_single_channel_outbound().await;
+    let session = client.session("uuid-a");
+
+    let task = tokio::spawn(async move {
+        let run = session
+            .execute_and_wait(
+                "playback",
+                Some("local_stream://moh"),
+                ExecuteOptions::default(),
+            )
+            .await
+            .unwrap();
+        run.cancel()
+            .await
+            .unwrap();
+        run.await
+    });
+
+    let (app_uuid, _) = read_execute_uuid(&mut mock, "playback").await;
+    mock.reply_ok()
+        .await;
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert!(cmd.starts_with("sendmsg uuid-a\n"), "got: {:?}", cmd);
+    assert!(cmd.contains("call-command: execute\n"), "got: {:?}", cmd);
+    assert!(cmd.contains("execute-app-name: break\n"), "got: {:?}", cmd);
+    mock.reply_ok()
+        .await;
+
+    send_execute_complete(&mut mock, &app_uuid, "FILE PLAYED", &[]).await;
+    let done = task
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(done.response(), Some("FILE PLAYED"));
+}
+
+#[tokio::test]
+async fn test_execute_and_wait_fails_when_channel_destroyed() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .execute_and_wait("park", None, "uuid-a", ExecuteOptions::default())
+                .await
+                .unwrap()
+                .await
+        }
+    });
+
+    read_execute_uuid(&mut mock, "park").await;
+    mock.reply_ok()
+        .await;
+    mock.send_event_plain("CHANNEL_DESTROY", &channel_headers("uuid-a"))
+        .await;
+
+    assert!(matches!(
+        task.await
+            .unwrap(),
+        Err(EslError::Generic { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_execute_and_wait_timeout() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+
+    let task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .execute_and_wait("park", None, "uuid-a", ExecuteOptions::default())
+                .await
+                .unwrap()
+                .timeout(Duration::from_millis(100))
+                .await
+        }
+    });
+
+    read_execute_uuid(&mut mock, "park").await;
+    mock.reply_ok()
+        .await;
+
+    match task
+        .await
+        .unwrap()
+    {
+        Err(EslError::Timeout { timeout_ms }) => assert_eq!(timeout_ms, 100),
+        other => panic!("expected Timeout, got {:?}", other),
+    }
+}
+
+async fn recv_session_event(session: &mut ChannelSession) -> Option<EslEvent> {
+    tokio::time::timeout(Duration::from_secs(5), session.recv())
+        .await
+        .expect("timeout")
+}
+
+fn channel_headers(uuid: &str) -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    headers.insert("Unique-ID".to_string(), uuid.to_string());
+    headers
+}
+
+#[tokio::test]
+async fn test_session_receives_only_its_channel_until_destroy() {
+    let (mut mock, client, mut events) = setup_connected_pair("ClueCon").await;
+    let mut session = client.session("uuid-a");
+    assert_eq!(session.uuid(), "uuid-a");
+
+    mock.send_event_plain("CHANNEL_ANSWER", &channel_headers("uuid-b"))
+        .await;
+    mock.send_event_plain("CHANNEL_ANSWER", &channel_headers("uuid-a"))
+        .await;
+    mock.send_event_plain("CHANNEL_DESTROY", &channel_headers("uuid-a"))
+        .await;
+    mock.send_event_plain("CHANNEL_ANSWER", &channel_headers("uuid-a"))
+        .await;
+
+    let event = recv_session_event(&mut session)
+        .await
+        .unwrap();
+    assert_eq!(event.event_type(), Some(EslEventType::ChannelAnswer));
+    assert_eq!(event.unique_id(), Some("uuid-a"));
+    let event = recv_session_event(&mut session)
+        .await
+        .unwrap();
+    assert_eq!(event.event_type(), Some(EslEventType::ChannelDestroy));
+    assert!(recv_session_event(&mut session)
+        .await
+        .is_none());
+
+    // The main stream still sees everything
+    for _ in 0..4 {
+        recv_event(&mut events).await;
+    }
+}
+
+#[tokio::test]
+async fn test_session_ends_on_disconnect() {
+    let (mock, client, _events) = setup_connected_pair("ClueCon").await;
+    let mut session = client.session("uuid-a");
+
+    mock.drop_connection()
+        .await;
+    assert!(recv_session_event(&mut session)
+        .await
+        .is_none());
+
+    // Sessions opened after the reader exited end immediately
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let mut late = client.session("uuid-b");
+    assert!(recv_session_event(&mut late)
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_session_helpers_target_uuid() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+    let session = client.session("uuid-a");
+
+    let task = tokio::spawn(async move {
+        session
+            .execute("playback", Some("tone_stream://%(100,0,440)"))
+            .await
+            .unwrap();
+        let unset = session
+            .getvar("missing")
+            .await
+            .unwrap();
+        let value = session
+            .getvar("sip_from_user")
+            .await
+            .unwrap();
+        let hangup = session
+            .hangup(Some(HangupCause::UserBusy))
+            .await;
+        (unset, value, hangup)
+    });
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert!(cmd.starts_with("sendmsg uuid-a\n"));
+    assert!(cmd.contains("execute-app-name: playback\n"));
+    mock.reply_ok()
+        .await;
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "api uuid_getvar uuid-a missing\n\n");
+    mock.reply_api("_undef_")
+        .await;
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "api uuid_getvar uuid-a sip_from_user\n\n");
+    mock.reply_api("1000")
+        .await;
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "api uuid_kill uuid-a USER_BUSY\n\n");
+    mock.reply_api("-ERR No such channel!\n")
+        .await;
+
+    let (unset, value, hangup) = task
+        .await
+        .unwrap();
+    assert_eq!(unset, None);
+    assert_eq!(value.as_deref(), Some("1000"));
+    assert!(matches!(hangup, Err(EslError::CommandFailed { .. })));
+}
+
+/// Accept an outbound socket for `uuid-a` started without `full`.
+async fn setup_single_channel_outbound() -> (MockClient, EslClient, EslEventStream) {
+    use tokio::net::{TcpListener, TcpStream};
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap();
+    let port = listener
+        .local_addr()
+        .unwrap()
+        .port();
+    let (accept_result, mock_stream) = tokio::join!(
+        EslClient::accept_outbound(&listener),
+        TcpStream::connect(("127.0.0.1", port))
+    );
+    let (client, events) = accept_result.unwrap();
+    let mut mock = MockClient::from_stream(mock_stream.unwrap());
+
+    let connect_task = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .connect_session()
+                .await
+        }
+    });
+    mock.read_command()
+        .await;
+    let mut channel_headers = HashMap::new();
+    channel_headers.insert("Unique-ID".to_string(), "uuid-a".to_string());
+    mock.send_connect_response_with_control(&channel_headers, "single-channel")
+        .await;
+    connect_task
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(client.is_single_channel());
+    (mock, client, events)
+}
+
+#[tokio::test]
+async fn test_session_helpers_single_channel_outbound() {
+    let (mut mock, client, _events) = setup_single_channel_outbound().await;
+
+    let session = client.session("uuid-a");
+    let task = tokio::spawn(async move {
+        let unset = session
+            .getvar("missing")
+            .await
+            .unwrap();
+        let value = session
+            .getvar("sip_from_user")
+            .await
+            .unwrap();
+        session
+            .setvar("greeting", "hello world")
+            .await
+            .unwrap();
+        session
+            .hangup(Some(HangupCause::UserBusy))
+            .await
+            .unwrap();
+        (unset, value)
+    });
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "getvar missing\n\n");
+    mock.reply_raw_text("")
+        .await;
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "getvar sip_from_user\n\n");
+    mock.reply_raw_text("1000")
+        .await;
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert!(cmd.starts_with("sendmsg uuid-a\n"));
+    assert!(cmd.contains("execute-app-name: set\n"));
+    assert!(cmd.contains("execute-app-arg: greeting=hello world\n"));
+    mock.reply_ok()
+        .await;
+
+    let cmd = mock
+        .read_command()
+        .await;
+    assert!(cmd.starts_with("sendmsg uuid-a\n"));
+    assert!(cmd.contains("call-command: hangup\n"));
+    assert!(cmd.contains("hangup-cause: USER_BUSY\n"));
+    mock.reply_ok()
+        .await;
+
+    let (unset, value) = task
+        .await
+        .unwrap();
+    assert_eq!(unset, None);
+    assert_eq!(value.as_deref(), Some("1000"));
+}
+
+#[tokio::test]
+async fn test_log_stream_receives_log_data() {
+    let (mut mock, client, mut events) = setup_connected_pair("ClueCon").await;
+    let mut logs = client.log_stream();
+
+    mock.send_log(6, "uuid-a", "Channel parked\n")
+        .await;
+    mock.send_heartbeat()
+        .await;
+
+    let line = tokio::time::timeout(Duration::from_secs(5), logs.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(line.level, LogLevel::Info);
+    assert_eq!(line.uuid(), Some("uuid-a"));
+    assert_eq!(line.line, Some(1042));
+    assert_eq!(line.text, "Channel parked");
+
+    // The log line never reaches the event stream
+    let event = recv_event(&mut events).await;
+    assert!(event.is_event_type(EslEventType::Heartbeat));
+}
+
+#[tokio::test]
+async fn test_log_stream_replaced_and_closed() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+    let mut first = client.log_stream();
+    let mut second = client.log_stream();
+
+    assert!(first
+        .recv()
+        .await
+        .is_none());
+
+    mock.send_log(3, "", "boom")
+        .await;
+    let line = tokio::time::timeout(Duration::from_secs(5), second.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(line.level, LogLevel::Error);
+    assert_eq!(line.uuid(), None);
+
+    mock.drop_connection()
+        .await;
+    let end = tokio::time::timeout(Duration::from_secs(5), second.recv())
+        .await
+        .unwrap();
+    assert!(end.is_none());
+}
@@ -3,16 +3,21 @@
 use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
 use std::collections::HashMap;
 use std::net::SocketAddr;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::net::{TcpListener, TcpStream};
 
+/// Any transport the mock can speak ESL over (TCP or TLS).
+pub trait MockStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> MockStream for T {}
+
 pub struct MockEslServer {
     listener: TcpListener,
     password: String,
 }
 
 pub struct MockClient {
-    stream: TcpStream,
+    stream: Box<dyn MockStream>,
 }
 
 impl MockEslServer {
@@ -39,40 +44,57 @@ impl MockEslServer {
 
     /// Accept a connection and perform the auth handshake
     pub async fn accept(&self) -> MockClient {
+        let stream = self
+            .accept_tcp()
+            .await;
+        self.authenticate(MockClient::from_stream(stream))
+            .await
+    }
+
+    /// Accept a TCP connection without any handshake
+    pub async fn accept_tcp(&self) -> TcpStream {
         let (stream, _addr) = self
             .listener
             .accept()
             .await
             .unwrap();
-        let mut client = MockClient { stream };
+        stream
+    }
 
-        // Send auth request
+    /// Perform the auth handshake on an accepted connection
+    pub async fn authenticate(&self, mut client: MockClient) -> MockClient {
         client
-            .send_raw("Content-Type: auth/request\n\n")
+            .serve_auth(&self.password)
+            .await;
+        client
+    }
+}
+
+impl MockClient {
+    pub fn from_stream(stream: impl MockStream + 'static) -> Self {
+        Self {
+            stream: Box::new(stream),
+        }
+    }
+
+    /// Run the server side of the auth handshake, expecting `password`
+    pub async fn serve_auth(&mut self, password: &str) {
+        // Send auth request
+        self.send_raw("Content-Type: auth/request\n\n")
             .await;
 
         // Read auth command
-        let cmd = client
+        let cmd = self
             .read_command()
             .await;
-        let expected = format!("auth {}\n\n", self.password);
+        let expected = format!("auth {}\n\n", password);
         if cmd == expected {
-            client
-                .reply_ok()
+            self.reply_ok()
                 .await;
         } else {
-            client
-                .reply_err("Invalid password")
+            self.reply_err("Invalid password")
                 .await;
         }
-
-        client
-    }
-}
-
-impl MockClient {
-    pub fn from_stream(stream: TcpStream) -> Self {
-        Self { stream }
     }
 
     pub async fn send_raw(&mut self, data: &str) {
@@ -105,6 +127,36 @@ impl MockClient {
             .await;
     }
 
+    /// Send a BACKGROUND_JOB event carrying a bgapi result body
+    pub async fn send_background_job(&mut self, job_uuid: &str, result: &str) {
+        let mut body = format!(
+            "Event-Name: BACKGROUND_JOB\nJob-UUID: {}\nJob-Command: status\nContent-Length: {}\n\n",
+            percent_encode(job_uuid.as_bytes(), NON_ALPHANUMERIC),
+            result.len()
+        );
+        body.push_str(result);
+
+        let envelope = format!(
+            "Content-Length: {}\nContent-Type: text/event-plain\n\n",
+            body.len()
+        );
+        self.send_raw(&format!("{}{}", envelope, body))
+            .await;
+    }
+
+    /// Send a log/data message as mod_event_socket formats it
+    pub async fn send_log(&mut self, level: u8, user_data: &str, text: &str) {
+        let data = format!(
+            "Content-Type: log/data\nContent-Length: {}\nLog-Level: {}\nText-Channel: 3\nLog-File: switch_ivr.c\nLog-Func: switch_ivr_park\nLog-Line: 1042\nUser-Data: {}\n\n{}",
+            text.len(),
+            level,
+            user_data,
+            text
+        );
+        self.send_raw(&data)
+            .await;
+    }
+
     /// Send a HEARTBEAT event with realistic headers
     pub async fn send_heartbeat(&mut self) {
         let mut headers = HashMap::new();
@@ -194,6 +246,16 @@ impl MockClient {
     /// (no outer envelope wrapper). This is unlike normal `command/reply`
     /// responses which write literal `Content-Type: command/reply\n`.
     pub async fn send_connect_response(&mut self, channel_headers: &HashMap<String, String>) {
+        self.send_connect_response_with_control(channel_headers, "full")
+            .await;
+    }
+
+    /// Send a connect response reporting `control` (`full` or `single-channel`).
+    pub async fn send_connect_response_with_control(
+        &mut self,
+        channel_headers: &HashMap<String, String>,
+        control: &str,
+    ) {
         let mut data = String::new();
 
         // Channel data headers first (like switch_channel_event_set_data)
@@ -210,7 +272,7 @@ impl MockClient {
             ("Content-Type", "command/reply"),
             ("Reply-Text", "+OK"),
             ("Socket-Mode", "async"),
-            ("Control", "full"),
+            ("Control", control),
         ];
         for (key, value) in &protocol_headers {
             data.push_str(&format!(
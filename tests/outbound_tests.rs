@@ -0,0 +1,373 @@
+//! Outbound server tests, with the test acting as FreeSWITCH
+
+#[allow(dead_code)]
+mod mock_server;
+
+use freeswitch_esl_tokio::{EslError, OutboundOptions, OutboundServer};
+use mock_server::MockClient;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+async fn start_server<F, Fut>(
+    options: OutboundOptions,
+    handler: F,
+) -> (
+    SocketAddr,
+    freeswitch_esl_tokio::ShutdownHandle,
+    tokio::task::JoinHandle<Result<(), EslError>>,
+)
+where
+    F: Fn(freeswitch_esl_tokio::OutboundCall) -> Fut + Send + Sync + 'static,
+    Fut: std::future::Future<Output = Result<(), EslError>> + Send + 'static,
+{
+    let server = OutboundServer::bind("127.0.0.1:0", options)
+        .await
+        .unwrap();
+    let addr = server
+        .local_addr()
+        .unwrap();
+    let shutdown = server.shutdown_handle();
+    let task = tokio::spawn(server.serve(handler));
+    (addr, shutdown, task)
+}
+
+/// Connect as FreeSWITCH and answer the session setup commands.
+async fn place_call(addr: SocketAddr, uuid: &str) -> MockClient {
+    let stream = TcpStream::connect(addr)
+        .await
+        .unwrap();
+    let mut fs = MockClient::from_stream(stream);
+
+    assert_eq!(
+        fs.read_command()
+            .await,
+        "connect\n\n"
+    );
+    let mut channel = HashMap::new();
+    channel.insert("Unique-ID".to_string(), uuid.to_string());
+    channel.insert("Caller-Caller-ID-Number".to_string(), "1000".to_string());
+    fs.send_connect_response(&channel)
+        .await;
+
+    assert_eq!(
+        fs.read_command()
+            .await,
+        "myevents plain\n\n"
+    );
+    fs.reply_ok()
+        .await;
+    assert_eq!(
+        fs.read_command()
+            .await,
+        "linger\n\n"
+    );
+    fs.reply_ok()
+        .await;
+    fs
+}
+
+#[tokio::test]
+async fn test_handler_gets_established_session() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let (addr, shutdown, task) = start_server(OutboundOptions::default(), move |call| {
+        let tx = tx.clone();
+        async move {
+            let caller = call
+                .channel_data
+                .header("Caller-Caller-ID-Number")
+                .map(str::to_string);
+            tx.send((
+                call.uuid()
+                    .map(str::to_string),
+                caller,
+            ))
+            .unwrap();
+            call.client
+                .api("status")
+                .await?;
+            Ok(())
+        }
+    })
+    .await;
+
+    let mut fs = place_call(addr, "call-1").await;
+    let (uuid, caller) = rx
+        .recv()
+        .await
+        .unwrap();
+    assert_eq!(uuid.as_deref(), Some("call-1"));
+    assert_eq!(caller.as_deref(), Some("1000"));
+
+    assert_eq!(
+        fs.read_command()
+            .await,
+        "api status\n\n"
+    );
+    fs.reply_api("UP")
+        .await;
+
+    shutdown.shutdown();
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_max_calls_rejects_extra_connections() {
+    let options = OutboundOptions {
+        max_calls: 1,
+        ..Default::default()
+    };
+    let (addr, shutdown, task) = start_server(options, |_call| async move {
+        std::future::pending::<()>().await;
+        Ok(())
+    })
+    .await;
+
+    let _first = place_call(addr, "call-1").await;
+
+    let mut second = TcpStream::connect(addr)
+        .await
+        .unwrap();
+    let mut buf = [0u8; 16];
+    let n = tokio::time::timeout(Duration::from_secs(5), second.read(&mut buf))
+        .await
+        .expect("rejected connection was not closed")
+        .unwrap_or(0);
+    assert_eq!(n, 0);
+
+    shutdown.shutdown();
+    task.abort();
+}
+
+#[tokio::test]
+async fn test_handler_panic_does_not_stop_server() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let (addr, shutdown, task) = start_server(OutboundOptions::default(), move |call| {
+        let tx = tx.clone();
+        async move {
+            if call.uuid() == Some("boom") {
+                panic!("handler bug");
+            }
+            tx.send(
+                call.uuid()
+                    .map(str::to_string),
+            )
+            .unwrap();
+            Ok(())
+        }
+    })
+    .await;
+
+    let _boom = place_call(addr, "boom").await;
+    let _ok = place_call(addr, "call-2").await;
+    assert_eq!(
+        rx.recv()
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("call-2")
+    );
+
+    shutdown.shutdown();
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_running_calls() {
+    let (release_tx, release_rx) = tokio::sync::watch::channel(false);
+    let (addr, shutdown, task) = start_server(OutboundOptions::default(), move |_call| {
+        let mut release_rx = release_rx.clone();
+        async move {
+            while !*release_rx.borrow() {
+                release_rx
+                    .changed()
+                    .await
+                    .unwrap();
+            }
+            Ok(())
+        }
+    })
+    .await;
+
+    let _call = place_call(addr, "call-1").await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    shutdown.shutdown();
+
+    // Still draining while the handler runs
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!task.is_finished());
+    assert!(TcpStream::connect(addr)
+        .await
+        .is_err());
+
+    release_tx
+        .send(true)
+        .unwrap();
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_drain_timeout_aborts_calls() {
+    let options = OutboundOptions {
+        drain_timeout: Some(Duration::from_millis(100)),
+        ..Default::default()
+    };
+    let (addr, shutdown, task) = start_server(options, |_call| async move {
+        std::future::pending::<()>().await;
+        Ok(())
+    })
+    .await;
+
+    let _call = place_call(addr, "call-1").await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    shutdown.shutdown();
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_single_channel_socket_skips_linger() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let (addr, shutdown, task) = start_server(OutboundOptions::default(), move |call| {
+        let tx = tx.clone();
+        async move {
+            tx.send(
+                call.client
+                    .is_single_channel(),
+            )
+            .unwrap();
+            call.client
+                .getvar("sip_from_user")
+                .await?;
+            Ok(())
+        }
+    })
+    .await;
+
+    let stream = TcpStream::connect(addr)
+        .await
+        .unwrap();
+    let mut fs = MockClient::from_stream(stream);
+    fs.read_command()
+        .await;
+    let mut channel = HashMap::new();
+    channel.insert("Unique-ID".to_string(), "call-1".to_string());
+    fs.send_connect_response_with_control(&channel, "single-channel")
+        .await;
+    assert_eq!(
+        fs.read_command()
+            .await,
+        "myevents plain\n\n"
+    );
+    fs.reply_ok()
+        .await;
+
+    // The handler runs without linger being sent
+    assert!(rx
+        .recv()
+        .await
+        .unwrap());
+    assert_eq!(
+        fs.read_command()
+            .await,
+        "getvar sip_from_user\n\n"
+    );
+    fs.reply_raw_text("1000")
+        .await;
+
+    shutdown.shutdown();
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_linger_failure_still_runs_handler() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let (addr, shutdown, task) = start_server(OutboundOptions::default(), move |call| {
+        let tx = tx.clone();
+        async move {
+            tx.send(
+                call.uuid()
+                    .map(str::to_string),
+            )
+            .unwrap();
+            Ok(())
+        }
+    })
+    .await;
+
+    let stream = TcpStream::connect(addr)
+        .await
+        .unwrap();
+    let mut fs = MockClient::from_stream(stream);
+    fs.read_command()
+        .await;
+    let mut channel = HashMap::new();
+    channel.insert("Unique-ID".to_string(), "call-1".to_string());
+    fs.send_connect_response(&channel)
+        .await;
+    fs.read_command()
+        .await;
+    fs.reply_ok()
+        .await;
+    assert_eq!(
+        fs.read_command()
+            .await,
+        "linger\n\n"
+    );
+    fs.reply_err("command not found")
+        .await;
+
+    assert_eq!(
+        rx.recv()
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("call-1")
+    );
+
+    shutdown.shutdown();
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_before_serve_stops_immediately() {
+    let server = OutboundServer::bind("127.0.0.1:0", OutboundOptions::default())
+        .await
+        .unwrap();
+    server
+        .shutdown_handle()
+        .shutdown();
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        server.serve(|_call| async move { Ok(()) }),
+    )
+    .await
+    .expect("server did not stop")
+    .unwrap();
+}
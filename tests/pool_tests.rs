@@ -0,0 +1,453 @@
+//! Connection pool tests using mock ESL servers
+
+#[allow(dead_code)]
+mod mock_server;
+
+use freeswitch_esl_tokio::{
+    EslError, EslEventType, EslPool, EslPoolEventStream, EventFormat, LoadBalance, PoolEvent,
+    PoolNode, PoolOptions, ReconnectOptions, SupervisedEvent,
+};
+use mock_server::{MockClient, MockEslServer};
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn options(strategy: LoadBalance) -> PoolOptions {
+    PoolOptions {
+        connections_per_node: 1,
+        strategy,
+        reconnect: ReconnectOptions {
+            initial_delay: Duration::from_secs(10),
+            jitter: 0.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+async fn connect(
+    a: &MockEslServer,
+    b: &MockEslServer,
+    options: PoolOptions,
+) -> (MockClient, MockClient, EslPool, EslPoolEventStream) {
+    let nodes = vec![
+        PoolNode::new("127.0.0.1", a.port(), "ClueCon"),
+        PoolNode::new("127.0.0.1", b.port(), "ClueCon"),
+    ];
+    let (mock_a, mock_b, result) =
+        tokio::join!(a.accept(), b.accept(), EslPool::connect(nodes, options));
+    let (pool, events) = result.expect("pool connect failed");
+    (mock_a, mock_b, pool, events)
+}
+
+async fn next_item(events: &mut EslPoolEventStream) -> PoolEvent {
+    tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("timeout")
+        .expect("stream ended")
+}
+
+/// Run `pool.api(command)` and answer it on `mock`.
+async fn api_on(pool: &EslPool, mock: &mut MockClient, command: &str, reply: &str) -> String {
+    let (response, _) = tokio::join!(pool.api(command), async {
+        assert_eq!(
+            mock.read_command()
+                .await,
+            format!("api {}\n\n", command)
+        );
+        mock.reply_api(reply)
+            .await;
+    });
+    response
+        .unwrap()
+        .body()
+        .unwrap()
+        .to_string()
+}
+
+fn channel_headers(uuid: &str) -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    headers.insert("Unique-ID".to_string(), uuid.to_string());
+    headers.insert("Core-UUID".to_string(), "core-b".to_string());
+    headers.insert("FreeSWITCH-Hostname".to_string(), "fs-b".to_string());
+    headers
+}
+
+#[tokio::test]
+async fn test_empty_node_list_rejected() {
+    let result = EslPool::connect(Vec::new(), PoolOptions::default()).await;
+    assert!(matches!(result, Err(EslError::Generic { .. })));
+}
+
+#[tokio::test]
+async fn test_round_robin_alternates_nodes() {
+    let a = MockEslServer::start("ClueCon").await;
+    let b = MockEslServer::start("ClueCon").await;
+    let (mut mock_a, mut mock_b, pool, _events) =
+        connect(&a, &b, options(LoadBalance::RoundRobin)).await;
+
+    assert_eq!(api_on(&pool, &mut mock_a, "status", "a").await, "a");
+    assert_eq!(api_on(&pool, &mut mock_b, "status", "b").await, "b");
+    assert_eq!(api_on(&pool, &mut mock_a, "status", "a").await, "a");
+    assert_eq!(api_on(&pool, &mut mock_b, "status", "b").await, "b");
+}
+
+#[tokio::test]
+async fn test_least_loaded_avoids_busy_node() {
+    let a = MockEslServer::start("ClueCon").await;
+    let b = MockEslServer::start("ClueCon").await;
+    let (mut mock_a, mut mock_b, pool, _events) =
+        connect(&a, &b, options(LoadBalance::LeastLoaded)).await;
+
+    // Leave a slow command in flight on node A
+    let slow = tokio::spawn({
+        let pool = pool.clone();
+        async move {
+            pool.api("show calls")
+                .await
+        }
+    });
+    assert_eq!(
+        mock_a
+            .read_command()
+            .await,
+        "api show calls\n\n"
+    );
+
+    // Round robin would send the second of these to A
+    assert_eq!(api_on(&pool, &mut mock_b, "status", "b").await, "b");
+    assert_eq!(api_on(&pool, &mut mock_b, "status", "b").await, "b");
+
+    mock_a
+        .reply_api("0 total.")
+        .await;
+    assert_eq!(
+        slow.await
+            .unwrap()
+            .unwrap()
+            .body(),
+        Some("0 total.")
+    );
+}
+
+#[tokio::test]
+async fn test_unhealthy_node_skipped() {
+    let a = MockEslServer::start("ClueCon").await;
+    let b = MockEslServer::start("ClueCon").await;
+    let (mock_a, mut mock_b, pool, mut events) =
+        connect(&a, &b, options(LoadBalance::RoundRobin)).await;
+
+    mock_a
+        .drop_connection()
+        .await;
+    loop {
+        let item = next_item(&mut events).await;
+        if matches!(item.event, SupervisedEvent::Disconnected(_)) {
+            assert_eq!(
+                item.node
+                    .index,
+                0
+            );
+            break;
+        }
+    }
+    assert_eq!(pool.healthy_connections(0), 0);
+    assert_eq!(pool.healthy_connections(1), 1);
+
+    assert_eq!(api_on(&pool, &mut mock_b, "status", "b").await, "b");
+    assert_eq!(api_on(&pool, &mut mock_b, "status", "b").await, "b");
+
+    mock_b
+        .drop_connection()
+        .await;
+    while pool.is_connected() {
+        next_item(&mut events).await;
+    }
+    assert!(matches!(
+        pool.api("status")
+            .await,
+        Err(EslError::NotConnected)
+    ));
+}
+
+#[tokio::test]
+async fn test_channel_pinned_from_events() {
+    let a = MockEslServer::start("ClueCon").await;
+    let b = MockEslServer::start("ClueCon").await;
+    let (mut mock_a, mut mock_b, pool, mut events) =
+        connect(&a, &b, options(LoadBalance::RoundRobin)).await;
+
+    let (result, _, _) = tokio::join!(
+        pool.subscribe_events(
+            EventFormat::Plain,
+            &[EslEventType::ChannelCreate, EslEventType::ChannelDestroy]
+        ),
+        async {
+            mock_a
+                .read_command()
+                .await;
+            mock_a
+                .reply_ok()
+                .await;
+        },
+        async {
+            mock_b
+                .read_command()
+                .await;
+            mock_b
+                .reply_ok()
+                .await;
+        }
+    );
+    result.unwrap();
+
+    mock_b
+        .send_event_plain("CHANNEL_CREATE", &channel_headers("call-b"))
+        .await;
+    let item = next_item(&mut events).await;
+    assert!(matches!(item.event, SupervisedEvent::Event(_)));
+    assert_eq!(
+        item.node
+            .index,
+        1
+    );
+
+    // Every channel command goes to B regardless of rotation
+    for _ in 0..2 {
+        let (response, _) = tokio::join!(
+            pool.api_for_channel("call-b", "uuid_getvar call-b foo"),
+            async {
+                assert_eq!(
+                    mock_b
+                        .read_command()
+                        .await,
+                    "api uuid_getvar call-b foo\n\n"
+                );
+                mock_b
+                    .reply_api("bar")
+                    .await;
+            }
+        );
+        assert_eq!(
+            response
+                .unwrap()
+                .body(),
+            Some("bar")
+        );
+    }
+
+    mock_b
+        .send_event_plain("CHANNEL_DESTROY", &channel_headers("call-b"))
+        .await;
+    next_item(&mut events).await;
+
+    // Ownership forgotten: the pool has to ask the nodes again
+    let (result, _, _) = tokio::join!(
+        pool.channel_node("call-b"),
+        async {
+            assert_eq!(
+                mock_a
+                    .read_command()
+                    .await,
+                "api uuid_exists call-b\n\n"
+            );
+            mock_a
+                .reply_api("false")
+                .await;
+        },
+        async {
+            assert_eq!(
+                mock_b
+                    .read_command()
+                    .await,
+                "api uuid_exists call-b\n\n"
+            );
+            mock_b
+                .reply_api("false")
+                .await;
+        }
+    );
+    assert!(matches!(result, Err(EslError::Generic { .. })));
+}
+
+#[tokio::test]
+async fn test_channel_owner_found_with_uuid_exists() {
+    let a = MockEslServer::start("ClueCon").await;
+    let b = MockEslServer::start("ClueCon").await;
+    let (mut mock_a, mut mock_b, pool, _events) =
+        connect(&a, &b, options(LoadBalance::RoundRobin)).await;
+
+    let (node, _, _) = tokio::join!(
+        pool.channel_node("call-x"),
+        async {
+            assert_eq!(
+                mock_a
+                    .read_command()
+                    .await,
+                "api uuid_exists call-x\n\n"
+            );
+            mock_a
+                .reply_api("false")
+                .await;
+        },
+        async {
+            assert_eq!(
+                mock_b
+                    .read_command()
+                    .await,
+                "api uuid_exists call-x\n\n"
+            );
+            mock_b
+                .reply_api("true")
+                .await;
+        }
+    );
+    assert_eq!(node.unwrap(), 1);
+
+    // Cached: no further uuid_exists queries
+    assert_eq!(
+        pool.channel_node("call-x")
+            .await
+            .unwrap(),
+        1
+    );
+
+    pool.pin_channel("call-x", 0);
+    assert_eq!(
+        pool.channel_node("call-x")
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_events_tagged_with_node_identity() {
+    let a = MockEslServer::start("ClueCon").await;
+    let b = MockEslServer::start("ClueCon").await;
+    let (mut mock_a, mut mock_b, pool, mut events) =
+        connect(&a, &b, options(LoadBalance::RoundRobin)).await;
+
+    assert_eq!(pool.nodes()[0].addr, format!("127.0.0.1:{}", a.port()));
+    assert_eq!(pool.nodes()[0].core_uuid, None);
+
+    mock_a
+        .send_heartbeat()
+        .await;
+    let item = next_item(&mut events).await;
+    assert_eq!(
+        item.node
+            .index,
+        0
+    );
+    assert_eq!(
+        item.node
+            .core_uuid
+            .as_deref(),
+        Some("test-core-uuid")
+    );
+    assert_eq!(
+        item.node
+            .hostname
+            .as_deref(),
+        Some("test-host")
+    );
+
+    mock_b
+        .send_event_plain("CHANNEL_CREATE", &channel_headers("call-b"))
+        .await;
+    let item = next_item(&mut events).await;
+    assert_eq!(
+        item.node
+            .index,
+        1
+    );
+    assert_eq!(
+        item.node
+            .hostname
+            .as_deref(),
+        Some("fs-b")
+    );
+
+    let nodes = pool.nodes();
+    assert_eq!(
+        nodes[0]
+            .hostname
+            .as_deref(),
+        Some("test-host")
+    );
+    assert_eq!(
+        nodes[1]
+            .core_uuid
+            .as_deref(),
+        Some("core-b")
+    );
+}
+
+#[tokio::test]
+async fn test_reconnect_forgets_node_channels() {
+    let a = MockEslServer::start("ClueCon").await;
+    let b = MockEslServer::start("ClueCon").await;
+    let mut opts = options(LoadBalance::RoundRobin);
+    opts.reconnect
+        .initial_delay = Duration::from_millis(10);
+    let (mock_a, mut mock_b, pool, mut events) = connect(&a, &b, opts).await;
+
+    pool.pin_channel("call-a", 0);
+    pool.pin_channel("call-b", 1);
+
+    // CHANNEL_DESTROY for call-a may be lost while node A is down
+    mock_a
+        .drop_connection()
+        .await;
+    let (_, mut mock_a) = tokio::join!(
+        async {
+            loop {
+                let item = next_item(&mut events).await;
+                if matches!(item.event, SupervisedEvent::Reconnected { .. }) {
+                    assert_eq!(
+                        item.node
+                            .index,
+                        0
+                    );
+                    break;
+                }
+            }
+        },
+        a.accept()
+    );
+
+    // Node B's pin survives without a lookup
+    assert_eq!(
+        pool.channel_node("call-b")
+            .await
+            .unwrap(),
+        1
+    );
+
+    let (result, _, _) = tokio::join!(
+        pool.channel_node("call-a"),
+        async {
+            assert_eq!(
+                mock_a
+                    .read_command()
+                    .await,
+                "api uuid_exists call-a\n\n"
+            );
+            mock_a
+                .reply_api("false")
+                .await;
+        },
+        async {
+            assert_eq!(
+                mock_b
+                    .read_command()
+                    .await,
+                "api uuid_exists call-a\n\n"
+            );
+            mock_b
+                .reply_api("false")
+                .await;
+        }
+    );
+    assert!(matches!(result, Err(EslError::Generic { .. })));
+}
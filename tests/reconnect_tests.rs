@@ -0,0 +1,348 @@
+//! Reconnecting client tests using mock ESL server
+
+#[allow(dead_code)]
+mod mock_server;
+
+use freeswitch_esl_tokio::{
+    EslEventType, EventFormat, ReconnectOptions, ReconnectingClient, ReconnectingEventStream,
+    SupervisedEvent,
+};
+use mock_server::{MockClient, MockEslServer};
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn fast_options() -> ReconnectOptions {
+    ReconnectOptions {
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+        jitter: 0.0,
+        ..Default::default()
+    }
+}
+
+async fn next_item(events: &mut ReconnectingEventStream) -> SupervisedEvent {
+    tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("timeout")
+        .expect("stream ended")
+}
+
+async fn connect(
+    server: &MockEslServer,
+    options: ReconnectOptions,
+) -> (MockClient, ReconnectingClient, ReconnectingEventStream) {
+    let (mock, result) = tokio::join!(
+        server.accept(),
+        ReconnectingClient::connect("127.0.0.1", server.port(), "ClueCon", options)
+    );
+    let (client, events) = result.expect("connect failed");
+    (mock, client, events)
+}
+
+#[tokio::test]
+async fn test_initial_auth_failure_is_returned() {
+    let server = MockEslServer::start("correct_password").await;
+    let (_, result) = tokio::join!(
+        server.accept(),
+        ReconnectingClient::connect("127.0.0.1", server.port(), "wrong", fast_options())
+    );
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_reconnect_replays_subscriptions_and_filters() {
+    let server = MockEslServer::start("ClueCon").await;
+    let (mut mock, client, mut events) = connect(&server, fast_options()).await;
+
+    let setup = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .subscribe_events(
+                    EventFormat::Plain,
+                    &[EslEventType::ChannelAnswer, EslEventType::Heartbeat],
+                )
+                .await
+                .unwrap();
+            client
+                .subscribe_events_raw(EventFormat::Plain, "CUSTOM sofia::register")
+                .await
+                .unwrap();
+            client
+                .filter_events("Event-Name", "CHANNEL_ANSWER")
+                .await
+                .unwrap();
+        }
+    });
+    for _ in 0..3 {
+        mock.read_command()
+            .await;
+        mock.reply_ok()
+            .await;
+    }
+    setup
+        .await
+        .unwrap();
+
+    mock.drop_connection()
+        .await;
+
+    assert!(matches!(
+        next_item(&mut events).await,
+        SupervisedEvent::Disconnected(_)
+    ));
+    assert!(matches!(
+        next_item(&mut events).await,
+        SupervisedEvent::Reconnecting { attempt: 1, .. }
+    ));
+    assert!(!client.is_connected());
+
+    let mut mock = server
+        .accept()
+        .await;
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(
+        cmd,
+        "event plain CHANNEL_ANSWER HEARTBEAT CUSTOM sofia::register\n\n"
+    );
+    mock.reply_ok()
+        .await;
+    let cmd = mock
+        .read_command()
+        .await;
+    assert_eq!(cmd, "filter Event-Name CHANNEL_ANSWER\n\n");
+    mock.reply_ok()
+        .await;
+
+    assert!(matches!(
+        next_item(&mut events).await,
+        SupervisedEvent::Reconnected { attempts: 1 }
+    ));
+    assert!(client.is_connected());
+
+    let mut headers = HashMap::new();
+    headers.insert("Unique-ID".to_string(), "after-reconnect".to_string());
+    mock.send_event_plain("CHANNEL_ANSWER", &headers)
+        .await;
+    match next_item(&mut events).await {
+        SupervisedEvent::Event(event) => {
+            assert_eq!(event.unique_id(), Some("after-reconnect"));
+        }
+        other => panic!("expected event, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_commands_fail_while_disconnected() {
+    let server = MockEslServer::start("ClueCon").await;
+    let options = ReconnectOptions {
+        initial_delay: Duration::from_secs(60),
+        jitter: 0.0,
+        ..Default::default()
+    };
+    let (mock, client, mut events) = connect(&server, options).await;
+
+    mock.drop_connection()
+        .await;
+    assert!(matches!(
+        next_item(&mut events).await,
+        SupervisedEvent::Disconnected(_)
+    ));
+
+    let result = client
+        .api("status")
+        .await;
+    assert!(result
+        .unwrap_err()
+        .is_connection_error());
+}
+
+#[tokio::test]
+async fn test_max_attempts_ends_stream() {
+    let server = MockEslServer::start("ClueCon").await;
+    let options = ReconnectOptions {
+        max_attempts: Some(2),
+        ..fast_options()
+    };
+    let (mock, _client, mut events) = connect(&server, options).await;
+    drop(server);
+    mock.drop_connection()
+        .await;
+
+    assert!(matches!(
+        next_item(&mut events).await,
+        SupervisedEvent::Disconnected(_)
+    ));
+    for expected in 1..=2 {
+        match next_item(&mut events).await {
+            SupervisedEvent::Reconnecting { attempt, .. } => assert_eq!(attempt, expected),
+            other => panic!("expected Reconnecting, got {:?}", other),
+        }
+    }
+    let end = tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("timeout");
+    assert!(end.is_none());
+}
+
+#[tokio::test]
+async fn test_disconnect_stops_supervisor() {
+    let server = MockEslServer::start("ClueCon").await;
+    let (_mock, client, mut events) = connect(&server, fast_options()).await;
+
+    client
+        .disconnect()
+        .await
+        .unwrap();
+
+    let end = tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("timeout");
+    assert!(end.is_none());
+    assert!(!client.is_connected());
+}
+
+#[tokio::test]
+async fn test_reconnect_replays_client_state_and_log() {
+    let server = MockEslServer::start("ClueCon").await;
+    let (mut mock, client, mut events) = connect(&server, fast_options()).await;
+
+    let setup = tokio::spawn({
+        let client = client.clone();
+        async move {
+            // Subscriptions made on the raw client are replayed too
+            client
+                .client()
+                .unwrap()
+                .subscribe_events(EventFormat::Plain, &[EslEventType::ChannelHangup])
+                .await
+                .unwrap();
+            client
+                .log("info")
+                .await
+                .unwrap();
+        }
+    });
+    for _ in 0..2 {
+        mock.read_command()
+            .await;
+        mock.reply_ok()
+            .await;
+    }
+    setup
+        .await
+        .unwrap();
+
+    mock.drop_connection()
+        .await;
+    assert!(matches!(
+        next_item(&mut events).await,
+        SupervisedEvent::Disconnected(_)
+    ));
+    assert!(client
+        .log_stream()
+        .is_err());
+    assert_eq!(
+        client
+            .subscriptions()
+            .event_list()
+            .as_deref(),
+        Some("CHANNEL_HANGUP")
+    );
+
+    let mut mock = server
+        .accept()
+        .await;
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "event plain CHANNEL_HANGUP\n\n"
+    );
+    mock.reply_ok()
+        .await;
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "log info\n\n"
+    );
+    mock.reply_ok()
+        .await;
+    loop {
+        if let SupervisedEvent::Reconnected { .. } = next_item(&mut events).await {
+            break;
+        }
+    }
+
+    let mut logs = client
+        .log_stream()
+        .unwrap();
+    mock.send_log(6, "", "after reconnect")
+        .await;
+    let line = tokio::time::timeout(Duration::from_secs(5), logs.recv())
+        .await
+        .expect("timeout")
+        .expect("log stream ended");
+    assert_eq!(line.text, "after reconnect");
+}
+
+#[tokio::test]
+async fn test_reconnect_replays_all_with_exclusions() {
+    let server = MockEslServer::start("ClueCon").await;
+    let (mut mock, client, mut events) = connect(&server, fast_options()).await;
+
+    let setup = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .subscribe_events(EventFormat::Plain, &[EslEventType::All])
+                .await
+                .unwrap();
+            client
+                .nixevent(&[EslEventType::Heartbeat])
+                .await
+                .unwrap();
+        }
+    });
+    for _ in 0..2 {
+        mock.read_command()
+            .await;
+        mock.reply_ok()
+            .await;
+    }
+    setup
+        .await
+        .unwrap();
+
+    mock.drop_connection()
+        .await;
+    let mut mock = server
+        .accept()
+        .await;
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "event plain ALL\n\n"
+    );
+    mock.reply_ok()
+        .await;
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "nixevent HEARTBEAT\n\n"
+    );
+    mock.reply_ok()
+        .await;
+    loop {
+        if let SupervisedEvent::Reconnected { .. } = next_item(&mut events).await {
+            break;
+        }
+    }
+    assert_eq!(
+        client
+            .subscriptions()
+            .excluded_events(),
+        ["HEARTBEAT"]
+    );
+}
@@ -0,0 +1,206 @@
+//! Fan-out event receiver tests using mock ESL server
+
+#[allow(dead_code)]
+mod mock_server;
+
+use freeswitch_esl_tokio::{
+    EslError, EslEvent, EslEventStream, EslEventType, EventFilter, EventFormat, FilteredEventStream,
+};
+use mock_server::{setup_connected_pair, MockClient};
+use std::collections::HashMap;
+use std::time::Duration;
+
+async fn send(mock: &mut MockClient, event_name: &str, uuid: &str, seq: u32) {
+    let mut headers = HashMap::new();
+    headers.insert("Unique-ID".to_string(), uuid.to_string());
+    headers.insert("Sequence".to_string(), seq.to_string());
+    mock.send_event_plain(event_name, &headers)
+        .await;
+}
+
+async fn next(stream: &mut FilteredEventStream) -> Result<EslEvent, EslError> {
+    tokio::time::timeout(Duration::from_secs(5), stream.recv())
+        .await
+        .expect("timeout")
+        .expect("stream ended")
+}
+
+async fn next_main(events: &mut EslEventStream) -> EslEvent {
+    tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("timeout")
+        .expect("stream ended")
+        .expect("event error")
+}
+
+fn seq(event: &EslEvent) -> &str {
+    event
+        .header("Sequence")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_receivers_get_only_matching_events() {
+    let (mut mock, client, mut events) = setup_connected_pair("ClueCon").await;
+    let mut hangups = client
+        .subscribe_stream(EventFilter::new().event_types(&[EslEventType::ChannelHangupComplete]));
+    let mut call_b = client.subscribe_stream(EventFilter::new().uuid("call-b"));
+
+    send(&mut mock, "CHANNEL_ANSWER", "call-a", 1).await;
+    send(&mut mock, "CHANNEL_ANSWER", "call-b", 2).await;
+    send(&mut mock, "CHANNEL_HANGUP_COMPLETE", "call-a", 3).await;
+    send(&mut mock, "CHANNEL_HANGUP_COMPLETE", "call-b", 4).await;
+
+    // The main stream still sees everything
+    for expected in ["1", "2", "3", "4"] {
+        assert_eq!(seq(&next_main(&mut events).await), expected);
+    }
+
+    assert_eq!(
+        seq(&next(&mut hangups)
+            .await
+            .unwrap()),
+        "3"
+    );
+    assert_eq!(
+        seq(&next(&mut hangups)
+            .await
+            .unwrap()),
+        "4"
+    );
+    assert_eq!(
+        seq(&next(&mut call_b)
+            .await
+            .unwrap()),
+        "2"
+    );
+    assert_eq!(
+        seq(&next(&mut call_b)
+            .await
+            .unwrap()),
+        "4"
+    );
+}
+
+#[tokio::test]
+async fn test_slow_receiver_lags_without_starving_others() {
+    let (mut mock, client, mut events) = setup_connected_pair("ClueCon").await;
+    let mut slow = client.subscribe_stream_with_capacity(EventFilter::new(), 1);
+    let mut fast = client.subscribe_stream(EventFilter::new());
+
+    for n in 1..=3 {
+        send(&mut mock, "HEARTBEAT", "", n).await;
+    }
+    for expected in ["1", "2", "3"] {
+        assert_eq!(
+            seq(&next(&mut fast)
+                .await
+                .unwrap()),
+            expected
+        );
+        assert_eq!(seq(&next_main(&mut events).await), expected);
+    }
+    assert_eq!(slow.skipped_count(), 2);
+
+    assert_eq!(
+        seq(&next(&mut slow)
+            .await
+            .unwrap()),
+        "1"
+    );
+    send(&mut mock, "HEARTBEAT", "", 4).await;
+    assert!(matches!(
+        next(&mut slow).await,
+        Err(EslError::Lagged { count: 2 })
+    ));
+    assert_eq!(
+        seq(&next(&mut slow)
+            .await
+            .unwrap()),
+        "4"
+    );
+    assert_eq!(
+        seq(&next(&mut fast)
+            .await
+            .unwrap()),
+        "4"
+    );
+}
+
+#[tokio::test]
+async fn test_dropped_receiver_does_not_affect_others() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+    let dropped = client.subscribe_stream(EventFilter::new());
+    let mut kept = client.subscribe_stream(EventFilter::new());
+    drop(dropped);
+
+    send(&mut mock, "HEARTBEAT", "", 1).await;
+    assert_eq!(
+        seq(&next(&mut kept)
+            .await
+            .unwrap()),
+        "1"
+    );
+}
+
+#[tokio::test]
+async fn test_receivers_end_on_disconnect() {
+    let (mock, client, _events) = setup_connected_pair("ClueCon").await;
+    let mut stream = client.subscribe_stream(EventFilter::new());
+
+    mock.drop_connection()
+        .await;
+    let end = tokio::time::timeout(Duration::from_secs(5), stream.recv())
+        .await
+        .expect("timeout");
+    assert!(end.is_none());
+
+    // Opened after the reader exited: ends at once
+    let mut late = client.subscribe_stream(EventFilter::new());
+    assert!(late
+        .recv()
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_subscribe_events_filtered_pushes_server_filters() {
+    let (mut mock, client, _events) = setup_connected_pair("ClueCon").await;
+    let filter: EventFilter =
+        "Event-Name in (CHANNEL_ANSWER, CHANNEL_HANGUP) and Unique-ID =~ /^call-/"
+            .parse()
+            .unwrap();
+
+    let (result, _) = tokio::join!(
+        client.subscribe_events_filtered(EventFormat::Plain, &[EslEventType::All], filter),
+        async {
+            // Filters are in place before any event is subscribed
+            assert_eq!(
+                mock.read_command()
+                    .await,
+                "filter Unique-ID /^call-/\n\n"
+            );
+            mock.reply_ok()
+                .await;
+            assert_eq!(
+                mock.read_command()
+                    .await,
+                "event plain ALL\n\n"
+            );
+            mock.reply_ok()
+                .await;
+        }
+    );
+    let mut calls = result.unwrap();
+
+    // The server would drop these, but the client filter is complete on its own
+    send(&mut mock, "CHANNEL_ANSWER", "other-a", 1).await;
+    send(&mut mock, "CHANNEL_CREATE", "call-a", 2).await;
+    send(&mut mock, "CHANNEL_HANGUP", "call-a", 3).await;
+    assert_eq!(
+        seq(&next(&mut calls)
+            .await
+            .unwrap()),
+        "3"
+    );
+}
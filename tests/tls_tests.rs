@@ -0,0 +1,275 @@
+//! TLS transport tests (run with `--features tls`)
+
+#![cfg(feature = "tls")]
+
+#[allow(dead_code)]
+mod mock_server;
+
+use freeswitch_esl_tokio::tls::rustls::crypto::{ring, CryptoProvider};
+use freeswitch_esl_tokio::tls::rustls::pki_types::pem::PemObject;
+use freeswitch_esl_tokio::tls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use freeswitch_esl_tokio::tls::rustls::server::WebPkiClientVerifier;
+use freeswitch_esl_tokio::tls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+use freeswitch_esl_tokio::tls::{TlsAcceptOptions, TlsConnectOptions, TlsIdentity};
+use freeswitch_esl_tokio::{
+    EslClient, EslConnectOptions, EslError, OutboundOptions, OutboundServer,
+};
+use mock_server::{MockClient, MockEslServer};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+const CA_PEM: &[u8] = include_bytes!("fixtures/tls/ca.pem");
+const SERVER_PEM: &[u8] = include_bytes!("fixtures/tls/server.pem");
+const SERVER_KEY: &[u8] = include_bytes!("fixtures/tls/server.key");
+const CLIENT_PEM: &[u8] = include_bytes!("fixtures/tls/client.pem");
+const CLIENT_KEY: &[u8] = include_bytes!("fixtures/tls/client.key");
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn test_ca() -> Arc<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for cert in CertificateDer::pem_slice_iter(CA_PEM) {
+        roots
+            .add(cert.unwrap())
+            .unwrap();
+    }
+    Arc::new(roots)
+}
+
+/// TLS acceptor for the mock FreeSWITCH (stunnel) side.
+fn mock_acceptor(require_client_cert: bool) -> TlsAcceptor {
+    let builder = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap();
+    let builder = if require_client_cert {
+        builder.with_client_cert_verifier(
+            WebPkiClientVerifier::builder_with_provider(test_ca(), provider())
+                .build()
+                .unwrap(),
+        )
+    } else {
+        builder.with_no_client_auth()
+    };
+    let certs = CertificateDer::pem_slice_iter(SERVER_PEM)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_slice(SERVER_KEY).unwrap();
+    TlsAcceptor::from(Arc::new(
+        builder
+            .with_single_cert(certs, key)
+            .unwrap(),
+    ))
+}
+
+/// TLS connector for the mock FreeSWITCH side of outbound connections.
+fn mock_connector() -> TlsConnector {
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(test_ca())
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+fn connect_options(identity: Option<TlsIdentity>) -> EslConnectOptions {
+    EslConnectOptions {
+        tls: Some(
+            TlsConnectOptions::new(CA_PEM, identity)
+                .unwrap()
+                .server_name("localhost"),
+        ),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_inbound_over_tls() {
+    let server = MockEslServer::start("ClueCon").await;
+    let port = server.port();
+    let acceptor = mock_acceptor(false);
+
+    let mock = async {
+        let tcp = server
+            .accept_tcp()
+            .await;
+        let tls = acceptor
+            .accept(tcp)
+            .await
+            .unwrap();
+        server
+            .authenticate(MockClient::from_stream(tls))
+            .await
+    };
+    let (mut mock, result) = tokio::join!(
+        mock,
+        EslClient::connect_with_options("127.0.0.1", port, "ClueCon", connect_options(None))
+    );
+    let (client, _events) = result.unwrap();
+
+    let (response, _) = tokio::join!(client.api("status"), async {
+        assert_eq!(
+            mock.read_command()
+                .await,
+            "api status\n\n"
+        );
+        mock.reply_api("UP 0 years")
+            .await;
+    });
+    assert_eq!(
+        response
+            .unwrap()
+            .body(),
+        Some("UP 0 years")
+    );
+}
+
+#[tokio::test]
+async fn test_inbound_mutual_tls() {
+    let server = MockEslServer::start("ClueCon").await;
+    let port = server.port();
+    let acceptor = mock_acceptor(true);
+
+    let mock = async {
+        let tcp = server
+            .accept_tcp()
+            .await;
+        let tls = acceptor
+            .accept(tcp)
+            .await
+            .unwrap();
+        server
+            .authenticate(MockClient::from_stream(tls))
+            .await
+    };
+    let identity = TlsIdentity::from_pem(CLIENT_PEM, CLIENT_KEY).unwrap();
+    let (_mock, result) = tokio::join!(
+        mock,
+        EslClient::connect_with_options(
+            "127.0.0.1",
+            port,
+            "ClueCon",
+            connect_options(Some(identity))
+        )
+    );
+    assert!(result
+        .unwrap()
+        .0
+        .is_connected());
+}
+
+#[tokio::test]
+async fn test_inbound_untrusted_server_rejected() {
+    let server = MockEslServer::start("ClueCon").await;
+    let port = server.port();
+    let acceptor = mock_acceptor(false);
+    tokio::spawn(async move {
+        let tcp = server
+            .accept_tcp()
+            .await;
+        let _ = acceptor
+            .accept(tcp)
+            .await;
+    });
+
+    // Trust an unrelated certificate instead of the test CA
+    let options = EslConnectOptions {
+        tls: Some(
+            TlsConnectOptions::new(CLIENT_PEM, None)
+                .unwrap()
+                .server_name("localhost"),
+        ),
+        ..Default::default()
+    };
+    let err = EslClient::connect_with_options("127.0.0.1", port, "ClueCon", options)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EslError::Io(_)), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn test_invalid_pem_rejected() {
+    assert!(matches!(
+        TlsConnectOptions::new(b"not a certificate", None),
+        Err(EslError::Tls { .. })
+    ));
+    assert!(matches!(
+        TlsIdentity::from_pem(CLIENT_PEM, b"not a key"),
+        Err(EslError::Tls { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_outbound_server_over_tls() {
+    let options = OutboundOptions {
+        myevents: None,
+        linger: false,
+        connect_options: EslConnectOptions {
+            tls_accept: Some(
+                TlsAcceptOptions::new(TlsIdentity::from_pem(SERVER_PEM, SERVER_KEY).unwrap(), None)
+                    .unwrap(),
+            ),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let server = OutboundServer::bind("127.0.0.1:0", options)
+        .await
+        .unwrap();
+    let addr = server
+        .local_addr()
+        .unwrap();
+    let shutdown = server.shutdown_handle();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let task = tokio::spawn(server.serve(move |call| {
+        let tx = tx.clone();
+        async move {
+            tx.send(
+                call.uuid()
+                    .map(str::to_string),
+            )
+            .unwrap();
+            Ok(())
+        }
+    }));
+
+    // Connect as FreeSWITCH through a TLS tunnel
+    let tcp = TcpStream::connect(addr)
+        .await
+        .unwrap();
+    let tls = mock_connector()
+        .connect(ServerName::try_from("localhost").unwrap(), tcp)
+        .await
+        .unwrap();
+    let mut fs = MockClient::from_stream(tls);
+
+    assert_eq!(
+        fs.read_command()
+            .await,
+        "connect\n\n"
+    );
+    let mut channel = HashMap::new();
+    channel.insert("Unique-ID".to_string(), "tls-call".to_string());
+    fs.send_connect_response(&channel)
+        .await;
+
+    assert_eq!(
+        rx.recv()
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("tls-call")
+    );
+
+    shutdown.shutdown();
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("server did not stop")
+        .unwrap()
+        .unwrap();
+}
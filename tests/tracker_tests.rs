@@ -0,0 +1,354 @@
+//! Channel tracker tests using mock ESL server
+
+#[allow(dead_code)]
+mod mock_server;
+
+use freeswitch_esl_tokio::{
+    CallHistoryKind, CallState, ChannelChange, ChannelChangeStream, ChannelTracker,
+    ReconnectOptions, ReconnectingClient, TrackerOptions,
+};
+use mock_server::{setup_connected_pair, MockClient, MockEslServer};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const SHOW_CHANNELS: &str = r#"{"row_count":2,"rows":[
+    {"uuid":"leg-a","name":"sofia/internal/1000@pbx","state":"CS_EXECUTE","callstate":"ACTIVE",
+     "direction":"inbound","cid_num":"1000","dest":"2000","call_uuid":"leg-a"},
+    {"uuid":"leg-b","name":"sofia/internal/2000@pbx","state":"CS_EXCHANGE_MEDIA",
+     "callstate":"ACTIVE","direction":"outbound","cid_num":"1000","call_uuid":"leg-a"}
+]}"#;
+
+fn no_dump() -> TrackerOptions {
+    TrackerOptions {
+        uuid_dump: false,
+        ..Default::default()
+    }
+}
+
+/// Answer the tracker's subscription and `show channels` with `listing`.
+async fn serve_bootstrap(mock: &mut MockClient, listing: &str) {
+    let cmd = mock
+        .read_command()
+        .await;
+    assert!(
+        cmd.starts_with("event plain CHANNEL_CREATE CHANNEL_DESTROY "),
+        "got: {:?}",
+        cmd
+    );
+    mock.reply_ok()
+        .await;
+    serve_show_channels(mock, listing).await;
+}
+
+async fn serve_show_channels(mock: &mut MockClient, listing: &str) {
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "api show channels as json\n\n"
+    );
+    mock.reply_api(listing)
+        .await;
+}
+
+async fn send(mock: &mut MockClient, event_name: &str, uuid: &str, headers: &[(&str, &str)]) {
+    let mut map = HashMap::new();
+    map.insert("Unique-ID".to_string(), uuid.to_string());
+    for (name, value) in headers {
+        map.insert(name.to_string(), value.to_string());
+    }
+    mock.send_event_plain(event_name, &map)
+        .await;
+}
+
+async fn next_change(changes: &mut ChannelChangeStream) -> ChannelChange {
+    tokio::time::timeout(Duration::from_secs(5), changes.recv())
+        .await
+        .expect("timeout")
+        .expect("stream ended")
+        .expect("lagged")
+}
+
+#[tokio::test]
+async fn test_tracker_bootstraps_and_follows_events() {
+    let (mut mock, client, events) = setup_connected_pair("ClueCon").await;
+    let (result, _) = tokio::join!(
+        ChannelTracker::start(client, events, no_dump()),
+        serve_bootstrap(&mut mock, SHOW_CHANNELS)
+    );
+    let tracker = result.unwrap();
+    let mut changes = tracker.changes();
+
+    assert_eq!(tracker.len(), 2);
+    assert_eq!(
+        tracker
+            .by_call_uuid("leg-a")
+            .len(),
+        2
+    );
+    assert_eq!(
+        tracker
+            .by_caller_number("1000")
+            .len(),
+        2
+    );
+    assert!(tracker
+        .by_caller_number("2000")
+        .is_empty());
+
+    send(
+        &mut mock,
+        "CHANNEL_HOLD",
+        "leg-a",
+        &[("Channel-Call-State", "HELD")],
+    )
+    .await;
+    match next_change(&mut changes).await {
+        ChannelChange::Updated { channel, .. } => {
+            assert_eq!(channel.uuid(), "leg-a");
+            assert_eq!(channel.call_state(), Some(CallState::Held));
+        }
+        other => panic!("expected Updated, got {:?}", other),
+    }
+    assert_eq!(
+        tracker
+            .get("leg-a")
+            .unwrap()
+            .call_state(),
+        Some(CallState::Held)
+    );
+
+    send(
+        &mut mock,
+        "CHANNEL_CREATE",
+        "leg-c",
+        &[("Caller-Caller-ID-Number", "3000")],
+    )
+    .await;
+    assert!(matches!(
+        next_change(&mut changes).await,
+        ChannelChange::Created(c) if c.uuid() == "leg-c"
+    ));
+    assert_eq!(tracker.by_caller_number("3000")[0].uuid(), "leg-c");
+
+    send(
+        &mut mock,
+        "CHANNEL_DESTROY",
+        "leg-b",
+        &[("Hangup-Cause", "NORMAL_CLEARING")],
+    )
+    .await;
+    match next_change(&mut changes).await {
+        ChannelChange::Removed(channel) => {
+            assert_eq!(channel.uuid(), "leg-b");
+            assert!(channel
+                .hangup_cause()
+                .is_some());
+        }
+        other => panic!("expected Removed, got {:?}", other),
+    }
+    assert!(tracker
+        .get("leg-b")
+        .is_none());
+
+    // The table survives the connection; change streams end
+    mock.drop_connection()
+        .await;
+    let end = tokio::time::timeout(Duration::from_secs(5), changes.recv())
+        .await
+        .expect("timeout");
+    assert!(end.is_none());
+    assert!(!tracker.is_running());
+    assert_eq!(tracker.len(), 2);
+}
+
+#[tokio::test]
+async fn test_tracker_correlates_call_legs() {
+    let (mut mock, client, events) = setup_connected_pair("ClueCon").await;
+    let (result, _) = tokio::join!(
+        ChannelTracker::start(client, events, no_dump()),
+        serve_bootstrap(&mut mock, SHOW_CHANNELS)
+    );
+    let tracker = result.unwrap();
+    let mut changes = tracker.changes();
+
+    // Bootstrap rows share Channel-Call-UUID
+    let call = tracker
+        .call_for("leg-b")
+        .unwrap();
+    assert_eq!(call.id(), "leg-a");
+    assert_eq!(
+        call.legs()
+            .len(),
+        2
+    );
+
+    send(
+        &mut mock,
+        "CHANNEL_BRIDGE",
+        "leg-a",
+        &[
+            ("Bridge-A-Unique-ID", "leg-a"),
+            ("Bridge-B-Unique-ID", "leg-b"),
+        ],
+    )
+    .await;
+    next_change(&mut changes).await;
+    let call = tracker
+        .call_for("leg-a")
+        .unwrap();
+    assert_eq!(call.bridges(), [("leg-a", "leg-b")]);
+    assert!(matches!(
+        call.history()
+            .last()
+            .map(|e| &e.kind),
+        Some(CallHistoryKind::Bridged { .. })
+    ));
+    assert_eq!(
+        tracker
+            .calls()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_tracker_merges_uuid_dump() {
+    let (mut mock, client, events) = setup_connected_pair("ClueCon").await;
+    let listing = r#"{"row_count":1,"rows":[{"uuid":"leg-a","cid_num":"1000"}]}"#;
+    let (result, _) = tokio::join!(
+        ChannelTracker::start(client, events, TrackerOptions::default()),
+        serve_bootstrap(&mut mock, listing)
+    );
+    let tracker = result.unwrap();
+    let mut changes = tracker.changes();
+
+    assert_eq!(
+        mock.read_command()
+            .await,
+        "event plain BACKGROUND_JOB\n\n"
+    );
+    mock.reply_ok()
+        .await;
+    let cmd = mock
+        .read_command()
+        .await;
+    assert!(cmd.starts_with("bgapi uuid_dump leg-a\n"), "got: {:?}", cmd);
+    let job_uuid = cmd
+        .lines()
+        .find_map(|l| l.strip_prefix("Job-UUID: "))
+        .unwrap()
+        .to_string();
+    mock.reply_raw_text(&format!("+OK Job-UUID: {}", job_uuid))
+        .await;
+    mock.send_background_job(
+        &job_uuid,
+        "Unique-ID: leg-a\nvariable_sip_call_id: abc%40pbx\n",
+    )
+    .await;
+
+    match next_change(&mut changes).await {
+        ChannelChange::Updated {
+            event_type: None,
+            channel,
+        } => assert_eq!(channel.variable("sip_call_id"), Some("abc@pbx")),
+        other => panic!("expected Updated, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_tracker_resynchronizes_after_reconnect() {
+    let server = MockEslServer::start("ClueCon").await;
+    let options = ReconnectOptions {
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+        jitter: 0.0,
+        ..Default::default()
+    };
+    let (mut mock, result) = tokio::join!(
+        server.accept(),
+        ReconnectingClient::connect("127.0.0.1", server.port(), "ClueCon", options)
+    );
+    let (client, events) = result.unwrap();
+
+    let (result, _) = tokio::join!(
+        ChannelTracker::start_reconnecting(client, events, no_dump()),
+        serve_bootstrap(&mut mock, SHOW_CHANNELS)
+    );
+    let tracker = result.unwrap();
+    let mut changes = tracker.changes();
+    assert_eq!(tracker.len(), 2);
+
+    // leg-b hung up while the connection was down
+    mock.drop_connection()
+        .await;
+    let mut mock = server
+        .accept()
+        .await;
+    let cmd = mock
+        .read_command()
+        .await;
+    assert!(
+        cmd.starts_with("event plain CHANNEL_CREATE"),
+        "got: {:?}",
+        cmd
+    );
+    mock.reply_ok()
+        .await;
+    serve_show_channels(
+        &mut mock,
+        r#"{"row_count":1,"rows":[{"uuid":"leg-a","callstate":"HELD"}]}"#,
+    )
+    .await;
+
+    assert!(matches!(
+        next_change(&mut changes).await,
+        ChannelChange::Removed(c) if c.uuid() == "leg-b"
+    ));
+    assert!(matches!(
+        next_change(&mut changes).await,
+        ChannelChange::Updated {
+            event_type: None,
+            ..
+        }
+    ));
+    assert!(matches!(
+        next_change(&mut changes).await,
+        ChannelChange::Synchronized { channels: 1 }
+    ));
+    let leg_a = tracker
+        .get("leg-a")
+        .unwrap();
+    assert_eq!(leg_a.call_state(), Some(CallState::Held));
+    // Headers from before the reconnect are kept
+    assert_eq!(leg_a.caller_id_number(), Some("1000"));
+}
+
+#[tokio::test]
+async fn test_tracker_zero_capacity_does_not_panic() {
+    let (mut mock, client, events) = setup_connected_pair("ClueCon").await;
+    let options = TrackerOptions {
+        changes_capacity: 0,
+        ..no_dump()
+    };
+    let (result, _) = tokio::join!(
+        ChannelTracker::start(client, events, options),
+        serve_bootstrap(&mut mock, SHOW_CHANNELS)
+    );
+    let tracker = result.unwrap();
+    assert_eq!(
+        tracker
+            .channels()
+            .len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_tracker_start_fails_on_invalid_listing() {
+    let (mut mock, client, events) = setup_connected_pair("ClueCon").await;
+    let (result, _) = tokio::join!(
+        ChannelTracker::start(client, events, no_dump()),
+        serve_bootstrap(&mut mock, "not json")
+    );
+    assert!(result.is_err());
+}